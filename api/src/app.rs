@@ -1,11 +1,27 @@
 use axum::http::{HeaderValue, Method, header};
-use axum::middleware::from_extractor_with_state;
+use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
+use axum::middleware::{from_extractor_with_state, from_fn};
 use beep_auth::KeycloakAuthRepository;
 use messages_core::{
-    create_repositories,
-    infrastructure::{OutboxRelayService, RabbitMqPublisher},
+    application::default_connection_name,
+    create_named_repositories,
+    domain::common::CoreError,
+    domain::message::{
+        duplicates::DuplicateContentPolicy, feed::MessageFeed, normalization::ContentNormalization,
+        rate_limit::{RateLimitPolicy, TokenBucketRateLimiter},
+    },
+    infrastructure::{
+        MessageFeedConsumer, OutboxRelayService, RabbitMqPublisher,
+        outbox::{EventRouting, consistency::OutboxConsistencyChecker}, retry::RetryPolicy,
+    },
 };
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::{
+    sync::{Mutex, watch},
+    task::JoinHandle,
+};
 use tower_http::cors::CorsLayer;
 use utoipa::OpenApi;
 use utoipa_axum::router::OpenApiRouter;
@@ -15,14 +31,20 @@ use utoipa_scalar::{Scalar, Servable};
 
 use crate::{
     Config,
+    grpc::{MessageGrpcService, MessageServiceServer},
     http::{
         health::routes::health_routes,
         server::{
             ApiError, AppState, authorization::SpiceDbAuthz,
-            authorization::SpiceDbConfig as LocalSpiceConfig, middleware::auth::AuthMiddleware,
+            authorization::SpiceDbConfig as LocalSpiceConfig,
+            middleware::{
+                auth::{AuthMiddleware, AuthState, OptionalAuth, token_cache::TokenCache},
+                authz_cache::request_authz_cache,
+            },
+            security::{DynSecurityEventSink, LoggingSecurityEventSink},
         },
     },
-    message_routes,
+    message_routes, public_message_routes,
     http::attachments::routes::attachments_routes
 };
 
@@ -39,6 +61,16 @@ pub struct App {
     pub state: AppState,
     app_router: axum::Router,
     health_router: axum::Router,
+    /// Message operations served over gRPC next to the HTTP API
+    grpc_service: MessageGrpcService,
+    publisher: Arc<RabbitMqPublisher>,
+    /// Exchange overrides used by the outbox, replaced by [`App::reload_routing`]
+    event_routing: EventRouting,
+    /// Feeds the live channel streams; closed on shutdown so they end
+    message_feed: MessageFeed,
+    /// Set to `true` to stop the HTTP servers and every background task
+    shutdown: watch::Sender<bool>,
+    background_tasks: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl App {
@@ -46,19 +78,46 @@ impl App {
     pub async fn new(config: Config) -> Result<Self, ApiError> {
         tracing::debug!("Creating repositories...");
 
-        let repositories = create_repositories(
+        let connection_name = config
+            .connection_name
+            .clone()
+            .unwrap_or_else(default_connection_name);
+        tracing::info!(%connection_name, "Naming outgoing connections");
+
+        let mut repositories = create_named_repositories(
             &config.database.mongo_uri,
             &config.database.mongo_db_name,
             &config.content_url,
+            &connection_name,
         )
         .await
         .map_err(|e| ApiError::StartupError {
             msg: format!("Failed to create repositories: {}", e),
         })?;
 
+        repositories.message_repository = repositories
+            .message_repository
+            .with_retry_policy(RetryPolicy::new(
+                config.database.max_retries,
+                Duration::from_millis(config.database.retry_backoff_ms),
+            ));
+
+        let routing = config.load_routing().map_err(|e| ApiError::StartupError {
+            msg: format!("Failed to load routing config: {}", e),
+        })?;
+        let event_routing = EventRouting::new(routing).map_err(|e| ApiError::StartupError {
+            msg: format!("Failed to load routing config: {}", e),
+        })?;
+        repositories.outbox_repository = repositories
+            .outbox_repository
+            .with_event_routing(event_routing.clone());
+
         // ---------- RabbitMQ / Outbox ----------
         tracing::info!("Initializing RabbitMQ publisher");
-        let rabbitmq_publisher = Arc::new(RabbitMqPublisher::new(config.rabbitmq.url.clone()));
+        let rabbitmq_publisher = Arc::new(
+            RabbitMqPublisher::new(config.rabbitmq.url.clone())
+                .with_connection_name(format!("{}-publisher", connection_name)),
+        );
 
         rabbitmq_publisher
             .connect()
@@ -74,15 +133,78 @@ impl App {
                 msg: format!("Failed to declare notifications exchange: {}", e),
             })?;
 
+        let (shutdown, _) = watch::channel(false);
+
         tracing::info!("Starting outbox relay service");
         let db = repositories.message_repository.db.clone();
-        let relay_service = OutboxRelayService::new(db, rabbitmq_publisher.clone());
-        tokio::spawn(async move {
-            relay_service.start().await;
+        let relay_service = OutboxRelayService::new(db, rabbitmq_publisher.clone())
+            .with_allowed_exchanges(config.rabbitmq.allowed_exchanges.clone())
+            .with_wire_formats(config.rabbitmq.wire_formats.clone())
+            .with_lease_duration(Duration::from_secs(config.rabbitmq.outbox_lease_secs))
+            .with_max_retries(config.rabbitmq.outbox_max_retries)
+            .with_base_backoff(Duration::from_millis(config.rabbitmq.outbox_base_backoff_ms))
+            .with_max_backoff(Duration::from_millis(config.rabbitmq.outbox_max_backoff_ms));
+        let relay_shutdown = shutdown.subscribe();
+        let relay_task = tokio::spawn(async move {
+            relay_service.start(relay_shutdown).await;
         });
+        let mut background_tasks = vec![relay_task];
+
+        if config.rabbitmq.outbox_consistency_check {
+            let checker = OutboxConsistencyChecker::new(repositories.message_repository.db.clone())
+                .with_interval(Duration::from_secs(config.rabbitmq.outbox_consistency_interval_secs))
+                .with_window(Duration::from_secs(config.rabbitmq.outbox_consistency_window_secs))
+                .with_lease_duration(Duration::from_secs(config.rabbitmq.outbox_lease_secs));
+            let checker_shutdown = shutdown.subscribe();
+            background_tasks.push(tokio::spawn(async move {
+                checker.start(checker_shutdown).await;
+            }));
+        }
+
+        // Every instance consumes message creations, updates and deletions to
+        // serve its own live streams, from wherever the routing sends them
+        let message_feed = MessageFeed::default();
+        let consumer = MessageFeedConsumer::new(config.rabbitmq.url.clone(), message_feed.clone())
+            .with_connection_name(format!("{}-feed", connection_name))
+            .with_event_routing(event_routing.clone());
+        let consumer_shutdown = shutdown.subscribe();
+        background_tasks.push(tokio::spawn(async move {
+            consumer.start(consumer_shutdown).await;
+        }));
 
         // ---------- Application service ----------
         let service: messages_core::application::MessagesService = repositories.clone().into();
+        let service = service.with_content_normalization(ContentNormalization::new(
+            config.message.normalize_content,
+            config.message.max_blank_lines,
+        ))
+        .with_max_content_length(config.message.max_content_length)
+        .with_max_bulk_delete(config.message.max_bulk_delete)
+        .with_erasure_mode(config.message.erasure_mode.into())
+        .with_bulk_event_mode(config.message.bulk_event_mode.into())
+        .with_health_check_timeout(Duration::from_millis(config.message.health_check_timeout_ms))
+        .with_dependency_probe(rabbitmq_publisher.clone())
+        .with_event_publisher(rabbitmq_publisher.clone())
+        .with_message_feed(message_feed.clone());
+        let service = if config.message.rate_limit_capacity > 0 {
+            let policy = RateLimitPolicy::new(
+                config.message.rate_limit_capacity,
+                config.message.rate_limit_refill_per_sec,
+            )
+            .map_err(|e| ApiError::StartupError {
+                msg: format!("Failed to configure rate limiting: {}", e),
+            })?;
+            service.with_rate_limiter(Arc::new(TokenBucketRateLimiter::new(policy)))
+        } else {
+            service
+        };
+        let service = match config.message.duplicate_action.into_action() {
+            Some(action) => service.with_duplicate_detection(DuplicateContentPolicy::new(
+                action,
+                chrono::Duration::seconds(config.message.duplicate_window_secs),
+            )),
+            None => service,
+        };
 
         // ---------- Authorization (SpiceDB) ----------
         let authz = {
@@ -104,7 +226,11 @@ impl App {
             Arc::new(client) as Arc<dyn crate::http::server::authorization::Authorization>
         };
 
-        let state = AppState::new(service, authz);
+        // Refused requests and failed logins go to the same sink
+        let security_events: DynSecurityEventSink = Arc::new(LoggingSecurityEventSink);
+        let state = AppState::new(service, authz)
+            .with_default_page_limit(config.message.default_page_limit)
+            .with_security_events(security_events.clone());
 
         // ---------- Keycloak ----------
         let keycloak_repository = KeycloakAuthRepository::new(
@@ -114,6 +240,10 @@ impl App {
             ),
             None,
         );
+        let token_cache = TokenCache::new(
+            Duration::from_secs(config.keycloak.token_cache_ttl_secs),
+            config.keycloak.token_cache_capacity,
+        );
 
         // ---------- CORS ----------
         let allowed_origins: Vec<HeaderValue> = config
@@ -135,13 +265,23 @@ impl App {
             .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, header::ACCEPT])
             .allow_credentials(true);
 
+        let auth_state = AuthState::new(keycloak_repository)
+            .with_token_cache(token_cache)
+            .with_security_events(security_events);
+        let grpc_service = MessageGrpcService::new(state.clone(), auth_state.clone());
+
         let (app_router, mut api) = OpenApiRouter::<AppState>::new()
             .merge(message_routes())
             .merge(attachments_routes())
             .route_layer(from_extractor_with_state::<
                 AuthMiddleware,
-                KeycloakAuthRepository,
-            >(keycloak_repository))
+                AuthState,
+            >(auth_state.clone()))
+            .merge(public_message_routes().route_layer(from_extractor_with_state::<
+                OptionalAuth,
+                AuthState,
+            >(auth_state)))
+            .layer(from_fn(request_authz_cache))
             .layer(cors)
             .split_for_parts();
 
@@ -171,6 +311,12 @@ impl App {
             state,
             app_router,
             health_router,
+            grpc_service,
+            publisher: rabbitmq_publisher,
+            event_routing,
+            message_feed,
+            shutdown,
+            background_tasks: Mutex::new(background_tasks),
         })
     }
 
@@ -182,6 +328,7 @@ impl App {
     pub async fn start(&self) -> Result<(), ApiError> {
         let health_addr = format!("0.0.0.0:{}", self.config.clone().message.health_port);
         let api_addr = format!("0.0.0.0:{}", self.config.clone().message.api_port);
+        let grpc_addr = SocketAddr::from(([0, 0, 0, 0], self.config.message.grpc_port));
         // Create TCP listeners for both messages
         let health_listener = tokio::net::TcpListener::bind(&health_addr)
             .await
@@ -194,22 +341,89 @@ impl App {
                 msg: format!("Failed to bind API message: {}", api_addr),
             })?;
 
-        tracing::info!(api_addr = %api_addr, health_addr = %health_addr, "Starting HTTP listeners");
-        // Run both listeners concurrently
-        tokio::try_join!(
-            axum::serve(health_listener, self.health_router.clone()),
-            axum::serve(api_listener, self.app_router.clone())
-        )
-        .expect("Failed to start messages");
+        tracing::info!(api_addr = %api_addr, health_addr = %health_addr, grpc_addr = %grpc_addr, "Starting HTTP and gRPC listeners");
+        // The health listener outlives the API one, so probes keep seeing the
+        // instance as draining until its last request is done
+        let (api_stopped, mut api_stopped_rx) = watch::channel(false);
+        let api = async {
+            let served = axum::serve(api_listener, with_client_addresses(self.app_router.clone()))
+                .with_graceful_shutdown(self.shutdown_requested())
+                .await;
+            api_stopped.send_replace(true);
+            served
+        };
+        let health = axum::serve(health_listener, self.health_router.clone()).with_graceful_shutdown(
+            async move {
+                let _ = api_stopped_rx.wait_for(|stopped| *stopped).await;
+            },
+        );
+        let grpc = async {
+            tonic::transport::Server::builder()
+                .add_service(MessageServiceServer::new(self.grpc_service.clone()))
+                .serve_with_shutdown(grpc_addr, self.shutdown_requested())
+                .await
+                .map_err(std::io::Error::other)
+        };
+        tokio::try_join!(health, api, grpc).map_err(|e| ApiError::StartupError {
+            msg: format!("A listener stopped on an error: {}", e),
+        })?;
         Ok(())
     }
 
+    /// Re-read the routing config and use it for every event written from now on
+    ///
+    /// An unreadable or invalid config is rejected and the active routing kept.
+    pub fn reload_routing(&self) -> Result<(), CoreError> {
+        let routing = self.config.load_routing()?;
+        self.event_routing.reload(routing)?;
+        tracing::info!("Routing config reloaded");
+        Ok(())
+    }
+
+    /// Ask the HTTP servers and background tasks to stop
+    pub fn request_shutdown(&self) {
+        // Readiness fails first so the orchestrator stops routing new traffic here
+        self.state.begin_draining();
+        // Open live streams would otherwise keep the graceful shutdown waiting
+        self.message_feed.close();
+        self.shutdown.send_replace(true);
+    }
+
+    fn shutdown_requested(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut receiver = self.shutdown.subscribe();
+        async move {
+            let _ = receiver.wait_for(|stop| *stop).await;
+        }
+    }
+
+    /// Stop every background task, wait for them to finish, then release the
+    /// broker connection and the database
     #[tracing::instrument(skip(self))]
     pub async fn shutdown(&self) {
+        self.request_shutdown();
+
+        let tasks: Vec<JoinHandle<()>> = self.background_tasks.lock().await.drain(..).collect();
+        for task in tasks {
+            if let Err(e) = task.await {
+                tracing::error!("Background task failed during shutdown: {}", e);
+            }
+        }
+
+        if let Err(e) = self.publisher.close().await {
+            tracing::warn!("Failed to close RabbitMQ connection: {}", e);
+        }
         self.state.shutdown().await;
     }
 }
 
+/// Serve `router` so handlers can read the client's address from `ConnectInfo`,
+/// which the security events report
+pub fn with_client_addresses(
+    router: axum::Router,
+) -> IntoMakeServiceWithConnectInfo<axum::Router, SocketAddr> {
+    router.into_make_service_with_connect_info::<SocketAddr>()
+}
+
 pub trait AppBuilder {
     fn build(config: Config) -> impl Future<Output = Result<App, ApiError>>;
     fn with_state(self, state: AppState) -> impl Future<Output = Result<App, ApiError>>;
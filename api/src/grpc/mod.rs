@@ -0,0 +1,39 @@
+//! gRPC interface to the message operations, for internal services
+//!
+//! Mirrors the message CRUD routes of the HTTP API on top of the same
+//! [`AppState`](crate::AppState). Callers authenticate with an
+//! `authorization: Bearer <token>` metadata entry, checked like the HTTP
+//! `Authorization` header.
+
+use tonic::Status;
+
+use crate::http::server::ApiError;
+
+pub mod pb {
+    tonic::include_proto!("beep.messages.v1");
+}
+
+mod service;
+
+pub use pb::message_service_server::MessageServiceServer;
+pub use service::MessageGrpcService;
+
+impl From<ApiError> for Status {
+    fn from(error: ApiError) -> Self {
+        let message = error.to_string();
+        match error {
+            ApiError::Unauthorized => Status::unauthenticated(message),
+            ApiError::Forbidden => Status::permission_denied(message),
+            ApiError::NotFound => Status::not_found(message),
+            ApiError::BadRequest { .. } | ApiError::InvalidUuid { .. } => {
+                Status::invalid_argument(message)
+            }
+            ApiError::Conflict { error_code } => Status::failed_precondition(error_code),
+            ApiError::TooManyRequests { .. } => Status::resource_exhausted(message),
+            ApiError::ServiceUnavailable { .. } => Status::unavailable(message),
+            ApiError::InternalServerError | ApiError::StartupError { .. } => {
+                Status::internal(message)
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use messages_core::domain::{
+    authorization::entities::Actor,
+    message::{
+        entities::{
+            AttachmentId, ChannelId, CreateMessageRequest, Message, MessageId,
+            ReturnedMessage, UpdateMessageRequest,
+        },
+        ports::MessageService,
+    },
+};
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::{
+    grpc::pb::{self, message_service_server::MessageService as GrpcMessageService},
+    http::server::{
+        ApiError, AppState,
+        authorization::with_request_authz_cache,
+        extractors::PageParams,
+        middleware::auth::{AuthState, authenticate_bearer, record_auth_failure},
+    },
+};
+
+/// Serves the message operations over gRPC with the HTTP API's state
+#[derive(Clone)]
+pub struct MessageGrpcService {
+    state: AppState,
+    auth_state: AuthState,
+}
+
+impl MessageGrpcService {
+    pub fn new(state: AppState, auth_state: AuthState) -> Self {
+        Self { state, auth_state }
+    }
+
+    /// Resolve the caller from the `authorization` metadata of `request`
+    async fn actor<T: Sync>(&self, request: &Request<T>) -> Result<Actor, Status> {
+        let ip = request.remote_addr().map(|addr| addr.ip());
+        let reject = |reason| {
+            record_auth_failure(&self.auth_state, ip, reason);
+            Status::from(ApiError::Unauthorized)
+        };
+
+        let Some(value) = request.metadata().get("authorization") else {
+            return Err(reject("authorization header missing"));
+        };
+        let value = value
+            .to_str()
+            .map_err(|_| reject("authorization header is not valid UTF-8"))?;
+        let identity = authenticate_bearer(value, &self.auth_state)
+            .await
+            .map_err(reject)?;
+        Ok(Actor::from(identity.user_id))
+    }
+}
+
+fn parse_uuid(field: &str, value: &str) -> Result<Uuid, Status> {
+    Uuid::try_parse(value).map_err(|_| {
+        Status::from(ApiError::InvalidUuid {
+            field: field.to_string(),
+        })
+    })
+}
+
+#[tonic::async_trait]
+impl GrpcMessageService for MessageGrpcService {
+    async fn create_message(
+        &self,
+        request: Request<pb::CreateMessageRequest>,
+    ) -> Result<Response<pb::CreateMessageResponse>, Status> {
+        with_request_authz_cache(async {
+            let actor = self.actor(&request).await?;
+            let ip = request.remote_addr().map(|addr| addr.ip());
+            let request = request.into_inner();
+            let reply_to_message_id = request
+                .reply_to_message_id
+                .as_deref()
+                .map(|id| parse_uuid("reply_to_message_id", id).map(MessageId::from))
+                .transpose()?;
+            let attachments = request
+                .attachment_ids
+                .iter()
+                .map(|id| parse_uuid("attachment_ids", id).map(AttachmentId::from))
+                .collect::<Result<_, _>>()?;
+
+            let input = CreateMessageRequest {
+                channel_id: ChannelId::from(parse_uuid("channel_id", &request.channel_id)?),
+                content: request.content,
+                reply_to_message_id,
+                attachments,
+                ephemeral: request.ephemeral,
+            }
+            .into_input(actor.author_id());
+            let created = self
+                .state
+                .service
+                .create_message(input)
+                .await
+                .map_err(|error| {
+                    self.state.report_refusal(&error, actor.0, ip);
+                    ApiError::from(error)
+                })?;
+
+            Ok(Response::new(pb::CreateMessageResponse {
+                message: Some(created.message.into()),
+                ephemeral: created.ephemeral,
+                duplicate: created.duplicate,
+            }))
+        })
+        .await
+    }
+
+    async fn get_message(
+        &self,
+        request: Request<pb::GetMessageRequest>,
+    ) -> Result<Response<pb::Message>, Status> {
+        with_request_authz_cache(async {
+            let actor = self.actor(&request).await?;
+            let message_id = MessageId::from(parse_uuid("id", &request.get_ref().id)?);
+            let message = self
+                .state
+                .service
+                .get_message(&actor, &message_id)
+                .await
+                .map_err(ApiError::from)?;
+            Ok(Response::new(message.into()))
+        })
+        .await
+    }
+
+    async fn list_messages(
+        &self,
+        request: Request<pb::ListMessagesRequest>,
+    ) -> Result<Response<pb::ListMessagesResponse>, Status> {
+        with_request_authz_cache(async {
+            let actor = self.actor(&request).await?;
+            let request = request.into_inner();
+            let channel_id = ChannelId::from(parse_uuid("channel_id", &request.channel_id)?);
+            let pagination = PageParams {
+                page: request.page,
+                limit: request.limit,
+            }
+            .resolve(self.state.default_page_limit);
+
+            let (messages, total) = self
+                .state
+                .service
+                .list_messages(&actor, &channel_id, &pagination)
+                .await
+                .map_err(ApiError::from)?;
+
+            Ok(Response::new(pb::ListMessagesResponse {
+                messages: messages.into_iter().map(Into::into).collect(),
+                total,
+                page: pagination.page,
+            }))
+        })
+        .await
+    }
+
+    async fn update_message(
+        &self,
+        request: Request<pb::UpdateMessageRequest>,
+    ) -> Result<Response<pb::Message>, Status> {
+        with_request_authz_cache(async {
+            let actor = self.actor(&request).await?;
+            let request = request.into_inner();
+            let input = UpdateMessageRequest {
+                content: request.content,
+            }
+            .into_input(MessageId::from(parse_uuid("id", &request.id)?));
+
+            let message = self
+                .state
+                .service
+                .update_message(&actor, input)
+                .await
+                .map_err(ApiError::from)?;
+            Ok(Response::new(message.into()))
+        })
+        .await
+    }
+
+    async fn delete_message(
+        &self,
+        request: Request<pb::DeleteMessageRequest>,
+    ) -> Result<Response<pb::DeleteMessageResponse>, Status> {
+        with_request_authz_cache(async {
+            let actor = self.actor(&request).await?;
+            let message_id = MessageId::from(parse_uuid("id", &request.get_ref().id)?);
+            self.state
+                .service
+                .delete_message(&actor, &message_id)
+                .await
+                .map_err(ApiError::from)?;
+            Ok(Response::new(pb::DeleteMessageResponse {}))
+        })
+        .await
+    }
+}
+
+impl From<Message> for pb::Message {
+    fn from(message: Message) -> Self {
+        Self {
+            id: message.id.to_string(),
+            channel_id: message.channel_id.to_string(),
+            author_id: message.author_id.to_string(),
+            content: message.content,
+            reply_to_message_id: message.reply_to_message_id.map(|id| id.to_string()),
+            attachments: message
+                .attachments
+                .into_iter()
+                .map(|id| pb::Attachment {
+                    id: id.to_string(),
+                    url: String::new(),
+                })
+                .collect(),
+            is_pinned: message.is_pinned,
+            created_at: message.created_at.to_rfc3339(),
+            updated_at: message.updated_at.map(|at| at.to_rfc3339()),
+        }
+    }
+}
+
+impl From<ReturnedMessage> for pb::Message {
+    fn from(message: ReturnedMessage) -> Self {
+        Self {
+            id: message.id.to_string(),
+            channel_id: message.channel_id.to_string(),
+            author_id: message.author_id.to_string(),
+            content: message.content,
+            reply_to_message_id: message.reply_to_message_id.map(|id| id.to_string()),
+            attachments: message
+                .attachments
+                .into_iter()
+                .map(|attachment| pb::Attachment {
+                    id: attachment.id.to_string(),
+                    url: attachment.url,
+                })
+                .collect(),
+            is_pinned: message.is_pinned,
+            created_at: message.created_at.to_rfc3339(),
+            updated_at: message.updated_at.map(|at| at.to_rfc3339()),
+        }
+    }
+}
@@ -1,9 +1,14 @@
-use axum::extract::State;
+use axum::{
+    extract::State,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
 use chrono::Utc;
+use std::time::Instant;
 use serde::Serialize;
 use utoipa::ToSchema;
 
-use messages_core::domain::health::port::HealthService;
+use messages_core::domain::health::{entities::DependencyHealth, port::HealthService};
 
 use crate::http::server::{ApiError, AppState, Response};
 
@@ -49,3 +54,138 @@ pub async fn health_check(
 
     Ok(Response::ok(response))
 }
+
+/// Response structure for the liveness check
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LivenessResponse {
+    pub status: String,
+    pub timestamp: String,
+}
+
+/// Handler for /health/live endpoint
+/// Answers as long as the process serves requests, without touching any dependency
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    responses(
+        (status = 200, description = "The process is up, including while it drains traffic", body = LivenessResponse)
+    )
+)]
+#[tracing::instrument]
+pub async fn liveness() -> Response<LivenessResponse> {
+    Response::ok(LivenessResponse {
+        status: "alive".to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+    })
+}
+
+/// Response structure for the readiness check
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub status: String,
+    pub dependencies: Vec<DependencyHealth>,
+    /// Names of the dependencies that failed their check
+    pub unhealthy: Vec<String>,
+    pub timestamp: String,
+}
+
+/// Handler for /health/ready endpoint
+/// Probes every dependency and reports each check's outcome and latency
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Every dependency is reachable", body = ReadinessResponse),
+        (status = 503, description = "A dependency failed or timed out, or the instance is draining for shutdown", body = ReadinessResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+pub async fn readiness(State(state): State<AppState>) -> Response<ReadinessResponse> {
+    // No need to probe anything once the instance is on its way out
+    if state.is_draining() {
+        let response = ReadinessResponse {
+            status: "draining".to_string(),
+            dependencies: Vec::new(),
+            unhealthy: Vec::new(),
+            timestamp: Utc::now().to_rfc3339(),
+        };
+        return Response::with_status(response, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    let dependencies = state.service.check_all().await;
+    let unhealthy: Vec<String> = dependencies
+        .iter()
+        .filter(|dependency| !dependency.healthy)
+        .map(|dependency| dependency.name.clone())
+        .collect();
+
+    let (status, status_code) = if unhealthy.is_empty() {
+        ("ready", StatusCode::OK)
+    } else {
+        ("unavailable", StatusCode::SERVICE_UNAVAILABLE)
+    };
+
+    let response = ReadinessResponse {
+        status: status.to_string(),
+        dependencies,
+        unhealthy,
+        timestamp: Utc::now().to_rfc3339(),
+    };
+
+    Response::with_status(response, status_code)
+}
+
+/// Response structure for the detailed health check
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DetailedHealthResponse {
+    pub status: String,
+    pub dependencies: Vec<DependencyHealth>,
+    /// Time taken to check every dependency
+    pub duration_ms: u64,
+    pub timestamp: String,
+}
+
+/// Handler for /health/detailed endpoint
+/// Pings every dependency and reports which one is down and why
+#[utoipa::path(
+    get,
+    path = "/health/detailed",
+    tag = "health",
+    responses(
+        (status = 200, description = "Every dependency is healthy", body = DetailedHealthResponse),
+        (status = 503, description = "A dependency is down", body = DetailedHealthResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+pub async fn detailed_health(State(state): State<AppState>) -> Response<DetailedHealthResponse> {
+    let started = Instant::now();
+    let dependencies = state.service.check_all().await;
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    let (status, status_code) = if dependencies.iter().all(|dependency| dependency.healthy) {
+        ("healthy", StatusCode::OK)
+    } else {
+        ("unhealthy", StatusCode::SERVICE_UNAVAILABLE)
+    };
+
+    let response = DetailedHealthResponse {
+        status: status.to_string(),
+        dependencies,
+        duration_ms,
+        timestamp: Utc::now().to_rfc3339(),
+    };
+
+    Response::with_status(response, status_code)
+}
+
+/// Handler for /metrics endpoint
+/// Exposes in-process counters in the Prometheus text format
+#[tracing::instrument(skip(state))]
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
@@ -1,3 +1,3 @@
 pub mod handler;
 pub mod routes;
-pub use handler::health_check;
+pub use handler::{detailed_health, health_check, liveness, metrics, readiness};
@@ -1,7 +1,15 @@
 use axum::{Router, routing::get};
 
-use crate::http::{health::health_check, server::AppState};
+use crate::http::{
+    health::{detailed_health, health_check, liveness, metrics, readiness},
+    server::AppState,
+};
 
 pub fn health_routes() -> Router<AppState> {
-    Router::new().route("/health", get(health_check))
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/health/live", get(liveness))
+        .route("/health/ready", get(readiness))
+        .route("/health/detailed", get(detailed_health))
+        .route("/metrics", get(metrics))
 }
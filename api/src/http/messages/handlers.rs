@@ -1,78 +1,74 @@
+use std::{collections::HashMap, net::SocketAddr};
+
 use axum::{
     Extension, Json,
-    extract::{Path, Query, State},
+    body::Body,
+    extract::{ConnectInfo, Path, Query, State},
+    http::{StatusCode, header},
+    response::{
+        IntoResponse, Response as AxumResponse,
+        sse::{Event, KeepAlive, Sse},
+    },
 };
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, stream};
 use messages_core::domain::{
-    common::GetPaginated,
+    authorization::entities::Actor,
+    common::{BatchResult, CoreError},
     message::{
         entities::{
-            AuthorId, ChannelId, CreateMessageRequest, Message, MessageId, ReturnedMessage, UpdateMessageRequest
+            AuthorId, ChannelId, ChannelMetadata, ChannelParticipant, ChannelReadState, CreateMessageRequest, CreatedMessage, ErasureReport, LatestMessagesRequest, MarkReadRequest, Message, MessageId, MessageSearchCriteria, PinMessageRequest, PinMessagesRequest, ReactionSummary, ReactionToggle, RecentChannel, ReorderPinsRequest, ReplyCount, ReturnedMessage, SetStickyRequest, UnreadCount, UpdateMessageRequest
         },
+        feed::LiveEvent,
         ports::MessageService,
     },
 };
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::http::server::authorization::{Permission, Resource};
 use crate::http::server::{
-    ApiError, AppState, Response, middleware::auth::entities::UserIdentity,
-    response::PaginatedResponse,
+    ApiError, AppState, Pagination, Response, UuidPath,
+    extractors::PageParams,
+    middleware::auth::entities::UserIdentity,
+    response::{CursorResponse, PaginatedResponse},
 };
 
+/// Actor of a route open to anonymous callers
+fn actor_or_anonymous(user_identity: Option<Extension<UserIdentity>>) -> Actor {
+    user_identity.map_or_else(Actor::anonymous, |Extension(user_identity)| {
+        Actor::from(user_identity.user_id)
+    })
+}
+
 #[utoipa::path(
     post,
     path = "/messages",
     tag = "messages",
     request_body = CreateMessageRequest,
     responses(
-        (status = 201, description = "Message created successfully", body = Message),
-        (status = 400, description = "Bad request - Invalid message name"),
+        (status = 201, description = "Message created successfully", body = CreatedMessage),
+        (status = 400, description = "Bad request - Invalid message name or ephemeral reply"),
         (status = 401, description = "Unauthorized"),
         (status = 403, description = "Forbidden"),
+        (status = 409, description = "Conflict - Same content posted too recently"),
+        (status = 429, description = "Too many messages - retry after the `Retry-After` delay"),
         (status = 500, description = "Internal message error")
     )
 )]
-#[tracing::instrument(skip(state, user_identity, request))]
+#[tracing::instrument(skip(state, user_identity, connect_info, request))]
 pub async fn create_message(
     State(state): State<AppState>,
     Extension(user_identity): Extension<UserIdentity>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
     Json(request): Json<CreateMessageRequest>,
-) -> Result<Response<Message>, ApiError> {
-    // Authorization: check user can send messages to this channel
-    let channel = request.channel_id;
-    let can_send = state
-        .authz
-        .check(
-            user_identity.user_id,
-            Permission::SendMessages,
-            Resource::Channel(channel.0),
-        )
-        .await
-        .map_err(|_| ApiError::InternalServerError)?;
-    
-    if !can_send {
-        return Err(ApiError::Forbidden);
-    }
-
-    let attachments = &request.attachments;
-    let can_attach = state
-            .authz
-            .check(
-                user_identity.user_id,
-                Permission::AttachFiles,
-                Resource::Channel(channel.0),
-            )
-            .await
-            .map_err(|_| ApiError::InternalServerError)?;
-
-    if !can_attach && !attachments.is_empty() {
-        return Err(ApiError::Forbidden);
-    }
-
+) -> Result<Response<CreatedMessage>, ApiError> {
     let owner_id = AuthorId::from(user_identity.user_id);
     let input = request.into_input(owner_id);
-    let message = state.service.create_message(input).await?;
+    let message = state.service.create_message(input).await.map_err(|error| {
+        let ip = connect_info.map(|Extension(ConnectInfo(addr))| addr.ip());
+        state.report_refusal(&error, user_identity.user_id, ip);
+        ApiError::from(error)
+    })?;
     Ok(Response::created(message))
 }
 
@@ -84,8 +80,9 @@ pub async fn create_message(
         ("id" = String, Path, description = "Message ID")
     ),
     responses(
+        (status = 400, description = "Bad request - Invalid UUID"),
         (status = 200, description = "Message retrieved successfully", body = Message),
-        (status = 401, description = "Unauthorized"),
+        (status = 401, description = "Unauthorized - Invalid bearer token"),
         (status = 403, description = "Forbidden - Message is private"),
         (status = 404, description = "Message not found"),
         (status = 500, description = "Internal message error")
@@ -93,69 +90,138 @@ pub async fn create_message(
 )]
 #[tracing::instrument(skip(state))]
 pub async fn get_message(
-    Path(id): Path<Uuid>,
+    UuidPath(id): UuidPath,
     State(state): State<AppState>,
-    Extension(user_identity): Extension<UserIdentity>,
+    user_identity: Option<Extension<UserIdentity>>,
 ) -> Result<Response<Message>, ApiError> {
     let message_id = MessageId::from(id);
-    let message = state.service.get_message(&message_id).await?;
-
-    // Authorization: check user can view the channel where this message belongs
-    let allowed = state
-        .authz
-        .check(
-            user_identity.user_id,
-            Permission::ViewChannels,
-            Resource::Channel(message.channel_id.0),
-        )
-        .await
-        .map_err(|_| ApiError::InternalServerError)?;
-    if !allowed {
-        return Err(ApiError::Forbidden);
-    }
-
+    let actor = actor_or_anonymous(user_identity);
+    let message = state.service.get_message(&actor, &message_id).await?;
     Ok(Response::ok(message))
 }
 
 #[utoipa::path(
     get,
-    path = "/channels/{channel_id}/messages",
+    path = "/messages/{id}/replies",
     tag = "messages",
     params(
-        ("channel_id" = String, Path, description = "Channel ID"),
-        GetPaginated
+        ("id" = String, Path, description = "ID of the message replied to"),
+        PageParams
     ),
     responses(
-        (status = 200, description = "List of messages retrieved successfully", body = PaginatedResponse<Message>),
+        (status = 200, description = "Replies to the message, oldest first", body = PaginatedResponse<Message>),
+        (status = 400, description = "Bad request - Invalid UUID"),
         (status = 401, description = "Unauthorized"),
         (status = 403, description = "Forbidden"),
+        (status = 404, description = "Message not found"),
         (status = 500, description = "Internal message error")
     )
 )]
 #[tracing::instrument(skip(state, user_identity, pagination))]
-pub async fn list_messages(
+pub async fn list_replies(
+    UuidPath(id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    Pagination(pagination): Pagination,
+) -> Result<Response<PaginatedResponse<Message>>, ApiError> {
+    let message_id = MessageId::from(id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let (replies, total) = state
+        .service
+        .list_replies(&actor, &message_id, &pagination)
+        .await?;
+
+    Ok(Response::ok(PaginatedResponse {
+        data: replies,
+        total,
+        page: pagination.page,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/messages/{id}/reply-count",
+    tag = "messages",
+    params(
+        ("id" = String, Path, description = "ID of the message replied to")
+    ),
+    responses(
+        (status = 200, description = "Number of replies to the message", body = ReplyCount),
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Message not found"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn reply_count(
+    UuidPath(id): UuidPath,
     State(state): State<AppState>,
     Extension(user_identity): Extension<UserIdentity>,
-    Path(channel_id): Path<Uuid>,
-    Query(pagination): Query<GetPaginated>,
+) -> Result<Response<ReplyCount>, ApiError> {
+    let message_id = MessageId::from(id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let count = state.service.reply_count(&actor, &message_id).await?;
+    Ok(Response::ok(count))
+}
+
+#[derive(Deserialize)]
+pub struct TimeRangeParams {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/channels/{channel_id}/messages",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID"),
+        PageParams,
+        ("from" = Option<String>, Query, description = "RFC 3339 start of a time range, included; requires `to`"),
+        ("to" = Option<String>, Query, description = "RFC 3339 end of a time range, excluded; requires `from`")
+    ),
+    responses(
+        (status = 400, description = "Bad request - Invalid UUID or time range"),
+        (status = 200, description = "List of messages retrieved successfully, oldest first when a time range is given", body = PaginatedResponse<Message>),
+        (status = 401, description = "Unauthorized - Invalid bearer token"),
+        (status = 403, description = "Forbidden - Channel is private"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, pagination))]
+pub async fn list_messages(
+    State(state): State<AppState>,
+    user_identity: Option<Extension<UserIdentity>>,
+    UuidPath(channel_id): UuidPath,
+    Pagination(pagination): Pagination,
+    Query(range): Query<TimeRangeParams>,
 ) -> Result<Response<PaginatedResponse<ReturnedMessage>>, ApiError> {
     let channel = ChannelId::from(channel_id);
+    let actor = actor_or_anonymous(user_identity);
 
-    // Authorization: ensure user can view the channel before listing
-    let allowed = state
-        .authz
-        .check(
-            user_identity.user_id,
-            Permission::ViewChannels,
-            Resource::Channel(channel.0),
-        )
-        .await
-        .map_err(|_| ApiError::InternalServerError)?;
-    if !allowed {
-        return Err(ApiError::Forbidden);
-    }
-
-    let (messages, total) = state.service.list_messages(&channel, &pagination).await?;
+    let (messages, total) = match (range.from, range.to) {
+        (None, None) => {
+            state
+                .service
+                .list_messages(&actor, &channel, &pagination)
+                .await?
+        }
+        (Some(from), Some(to)) => {
+            state
+                .service
+                .list_messages_in_range(&actor, &channel, from, to, &pagination)
+                .await?
+        }
+        _ => {
+            return Err(ApiError::BadRequest {
+                msg: "from and to must be given together".to_string(),
+            });
+        }
+    };
 
     let response = PaginatedResponse {
         data: messages,
@@ -166,11 +232,90 @@ pub async fn list_messages(
     Ok(Response::ok(response))
 }
 
+#[derive(Deserialize)]
+pub struct CursorParams {
+    pub before: Option<MessageId>,
+    pub limit: Option<u32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/channels/{channel_id}/messages/cursor",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID"),
+        ("before" = Option<String>, Query, description = "Last message of the previous page, omitted for the latest page"),
+        ("limit" = Option<u32>, Query, description = "Page size (deployment default, max 50)")
+    ),
+    responses(
+        (status = 400, description = "Bad request - Invalid UUID or the cursor message no longer exists"),
+        (status = 200, description = "Messages older than the cursor, newest first", body = CursorResponse<ReturnedMessage>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, params))]
+pub async fn list_messages_cursor(
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    UuidPath(channel_id): UuidPath,
+    Query(params): Query<CursorParams>,
+) -> Result<Response<CursorResponse<ReturnedMessage>>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+    let (messages, next_cursor) = state
+        .service
+        .list_messages_before(&actor, &channel, params.before.as_ref(), params.limit.unwrap_or(state.default_page_limit))
+        .await?;
+
+    Ok(Response::ok(CursorResponse {
+        data: messages,
+        next_cursor,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/channels/{channel_id}/stream",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    responses(
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 200, description = "Server-sent `message.created`, `message.updated` and `message.deleted` events, named after the change", body = LiveEvent, content_type = "text/event-stream"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn stream_channel(
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    UuidPath(channel_id): UuidPath,
+) -> Result<Sse<impl futures::Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    // Access is checked once on connect. A disconnecting client drops the
+    // stream, which releases its subscription.
+    let live = state.service.subscribe_channel(&actor, &channel).await?;
+    let events = live.map(|event| Event::default().event(event.name()).json_data(event));
+
+    // The keep-alive comment stops idle proxies from closing a quiet stream
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
 #[derive(Deserialize)]
 pub struct SearchParams {
     pub q: String,
+    pub author: Option<Uuid>,
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
     #[serde(flatten)]
-    pub pagination: Option<GetPaginated>,
+    pub pagination: PageParams,
 }
 
 #[utoipa::path(
@@ -180,10 +325,13 @@ pub struct SearchParams {
     params(
         ("channel_id" = String, Path, description = "Channel ID"),
         ("q" = String, Query, description = "Search query"),
-    ("page" = Option<u32>, Query, description = "Page number"),
-    ("limit" = Option<u32>, Query, description = "Page size")
+        ("author" = Option<String>, Query, description = "Only messages by this user"),
+        ("after" = Option<String>, Query, description = "RFC 3339 instant; only messages created after it"),
+        ("before" = Option<String>, Query, description = "RFC 3339 instant; only messages created before it"),
+        PageParams
     ),
     responses(
+        (status = 400, description = "Bad request - Invalid UUID or time window"),
         (status = 200, description = "Search results retrieved successfully", body = PaginatedResponse<Message>),
         (status = 401, description = "Unauthorized"),
         (status = 403, description = "Forbidden"),
@@ -194,30 +342,22 @@ pub struct SearchParams {
 pub async fn search_messages(
     State(state): State<AppState>,
     Extension(user_identity): Extension<UserIdentity>,
-    Path(channel_id): Path<Uuid>,
+    UuidPath(channel_id): UuidPath,
     Query(params): Query<SearchParams>,
 ) -> Result<Response<PaginatedResponse<Message>>, ApiError> {
     let channel = ChannelId::from(channel_id);
-
-    // Authorization: ensure user can view the channel before searching
-    let allowed = state
-        .authz
-        .check(
-            user_identity.user_id,
-            Permission::ViewChannels,
-            Resource::Channel(channel.0),
-        )
-        .await
-        .map_err(|_| ApiError::InternalServerError)?;
-    if !allowed {
-        return Err(ApiError::Forbidden);
-    }
-
-    let pagination = params.pagination.unwrap_or_default();
+    let actor = Actor::from(user_identity.user_id);
+    let pagination = params.pagination.resolve(state.default_page_limit);
+    let criteria = MessageSearchCriteria {
+        query: params.q,
+        author_id: params.author.map(AuthorId::from),
+        after: params.after,
+        before: params.before,
+    };
 
     let (messages, total) = state
         .service
-        .search_messages(&channel, &params.q, &pagination)
+        .search_messages(&actor, &channel, &criteria, &pagination)
         .await?;
 
     let response = PaginatedResponse {
@@ -229,6 +369,164 @@ pub async fn search_messages(
     Ok(Response::ok(response))
 }
 
+#[utoipa::path(
+    get,
+    path = "/channels/{channel_id}/participants",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID"),
+        PageParams
+    ),
+    responses(
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 200, description = "Distinct authors of the channel with their message counts", body = PaginatedResponse<ChannelParticipant>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, pagination))]
+pub async fn list_channel_participants(
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    UuidPath(channel_id): UuidPath,
+    Pagination(pagination): Pagination,
+) -> Result<Response<PaginatedResponse<ChannelParticipant>>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let (participants, total) = state
+        .service
+        .list_channel_participants(&actor, &channel, &pagination)
+        .await?;
+
+    let response = PaginatedResponse {
+        data: participants,
+        total,
+        page: pagination.page,
+    };
+
+    Ok(Response::ok(response))
+}
+
+#[derive(Deserialize)]
+pub struct RecentChannelsParams {
+    pub limit: Option<u32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/recent-channels",
+    tag = "messages",
+    params(
+        ("limit" = Option<u32>, Query, description = "Maximum number of channels (default 10, max 50)")
+    ),
+    responses(
+        (status = 200, description = "Channels ordered by the caller's latest activity", body = Vec<RecentChannel>),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, params))]
+pub async fn list_recent_channels(
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    Query(params): Query<RecentChannelsParams>,
+) -> Result<Response<Vec<RecentChannel>>, ApiError> {
+    let author = AuthorId::from(user_identity.user_id);
+    let recent = state
+        .service
+        .list_recent_channels(&author, params.limit.unwrap_or(10))
+        .await?;
+    Ok(Response::ok(recent))
+}
+
+#[utoipa::path(
+    post,
+    path = "/channels/latest",
+    tag = "messages",
+    request_body = LatestMessagesRequest,
+    responses(
+        (status = 200, description = "Latest message of each channel, keyed by channel ID; channels without a visible message are absent", body = HashMap<String, Message>),
+        (status = 400, description = "Bad request - Too many channels"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, request))]
+pub async fn latest_messages_for_channels(
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    Json(request): Json<LatestMessagesRequest>,
+) -> Result<Response<HashMap<ChannelId, Message>>, ApiError> {
+    let actor = Actor::from(user_identity.user_id);
+    let latest = state
+        .service
+        .latest_messages_for_channels(&actor, &request.channel_ids)
+        .await?;
+    Ok(Response::ok(latest))
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/export",
+    tag = "messages",
+    responses(
+        (status = 200, description = "JSON document with every message written by the caller", content_type = "application/json"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn export_user_data(
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<AxumResponse, ApiError> {
+    // Only the caller's own data can be exported through this route
+    let author = AuthorId::from(user_identity.user_id);
+    let messages = state.service.export_user_data(&author).await?;
+
+    // Stream `{"user_id": ..., "messages": [...]}` one message at a time
+    let opening = format!("{{\"user_id\":\"{}\",\"messages\":[", user_identity.user_id);
+    let body = stream::once(async move { Ok::<_, CoreError>(opening) })
+        .chain(messages.enumerate().map(|(index, message)| {
+            let json = serde_json::to_string(&message?)
+                .map_err(|e| CoreError::SerializationError { msg: e.to_string() })?;
+            Ok(if index == 0 { json } else { format!(",{json}") })
+        }))
+        .chain(stream::once(async { Ok("]}".to_string()) }));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"export.json\""),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/me/data",
+    tag = "messages",
+    responses(
+        (status = 200, description = "The caller's messages were anonymized or deleted", body = ErasureReport),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn erase_user_data(
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<Response<ErasureReport>, ApiError> {
+    // Only the caller's own data can be erased through this route
+    let author = AuthorId::from(user_identity.user_id);
+    let report = state.service.erase_user_data(&author).await?;
+    Ok(Response::deleted(report))
+}
+
 #[utoipa::path(
     put,
     path = "/messages/{id}",
@@ -248,21 +546,16 @@ pub async fn search_messages(
 )]
 #[tracing::instrument(skip(state, user_identity, request))]
 pub async fn update_message(
-    Path(id): Path<Uuid>,
+    UuidPath(id): UuidPath,
     State(state): State<AppState>,
     Extension(user_identity): Extension<UserIdentity>,
     Json(request): Json<UpdateMessageRequest>,
 ) -> Result<Response<Message>, ApiError> {
     let message_id = MessageId::from(id);
-
-    // Check if message exists and user is the owner
-    let existing_message = state.service.get_message(&message_id).await?;
-    if existing_message.author_id.0 != user_identity.user_id {
-        return Err(ApiError::Forbidden);
-    }
+    let actor = Actor::from(user_identity.user_id);
 
     let input = request.into_input(message_id);
-    let message = state.service.update_message(input).await?;
+    let message = state.service.update_message(&actor, input).await?;
     Ok(Response::ok(message))
 }
 
@@ -274,6 +567,7 @@ pub async fn update_message(
         ("id" = String, Path, description = "Message ID")
     ),
     responses(
+        (status = 400, description = "Bad request - Invalid UUID"),
         (status = 200, description = "Message deleted successfully"),
         (status = 401, description = "Unauthorized"),
         (status = 403, description = "Forbidden - Not the message owner"),
@@ -283,18 +577,560 @@ pub async fn update_message(
 )]
 #[tracing::instrument(skip(state, user_identity))]
 pub async fn delete_message(
-    Path(id): Path<Uuid>,
+    UuidPath(id): UuidPath,
     State(state): State<AppState>,
     Extension(user_identity): Extension<UserIdentity>,
 ) -> Result<Response<()>, ApiError> {
     let message_id = MessageId::from(id);
+    let actor = Actor::from(user_identity.user_id);
 
-    // Check if message exists and user is the owner
-    let existing_message = state.service.get_message(&message_id).await?;
-    if existing_message.author_id.0 != user_identity.user_id {
-        return Err(ApiError::Forbidden);
-    }
-
-    state.service.delete_message(&message_id).await?;
+    state.service.delete_message(&actor, &message_id).await?;
     Ok(Response::deleted(()))
 }
+
+#[utoipa::path(
+    post,
+    path = "/channels/{channel_id}/typing",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    responses(
+        (status = 204, description = "The channel was told the user is typing"),
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn publish_typing(
+    UuidPath(channel_id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<StatusCode, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    state.service.publish_typing(&actor, &channel).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/channels/{channel_id}/messages/bulk-delete",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    request_body(content = Vec<MessageId>, description = "Messages to delete"),
+    responses(
+        (status = 200, description = "Per-message outcome of the batch", body = BatchResult),
+        (status = 400, description = "Bad request - Invalid UUID or too many messages"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, ids))]
+pub async fn bulk_delete_messages(
+    UuidPath(channel_id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    Json(ids): Json<Vec<MessageId>>,
+) -> Result<Response<BatchResult>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let result = state.service.bulk_delete(&actor, &channel, &ids).await?;
+    Ok(Response::ok(result))
+}
+
+#[utoipa::path(
+    get,
+    path = "/channels/{channel_id}/messages/pinned",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID"),
+        PageParams
+    ),
+    responses(
+        (status = 200, description = "Pinned messages in display order", body = PaginatedResponse<Message>),
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, pagination))]
+pub async fn list_pinned_messages(
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    UuidPath(channel_id): UuidPath,
+    Pagination(pagination): Pagination,
+) -> Result<Response<PaginatedResponse<Message>>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    // A channel holds at most MAX_PINS_PER_CHANNEL pins, so they are paged in memory
+    let pinned = state.service.list_pinned_messages(&actor, &channel).await?;
+    let total = pinned.len() as u64;
+    let limit = pagination.limit as usize;
+    let offset = (pagination.page.max(1) as usize - 1) * limit;
+
+    let response = PaginatedResponse {
+        data: pinned.into_iter().skip(offset).take(limit).collect(),
+        total,
+        page: pagination.page,
+    };
+
+    Ok(Response::ok(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/channels/{channel_id}/pins",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    request_body = PinMessagesRequest,
+    responses(
+        (status = 200, description = "Per-message outcome of the batch", body = BatchResult),
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, request))]
+pub async fn pin_messages(
+    UuidPath(channel_id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    Json(request): Json<PinMessagesRequest>,
+) -> Result<Response<BatchResult>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let result = state
+        .service
+        .pin_messages(&actor, &channel, &request.message_ids)
+        .await?;
+    Ok(Response::ok(result))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/channels/{channel_id}/pins",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    request_body = PinMessagesRequest,
+    responses(
+        (status = 200, description = "Per-message outcome of the batch", body = BatchResult),
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, request))]
+pub async fn unpin_messages(
+    UuidPath(channel_id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    Json(request): Json<PinMessagesRequest>,
+) -> Result<Response<BatchResult>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let result = state
+        .service
+        .unpin_messages(&actor, &channel, &request.message_ids)
+        .await?;
+    Ok(Response::ok(result))
+}
+
+#[utoipa::path(
+    put,
+    path = "/channels/{channel_id}/pins/order",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    request_body = ReorderPinsRequest,
+    responses(
+        (status = 200, description = "Pinned messages in their new order", body = Vec<Message>),
+        (status = 400, description = "Bad request - Invalid UUID or the ids don't match the channel's pins"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, request))]
+pub async fn reorder_pins(
+    UuidPath(channel_id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    Json(request): Json<ReorderPinsRequest>,
+) -> Result<Response<Vec<Message>>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let pinned = state
+        .service
+        .reorder_pins(&actor, &channel, &request.message_ids)
+        .await?;
+    Ok(Response::ok(pinned))
+}
+
+#[utoipa::path(
+    get,
+    path = "/channels/{channel_id}/metadata",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    responses(
+        (status = 200, description = "Channel metadata, including its sticky message", body = ChannelMetadata),
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn get_channel_metadata(
+    UuidPath(channel_id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<Response<ChannelMetadata>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let metadata = state.service.get_channel_metadata(&actor, &channel).await?;
+    Ok(Response::ok(metadata))
+}
+
+#[utoipa::path(
+    put,
+    path = "/channels/{channel_id}/sticky",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    request_body = SetStickyRequest,
+    responses(
+        (status = 200, description = "Sticky message set, replacing any previous one", body = ChannelMetadata),
+        (status = 400, description = "Bad request - Invalid UUID or the message belongs to another channel"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Message not found"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, request))]
+pub async fn set_sticky(
+    UuidPath(channel_id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    Json(request): Json<SetStickyRequest>,
+) -> Result<Response<ChannelMetadata>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let metadata = state
+        .service
+        .set_sticky(&actor, &channel, &request.message_id)
+        .await?;
+    Ok(Response::ok(metadata))
+}
+
+#[utoipa::path(
+    put,
+    path = "/channels/{channel_id}/read",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    request_body = MarkReadRequest,
+    responses(
+        (status = 200, description = "Read state after the call, unchanged for an older message", body = ChannelReadState),
+        (status = 400, description = "Bad request - Invalid UUID or the message belongs to another channel"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Message not found"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, request))]
+pub async fn mark_read(
+    UuidPath(channel_id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    Json(request): Json<MarkReadRequest>,
+) -> Result<Response<ChannelReadState>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let read_state = state
+        .service
+        .mark_read(&actor, &channel, &request.message_id)
+        .await?;
+    Ok(Response::ok(read_state))
+}
+
+#[utoipa::path(
+    get,
+    path = "/channels/{channel_id}/unread-count",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    responses(
+        (status = 200, description = "Messages created after the last one read", body = UnreadCount),
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn unread_count(
+    UuidPath(channel_id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<Response<UnreadCount>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let unread = state.service.unread_count(&actor, &channel).await?;
+    Ok(Response::ok(unread))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/channels/{channel_id}/sticky",
+    tag = "messages",
+    params(
+        ("channel_id" = String, Path, description = "Channel ID")
+    ),
+    responses(
+        (status = 200, description = "Sticky message cleared", body = ChannelMetadata),
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn clear_sticky(
+    UuidPath(channel_id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<Response<ChannelMetadata>, ApiError> {
+    let channel = ChannelId::from(channel_id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let metadata = state.service.clear_sticky(&actor, &channel).await?;
+    Ok(Response::ok(metadata))
+}
+
+#[utoipa::path(
+    post,
+    path = "/messages/{id}/pin",
+    tag = "messages",
+    params(
+        ("id" = String, Path, description = "Message ID")
+    ),
+    request_body = PinMessageRequest,
+    responses(
+        (status = 200, description = "Message pinned", body = Message),
+        (status = 400, description = "Bad request - Invalid UUID or reason too long"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Message not found"),
+        (status = 409, description = "Conflict - Channel pin limit reached"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity, request))]
+pub async fn pin_message(
+    UuidPath(id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+    Json(request): Json<PinMessageRequest>,
+) -> Result<Response<Message>, ApiError> {
+    let message_id = MessageId::from(id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let pinned = state
+        .service
+        .pin_message(&actor, &message_id, request.reason)
+        .await?;
+    Ok(Response::ok(pinned))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/messages/{id}/pin",
+    tag = "messages",
+    params(
+        ("id" = String, Path, description = "Message ID")
+    ),
+    responses(
+        (status = 200, description = "Message unpinned and its pin details cleared", body = Message),
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Message not found"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn unpin_message(
+    UuidPath(id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<Response<Message>, ApiError> {
+    let message_id = MessageId::from(id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let unpinned = state.service.unpin_message(&actor, &message_id).await?;
+    Ok(Response::ok(unpinned))
+}
+
+#[utoipa::path(
+    post,
+    path = "/messages/{id}/reactions/{emoji}/toggle",
+    tag = "messages",
+    params(
+        ("id" = String, Path, description = "Message ID"),
+        ("emoji" = String, Path, description = "Emoji or shortcode, URL-encoded")
+    ),
+    responses(
+        (status = 200, description = "Reaction state after the toggle", body = ReactionToggle),
+        (status = 400, description = "Bad request - Invalid UUID or emoji"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Message not found"),
+        (status = 409, description = "Conflict - Message reaction limit reached"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn toggle_reaction(
+    UuidPath(id): UuidPath,
+    Path((_, emoji)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<Response<ReactionToggle>, ApiError> {
+    let message_id = MessageId::from(id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let toggle = state
+        .service
+        .toggle_reaction(&actor, &message_id, &emoji)
+        .await?;
+    Ok(Response::ok(toggle))
+}
+
+#[utoipa::path(
+    put,
+    path = "/messages/{id}/reactions/{emoji}",
+    tag = "messages",
+    params(
+        ("id" = String, Path, description = "Message ID"),
+        ("emoji" = String, Path, description = "Emoji or shortcode, URL-encoded")
+    ),
+    responses(
+        (status = 200, description = "Reaction added, or already present", body = ReactionToggle),
+        (status = 400, description = "Bad request - Invalid UUID or emoji"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Message not found"),
+        (status = 409, description = "Conflict - Message reaction limit reached"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn add_reaction(
+    UuidPath(id): UuidPath,
+    Path((_, emoji)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<Response<ReactionToggle>, ApiError> {
+    let message_id = MessageId::from(id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let reaction = state
+        .service
+        .add_reaction(&actor, &message_id, &emoji)
+        .await?;
+    Ok(Response::ok(reaction))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/messages/{id}/reactions/{emoji}",
+    tag = "messages",
+    params(
+        ("id" = String, Path, description = "Message ID"),
+        ("emoji" = String, Path, description = "Emoji or shortcode, URL-encoded")
+    ),
+    responses(
+        (status = 200, description = "Reaction removed, or already absent", body = ReactionToggle),
+        (status = 400, description = "Bad request - Invalid UUID or emoji"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Message not found"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn remove_reaction(
+    UuidPath(id): UuidPath,
+    Path((_, emoji)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<Response<ReactionToggle>, ApiError> {
+    let message_id = MessageId::from(id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let reaction = state
+        .service
+        .remove_reaction(&actor, &message_id, &emoji)
+        .await?;
+    Ok(Response::ok(reaction))
+}
+
+#[utoipa::path(
+    get,
+    path = "/messages/{id}/reactions",
+    tag = "messages",
+    params(
+        ("id" = String, Path, description = "Message ID")
+    ),
+    responses(
+        (status = 200, description = "Reactions grouped by emoji", body = Vec<ReactionSummary>),
+        (status = 400, description = "Bad request - Invalid UUID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Message not found"),
+        (status = 500, description = "Internal message error")
+    )
+)]
+#[tracing::instrument(skip(state, user_identity))]
+pub async fn list_reactions(
+    UuidPath(id): UuidPath,
+    State(state): State<AppState>,
+    Extension(user_identity): Extension<UserIdentity>,
+) -> Result<Response<Vec<ReactionSummary>>, ApiError> {
+    let message_id = MessageId::from(id);
+    let actor = Actor::from(user_identity.user_id);
+
+    let reactions = state.service.list_reactions(&actor, &message_id).await?;
+    Ok(Response::ok(reactions))
+}
@@ -4,7 +4,29 @@ use crate::{
     http::messages::handlers::{
         __path_create_message, __path_delete_message, __path_get_message, __path_list_messages,
         __path_update_message, create_message, delete_message, get_message, list_messages,
+        __path_bulk_delete_messages, bulk_delete_messages,
+        __path_publish_typing, publish_typing,
            __path_search_messages, update_message, search_messages,
+        __path_list_recent_channels, list_recent_channels,
+        __path_latest_messages_for_channels, latest_messages_for_channels,
+        __path_list_messages_cursor, list_messages_cursor,
+        __path_stream_channel, stream_channel,
+        __path_list_channel_participants, list_channel_participants,
+        __path_export_user_data, export_user_data,
+        __path_erase_user_data, erase_user_data,
+        __path_pin_messages, __path_unpin_messages, pin_messages, unpin_messages,
+        __path_list_pinned_messages, list_pinned_messages,
+        __path_reorder_pins, reorder_pins,
+        __path_get_channel_metadata, get_channel_metadata,
+        __path_set_sticky, __path_clear_sticky, set_sticky, clear_sticky,
+        __path_mark_read, mark_read,
+        __path_list_replies, list_replies,
+        __path_reply_count, reply_count,
+        __path_unread_count, unread_count,
+        __path_pin_message, __path_unpin_message, pin_message, unpin_message,
+        __path_toggle_reaction, toggle_reaction,
+        __path_add_reaction, __path_remove_reaction, add_reaction, remove_reaction,
+        __path_list_reactions, list_reactions,
     },
     http::server::AppState,
 };
@@ -12,9 +34,36 @@ use crate::{
 pub fn message_routes() -> OpenApiRouter<AppState> {
     OpenApiRouter::new()
         .routes(routes!(create_message))
-        .routes(routes!(get_message))
-        .routes(routes!(list_messages))
+        .routes(routes!(list_messages_cursor))
+        .routes(routes!(stream_channel))
         .routes(routes!(search_messages))
+        .routes(routes!(list_channel_participants))
+        .routes(routes!(list_recent_channels))
+        .routes(routes!(latest_messages_for_channels))
+        .routes(routes!(export_user_data))
+        .routes(routes!(erase_user_data))
+        .routes(routes!(pin_messages, unpin_messages))
+        .routes(routes!(list_pinned_messages))
+        .routes(routes!(reorder_pins))
+        .routes(routes!(get_channel_metadata))
+        .routes(routes!(set_sticky, clear_sticky))
+        .routes(routes!(mark_read))
+        .routes(routes!(unread_count))
+        .routes(routes!(pin_message, unpin_message))
+        .routes(routes!(toggle_reaction))
+        .routes(routes!(add_reaction, remove_reaction))
+        .routes(routes!(list_reactions))
+        .routes(routes!(list_replies))
+        .routes(routes!(reply_count))
         .routes(routes!(update_message))
         .routes(routes!(delete_message))
+        .routes(routes!(bulk_delete_messages))
+        .routes(routes!(publish_typing))
+}
+
+/// Reads also open to anonymous callers, authorized against public channels
+pub fn public_message_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get_message))
+        .routes(routes!(list_messages))
 }
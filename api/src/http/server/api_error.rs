@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use messages_core::domain::common::CoreError;
@@ -26,6 +26,10 @@ pub enum ApiError {
     BadRequest { msg: String },
     #[error("Conflict")]
     Conflict { error_code: String },
+    #[error("Invalid UUID in path parameter '{field}'")]
+    InvalidUuid { field: String },
+    #[error("Too many requests, retry in {retry_after_secs} seconds")]
+    TooManyRequests { retry_after_secs: u64 },
 }
 
 impl ApiError {
@@ -39,6 +43,8 @@ impl ApiError {
             ApiError::NotFound { .. } => StatusCode::NOT_FOUND,
             ApiError::BadRequest { .. } => StatusCode::BAD_REQUEST,
             ApiError::Conflict { .. } => StatusCode::CONFLICT,
+            ApiError::InvalidUuid { .. } => StatusCode::BAD_REQUEST,
+            ApiError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
         }
     }
 }
@@ -51,11 +57,25 @@ impl Into<ErrorBody> for ApiError {
             ApiError::Conflict { error_code } => ErrorBody {
                 message: message,
                 error_code: Some(error_code),
+                field: None,
+                status: status,
+            },
+            ApiError::InvalidUuid { field } => ErrorBody {
+                message: message,
+                error_code: Some("INVALID_UUID".to_string()),
+                field: Some(field),
+                status: status,
+            },
+            ApiError::TooManyRequests { .. } => ErrorBody {
+                message: message,
+                error_code: Some("RATE_LIMITED".to_string()),
+                field: None,
                 status: status,
             },
             _ => ErrorBody {
                 message: message,
                 error_code: None,
+                field: None,
                 status: status,
             },
         }
@@ -64,7 +84,17 @@ impl Into<ErrorBody> for ApiError {
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        (self.status_code(), Json::<ErrorBody>(self.into())).into_response()
+        let retry_after = match &self {
+            ApiError::TooManyRequests { retry_after_secs } => Some(*retry_after_secs),
+            _ => None,
+        };
+        let mut response = (self.status_code(), Json::<ErrorBody>(self.into())).into_response();
+        if let Some(retry_after_secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+        }
+        response
     }
 }
 
@@ -74,10 +104,40 @@ impl From<CoreError> for ApiError {
             CoreError::Unhealthy => ApiError::ServiceUnavailable {
                 msg: "Service is unhealthy".to_string(),
             },
+            CoreError::Forbidden => ApiError::Forbidden,
             CoreError::MessageNotFound { .. } => ApiError::NotFound,
             CoreError::InvalidMessageName => ApiError::BadRequest {
                 msg: "Server name cannot be empty".to_string(),
             },
+            CoreError::EphemeralReply => ApiError::BadRequest {
+                msg: "Ephemeral messages cannot be replies".to_string(),
+            },
+            CoreError::InvalidBatch { reason } | CoreError::InvalidPinOrder { reason } => {
+                ApiError::BadRequest { msg: reason }
+            }
+            error @ (CoreError::InvalidReactionEmoji { .. }
+            | CoreError::PinReasonTooLong { .. }
+            | CoreError::MessageTooLong { .. }
+            | CoreError::MessageNotInChannel { .. }
+            | CoreError::InvalidReplyTarget { .. }
+            | CoreError::InvalidCursor { .. }
+            | CoreError::InvalidTimeRange) => {
+                ApiError::BadRequest {
+                    msg: error.to_string(),
+                }
+            }
+            CoreError::PinLimitReached => ApiError::Conflict {
+                error_code: "PIN_LIMIT_REACHED".to_string(),
+            },
+            CoreError::ReactionLimitReached => ApiError::Conflict {
+                error_code: "REACTION_LIMIT_REACHED".to_string(),
+            },
+            CoreError::DuplicateContent => ApiError::Conflict {
+                error_code: "DUPLICATE_CONTENT".to_string(),
+            },
+            CoreError::RateLimited { retry_after_secs } => {
+                ApiError::TooManyRequests { retry_after_secs }
+            }
             _ => ApiError::InternalServerError,
         }
     }
@@ -87,5 +147,8 @@ impl From<CoreError> for ApiError {
 pub struct ErrorBody {
     pub message: String,
     pub error_code: Option<String>,
+    /// Request field the error refers to, when there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
     pub status: u16,
 }
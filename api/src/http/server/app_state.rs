@@ -1,19 +1,85 @@
-use messages_core::{MessagesService, application::MessageRepositories};
-use std::sync::Arc;
+use messages_core::{
+    MessagesService,
+    application::MessageRepositories,
+    domain::common::{CoreError, DEFAULT_PAGE_LIMIT},
+};
+use std::{
+    net::IpAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use uuid::Uuid;
 
-use crate::http::server::authorization::DynAuthz;
+use crate::http::server::{
+    authorization::{CachedAuthz, DynAuthz, ServiceAuthorizer},
+    metrics::Metrics,
+    security::{DynSecurityEventSink, LoggingSecurityEventSink, SecurityEvent},
+};
 
 /// Application state shared across request handlers
 #[derive(Clone)]
 pub struct AppState {
     pub service: MessagesService,
     pub authz: DynAuthz,
+    pub metrics: Arc<Metrics>,
+    /// Page size of listings whose request omits `limit`
+    pub default_page_limit: u32,
+    /// Receives the requests refused for flooding or for their content
+    pub security_events: DynSecurityEventSink,
+    /// Set once a graceful shutdown starts, so readiness fails while traffic drains
+    draining: Arc<AtomicBool>,
 }
 
 impl AppState {
     /// Create a new AppState with the given service and authorization client
+    ///
+    /// The client is wrapped so that repeated checks within a request are
+    /// answered from a per-request cache, then handed to the service which
+    /// enforces it on every operation and counts the ones it refuses in `metrics`.
     pub fn new(service: MessagesService, authz: DynAuthz) -> Self {
-        Self { service, authz }
+        let metrics = Arc::new(Metrics::new());
+        let authz: DynAuthz = Arc::new(CachedAuthz::new(authz));
+        let service = service.with_authorizer(Arc::new(ServiceAuthorizer::new(
+            authz.clone(),
+            metrics.clone(),
+        )));
+        Self {
+            service,
+            authz,
+            metrics,
+            default_page_limit: DEFAULT_PAGE_LIMIT,
+            security_events: Arc::new(LoggingSecurityEventSink),
+            draining: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Use another page size for listings whose request omits `limit`
+    pub fn with_default_page_limit(mut self, default_page_limit: u32) -> Self {
+        self.default_page_limit = default_page_limit;
+        self
+    }
+
+    pub fn with_security_events(mut self, security_events: DynSecurityEventSink) -> Self {
+        self.security_events = security_events;
+        self
+    }
+
+    /// Report `user_id`'s request to the security event sink if `error` refused it
+    pub fn report_refusal(&self, error: &CoreError, user_id: Uuid, ip: Option<IpAddr>) {
+        if let Some(event) = SecurityEvent::refusal(error, user_id, ip) {
+            self.security_events.record(event);
+        }
+    }
+
+    /// Report the instance as not ready from now on; liveness is unaffected
+    pub fn begin_draining(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
     }
 
     /// Shutdown the underlying database pool
@@ -34,6 +100,6 @@ impl From<MessageRepositories> for AppState {
             repositories.outbox_repository,
         );
         let authz = Arc::new(crate::http::server::authorization::DummyAuthz::new());
-        AppState { service, authz }
+        AppState::new(service, authz)
     }
 }
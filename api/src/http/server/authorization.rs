@@ -1,28 +1,27 @@
-use std::sync::Arc;
+use messages_core::domain::{
+    authorization::{entities::Actor, ports::Authorizer},
+    common::CoreError,
+};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+};
 use uuid::Uuid;
 
-/// A small, local abstraction for authorization checks used by HTTP handlers.
-///
-/// We provide a DummyAuthz (allow-all) implementation by default, and a
-/// SpiceDB-backed implementation when the `spicedb` feature is enabled.
-pub enum Resource {
-    Channel(Uuid),
-    User(Uuid),
-}
+use crate::http::server::metrics::Metrics;
 
-#[derive(Clone, Copy, Debug)]
-pub enum Permission {
-    ViewChannels,
-    SendMessages,
-    ManageMessages,
-    ManageChannels,
-    AttachFiles
-}
+pub use messages_core::domain::authorization::entities::{Permission, Resource};
 
 /// Simple error type for authz failures.
 #[derive(Debug)]
 pub struct AuthzError(pub String);
 
+/// A small, local abstraction for authorization checks.
+///
+/// We provide a DummyAuthz (allow-all) implementation by default, and a
+/// SpiceDB-backed implementation when the `spicedb` feature is enabled.
+/// The domain services reach it through [`ServiceAuthorizer`].
 #[async_trait::async_trait]
 pub trait Authorization: Send + Sync + 'static {
     async fn check(
@@ -58,6 +57,92 @@ impl Authorization for DummyAuthz {
 /// Public wrapper so AppState can hold a shared authorization client.
 pub type DynAuthz = Arc<dyn Authorization>;
 
+tokio::task_local! {
+    /// Answers already given during the current request
+    static REQUEST_AUTHZ_CACHE: Mutex<HashMap<(Uuid, Permission, Resource), bool>>;
+}
+
+/// Run `future` with its own authorization cache, dropped when it completes
+pub async fn with_request_authz_cache<F: Future>(future: F) -> F::Output {
+    REQUEST_AUTHZ_CACHE
+        .scope(Mutex::new(HashMap::new()), future)
+        .await
+}
+
+/// Reuses the answer to a check already made during the same request
+///
+/// Only checks run inside [`with_request_authz_cache`] are cached, and only
+/// for that scope, so a revoked permission is seen by the next request.
+/// Failed checks are not cached.
+pub struct CachedAuthz {
+    inner: DynAuthz,
+}
+
+impl CachedAuthz {
+    pub fn new(inner: DynAuthz) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait::async_trait]
+impl Authorization for CachedAuthz {
+    async fn check(
+        &self,
+        actor: Uuid,
+        permission: Permission,
+        resource: Resource,
+    ) -> Result<bool, AuthzError> {
+        let key = (actor, permission, resource);
+        let cached = REQUEST_AUTHZ_CACHE
+            .try_with(|cache| cache.lock().unwrap().get(&key).copied())
+            .ok()
+            .flatten();
+        if let Some(allowed) = cached {
+            return Ok(allowed);
+        }
+
+        let allowed = self.inner.check(actor, permission, resource).await?;
+        let _ = REQUEST_AUTHZ_CACHE.try_with(|cache| cache.lock().unwrap().insert(key, allowed));
+        Ok(allowed)
+    }
+}
+
+/// Exposes an authorization client to the domain services
+///
+/// Only the operations they refuse are counted in `metrics`; a channel left
+/// out of a listing is not a denied request.
+pub struct ServiceAuthorizer {
+    authz: DynAuthz,
+    metrics: Arc<Metrics>,
+}
+
+impl ServiceAuthorizer {
+    pub fn new(authz: DynAuthz, metrics: Arc<Metrics>) -> Self {
+        Self { authz, metrics }
+    }
+}
+
+#[async_trait::async_trait]
+impl Authorizer for ServiceAuthorizer {
+    async fn check(
+        &self,
+        actor: &Actor,
+        permission: Permission,
+        resource: Resource,
+    ) -> Result<bool, CoreError> {
+        self.authz
+            .check(actor.0, permission, resource)
+            .await
+            .map_err(|e| CoreError::UnknownError {
+                message: format!("authorization check failed: {}", e.0),
+            })
+    }
+
+    fn record_denial(&self, _actor: &Actor, permission: Permission, resource: Resource) {
+        self.metrics.record_authz_denial(permission, resource.kind());
+    }
+}
+
 mod spicedb_impl {
     use super::*;
     use beep_authz::{
@@ -0,0 +1,77 @@
+use axum::{
+    extract::{FromRequestParts, Query, RawPathParams},
+    http::request::Parts,
+};
+use messages_core::domain::common::GetPaginated;
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::http::server::{ApiError, AppState};
+
+/// Path extractor for the UUID held by the route's first path parameter
+///
+/// Any later parameter is left to another extractor, as the reaction routes
+/// do for `{emoji}` after `{id}`. Unlike `Path<Uuid>`, a malformed value is
+/// rejected with a structured [`ApiError::InvalidUuid`] naming the parameter.
+#[derive(Debug, Clone, Copy)]
+pub struct UuidPath(pub Uuid);
+
+impl<S> FromRequestParts<S> for UuidPath
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let params = RawPathParams::from_request_parts(parts, state)
+            .await
+            .map_err(|_| ApiError::InternalServerError)?;
+        let (field, value) = params
+            .iter()
+            .next()
+            .ok_or(ApiError::InternalServerError)?;
+
+        Uuid::parse_str(value)
+            .map(UuidPath)
+            .map_err(|_| ApiError::InvalidUuid {
+                field: field.to_string(),
+            })
+    }
+}
+
+/// Query parameters of paginated listings, both optional
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageParams {
+    /// Page number, starting at 1
+    pub page: Option<u32>,
+    /// Page size, defaults to the deployment's default page limit
+    pub limit: Option<u32>,
+}
+
+impl PageParams {
+    /// Fill in what the client omitted and cap the page size
+    pub fn resolve(&self, default_limit: u32) -> GetPaginated {
+        GetPaginated {
+            page: self.page.unwrap_or(1),
+            limit: self.limit.unwrap_or(default_limit),
+        }
+        .normalized()
+    }
+}
+
+/// Pagination of a listing, with the configured default page limit applied
+#[derive(Debug)]
+pub struct Pagination(pub GetPaginated);
+
+impl FromRequestParts<AppState> for Pagination {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<PageParams>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| ApiError::BadRequest { msg: e.body_text() })?;
+        Ok(Pagination(params.resolve(state.default_page_limit)))
+    }
+}
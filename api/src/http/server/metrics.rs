@@ -0,0 +1,52 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex};
+
+use crate::http::server::authorization::Permission;
+
+/// In-process counters exposed in the Prometheus text format on `/metrics`
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Requests refused by authorization, keyed by permission and resource kind
+    authz_denials: Mutex<BTreeMap<(&'static str, &'static str), u64>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_authz_denial(&self, permission: Permission, resource_kind: &'static str) {
+        let mut denials = self.authz_denials.lock().unwrap();
+        *denials
+            .entry((permission.as_str(), resource_kind))
+            .or_default() += 1;
+    }
+
+    pub fn authz_denials(&self, permission: Permission, resource_kind: &str) -> u64 {
+        let denials = self.authz_denials.lock().unwrap();
+        denials
+            .get(&(permission.as_str(), resource_kind))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Render every counter in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let denials = self.authz_denials.lock().unwrap();
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "# HELP authz_denials_total Authorization checks that were denied"
+        );
+        let _ = writeln!(out, "# TYPE authz_denials_total counter");
+        for ((permission, resource), count) in denials.iter() {
+            let _ = writeln!(
+                out,
+                "authz_denials_total{{permission=\"{}\",resource=\"{}\"}} {}",
+                permission, resource, count
+            );
+        }
+
+        out
+    }
+}
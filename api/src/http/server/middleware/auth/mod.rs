@@ -1,80 +1,223 @@
-use axum::{extract::FromRequestParts, http::request::Parts};
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::request::Parts,
+};
+use async_trait::async_trait;
 use beep_auth::{AuthRepository, KeycloakAuthRepository};
 use uuid::Uuid;
 
-use crate::http::server::ApiError;
+use crate::http::server::{
+    ApiError,
+    security::{
+        DynSecurityEventSink, LoggingSecurityEventSink, SecurityEvent, SecurityEventKind,
+    },
+};
 pub mod entities;
+pub mod token_cache;
 
-pub struct AuthMiddleware;
-
-impl FromRequestParts<KeycloakAuthRepository> for AuthMiddleware {
-    type Rejection = ApiError;
-
-    async fn from_request_parts(
-        parts: &mut Parts,
-        state: &KeycloakAuthRepository,
-    ) -> Result<Self, Self::Rejection> {
-        tracing::debug!(
-            "Authentication middleware: checking request to {}",
-            parts.uri
-        );
-
-        // Extract the Authorization header
-        let auth_header = parts.headers.get(axum::http::header::AUTHORIZATION);
-
-        if auth_header.is_none() {
-            tracing::warn!("Authentication failed: Authorization header missing");
-            return Err(ApiError::Unauthorized);
-        }
-
-        // Ensure the header exists and starts with "Bearer "
-        let auth_value = auth_header.unwrap().to_str().map_err(|e| {
-            tracing::warn!(
-                "Authentication failed: Authorization header is not valid UTF-8: {}",
-                e
-            );
-            ApiError::Unauthorized
-        })?;
-
-        tracing::debug!("Authorization header present, checking Bearer prefix");
+use token_cache::TokenCache;
 
-        let token = auth_value.strip_prefix("Bearer ").ok_or_else(|| {
-            tracing::warn!("Authentication failed: Authorization header doesn't start with 'Bearer '. Header value starts with: {:?}", &auth_value.chars().take(10).collect::<String>());
-            ApiError::Unauthorized
-        })?;
+/// Validates bearer tokens and resolves the user they were issued to
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Id of the token's user, or the reason the token was rejected
+    async fn identify(&self, token: &str) -> Result<Uuid, &'static str>;
+}
 
-        tracing::debug!(
-            "Token extracted, length: {} chars, validating with Keycloak",
-            token.len()
-        );
+pub type DynIdentityProvider = Arc<dyn IdentityProvider>;
 
-        // Validate the token
-        let keycloak_identity = state.identify(token).await.map_err(|e| {
+#[async_trait]
+impl IdentityProvider for KeycloakAuthRepository {
+    async fn identify(&self, token: &str) -> Result<Uuid, &'static str> {
+        let keycloak_identity = AuthRepository::identify(self, token).await.map_err(|e| {
             tracing::warn!(
                 "Authentication failed: Keycloak token validation failed: {:?}",
                 e
             );
-            ApiError::Unauthorized
+            "token rejected by identity provider"
         })?;
 
         let user_id_str = keycloak_identity.id();
         tracing::debug!("Keycloak validation successful, user ID: {}", user_id_str);
 
-        let user_id = Uuid::try_parse(user_id_str).map_err(|e| {
+        Uuid::try_parse(user_id_str).map_err(|e| {
             tracing::error!(
                 "Authentication failed: Invalid UUID format from Keycloak: '{}', error: {}",
                 user_id_str,
                 e
             );
-            ApiError::Unauthorized
-        })?;
+            "identity provider returned an invalid user id"
+        })
+    }
+}
+
+/// State the authentication middleware runs with
+#[derive(Clone)]
+pub struct AuthState {
+    pub identity: DynIdentityProvider,
+    /// Identities already resolved, so a token is only sent to the provider once
+    pub token_cache: Arc<TokenCache>,
+    /// Receives every rejected authentication attempt
+    pub security_events: DynSecurityEventSink,
+}
+
+impl AuthState {
+    pub fn new(keycloak: KeycloakAuthRepository) -> Self {
+        Self::with_identity_provider(Arc::new(keycloak))
+    }
+
+    pub fn with_identity_provider(identity: DynIdentityProvider) -> Self {
+        Self {
+            identity,
+            token_cache: Arc::new(TokenCache::default()),
+            security_events: Arc::new(LoggingSecurityEventSink),
+        }
+    }
+
+    pub fn with_token_cache(mut self, token_cache: TokenCache) -> Self {
+        self.token_cache = Arc::new(token_cache);
+        self
+    }
+
+    pub fn with_security_events(mut self, security_events: DynSecurityEventSink) -> Self {
+        self.security_events = security_events;
+        self
+    }
+}
+
+pub struct AuthMiddleware;
+
+impl FromRequestParts<AuthState> for AuthMiddleware {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AuthState,
+    ) -> Result<Self, Self::Rejection> {
+        tracing::debug!(
+            "Authentication middleware: checking request to {}",
+            parts.uri
+        );
 
-        let user_identity = entities::UserIdentity { user_id };
+        let user_identity = authenticate(parts, state)
+            .await
+            .map_err(|reason| reject(parts, state, reason))?;
 
-        tracing::debug!("Authentication successful for user: {}", user_id);
+        tracing::debug!(
+            "Authentication successful for user: {}",
+            user_identity.user_id
+        );
 
         // Add auth state to request
         parts.extensions.insert(user_identity);
         Ok(Self)
     }
 }
+
+/// Authenticates the caller when credentials are sent, letting anonymous requests through
+///
+/// Handlers behind it read `Option<Extension<UserIdentity>>`. A request that
+/// sends an Authorization header the provider does not accept is still
+/// rejected, it never falls back to anonymous.
+pub struct OptionalAuth;
+
+impl FromRequestParts<AuthState> for OptionalAuth {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AuthState,
+    ) -> Result<Self, Self::Rejection> {
+        if !parts.headers.contains_key(axum::http::header::AUTHORIZATION) {
+            tracing::debug!("No credentials sent to {}, continuing anonymously", parts.uri);
+            return Ok(Self);
+        }
+
+        let user_identity = authenticate(parts, state)
+            .await
+            .map_err(|reason| reject(parts, state, reason))?;
+        parts.extensions.insert(user_identity);
+        Ok(Self)
+    }
+}
+
+/// Report a failed authentication attempt and build the response to it
+fn reject(parts: &Parts, state: &AuthState, reason: &'static str) -> ApiError {
+    let ip = parts
+        .extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    record_auth_failure(state, ip, reason);
+    ApiError::Unauthorized
+}
+
+/// Report a failed authentication attempt from `ip` to the security event sink
+pub(crate) fn record_auth_failure(state: &AuthState, ip: Option<IpAddr>, reason: &'static str) {
+    state.security_events.record(SecurityEvent {
+        kind: SecurityEventKind::AuthFailure,
+        user_id: None,
+        ip,
+        reason: reason.to_string(),
+    });
+}
+
+/// Resolve the caller from the bearer token, returning the rejection reason on failure
+async fn authenticate(
+    parts: &Parts,
+    state: &AuthState,
+) -> Result<entities::UserIdentity, &'static str> {
+    // Extract the Authorization header
+    let Some(auth_header) = parts.headers.get(axum::http::header::AUTHORIZATION) else {
+        tracing::warn!("Authentication failed: Authorization header missing");
+        return Err("authorization header missing");
+    };
+
+    // Ensure the header exists and starts with "Bearer "
+    let auth_value = auth_header.to_str().map_err(|e| {
+        tracing::warn!(
+            "Authentication failed: Authorization header is not valid UTF-8: {}",
+            e
+        );
+        "authorization header is not valid UTF-8"
+    })?;
+
+    authenticate_bearer(auth_value, state).await
+}
+
+/// Resolve the caller from an `Authorization` value, which must be a bearer token
+///
+/// Shared by every transport, so a token is validated and cached the same way
+/// whichever one it came through.
+pub(crate) async fn authenticate_bearer(
+    auth_value: &str,
+    state: &AuthState,
+) -> Result<entities::UserIdentity, &'static str> {
+    tracing::debug!("Authorization header present, checking Bearer prefix");
+
+    let token = auth_value.strip_prefix("Bearer ").ok_or_else(|| {
+        tracing::warn!("Authentication failed: Authorization header doesn't start with 'Bearer '. Header value starts with: {:?}", &auth_value.chars().take(10).collect::<String>());
+        "authorization header is not a bearer token"
+    })?;
+
+    if let Some(user_identity) = state.token_cache.get(token) {
+        tracing::debug!("Token found in cache, skipping validation");
+        return Ok(user_identity);
+    }
+
+    tracing::debug!(
+        "Token extracted, length: {} chars, validating with Keycloak",
+        token.len()
+    );
+
+    // Validate the token
+    let user_id = state.identity.identify(token).await?;
+
+    let user_identity = entities::UserIdentity { user_id };
+    state.token_cache.insert(token, user_identity.clone());
+    Ok(user_identity)
+}
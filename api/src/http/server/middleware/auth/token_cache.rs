@@ -0,0 +1,151 @@
+//! Identities already resolved from bearer tokens
+//!
+//! Saves a round-trip to the identity provider for every request a client
+//! makes with the same token. An entry lives until the token expires, or for
+//! the configured TTL when that comes first.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use jsonwebtoken::{DecodingKey, Validation, decode};
+use serde::Deserialize;
+
+use crate::http::server::middleware::auth::entities::UserIdentity;
+
+/// How long a resolved identity is reused when the token doesn't expire sooner
+pub const DEFAULT_TOKEN_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Tokens remembered at once before the least recently used one is dropped
+pub const DEFAULT_TOKEN_CACHE_CAPACITY: usize = 10_000;
+
+struct CachedIdentity {
+    identity: UserIdentity,
+    expires_at: Instant,
+    /// Value of the use counter when the entry was last read or written
+    last_used: u64,
+}
+
+struct Entries {
+    by_token: HashMap<String, CachedIdentity>,
+    uses: u64,
+}
+
+/// Bounded TTL cache of the identity behind each token, evicting the least recently used
+pub struct TokenCache {
+    entries: Mutex<Entries>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl TokenCache {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(Entries {
+                by_token: HashMap::new(),
+                uses: 0,
+            }),
+            ttl,
+            capacity,
+        }
+    }
+
+    /// Identity resolved for this token, unless it expired since
+    pub fn get(&self, token: &str) -> Option<UserIdentity> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.uses += 1;
+        let uses = entries.uses;
+
+        match entries.by_token.get_mut(token) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                entry.last_used = uses;
+                Some(entry.identity.clone())
+            }
+            Some(_) => {
+                entries.by_token.remove(token);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Remember the identity a token resolved to
+    pub fn insert(&self, token: &str, identity: UserIdentity) {
+        let ttl = match seconds_until_expiry(token) {
+            Some(0) => return,
+            Some(seconds) => self.ttl.min(Duration::from_secs(seconds)),
+            None => self.ttl,
+        };
+        if ttl.is_zero() || self.capacity == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.uses += 1;
+        let uses = entries.uses;
+
+        if !entries.by_token.contains_key(token) && entries.by_token.len() >= self.capacity {
+            entries.by_token.retain(|_, entry| entry.expires_at > now);
+            if entries.by_token.len() >= self.capacity {
+                let least_recent = entries
+                    .by_token
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(token, _)| token.clone());
+                if let Some(least_recent) = least_recent {
+                    entries.by_token.remove(&least_recent);
+                }
+            }
+        }
+
+        entries.by_token.insert(
+            token.to_string(),
+            CachedIdentity {
+                identity,
+                expires_at: now + ttl,
+                last_used: uses,
+            },
+        );
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().by_token.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for TokenCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TOKEN_CACHE_TTL, DEFAULT_TOKEN_CACHE_CAPACITY)
+    }
+}
+
+#[derive(Deserialize)]
+struct ExpiryClaim {
+    exp: Option<i64>,
+}
+
+/// Seconds left before the token's `exp`, `None` when it has none or isn't a JWT
+///
+/// The signature is not checked here: the identity provider already validated
+/// the token, this only bounds how long that answer is reused.
+fn seconds_until_expiry(token: &str) -> Option<u64> {
+    let mut validation = Validation::default();
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    validation.required_spec_claims.clear();
+
+    let claims = decode::<ExpiryClaim>(token, &DecodingKey::from_secret(&[]), &validation)
+        .ok()?
+        .claims;
+    let exp = claims.exp?;
+    Some(exp.saturating_sub(Utc::now().timestamp()).max(0) as u64)
+}
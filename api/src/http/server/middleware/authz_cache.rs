@@ -0,0 +1,11 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+
+use crate::http::server::authorization::with_request_authz_cache;
+
+/// Give each request its own authorization cache
+///
+/// Repeated checks of the same permission on the same resource within the
+/// request are answered once by the authorization backend.
+pub async fn request_authz_cache(request: Request, next: Next) -> Response {
+    with_request_authz_cache(next.run(request)).await
+}
@@ -1,9 +1,13 @@
 pub mod api_error;
 pub mod app_state;
+pub mod extractors;
+pub mod metrics;
 pub mod middleware;
 pub mod response;
+pub mod security;
 pub mod authorization;
 
 pub use api_error::ApiError;
 pub use app_state::AppState;
+pub use extractors::{Pagination, UuidPath};
 pub use response::Response;
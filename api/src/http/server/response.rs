@@ -3,7 +3,7 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response as AxumResponse},
 };
-use messages_core::domain::common::TotalPaginatedElements;
+use messages_core::domain::{common::TotalPaginatedElements, message::entities::MessageId};
 use serde::Serialize;
 use utoipa::ToSchema;
 
@@ -43,7 +43,6 @@ where
     }
 
     /// Create a response with a custom status code
-    #[allow(dead_code)]
     pub fn with_status(data: T, status_code: StatusCode) -> Self {
         Self { data, status_code }
     }
@@ -64,3 +63,10 @@ pub struct PaginatedResponse<T> {
     pub total: TotalPaginatedElements,
     pub page: u32,
 }
+
+#[derive(Serialize, ToSchema)]
+pub struct CursorResponse<T> {
+    pub data: Vec<T>,
+    /// Pass as `before` to fetch the next page, absent on the last page
+    pub next_cursor: Option<MessageId>,
+}
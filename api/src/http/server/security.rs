@@ -0,0 +1,82 @@
+use std::{net::IpAddr, sync::Arc};
+
+use messages_core::domain::common::CoreError;
+use uuid::Uuid;
+
+/// Kind of request worth surfacing to security monitoring
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecurityEventKind {
+    /// The request carried no valid credentials
+    AuthFailure,
+    /// The caller exceeded a rate limit
+    RateLimited,
+    /// The request was refused because of its content
+    BlockedContent,
+}
+
+impl SecurityEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecurityEventKind::AuthFailure => "auth_failure",
+            SecurityEventKind::RateLimited => "rate_limited",
+            SecurityEventKind::BlockedContent => "blocked_content",
+        }
+    }
+}
+
+/// Notable request reported to a [`SecurityEventSink`]
+#[derive(Clone, Debug)]
+pub struct SecurityEvent {
+    pub kind: SecurityEventKind,
+    /// Authenticated caller, when known
+    pub user_id: Option<Uuid>,
+    /// Peer address, when the server exposes connection info
+    pub ip: Option<IpAddr>,
+    pub reason: String,
+}
+
+impl SecurityEvent {
+    /// Event reporting that `user_id`'s request failed with `error`, when the
+    /// error is one monitoring cares about
+    pub fn refusal(error: &CoreError, user_id: Uuid, ip: Option<IpAddr>) -> Option<Self> {
+        let (kind, reason) = match error {
+            CoreError::RateLimited { .. } => {
+                (SecurityEventKind::RateLimited, "message rate limit exceeded")
+            }
+            CoreError::DuplicateContent => {
+                (SecurityEventKind::BlockedContent, "same content posted too recently")
+            }
+            _ => return None,
+        };
+        Some(Self {
+            kind,
+            user_id: Some(user_id),
+            ip,
+            reason: reason.to_string(),
+        })
+    }
+}
+
+/// Destination for security events, e.g. a log stream or an abuse detector
+pub trait SecurityEventSink: Send + Sync {
+    fn record(&self, event: SecurityEvent);
+}
+
+pub type DynSecurityEventSink = Arc<dyn SecurityEventSink>;
+
+/// Default sink writing every event as a warning on the `security` target
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LoggingSecurityEventSink;
+
+impl SecurityEventSink for LoggingSecurityEventSink {
+    fn record(&self, event: SecurityEvent) {
+        tracing::warn!(
+            target: "security",
+            kind = event.kind.as_str(),
+            user_id = ?event.user_id,
+            ip = ?event.ip,
+            reason = %event.reason,
+            "Security event"
+        );
+    }
+}
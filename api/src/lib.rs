@@ -1,9 +1,10 @@
 pub mod app;
 pub mod config;
+pub mod grpc;
 pub mod http;
 pub use app::App;
 pub use config::Config;
 pub use http::health::routes::health_routes;
-pub use http::messages::routes::message_routes;
+pub use http::messages::routes::{message_routes, public_message_routes};
 pub use http::server::middleware::auth::{AuthMiddleware, entities::AuthValidator};
 pub use http::server::{ApiError, AppState};
@@ -3,6 +3,7 @@ use api::http::server::ApiError;
 
 use api::config::Config;
 use clap::Parser;
+use std::sync::Arc;
 
 use tracing::{info, trace};
 use tracing_subscriber::EnvFilter;
@@ -29,8 +30,62 @@ async fn main() -> Result<(), ApiError> {
 
     let config: Config = Config::parse();
     trace!("...config and env vars loaded.");
-    let app = App::new(config).await?;
+    let app = Arc::new(App::new(config).await?);
+
+    // One signal stops the HTTP servers and the background tasks together
+    let signal_app = app.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        info!("Shutdown signal received");
+        signal_app.request_shutdown();
+    });
+
+    // SIGHUP re-reads the routing config without a restart
+    #[cfg(unix)]
+    {
+        let reload_app = app.clone();
+        tokio::spawn(async move {
+            use tokio::signal::unix::{SignalKind, signal};
+            let Ok(mut hangup) = signal(SignalKind::hangup()) else {
+                return;
+            };
+            while hangup.recv().await.is_some() {
+                if let Err(e) = reload_app.reload_routing() {
+                    tracing::error!("Routing config not reloaded: {}", e);
+                }
+            }
+        });
+    }
+
     info!("Starting the service");
     app.start().await?;
+    app.shutdown().await;
+    info!("Service stopped");
     Ok(())
 }
+
+/// Resolve on Ctrl+C or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::{Arc, Mutex};
+
+use api as crate_api;
+use beep_auth::KeycloakAuthRepository;
+use crate_api::grpc::{MessageGrpcService, pb, pb::message_service_server::MessageService};
+use crate_api::http::server::ApiError;
+use crate_api::http::server::app_state::AppState;
+use crate_api::http::server::authorization::{Authorization, AuthzError, Permission, Resource};
+use crate_api::http::server::middleware::auth::AuthState;
+use crate_api::http::server::security::{SecurityEvent, SecurityEventKind, SecurityEventSink};
+use messages_core::domain::common::CoreError;
+use messages_core::{MessagesService, create_repositories};
+use tonic::{Code, Request, Status};
+use uuid::Uuid;
+
+#[derive(Default)]
+struct RecordingSink {
+    events: Mutex<Vec<SecurityEvent>>,
+}
+
+impl SecurityEventSink for RecordingSink {
+    fn record(&self, event: SecurityEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+struct AllowAll;
+
+#[async_trait::async_trait]
+impl Authorization for AllowAll {
+    async fn check(&self, _: Uuid, _: Permission, _: Resource) -> Result<bool, AuthzError> {
+        Ok(true)
+    }
+}
+
+async fn grpc_service(sink: Arc<RecordingSink>) -> MessageGrpcService {
+    // The mongo client connects lazily and no query runs in these tests
+    let repos = create_repositories(
+        "mongodb://127.0.0.1:1",
+        "message_test_db",
+        &"http://localhost:3004".into(),
+    )
+    .await
+    .expect("create repos");
+    let state = AppState::new(MessagesService::from(repos), Arc::new(AllowAll));
+
+    // Rejected calls never reach Keycloak, so the URL is never contacted
+    let keycloak = KeycloakAuthRepository::new("http://127.0.0.1:1/realms/test".to_string(), None);
+    let auth_state = AuthState::new(keycloak).with_security_events(sink);
+
+    MessageGrpcService::new(state, auth_state)
+}
+
+fn get_request(id: &str) -> Request<pb::GetMessageRequest> {
+    Request::new(pb::GetMessageRequest { id: id.to_string() })
+}
+
+#[tokio::test]
+async fn calls_without_credentials_are_unauthenticated_and_reported() {
+    let sink = Arc::new(RecordingSink::default());
+    let service = grpc_service(sink.clone()).await;
+
+    let status = service
+        .get_message(get_request(&Uuid::new_v4().to_string()))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let events = sink.events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, SecurityEventKind::AuthFailure);
+    assert_eq!(events[0].reason, "authorization header missing");
+}
+
+#[tokio::test]
+async fn non_bearer_credentials_are_unauthenticated() {
+    let sink = Arc::new(RecordingSink::default());
+    let service = grpc_service(sink.clone()).await;
+
+    let mut request = Request::new(pb::DeleteMessageRequest {
+        id: Uuid::new_v4().to_string(),
+    });
+    request
+        .metadata_mut()
+        .insert("authorization", "Basic dXNlcjpwYXNz".parse().unwrap());
+    let status = service.delete_message(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let events = sink.events.lock().unwrap();
+    assert_eq!(events[0].reason, "authorization header is not a bearer token");
+}
+
+#[test]
+fn core_errors_map_to_grpc_codes() {
+    let code = |error: CoreError| Status::from(ApiError::from(error)).code();
+
+    assert_eq!(code(CoreError::MessageNotFound { id: Uuid::nil().into() }), Code::NotFound);
+    assert_eq!(code(CoreError::Forbidden), Code::PermissionDenied);
+    assert_eq!(code(CoreError::RateLimited { retry_after_secs: 3 }), Code::ResourceExhausted);
+}
+
+#[test]
+fn invalid_input_maps_to_invalid_argument() {
+    let status = Status::from(ApiError::InvalidUuid {
+        field: "channel_id".to_string(),
+    });
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert!(status.message().contains("channel_id"));
+
+    let status = Status::from(ApiError::BadRequest {
+        msg: "content is empty".to_string(),
+    });
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
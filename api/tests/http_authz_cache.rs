@@ -0,0 +1,91 @@
+use std::sync::{Arc, Mutex};
+
+use api as crate_api;
+use axum::{
+    Router,
+    body::Body,
+    extract::State,
+    http::{Request, StatusCode},
+    middleware::from_fn,
+    routing::get,
+};
+use crate_api::http::server::app_state::AppState;
+use crate_api::http::server::authorization::{Authorization, AuthzError, Permission, Resource};
+use crate_api::http::server::middleware::authz_cache::request_authz_cache;
+use messages_core::{MessagesService, create_repositories};
+use tower::util::ServiceExt;
+use uuid::Uuid;
+
+/// Allows everything and records every check it answers
+#[derive(Default)]
+struct CountingAuthz {
+    checks: Mutex<Vec<(Uuid, Permission, Resource)>>,
+}
+
+#[async_trait::async_trait]
+impl Authorization for CountingAuthz {
+    async fn check(
+        &self,
+        actor: Uuid,
+        permission: Permission,
+        resource: Resource,
+    ) -> Result<bool, AuthzError> {
+        self.checks.lock().unwrap().push((actor, permission, resource));
+        Ok(true)
+    }
+}
+
+/// Check the same permission twice and another one once, as a handler might
+async fn repeated_checks(State(state): State<AppState>) -> StatusCode {
+    let (actor, channel) = (Uuid::nil(), Resource::Channel(Uuid::nil()));
+    for permission in [Permission::SendMessages, Permission::SendMessages, Permission::AttachFiles] {
+        assert!(state.authz.check(actor, permission, channel).await.unwrap());
+    }
+    StatusCode::OK
+}
+
+async fn router(authz: Arc<CountingAuthz>) -> Router {
+    // The mongo client connects lazily and no query runs in these tests
+    let repos = create_repositories(
+        "mongodb://127.0.0.1:1",
+        "message_test_db",
+        &"http://localhost:3004".into(),
+    )
+    .await
+    .expect("create repos");
+    let state = AppState::new(MessagesService::from(repos), authz);
+
+    Router::new()
+        .route("/check", get(repeated_checks))
+        .with_state(state)
+        .layer(from_fn(request_authz_cache))
+}
+
+async fn call(router: &Router) {
+    let request = Request::builder().uri("/check").body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn authorizer_is_consulted_once_per_distinct_check_in_a_request() {
+    let authz = Arc::new(CountingAuthz::default());
+    let router = router(authz.clone()).await;
+
+    call(&router).await;
+
+    let checks = authz.checks.lock().unwrap();
+    let permissions: Vec<Permission> = checks.iter().map(|(_, permission, _)| *permission).collect();
+    assert_eq!(permissions, vec![Permission::SendMessages, Permission::AttachFiles]);
+}
+
+#[tokio::test]
+async fn cached_answers_do_not_outlive_their_request() {
+    let authz = Arc::new(CountingAuthz::default());
+    let router = router(authz.clone()).await;
+
+    call(&router).await;
+    call(&router).await;
+
+    assert_eq!(authz.checks.lock().unwrap().len(), 4);
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use api as crate_api;
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+    routing::post,
+};
+use crate_api::http::health::routes::health_routes;
+use crate_api::http::messages::handlers;
+use crate_api::http::server::app_state::AppState;
+use crate_api::http::server::authorization::{
+    Authorization, AuthzError, Permission, Resource, ServiceAuthorizer,
+};
+use crate_api::http::server::metrics::Metrics;
+use crate_api::http::server::middleware::auth::entities::UserIdentity;
+use messages_core::domain::authorization::{entities::Actor, ports::Authorizer};
+use messages_core::{MessagesService, create_repositories};
+use serde_json::json;
+use tower::util::ServiceExt;
+use tower_http::add_extension::AddExtensionLayer;
+use uuid::Uuid;
+
+struct DenyAll;
+
+#[async_trait::async_trait]
+impl Authorization for DenyAll {
+    async fn check(
+        &self,
+        _actor: Uuid,
+        _permission: Permission,
+        _resource: Resource,
+    ) -> Result<bool, AuthzError> {
+        Ok(false)
+    }
+}
+
+#[tokio::test]
+async fn forbidden_request_increments_denial_counter() {
+    // The mongo client connects lazily and the request is denied before any query runs
+    let repos = create_repositories(
+        "mongodb://127.0.0.1:1",
+        "message_test_db",
+        &"http://localhost:3004".into(),
+    )
+    .await
+    .expect("create repos");
+    let state = AppState::new(MessagesService::from(repos), Arc::new(DenyAll));
+
+    let router = Router::new()
+        .route("/messages", post(handlers::create_message))
+        .with_state(state.clone())
+        .layer(AddExtensionLayer::new(UserIdentity {
+            user_id: Uuid::new_v4(),
+        }));
+
+    let body = json!({
+        "channel_id": Uuid::new_v4(),
+        "content": "denied",
+        "reply_to_message_id": null,
+        "attachments": []
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/messages")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = router.oneshot(request).await.expect("router oneshot");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    assert_eq!(
+        state.metrics.authz_denials(Permission::SendMessages, "channel"),
+        1
+    );
+
+    let metrics_router = health_routes().with_state(state);
+    let request = Request::builder()
+        .uri("/metrics")
+        .body(Body::empty())
+        .unwrap();
+    let response = metrics_router.oneshot(request).await.expect("metrics oneshot");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let bytes = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    let text = String::from_utf8(bytes.to_vec()).expect("utf-8 body");
+    assert!(text.contains(
+        "authz_denials_total{permission=\"send_messages\",resource=\"channel\"} 1"
+    ));
+}
+
+#[tokio::test]
+async fn checks_that_only_filter_a_listing_are_not_counted() {
+    let metrics = Arc::new(Metrics::new());
+    let authorizer = ServiceAuthorizer::new(Arc::new(DenyAll), metrics.clone());
+    let actor = Actor::from(Uuid::new_v4());
+    let channel = Resource::Channel(Uuid::new_v4());
+
+    // The cross-channel listings call `check` and leave denied channels out
+    let allowed = authorizer
+        .check(&actor, Permission::ViewChannels, channel)
+        .await
+        .expect("check");
+    assert!(!allowed);
+    assert_eq!(metrics.authz_denials(Permission::ViewChannels, "channel"), 0);
+
+    authorizer.record_denial(&actor, Permission::ViewChannels, channel);
+    assert_eq!(metrics.authz_denials(Permission::ViewChannels, "channel"), 1);
+}
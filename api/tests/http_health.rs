@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use api as crate_api;
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use crate_api::http::{health::routes::health_routes, server::app_state::AppState};
+use messages_core::create_repositories;
+use tower::util::ServiceExt;
+
+async fn state() -> AppState {
+    // Nothing listens on port 1, so the database check always fails
+    let repos = create_repositories(
+        "mongodb://127.0.0.1:1",
+        "message_test_db",
+        &"http://localhost:3004".into(),
+    )
+    .await
+    .expect("create repos");
+    let mut state: AppState = repos.into();
+    state.service = state
+        .service
+        .with_health_check_timeout(Duration::from_millis(50));
+    state
+}
+
+async fn get(state: &AppState, uri: &str) -> (StatusCode, serde_json::Value) {
+    let router: Router = health_routes().with_state(state.clone());
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn liveness_does_not_depend_on_the_database() {
+    let state = state().await;
+
+    let (status, body) = get(&state, "/health/live").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "alive");
+}
+
+#[tokio::test]
+async fn readiness_lists_unreachable_dependencies() {
+    let state = state().await;
+
+    let (status, body) = get(&state, "/health/ready").await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["unhealthy"], serde_json::json!(["mongodb"]));
+}
+
+#[tokio::test]
+async fn draining_fails_readiness_but_not_liveness() {
+    let state = state().await;
+    state.begin_draining();
+
+    let (status, body) = get(&state, "/health/ready").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "draining");
+
+    let (status, _) = get(&state, "/health/live").await;
+    assert_eq!(status, StatusCode::OK);
+}
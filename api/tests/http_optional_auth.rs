@@ -0,0 +1,108 @@
+use std::sync::{Arc, Mutex};
+
+use api as crate_api;
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header},
+    middleware::from_extractor_with_state,
+};
+use crate_api::http::server::app_state::AppState;
+use crate_api::http::server::authorization::{Authorization, AuthzError, Permission, Resource};
+use crate_api::http::server::middleware::auth::{AuthState, IdentityProvider, OptionalAuth};
+use crate_api::public_message_routes;
+use messages_core::{MessagesService, create_repositories};
+use tower::util::ServiceExt;
+use uuid::Uuid;
+
+/// Accepts only the token "valid", issued to `USER`
+struct StubProvider;
+
+const USER: Uuid = Uuid::from_u128(7);
+
+#[async_trait::async_trait]
+impl IdentityProvider for StubProvider {
+    async fn identify(&self, token: &str) -> Result<Uuid, &'static str> {
+        match token {
+            "valid" => Ok(USER),
+            _ => Err("token rejected by identity provider"),
+        }
+    }
+}
+
+/// Denies everything and records who asked, so no query ever reaches the database
+#[derive(Default)]
+struct DenyingAuthz {
+    actors: Mutex<Vec<Uuid>>,
+}
+
+#[async_trait::async_trait]
+impl Authorization for DenyingAuthz {
+    async fn check(
+        &self,
+        actor: Uuid,
+        _permission: Permission,
+        _resource: Resource,
+    ) -> Result<bool, AuthzError> {
+        self.actors.lock().unwrap().push(actor);
+        Ok(false)
+    }
+}
+
+async fn router(authz: Arc<DenyingAuthz>) -> Router {
+    let repos = create_repositories(
+        "mongodb://127.0.0.1:1",
+        "message_test_db",
+        &"http://localhost:3004".into(),
+    )
+    .await
+    .expect("create repos");
+    let state = AppState::new(MessagesService::from(repos), authz);
+    let auth_state = AuthState::with_identity_provider(Arc::new(StubProvider));
+
+    let (router, _) = public_message_routes()
+        .route_layer(from_extractor_with_state::<OptionalAuth, AuthState>(auth_state))
+        .split_for_parts();
+    router.with_state(state)
+}
+
+async fn list(router: &Router, authorization: Option<&str>) -> StatusCode {
+    let mut request = Request::builder().uri(format!("/channels/{}/messages", Uuid::new_v4()));
+    if let Some(authorization) = authorization {
+        request = request.header(header::AUTHORIZATION, authorization);
+    }
+    let response = router
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    response.status()
+}
+
+#[tokio::test]
+async fn anonymous_reads_are_authorized_as_the_anonymous_actor() {
+    let authz = Arc::new(DenyingAuthz::default());
+    let router = router(authz.clone()).await;
+
+    assert_eq!(list(&router, None).await, StatusCode::FORBIDDEN);
+    assert_eq!(*authz.actors.lock().unwrap(), vec![Uuid::nil()]);
+}
+
+#[tokio::test]
+async fn valid_token_reads_as_its_user() {
+    let authz = Arc::new(DenyingAuthz::default());
+    let router = router(authz.clone()).await;
+
+    assert_eq!(list(&router, Some("Bearer valid")).await, StatusCode::FORBIDDEN);
+    assert_eq!(*authz.actors.lock().unwrap(), vec![USER]);
+}
+
+#[tokio::test]
+async fn invalid_credentials_are_rejected_rather_than_made_anonymous() {
+    let authz = Arc::new(DenyingAuthz::default());
+    let router = router(authz.clone()).await;
+
+    assert_eq!(list(&router, Some("Bearer forged")).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(list(&router, Some("Basic dXNlcjpwYXNz")).await, StatusCode::UNAUTHORIZED);
+    assert!(authz.actors.lock().unwrap().is_empty());
+}
@@ -0,0 +1,72 @@
+use api as crate_api;
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+    routing::get,
+};
+use crate_api::http::server::{Pagination, app_state::AppState};
+use messages_core::create_repositories;
+use messages_core::domain::common::{DEFAULT_PAGE_LIMIT, GetPaginated, MAX_PAGE_LIMIT};
+use tower::util::ServiceExt;
+
+async fn state() -> AppState {
+    // The mongo client connects lazily and no query runs in these tests
+    let repos = create_repositories(
+        "mongodb://127.0.0.1:1",
+        "message_test_db",
+        &"http://localhost:3004".into(),
+    )
+    .await
+    .expect("create repos");
+    repos.into()
+}
+
+/// Answer `<page>/<limit>` as resolved by the extractor
+async fn pagination_of(state: AppState, uri: &str) -> String {
+    let router = Router::new()
+        .route(
+            "/page",
+            get(|Pagination(pagination): Pagination| async move {
+                format!("{}/{}", pagination.page, pagination.limit)
+            }),
+        )
+        .with_state(state);
+
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn omitted_limit_uses_configured_default() {
+    let state = state().await.with_default_page_limit(7);
+
+    assert_eq!(pagination_of(state.clone(), "/page").await, "1/7");
+    assert_eq!(pagination_of(state, "/page?page=3").await, "3/7");
+}
+
+#[tokio::test]
+async fn explicit_limit_is_capped_at_max() {
+    let state = state().await.with_default_page_limit(7);
+
+    assert_eq!(pagination_of(state.clone(), "/page?limit=10").await, "1/10");
+    assert_eq!(
+        pagination_of(state, "/page?limit=500").await,
+        format!("1/{MAX_PAGE_LIMIT}")
+    );
+}
+
+#[tokio::test]
+async fn unconfigured_default_matches_get_paginated_default() {
+    let state = state().await;
+    let default = GetPaginated::default();
+
+    assert_eq!(state.default_page_limit, DEFAULT_PAGE_LIMIT);
+    assert_eq!(
+        pagination_of(state, "/page").await,
+        format!("{}/{}", default.page, default.limit)
+    );
+}
@@ -0,0 +1,51 @@
+use api as crate_api;
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+    routing::get,
+};
+use crate_api::http::messages::handlers;
+use crate_api::http::server::app_state::AppState;
+use crate_api::http::server::middleware::auth::entities::UserIdentity;
+use messages_core::create_repositories;
+use serde_json::Value;
+use tower::util::ServiceExt;
+use tower_http::add_extension::AddExtensionLayer;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn malformed_message_id_returns_structured_error() {
+    // The mongo client connects lazily and the path is rejected before any query runs
+    let repos = create_repositories(
+        "mongodb://127.0.0.1:1",
+        "message_test_db",
+        &"http://localhost:3004".into(),
+    )
+    .await
+    .expect("create repos");
+    let state: AppState = repos.into();
+
+    let router = Router::new()
+        .route("/messages/{id}", get(handlers::get_message))
+        .with_state(state)
+        .layer(AddExtensionLayer::new(UserIdentity {
+            user_id: Uuid::new_v4(),
+        }));
+
+    let request = Request::builder()
+        .method("GET")
+        .uri("/messages/not-a-uuid")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.expect("router oneshot");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let bytes = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    let body: Value = serde_json::from_slice(&bytes).expect("json body");
+    assert_eq!(body["error_code"], "INVALID_UUID");
+    assert_eq!(body["field"], "id");
+    assert_eq!(body["status"], 400);
+}
@@ -0,0 +1,22 @@
+use api as crate_api;
+use axum::{
+    body::to_bytes,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use crate_api::http::server::ApiError;
+use messages_core::domain::common::CoreError;
+use serde_json::Value;
+
+#[tokio::test]
+async fn rate_limited_maps_to_too_many_requests_with_retry_after() {
+    let response = ApiError::from(CoreError::RateLimited { retry_after_secs: 7 }).into_response();
+
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "7");
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error_code"], "RATE_LIMITED");
+    assert_eq!(body["status"], 429);
+}
@@ -0,0 +1,209 @@
+use std::sync::{Arc, Mutex};
+
+use api as crate_api;
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header},
+    middleware::from_extractor_with_state,
+    routing::{get, post},
+};
+use beep_auth::KeycloakAuthRepository;
+use crate_api::app::with_client_addresses;
+use crate_api::http::messages::handlers;
+use crate_api::http::server::app_state::AppState;
+use crate_api::http::server::authorization::{Authorization, AuthzError, Permission, Resource};
+use crate_api::http::server::middleware::auth::entities::UserIdentity;
+use crate_api::http::server::middleware::auth::{AuthMiddleware, AuthState};
+use crate_api::http::server::security::{SecurityEvent, SecurityEventKind, SecurityEventSink};
+use messages_core::domain::message::duplicates::{DuplicateContentAction, DuplicateContentPolicy};
+use messages_core::domain::message::entities::{AuthorId, ChannelId};
+use messages_core::domain::message::rate_limit::{
+    RateLimitPolicy, RateLimitedAction, RateLimiter, TokenBucketRateLimiter,
+};
+use messages_core::{MessagesService, create_repositories};
+use serde_json::json;
+use tower::util::ServiceExt;
+use tower_http::add_extension::AddExtensionLayer;
+use uuid::Uuid;
+
+#[derive(Default)]
+struct RecordingSink {
+    events: Mutex<Vec<SecurityEvent>>,
+}
+
+impl SecurityEventSink for RecordingSink {
+    fn record(&self, event: SecurityEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+struct AllowAll;
+
+#[async_trait::async_trait]
+impl Authorization for AllowAll {
+    async fn check(&self, _: Uuid, _: Permission, _: Resource) -> Result<bool, AuthzError> {
+        Ok(true)
+    }
+}
+
+fn router(sink: Arc<RecordingSink>) -> Router {
+    // Rejected requests never reach Keycloak, so the URL is never contacted
+    let keycloak = KeycloakAuthRepository::new("http://127.0.0.1:1/realms/test".to_string(), None);
+    let state = AuthState::new(keycloak).with_security_events(sink);
+
+    Router::new()
+        .route("/ping", get(|| async { "pong" }))
+        .route_layer(from_extractor_with_state::<AuthMiddleware, AuthState>(state))
+}
+
+#[tokio::test]
+async fn missing_credentials_are_reported_to_the_sink() {
+    let sink = Arc::new(RecordingSink::default());
+
+    let request = Request::builder().uri("/ping").body(Body::empty()).unwrap();
+    let response = router(sink.clone()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let events = sink.events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, SecurityEventKind::AuthFailure);
+    assert_eq!(events[0].user_id, None);
+    assert_eq!(events[0].reason, "authorization header missing");
+}
+
+#[tokio::test]
+async fn reported_events_name_the_client_address() {
+    let sink = Arc::new(RecordingSink::default());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = router(sink.clone());
+    tokio::spawn(async move {
+        axum::serve(listener, with_client_addresses(router))
+            .await
+            .unwrap();
+    });
+
+    let response = reqwest::get(format!("http://{addr}/ping")).await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let events = sink.events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].ip, Some(addr.ip()));
+}
+
+#[tokio::test]
+async fn non_bearer_credentials_are_reported_to_the_sink() {
+    let sink = Arc::new(RecordingSink::default());
+
+    let request = Request::builder()
+        .uri("/ping")
+        .header(header::AUTHORIZATION, "Basic dXNlcjpwYXNz")
+        .body(Body::empty())
+        .unwrap();
+    let response = router(sink.clone()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let events = sink.events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].reason, "authorization header is not a bearer token");
+}
+
+/// Post `content` to `channel` as `user` through the create handler
+async fn post_message(
+    state: AppState,
+    user: Uuid,
+    channel: Uuid,
+    content: &str,
+    ephemeral: bool,
+) -> StatusCode {
+    let router = Router::new()
+        .route("/messages", post(handlers::create_message))
+        .with_state(state)
+        .layer(AddExtensionLayer::new(UserIdentity { user_id: user }));
+
+    let body = json!({
+        "channel_id": channel,
+        "content": content,
+        "reply_to_message_id": null,
+        "attachments": [],
+        "ephemeral": ephemeral
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/messages")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    router.oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn rate_limited_messages_are_reported_to_the_sink() {
+    let sink = Arc::new(RecordingSink::default());
+    let user = Uuid::new_v4();
+    let channel = Uuid::new_v4();
+
+    // The author's only token is already spent, so the request is refused
+    // before any query reaches the lazily connecting mongo client
+    let limiter = Arc::new(TokenBucketRateLimiter::new(
+        RateLimitPolicy::new(1, 0.01).expect("valid policy"),
+    ));
+    limiter
+        .check_and_consume(
+            &AuthorId::from(user),
+            RateLimitedAction::SendMessage(ChannelId::from(channel)),
+        )
+        .unwrap();
+    let repos = create_repositories(
+        "mongodb://127.0.0.1:1",
+        "message_test_db",
+        &"http://localhost:3004".into(),
+    )
+    .await
+    .expect("create repos");
+    let service = MessagesService::from(repos).with_rate_limiter(limiter);
+    let state = AppState::new(service, Arc::new(AllowAll)).with_security_events(sink.clone());
+
+    let status = post_message(state, user, channel, "flood", false).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    let events = sink.events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, SecurityEventKind::RateLimited);
+    assert_eq!(events[0].user_id, Some(user));
+}
+
+#[tokio::test]
+async fn duplicate_content_rejections_are_reported_to_the_sink() {
+    let sink = Arc::new(RecordingSink::default());
+    let user = Uuid::new_v4();
+    let channel = Uuid::new_v4();
+
+    // An ephemeral post is accepted without being stored, so the detector
+    // records it even though writing its event then fails fast
+    let repos = create_repositories(
+        "mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=50",
+        "message_test_db",
+        &"http://localhost:3004".into(),
+    )
+    .await
+    .expect("create repos");
+    let service = MessagesService::from(repos).with_duplicate_detection(DuplicateContentPolicy::new(
+        DuplicateContentAction::Reject,
+        chrono::Duration::seconds(60),
+    ));
+    let state = AppState::new(service, Arc::new(AllowAll)).with_security_events(sink.clone());
+
+    let status = post_message(state.clone(), user, channel, "buy now", true).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(sink.events.lock().unwrap().is_empty());
+
+    let status = post_message(state, user, channel, "buy now", false).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let events = sink.events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, SecurityEventKind::BlockedContent);
+    assert_eq!(events[0].user_id, Some(user));
+}
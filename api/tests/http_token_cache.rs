@@ -0,0 +1,119 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use api as crate_api;
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header},
+    middleware::from_extractor_with_state,
+    routing::get,
+};
+use crate_api::http::server::middleware::auth::{
+    AuthMiddleware, AuthState, IdentityProvider, entities::UserIdentity, token_cache::TokenCache,
+};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use tower::util::ServiceExt;
+use uuid::Uuid;
+
+/// Accepts every token and counts how often it was asked
+#[derive(Default)]
+struct CountingProvider {
+    calls: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl IdentityProvider for CountingProvider {
+    async fn identify(&self, _token: &str) -> Result<Uuid, &'static str> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(Uuid::nil())
+    }
+}
+
+fn router(provider: Arc<CountingProvider>) -> Router {
+    let state = AuthState::with_identity_provider(provider)
+        .with_token_cache(TokenCache::new(Duration::from_secs(60), 16));
+
+    Router::new()
+        .route("/ping", get(|| async { "pong" }))
+        .route_layer(from_extractor_with_state::<AuthMiddleware, AuthState>(state))
+}
+
+async fn call(router: &Router, token: &str) {
+    let request = Request::builder()
+        .uri("/ping")
+        .header(header::AUTHORIZATION, format!("Bearer {token}"))
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// Signed JWT expiring `seconds` from now
+fn jwt_expiring_in(seconds: i64) -> String {
+    let exp = chrono::Utc::now().timestamp() + seconds;
+    encode(
+        &Header::default(),
+        &serde_json::json!({ "sub": "user", "exp": exp }),
+        &EncodingKey::from_secret(b"secret"),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn same_token_is_only_validated_once() {
+    let provider = Arc::new(CountingProvider::default());
+    let router = router(provider.clone());
+
+    call(&router, "token-a").await;
+    call(&router, "token-a").await;
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+
+    call(&router, "token-b").await;
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn expired_token_is_not_cached() {
+    let provider = Arc::new(CountingProvider::default());
+    let router = router(provider.clone());
+    let token = jwt_expiring_in(-10);
+
+    call(&router, &token).await;
+    call(&router, &token).await;
+
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn least_recently_used_token_is_evicted_when_full() {
+    let cache = TokenCache::new(Duration::from_secs(60), 2);
+    let identity = UserIdentity { user_id: Uuid::nil() };
+
+    cache.insert("a", identity.clone());
+    cache.insert("b", identity.clone());
+    assert!(cache.get("a").is_some());
+    cache.insert("c", identity);
+
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get("a").is_some());
+    assert!(cache.get("b").is_none());
+    assert!(cache.get("c").is_some());
+}
+
+#[test]
+fn entry_does_not_outlive_its_token() {
+    let cache = TokenCache::new(Duration::from_secs(3600), 16);
+    let token = jwt_expiring_in(2);
+
+    cache.insert(&token, UserIdentity { user_id: Uuid::nil() });
+    assert!(cache.get(&token).is_some());
+
+    std::thread::sleep(Duration::from_millis(2100));
+    assert!(cache.get(&token).is_none());
+}
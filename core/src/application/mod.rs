@@ -1,4 +1,5 @@
 use mongodb::{Client as MongoClient, options::ClientOptions};
+use uuid::Uuid;
 
 use crate::{
     domain::common::{CoreError, services::Service},
@@ -26,19 +27,58 @@ pub struct MessageRepositories {
     pub outbox_repository: MongoOutboxEventRepository,
 }
 
+/// Name this process reports to MongoDB and RabbitMQ when none is configured
+///
+/// The crate name followed by a random instance id, so that connections of
+/// different replicas can be told apart.
+pub fn default_connection_name() -> String {
+    format!("{}-{}", env!("CARGO_PKG_NAME"), Uuid::new_v4())
+}
+
+/// Parse the MongoDB URI and name the connection `app_name`
+///
+/// The name shows up in the server's `currentOp` and logs, which attributes
+/// load to this service.
+pub async fn mongo_client_options(mongo_uri: &str, app_name: &str) -> Result<ClientOptions, CoreError> {
+    let mut mongo_options = ClientOptions::parse(mongo_uri).await.map_err(mongo_unavailable)?;
+    mongo_options.app_name = Some(app_name.to_string());
+    Ok(mongo_options)
+}
+
+fn mongo_unavailable(error: mongodb::error::Error) -> CoreError {
+    tracing::error!(error = %error, "could not set up the mongodb client");
+    CoreError::DependencyUnavailable {
+        dependency: "mongodb".to_string(),
+        reason: error.to_string(),
+    }
+}
+
+/// Build every repository on one MongoDB client
+///
+/// The client is the only connection opened here and it is created after
+/// every step that can fail, so an error never leaves a pool behind. The
+/// error names the dependency that could not be reached.
 #[tracing::instrument(skip(mongo_uri, mongo_db_name))]
 pub async fn create_repositories(
     mongo_uri: &str,
     mongo_db_name: &str,
     client_url: &String,
 ) -> Result<MessageRepositories, CoreError> {
-    tracing::info!(db = %mongo_db_name, "creating mongodb client");
-    let mongo_options = ClientOptions::parse(mongo_uri)
-        .await
-        .map_err(|e| CoreError::ServiceUnavailable(e.to_string()))?;
+    create_named_repositories(mongo_uri, mongo_db_name, client_url, &default_connection_name()).await
+}
+
+/// Like [`create_repositories`], with the MongoDB connection named `app_name`
+#[tracing::instrument(skip(mongo_uri, mongo_db_name))]
+pub async fn create_named_repositories(
+    mongo_uri: &str,
+    mongo_db_name: &str,
+    client_url: &String,
+    app_name: &str,
+) -> Result<MessageRepositories, CoreError> {
+    tracing::info!(db = %mongo_db_name, app_name, "creating mongodb client");
+    let mongo_options = mongo_client_options(mongo_uri, app_name).await?;
 
-    let mongo_client = MongoClient::with_options(mongo_options)
-        .map_err(|e| CoreError::ServiceUnavailable(e.to_string()))?;
+    let mongo_client = MongoClient::with_options(mongo_options).map_err(mongo_unavailable)?;
 
     let mongo_db = mongo_client.database(mongo_db_name);
 
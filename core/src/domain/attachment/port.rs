@@ -1,3 +1,5 @@
+use std::sync::{Arc, Mutex};
+
 use crate::{
     domain::{
         common::CoreError, message::entities::{Attachment, AttachmentId},
@@ -23,13 +25,26 @@ pub trait AttachmentRepository: Send + Sync {
         &self,
         id: String,
     ) -> impl Future<Output = Result<Attachment, CoreError>> + Send;
+    /// Resolve several attachments in one call, leaving out those that fail to resolve
+    fn get_attachments(
+        &self,
+        ids: &[AttachmentId],
+    ) -> impl Future<Output = Result<Vec<Attachment>, CoreError>> + Send;
 }
 
-pub struct MockAttachmentRepository;
+#[derive(Clone, Default)]
+pub struct MockAttachmentRepository {
+    requested: Arc<Mutex<Vec<AttachmentId>>>,
+}
 
 impl MockAttachmentRepository {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Ids passed to `get_attachments`, one entry per requested id
+    pub fn requested_ids(&self) -> Vec<AttachmentId> {
+        self.requested.lock().unwrap().clone()
     }
 }
 
@@ -65,4 +80,19 @@ impl AttachmentRepository for MockAttachmentRepository {
         };
         async move { Ok(attachment) }
     }
+
+    fn get_attachments(
+        &self,
+        ids: &[AttachmentId],
+    ) -> impl Future<Output = Result<Vec<Attachment>, CoreError>> + Send {
+        self.requested.lock().unwrap().extend_from_slice(ids);
+        let attachments = ids
+            .iter()
+            .map(|id| Attachment {
+                id: *id,
+                url: format!("http://example.com/attachment/{}", id),
+            })
+            .collect();
+        async move { Ok(attachments) }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,77 @@
+use uuid::Uuid;
+
+use crate::domain::message::entities::AuthorId;
+
+/// User on whose behalf a service operation runs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Actor(pub Uuid);
+
+impl Actor {
+    /// Caller who sent no credentials
+    ///
+    /// Authorizers only grant it what is open to everyone, such as viewing a
+    /// public channel.
+    pub fn anonymous() -> Self {
+        Self(Uuid::nil())
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        self.0.is_nil()
+    }
+
+    /// The actor as the author of the messages they write
+    pub fn author_id(&self) -> AuthorId {
+        AuthorId::from(self.0)
+    }
+}
+
+impl From<Uuid> for Actor {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl From<AuthorId> for Actor {
+    fn from(author_id: AuthorId) -> Self {
+        Self(author_id.0)
+    }
+}
+
+/// Object a permission is checked against
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Resource {
+    Channel(Uuid),
+    User(Uuid),
+}
+
+impl Resource {
+    /// Resource type used as a metrics label
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Resource::Channel(_) => "channel",
+            Resource::User(_) => "user",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Permission {
+    ViewChannels,
+    SendMessages,
+    ManageMessages,
+    ManageChannels,
+    AttachFiles,
+}
+
+impl Permission {
+    /// Permission name used as a metrics label
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ViewChannels => "view_channels",
+            Permission::SendMessages => "send_messages",
+            Permission::ManageMessages => "manage_messages",
+            Permission::ManageChannels => "manage_channels",
+            Permission::AttachFiles => "attach_files",
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::domain::{
+    authorization::entities::{Actor, Permission, Resource},
+    common::CoreError,
+};
+
+/// Decides whether an actor holds a permission on a resource
+///
+/// Services consult it before every guarded operation, so the rules hold
+/// whichever adapter drives the service.
+#[async_trait]
+pub trait Authorizer: Send + Sync {
+    async fn check(
+        &self,
+        actor: &Actor,
+        permission: Permission,
+        resource: Resource,
+    ) -> Result<bool, CoreError>;
+
+    /// Told when a check made an operation fail as forbidden
+    ///
+    /// Listings that merely leave out what the actor may not see don't call
+    /// it, so adapters can count refused requests apart from filtering.
+    fn record_denial(&self, _actor: &Actor, _permission: Permission, _resource: Resource) {}
+}
+
+pub type DynAuthorizer = Arc<dyn Authorizer>;
+
+/// Refuses every permission; the default, so a service built without an
+/// authorizer fails closed
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DenyAllAuthorizer;
+
+#[async_trait]
+impl Authorizer for DenyAllAuthorizer {
+    async fn check(
+        &self,
+        _actor: &Actor,
+        _permission: Permission,
+        _resource: Resource,
+    ) -> Result<bool, CoreError> {
+        Ok(false)
+    }
+}
+
+/// Grants every permission; for tests that don't exercise authorization, never
+/// configure it in a deployment
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AllowAllAuthorizer;
+
+#[async_trait]
+impl Authorizer for AllowAllAuthorizer {
+    async fn check(
+        &self,
+        _actor: &Actor,
+        _permission: Permission,
+        _resource: Resource,
+    ) -> Result<bool, CoreError> {
+        Ok(true)
+    }
+}
+
+/// Authorizer granting only what was explicitly granted to it
+#[derive(Clone, Default)]
+pub struct MockAuthorizer {
+    grants: Arc<Mutex<Vec<(Actor, Permission, Resource)>>>,
+}
+
+impl MockAuthorizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn grant(&self, actor: Actor, permission: Permission, resource: Resource) {
+        self.grants
+            .lock()
+            .unwrap()
+            .push((actor, permission, resource));
+    }
+}
+
+#[async_trait]
+impl Authorizer for MockAuthorizer {
+    async fn check(
+        &self,
+        actor: &Actor,
+        permission: Permission,
+        resource: Resource,
+    ) -> Result<bool, CoreError> {
+        Ok(self
+            .grants
+            .lock()
+            .unwrap()
+            .contains(&(*actor, permission, resource)))
+    }
+}
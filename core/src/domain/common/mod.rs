@@ -1,9 +1,10 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use utoipa::{IntoParams, ToSchema};
 
-use crate::domain::message::entities::MessageId;
+use crate::domain::message::entities::{ChannelId, MessageId};
 
+pub mod clock;
 pub mod services;
 
 #[derive(Error, Debug, Clone)]
@@ -11,15 +12,72 @@ pub enum CoreError {
     #[error("Service is currently unavailable")]
     ServiceUnavailable(String),
 
+    #[error("Could not connect to {dependency}: {reason}")]
+    DependencyUnavailable { dependency: String, reason: String },
+
+    #[error("The actor is not allowed to perform this operation")]
+    Forbidden,
+
     #[error("Message with id {id} not found")]
     MessageNotFound { id: MessageId },
 
+    #[error("Cursor message {id} no longer exists in the channel")]
+    InvalidCursor { id: MessageId },
+
+    #[error("Invalid time range: the start must not be after the end")]
+    InvalidTimeRange,
+
     #[error("Failed to insert message with name {name}")]
     FailedToInsertMessage { name: String },
 
     #[error("Message name cannot be empty")]
     InvalidMessageName,
 
+    #[error("Message content cannot be longer than {max} bytes")]
+    MessageTooLong { max: usize },
+
+    #[error("Ephemeral messages cannot be replies")]
+    EphemeralReply,
+
+    #[error("Message {id} cannot be replied to in this channel")]
+    InvalidReplyTarget { id: MessageId },
+
+    #[error("Invalid message batch: {reason}")]
+    InvalidBatch { reason: String },
+
+    #[error("Reaction emoji must be 1 to {max} characters without whitespace")]
+    InvalidReactionEmoji { max: usize },
+
+    #[error("Pin reason cannot be longer than {max} characters")]
+    PinReasonTooLong { max: usize },
+
+    #[error("Invalid pin order: {reason}")]
+    InvalidPinOrder { reason: String },
+
+    #[error("The channel already has the maximum number of pinned messages")]
+    PinLimitReached,
+
+    #[error("The message already has the maximum number of reactions")]
+    ReactionLimitReached,
+
+    #[error("Message {id} does not belong to channel {channel_id}")]
+    MessageNotInChannel { id: MessageId, channel_id: ChannelId },
+
+    #[error("The same content was posted too recently")]
+    DuplicateContent,
+
+    #[error("Too many messages, retry in {retry_after_secs} seconds")]
+    RateLimited { retry_after_secs: u64 },
+
+    #[error("Invalid wire format rule '{rule}', expected exchange[/routing_key]=json|protobuf")]
+    InvalidWireFormat { rule: String },
+
+    #[error("Invalid routing config: {reason}")]
+    InvalidRouting { reason: String },
+
+    #[error("Invalid rate limit policy: {reason}")]
+    InvalidRateLimitPolicy { reason: String },
+
     #[error("Health check failed")]
     Unhealthy,
 
@@ -44,6 +102,12 @@ pub enum CoreError {
     RabbitMqError { msg: String },
 }
 
+/// Page size used when the client doesn't ask for one
+pub const DEFAULT_PAGE_LIMIT: u32 = 20;
+
+/// Largest page size any listing returns, whatever the client asks for
+pub const MAX_PAGE_LIMIT: u32 = 50;
+
 #[derive(Debug, Deserialize, ToSchema, IntoParams)]
 #[into_params(parameter_in = Query)]
 pub struct GetPaginated {
@@ -51,10 +115,59 @@ pub struct GetPaginated {
     pub limit: u32,
 }
 
+impl GetPaginated {
+    /// The same request with `page` at least 1 and `limit` within 1..=[`MAX_PAGE_LIMIT`]
+    ///
+    /// Repositories page with this, so a `page` or `limit` of 0 can neither
+    /// underflow the offset nor turn into an unbounded query.
+    pub fn normalized(&self) -> Self {
+        Self {
+            page: self.page.max(1),
+            limit: self.limit.clamp(1, MAX_PAGE_LIMIT),
+        }
+    }
+
+    /// Number of items before the requested page, once normalized
+    pub fn offset(&self) -> u64 {
+        let normalized = self.normalized();
+        (normalized.page as u64 - 1) * normalized.limit as u64
+    }
+}
+
 impl Default for GetPaginated {
     fn default() -> Self {
-        Self { page: 1, limit: 20 }
+        Self {
+            page: 1,
+            limit: DEFAULT_PAGE_LIMIT,
+        }
     }
 }
 
 pub type TotalPaginatedElements = u64;
+
+/// Item of a batch operation that could not be applied
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchFailure {
+    pub id: MessageId,
+    pub reason: String,
+}
+
+/// Per-item outcome of a batch operation
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct BatchResult {
+    pub succeeded: Vec<MessageId>,
+    pub failed: Vec<BatchFailure>,
+}
+
+impl BatchResult {
+    pub fn succeed(&mut self, id: MessageId) {
+        self.succeeded.push(id);
+    }
+
+    pub fn fail(&mut self, id: MessageId, reason: impl Into<String>) {
+        self.failed.push(BatchFailure {
+            id,
+            reason: reason.into(),
+        });
+    }
+}
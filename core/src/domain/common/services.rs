@@ -1,4 +1,6 @@
-use crate::domain::{health::port::HealthRepository, message::ports::MessageRepository, attachment::port::AttachmentRepository, outbox::ports::OutboxEventRepository};
+use std::{sync::Arc, time::Duration};
+
+use crate::domain::{authorization::ports::{DenyAllAuthorizer, DynAuthorizer}, common::clock::{Clock, SystemClock}, health::port::{DynDependencyProbe, HealthRepository}, message::{duplicates::{DuplicateContentPolicy, DuplicateDetector}, entities::{BulkEventMode, DEFAULT_MAX_BULK_DELETE, DEFAULT_MAX_CONTENT_LENGTH, ErasureMode}, feed::MessageFeed, normalization::ContentNormalization, ports::MessageRepository, rate_limit::DynRateLimiter}, attachment::port::AttachmentRepository, outbox::ports::{DynEventPublisher, OutboxEventRepository}};
 
 #[derive(Clone)]
 
@@ -13,8 +15,24 @@ where
     pub(crate) health_repository: H,
     pub(crate) attachment_repository: A,
     pub(crate) outbox_repository: O,
+    pub(crate) content_normalization: ContentNormalization,
+    pub(crate) max_content_length: usize,
+    pub(crate) erasure_mode: ErasureMode,
+    pub(crate) bulk_event_mode: BulkEventMode,
+    pub(crate) max_bulk_delete: usize,
+    pub(crate) duplicate_detector: Option<Arc<DuplicateDetector>>,
+    pub(crate) rate_limiter: Option<DynRateLimiter>,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) health_check_timeout: Duration,
+    pub(crate) dependency_probes: Vec<DynDependencyProbe>,
+    pub(crate) authorizer: DynAuthorizer,
+    pub(crate) message_feed: MessageFeed,
+    pub(crate) event_publisher: Option<DynEventPublisher>,
 }
 
+/// Time a single dependency check may take before it is reported as failed
+pub const DEFAULT_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
 impl<S, H, A, O> Service<S, H, A, O>
 where
     S: MessageRepository,
@@ -28,6 +46,99 @@ where
             health_repository,
             attachment_repository,
             outbox_repository,
+            content_normalization: ContentNormalization::default(),
+            max_content_length: DEFAULT_MAX_CONTENT_LENGTH,
+            erasure_mode: ErasureMode::default(),
+            bulk_event_mode: BulkEventMode::default(),
+            max_bulk_delete: DEFAULT_MAX_BULK_DELETE,
+            duplicate_detector: None,
+            rate_limiter: None,
+            clock: Arc::new(SystemClock),
+            health_check_timeout: DEFAULT_HEALTH_CHECK_TIMEOUT,
+            dependency_probes: Vec::new(),
+            authorizer: Arc::new(DenyAllAuthorizer),
+            message_feed: MessageFeed::default(),
+            event_publisher: None,
         }
     }
+
+    /// Configure how message content is normalized on create and update
+    pub fn with_content_normalization(mut self, content_normalization: ContentNormalization) -> Self {
+        self.content_normalization = content_normalization;
+        self
+    }
+
+    /// Configure the longest content, in bytes, accepted on create and update
+    pub fn with_max_content_length(mut self, max_content_length: usize) -> Self {
+        self.max_content_length = max_content_length;
+        self
+    }
+
+    /// Configure whether erased users' messages are anonymized or deleted
+    pub fn with_erasure_mode(mut self, erasure_mode: ErasureMode) -> Self {
+        self.erasure_mode = erasure_mode;
+        self
+    }
+
+    /// Configure whether bulk operations emit one summary event or one event per message
+    pub fn with_bulk_event_mode(mut self, bulk_event_mode: BulkEventMode) -> Self {
+        self.bulk_event_mode = bulk_event_mode;
+        self
+    }
+
+    /// Configure the most messages a single bulk delete may remove
+    pub fn with_max_bulk_delete(mut self, max_bulk_delete: usize) -> Self {
+        self.max_bulk_delete = max_bulk_delete;
+        self
+    }
+
+    /// Enable detection of users reposting identical content within a window
+    pub fn with_duplicate_detection(mut self, policy: DuplicateContentPolicy) -> Self {
+        self.duplicate_detector = Some(Arc::new(DuplicateDetector::new(policy)));
+        self
+    }
+
+    /// Throttle users posting too many messages in a channel
+    pub fn with_rate_limiter(mut self, rate_limiter: DynRateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Configure how long each readiness dependency check may take
+    pub fn with_health_check_timeout(mut self, timeout: Duration) -> Self {
+        self.health_check_timeout = timeout;
+        self
+    }
+
+    /// Add a dependency to the detailed health check, next to the database
+    pub fn with_dependency_probe(mut self, probe: DynDependencyProbe) -> Self {
+        self.dependency_probes.push(probe);
+        self
+    }
+
+    /// Replace the authorizer guarding message operations, which refuses everything by default
+    pub fn with_authorizer(mut self, authorizer: DynAuthorizer) -> Self {
+        self.authorizer = authorizer;
+        self
+    }
+
+    /// Share the feed live channel streams subscribe to, fed by the broker consumer
+    pub fn with_message_feed(mut self, message_feed: MessageFeed) -> Self {
+        self.message_feed = message_feed;
+        self
+    }
+
+    /// Publish ephemeral signals, such as typing indicators, straight to the broker
+    ///
+    /// Without one those signals are dropped.
+    pub fn with_event_publisher(mut self, event_publisher: DynEventPublisher) -> Self {
+        self.event_publisher = Some(event_publisher);
+        self
+    }
+
+    /// Replace the clock used for time-based rules
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
 }
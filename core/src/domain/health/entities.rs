@@ -1,3 +1,6 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
 use crate::domain::common::CoreError;
 
 pub struct IsHealthy(bool);
@@ -19,3 +22,22 @@ impl IsHealthy {
         }
     }
 }
+
+/// Result of probing one dependency during a readiness check
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DependencyCheck {
+    pub name: String,
+    /// The dependency answered healthy within the check timeout
+    pub ok: bool,
+    pub latency_ms: u64,
+}
+
+/// Status of one dependency as reported by the detailed health check
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DependencyHealth {
+    pub name: String,
+    pub healthy: bool,
+    pub latency_ms: u64,
+    /// Why the dependency is reported unhealthy
+    pub detail: Option<String>,
+}
@@ -1,12 +1,36 @@
-use crate::domain::{common::CoreError, health::entities::IsHealthy};
-use std::future::Future;
+use crate::domain::{
+    common::CoreError,
+    health::entities::{DependencyCheck, DependencyHealth, IsHealthy},
+};
+use std::{future::Future, sync::Arc};
 
 pub trait HealthRepository: Send + Sync {
+    /// Dependency name reported by readiness checks
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
     fn ping(&self) -> impl Future<Output = IsHealthy> + Send;
 }
 
+/// Extra dependency probed by the detailed health check, such as the message broker
+#[async_trait::async_trait]
+pub trait DependencyProbe: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn ping(&self) -> IsHealthy;
+}
+
+pub type DynDependencyProbe = Arc<dyn DependencyProbe>;
+
 pub trait HealthService: Send + Sync {
     fn check_health(&self) -> impl Future<Output = Result<IsHealthy, CoreError>> + Send;
+
+    /// Probe every dependency, timing each one and failing checks that exceed the timeout
+    fn check_readiness(&self) -> impl Future<Output = Vec<DependencyCheck>> + Send;
+
+    /// Probe the database and every registered [`DependencyProbe`], explaining each failure
+    fn check_all(&self) -> impl Future<Output = Vec<DependencyHealth>> + Send;
 }
 pub struct MockHealthRepository;
 
@@ -1,7 +1,12 @@
 
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
 use crate::domain::{
     attachment::port::AttachmentRepository, common::{CoreError, services::Service}, health::{
-        entities::IsHealthy,
+        entities::{DependencyCheck, DependencyHealth, IsHealthy},
         port::{HealthRepository, HealthService},
     }, message::ports::MessageRepository, outbox::ports::OutboxEventRepository
 };
@@ -16,4 +21,68 @@ where
     async fn check_health(&self) -> Result<IsHealthy, CoreError> {
         self.health_repository.ping().await.to_result()
     }
+
+    async fn check_readiness(&self) -> Vec<DependencyCheck> {
+        vec![
+            probe(
+                self.health_repository.name(),
+                self.health_check_timeout,
+                self.health_repository.ping(),
+            )
+            .await,
+        ]
+    }
+
+    async fn check_all(&self) -> Vec<DependencyHealth> {
+        let mut checks = vec![
+            check_dependency(
+                self.health_repository.name(),
+                self.health_check_timeout,
+                self.health_repository.ping(),
+            )
+            .await,
+        ];
+        for probe in &self.dependency_probes {
+            checks.push(check_dependency(probe.name(), self.health_check_timeout, probe.ping()).await);
+        }
+        checks
+    }
+}
+
+async fn probe(
+    name: &str,
+    timeout: Duration,
+    check: impl Future<Output = IsHealthy>,
+) -> DependencyCheck {
+    let started = Instant::now();
+    let ok = match tokio::time::timeout(timeout, check).await {
+        Ok(is_healthy) => is_healthy.value(),
+        Err(_) => false,
+    };
+
+    DependencyCheck {
+        name: name.to_string(),
+        ok,
+        latency_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+async fn check_dependency(
+    name: &str,
+    timeout: Duration,
+    check: impl Future<Output = IsHealthy>,
+) -> DependencyHealth {
+    let started = Instant::now();
+    let detail = match tokio::time::timeout(timeout, check).await {
+        Ok(is_healthy) if is_healthy.value() => None,
+        Ok(_) => Some("ping failed".to_string()),
+        Err(_) => Some(format!("no answer within {}ms", timeout.as_millis())),
+    };
+
+    DependencyHealth {
+        name: name.to_string(),
+        healthy: detail.is_none(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        detail,
+    }
 }
@@ -0,0 +1,113 @@
+//! Detection of the same user posting identical content in quick succession
+//!
+//! Content is hashed after light normalization (case and whitespace) so that
+//! trivially altered copies still match. Only stored messages are recorded, so
+//! a retry after a failed insert is not mistaken for a repeat. The recent
+//! hashes live in memory and those out of the configured window are dropped
+//! every [`PRUNE_EVERY_RECORDS`] records.
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain::message::entities::AuthorId;
+
+/// Records between two sweeps of the hashes that fell out of the window
+pub const PRUNE_EVERY_RECORDS: u32 = 1_000;
+
+/// What to do with a message repeating recent content
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateContentAction {
+    /// Fail the creation with `CoreError::DuplicateContent`
+    Reject,
+    /// Accept the message and mark it as a duplicate in the creation response
+    ///
+    /// The mark is not stored with the message nor carried by its event.
+    Flag,
+}
+
+/// How duplicate content is handled and how far back to look
+#[derive(Clone, Copy, Debug)]
+pub struct DuplicateContentPolicy {
+    pub action: DuplicateContentAction,
+    pub window: Duration,
+}
+
+impl DuplicateContentPolicy {
+    pub fn new(action: DuplicateContentAction, window: Duration) -> Self {
+        Self { action, window }
+    }
+}
+
+/// Hash of `content` ignoring case and whitespace differences
+pub fn content_hash(content: &str) -> u64 {
+    let normalized = content
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut hasher = DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Default)]
+struct LastSeen {
+    by_key: HashMap<(AuthorId, u64), DateTime<Utc>>,
+    /// Records since the expired hashes were last dropped
+    records_since_prune: u32,
+}
+
+/// Remembers when each author last posted each content hash
+pub struct DuplicateDetector {
+    policy: DuplicateContentPolicy,
+    last_seen: Mutex<LastSeen>,
+}
+
+impl DuplicateDetector {
+    pub fn new(policy: DuplicateContentPolicy) -> Self {
+        Self {
+            policy,
+            last_seen: Mutex::new(LastSeen::default()),
+        }
+    }
+
+    pub fn policy(&self) -> &DuplicateContentPolicy {
+        &self.policy
+    }
+
+    /// Whether `author_id` already posted `content` within the window ending at `now`
+    pub fn is_duplicate(&self, author_id: &AuthorId, content: &str, now: DateTime<Utc>) -> bool {
+        let window_start = now - self.policy.window;
+        self.last_seen
+            .lock()
+            .unwrap()
+            .by_key
+            .get(&(*author_id, content_hash(content)))
+            .is_some_and(|seen_at| *seen_at > window_start)
+    }
+
+    /// Remember that `author_id` posted `content` at `now`
+    pub fn record(&self, author_id: &AuthorId, content: &str, now: DateTime<Utc>) {
+        let window_start = now - self.policy.window;
+        let mut last_seen = self.last_seen.lock().unwrap();
+
+        last_seen.records_since_prune += 1;
+        if last_seen.records_since_prune >= PRUNE_EVERY_RECORDS {
+            last_seen.records_since_prune = 0;
+            last_seen.by_key.retain(|_, seen_at| *seen_at > window_start);
+        }
+
+        last_seen.by_key.insert((*author_id, content_hash(content)), now);
+    }
+
+    /// Hashes currently remembered, expired or not
+    pub fn tracked_hashes(&self) -> usize {
+        self.last_seen.lock().unwrap().by_key.len()
+    }
+}
@@ -48,6 +48,13 @@ impl From<ChannelId> for Uuid {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
 pub struct AuthorId(pub Uuid);
 
+impl AuthorId {
+    /// Placeholder author given to messages whose author erased their data
+    pub fn anonymous() -> Self {
+        AuthorId(Uuid::nil())
+    }
+}
+
 impl std::fmt::Display for AuthorId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -112,6 +119,17 @@ pub struct Message {
     pub reply_to_message_id: Option<MessageId>,
     pub attachments: Vec<AttachmentId>,
     pub is_pinned: bool,
+    /// Moderator who pinned the message, when pinned individually
+    #[serde(default)]
+    pub pinned_by: Option<AuthorId>,
+    #[serde(default)]
+    pub pinned_at: Option<DateTime<Utc>>,
+    /// Why the message was pinned, cleared on unpin
+    #[serde(default)]
+    pub pin_reason: Option<String>,
+    /// Manual rank among the channel's pins, set by reordering and cleared on unpin
+    #[serde(default)]
+    pub pin_position: Option<u32>,
 
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -132,6 +150,54 @@ pub struct ReturnedMessage {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
+/// Longest message content, in bytes, accepted unless configured otherwise
+pub const DEFAULT_MAX_CONTENT_LENGTH: usize = 4000;
+
+/// Most messages deleted in one bulk delete unless configured otherwise
+pub const DEFAULT_MAX_BULK_DELETE: usize = 100;
+
+/// Maximum number of characters kept in a reply preview
+pub const REPLY_PREVIEW_MAX_CHARS: usize = 100;
+
+/// Truncate `content` to at most `max_chars` characters without splitting a
+/// UTF-8 code point, appending an ellipsis when something was cut.
+pub fn truncate_content(content: &str, max_chars: usize) -> String {
+    match content.char_indices().nth(max_chars) {
+        Some((byte_index, _)) => format!("{}…", &content[..byte_index]),
+        None => content.to_string(),
+    }
+}
+
+/// Snippet of the parent message returned alongside a newly created reply
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ReplyPreview {
+    pub message_id: MessageId,
+    pub author_id: AuthorId,
+    pub content: String,
+}
+
+impl From<&Message> for ReplyPreview {
+    fn from(parent: &Message) -> Self {
+        Self {
+            message_id: parent.id,
+            author_id: parent.author_id,
+            content: truncate_content(&parent.content, REPLY_PREVIEW_MAX_CHARS),
+        }
+    }
+}
+
+/// Message returned by `create_message`, with the parent preview when it is a reply
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CreatedMessage {
+    #[serde(flatten)]
+    pub message: Message,
+    pub reply_preview: Option<ReplyPreview>,
+    /// The message was only broadcast and will not be returned by later reads
+    pub ephemeral: bool,
+    /// The author posted the same content within the duplicate detection window
+    pub duplicate: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct InsertMessageInput {
     pub id: MessageId,
@@ -140,6 +206,9 @@ pub struct InsertMessageInput {
     pub content: String,
     pub reply_to_message_id: Option<MessageId>,
     pub attachments: Vec<AttachmentId>,
+    /// Deliver the message through the broker without storing it
+    #[serde(default)]
+    pub ephemeral: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
@@ -148,6 +217,8 @@ pub struct CreateMessageRequest {
     pub content: String,
     pub reply_to_message_id: Option<MessageId>,
     pub attachments: Vec<AttachmentId>,
+    #[serde(default)]
+    pub ephemeral: bool,
 }
 
 impl CreateMessageRequest {
@@ -159,21 +230,88 @@ impl CreateMessageRequest {
             content: self.content,
             reply_to_message_id: self.reply_to_message_id,
             attachments: self.attachments,
+            ephemeral: self.ephemeral,
         }
     }
 }
 
+/// What a message search matches; every filter that is set must hold
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageSearchCriteria {
+    /// Case-insensitive substring of the content
+    pub query: String,
+    pub author_id: Option<AuthorId>,
+    /// Only messages created strictly after this instant
+    pub after: Option<DateTime<Utc>>,
+    /// Only messages created strictly before this instant
+    pub before: Option<DateTime<Utc>>,
+}
+
+impl MessageSearchCriteria {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_author(mut self, author_id: AuthorId) -> Self {
+        self.author_id = Some(author_id);
+        self
+    }
+
+    pub fn with_after(mut self, after: DateTime<Utc>) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    pub fn with_before(mut self, before: DateTime<Utc>) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    /// Whether `message` satisfies every filter
+    pub fn matches(&self, message: &Message) -> bool {
+        message
+            .content
+            .to_lowercase()
+            .contains(&self.query.to_lowercase())
+            && self.author_id.is_none_or(|author_id| message.author_id == author_id)
+            && self.after.is_none_or(|after| message.created_at > after)
+            && self.before.is_none_or(|before| message.created_at < before)
+    }
+}
+
+impl From<&str> for MessageSearchCriteria {
+    fn from(query: &str) -> Self {
+        Self::new(query)
+    }
+}
+
+/// Channel the user recently posted in, with the time of their latest message there
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct RecentChannel {
+    pub channel_id: ChannelId,
+    pub last_message_at: DateTime<Utc>,
+}
+
+/// Author who posted in a channel, with how many of their messages remain there
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct ChannelParticipant {
+    pub author_id: AuthorId,
+    pub message_count: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct UpdateMessageInput {
     pub id: MessageId,
     pub content: Option<String>,
-    pub is_pinned: Option<bool>,
 }
 
+/// Edit of a message by its author; pins only change through the pin operations
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct UpdateMessageRequest {
     pub content: Option<String>,
-    pub is_pinned: Option<bool>,
 }
 
 impl UpdateMessageRequest {
@@ -181,11 +319,127 @@ impl UpdateMessageRequest {
         UpdateMessageInput {
             id,
             content: self.content,
-            is_pinned: self.is_pinned,
         }
     }
 }
 
+/// Maximum length, in characters, of a reaction emoji or shortcode
+pub const MAX_REACTION_EMOJI_CHARS: usize = 64;
+
+/// Maximum number of reactions, all users and emojis together, kept on a message
+///
+/// Reactions are stored on the message document, which must stay well under
+/// MongoDB's 16MB document limit.
+pub const MAX_REACTIONS_PER_MESSAGE: usize = 1_000;
+
+/// Emoji reaction left by a user on a message
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct Reaction {
+    pub message_id: MessageId,
+    pub user_id: AuthorId,
+    pub emoji: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A user's reaction state on a message after a toggle
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct ReactionToggle {
+    pub message_id: MessageId,
+    pub emoji: String,
+    /// The user now has this reaction on the message
+    pub reacted: bool,
+    /// Number of users with this reaction on the message
+    pub count: u64,
+}
+
+/// Users who reacted to a message with the same emoji
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct ReactionSummary {
+    pub emoji: String,
+    pub count: u64,
+    /// In the order they reacted
+    pub user_ids: Vec<AuthorId>,
+}
+
+/// Maximum number of channels whose latest message can be fetched in one call
+pub const MAX_LATEST_CHANNELS: usize = 100;
+
+/// Channels whose latest message is wanted, e.g. for a server's sidebar
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct LatestMessagesRequest {
+    pub channel_ids: Vec<ChannelId>,
+}
+
+/// Maximum number of pinned messages in a single channel
+pub const MAX_PINS_PER_CHANNEL: u64 = 50;
+
+/// Maximum length, in characters, of the note left when pinning a message
+pub const MAX_PIN_REASON_CHARS: usize = 200;
+
+/// Optional note explaining why a message is pinned
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct PinMessageRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Messages to pin or unpin in one call
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PinMessagesRequest {
+    pub message_ids: Vec<MessageId>,
+}
+
+/// Every pinned message of a channel, in the order they should be listed
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ReorderPinsRequest {
+    pub message_ids: Vec<MessageId>,
+}
+
+/// Channel-level details kept alongside its messages
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ChannelMetadata {
+    pub channel_id: ChannelId,
+    /// Message always shown first in the channel, separately from the pins
+    pub sticky_message_id: Option<MessageId>,
+    pub sticky_message: Option<Message>,
+}
+
+/// Message to make the channel's sticky, replacing the current one
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SetStickyRequest {
+    pub message_id: MessageId,
+}
+
+/// How far a user has read a channel
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct ChannelReadState {
+    pub user_id: AuthorId,
+    pub channel_id: ChannelId,
+    /// Latest message the user has seen; every message after it is unread
+    pub last_read_message_id: MessageId,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Message the user has read the channel up to
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct MarkReadRequest {
+    pub message_id: MessageId,
+}
+
+/// Messages of a channel the user has not read yet
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct UnreadCount {
+    pub channel_id: ChannelId,
+    pub unread_count: u64,
+}
+
+/// Size of a message's thread, without the replies themselves
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct ReplyCount {
+    pub message_id: MessageId,
+    pub reply_count: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateMessageEvent {
     pub id: MessageId,
@@ -197,3 +451,33 @@ pub struct UpdateMessageEvent {
 pub struct DeleteMessageEvent {
     pub id: MessageId,
 }
+
+/// Content left in place of a message anonymized by a data erasure
+pub const ERASED_MESSAGE_CONTENT: &str = "[deleted]";
+
+/// What happens to a user's messages when their data is erased
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErasureMode {
+    /// Keep the messages with tombstone content and an anonymous author
+    #[default]
+    Anonymize,
+    /// Remove the messages entirely
+    Delete,
+}
+
+/// How a bulk operation reports the messages it changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BulkEventMode {
+    /// Emit a single `messages.bulk_changed` summary event
+    #[default]
+    Summary,
+    /// Emit the regular per-message update or delete events
+    PerItem,
+}
+
+/// Outcome of a data erasure
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ErasureReport {
+    /// Number of messages anonymized or deleted
+    pub affected: u64,
+}
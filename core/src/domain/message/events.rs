@@ -2,9 +2,13 @@ use events_protobuf::messages_events::create_message_event::Attachment;
 use events_protobuf::messages_events::{
     CreateMessageEvent, DeleteMessageEvent, NotifyEntry, UpdateMessageEvent
 };
+use chrono::{DateTime, Utc};
+use serde_json::json;
 use uuid::Uuid;
 
-use crate::domain::message::entities::{ChannelId, MessageId};
+use crate::domain::common::CoreError;
+use crate::domain::message::entities::{AuthorId, ChannelId, Message, MessageId};
+use crate::infrastructure::outbox::{MessageRoutingInfo, OutboxEventRecord};
 
 /// Convert domain entities to protobuf CreateMessageEvent
 pub fn create_message_event_from_domain(
@@ -34,6 +38,38 @@ pub fn create_message_event_from_domain(
     }
 }
 
+/// Outbox record announcing a newly created message, tagged with the message id
+pub fn created_event_record(
+    message: &Message,
+) -> Result<OutboxEventRecord<MessageRoutingInfo>, CoreError> {
+    // Attachment URLs are signed on demand, so the event only carries ids
+    let attachments = message
+        .attachments
+        .iter()
+        .cloned()
+        .map(|id| crate::domain::message::entities::Attachment {
+            id,
+            url: String::new(),
+        })
+        .collect();
+    let event = create_message_event_from_domain(
+        message.id.0,
+        message.channel_id.0,
+        message.author_id.0,
+        message.content.clone(),
+        message.reply_to_message_id.map(|id| id.0),
+        attachments,
+    );
+    let event_bytes = event_to_bytes(&event)
+        .map_err(|e| CoreError::SerializationError { msg: e.to_string() })?;
+
+    Ok(OutboxEventRecord::new(
+        MessageRoutingInfo::new("notifications", "message.created"),
+        event_bytes,
+    )
+    .with_aggregate_id(message.id.0))
+}
+
 pub fn delete_message_event_from_domain(
     message_id: MessageId,
     channel_id: ChannelId,
@@ -60,9 +96,200 @@ pub fn update_message_event_from_domain(
     }
 }
 
+/// Summary emitted once for a bulk operation instead of one event per message
+///
+/// Defined locally because the shared protobuf schema has no bulk event yet.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BulkMessagesChangedEvent {
+    /// Name of the bulk operation, e.g. `pin` or `erase`
+    #[prost(string, tag = "1")]
+    pub operation: String,
+    /// Channel the operation was scoped to, empty when it spans several channels
+    #[prost(string, tag = "2")]
+    pub channel_id: String,
+    #[prost(uint32, tag = "3")]
+    pub affected_count: u32,
+    #[prost(string, repeated, tag = "4")]
+    pub message_ids: Vec<String>,
+}
+
+pub fn bulk_messages_changed_event_from_domain(
+    operation: &str,
+    channel_id: Option<ChannelId>,
+    message_ids: &[MessageId],
+) -> BulkMessagesChangedEvent {
+    BulkMessagesChangedEvent {
+        operation: operation.to_string(),
+        channel_id: channel_id.map(|id| id.to_string()).unwrap_or_default(),
+        affected_count: message_ids.len() as u32,
+        message_ids: message_ids.iter().map(|id| id.to_string()).collect(),
+    }
+}
+
+/// Emitted when a user adds or removes a reaction on a message
+///
+/// Defined locally because the shared protobuf schema has no reaction event yet.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MessageReactionEvent {
+    #[prost(string, tag = "1")]
+    pub message_id: String,
+    #[prost(string, tag = "2")]
+    pub channel_id: String,
+    #[prost(string, tag = "3")]
+    pub user_id: String,
+    #[prost(string, tag = "4")]
+    pub emoji: String,
+}
+
+pub fn message_reaction_event_from_domain(
+    message_id: MessageId,
+    channel_id: ChannelId,
+    user_id: AuthorId,
+    emoji: &str,
+) -> MessageReactionEvent {
+    MessageReactionEvent {
+        message_id: message_id.to_string(),
+        channel_id: channel_id.to_string(),
+        user_id: user_id.to_string(),
+        emoji: emoji.to_string(),
+    }
+}
+
+/// Emitted when a single message is pinned or unpinned
+///
+/// Defined locally because the shared protobuf schema has no pin event yet.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MessagePinEvent {
+    #[prost(string, tag = "1")]
+    pub message_id: String,
+    #[prost(string, tag = "2")]
+    pub channel_id: String,
+    /// User who pinned or unpinned the message
+    #[prost(string, tag = "3")]
+    pub actor_id: String,
+    /// Note left when pinning, empty when there is none or on unpin
+    #[prost(string, tag = "4")]
+    pub reason: String,
+}
+
+pub fn message_pin_event_from_domain(message: &Message, actor_id: AuthorId) -> MessagePinEvent {
+    MessagePinEvent {
+        message_id: message.id.to_string(),
+        channel_id: message.channel_id.to_string(),
+        actor_id: actor_id.to_string(),
+        reason: message.pin_reason.clone().unwrap_or_default(),
+    }
+}
+
+/// Announced while a user is typing in a channel
+///
+/// Published straight to the broker and never stored, so a missed one is
+/// simply gone. Defined locally because the shared protobuf schema has no
+/// typing event yet.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ChannelTypingEvent {
+    #[prost(string, tag = "1")]
+    pub channel_id: String,
+    #[prost(string, tag = "2")]
+    pub user_id: String,
+    /// RFC3339 time the user was seen typing
+    #[prost(string, tag = "3")]
+    pub timestamp: String,
+}
+
+pub fn channel_typing_event_from_domain(
+    channel_id: ChannelId,
+    user_id: AuthorId,
+    at: DateTime<Utc>,
+) -> ChannelTypingEvent {
+    ChannelTypingEvent {
+        channel_id: channel_id.to_string(),
+        user_id: user_id.to_string(),
+        timestamp: at.to_rfc3339(),
+    }
+}
+
 /// Serialize any prost::Message to protobuf bytes for RabbitMQ publishing
 pub fn event_to_bytes<M: prost::Message>(event: &M) -> Result<Vec<u8>, prost::EncodeError> {
     let mut buf = Vec::new();
     event.encode(&mut buf)?;
     Ok(buf)
 }
+
+/// Render a stored protobuf event as JSON, for consumers that can't parse protobuf
+///
+/// The event type is found from the routing key it was written with.
+/// `notify_entries` is left out: this service always writes it empty.
+pub fn event_to_json(routing_key: &str, payload: &[u8]) -> Result<Vec<u8>, CoreError> {
+    let value = match routing_key {
+        "message.created" => {
+            let event: CreateMessageEvent = decode_event(payload)?;
+            json!({
+                "message_id": event.message_id,
+                "channel_id": event.channel_id,
+                "author_id": event.author_id,
+                "content": event.content,
+                "reply_to_message_id": Some(event.reply_to_message_id).filter(|id| !id.is_empty()),
+                "attachments": event
+                    .attachments
+                    .into_iter()
+                    .map(|attachment| json!({ "id": attachment.id, "url": attachment.url }))
+                    .collect::<Vec<_>>(),
+            })
+        }
+        "message.updated" => {
+            let event: UpdateMessageEvent = decode_event(payload)?;
+            json!({
+                "message_id": event.message_id,
+                "channel_id": event.channel_id,
+                "content": event.content,
+                "is_pinned": event.is_pinned,
+            })
+        }
+        "message.deleted" => {
+            let event: DeleteMessageEvent = decode_event(payload)?;
+            json!({
+                "message_id": event.message_id,
+                "channel_id": event.channel_id,
+            })
+        }
+        "messages.bulk_changed" => {
+            let event: BulkMessagesChangedEvent = decode_event(payload)?;
+            json!({
+                "operation": event.operation,
+                "channel_id": Some(event.channel_id).filter(|id| !id.is_empty()),
+                "affected_count": event.affected_count,
+                "message_ids": event.message_ids,
+            })
+        }
+        "message.reaction.added" | "message.reaction.removed" => {
+            let event: MessageReactionEvent = decode_event(payload)?;
+            json!({
+                "message_id": event.message_id,
+                "channel_id": event.channel_id,
+                "user_id": event.user_id,
+                "emoji": event.emoji,
+            })
+        }
+        "message.pinned" | "message.unpinned" => {
+            let event: MessagePinEvent = decode_event(payload)?;
+            json!({
+                "message_id": event.message_id,
+                "channel_id": event.channel_id,
+                "actor_id": event.actor_id,
+                "reason": Some(event.reason).filter(|reason| !reason.is_empty()),
+            })
+        }
+        _ => {
+            return Err(CoreError::SerializationError {
+                msg: format!("No JSON rendering for events routed as '{}'", routing_key),
+            });
+        }
+    };
+
+    serde_json::to_vec(&value).map_err(|e| CoreError::SerializationError { msg: e.to_string() })
+}
+
+fn decode_event<M: prost::Message + Default>(payload: &[u8]) -> Result<M, CoreError> {
+    M::decode(payload).map_err(|e| CoreError::SerializationError { msg: e.to_string() })
+}
@@ -0,0 +1,166 @@
+//! Live fan-out of message changes
+//!
+//! The broker consumer publishes every `message.created`, `message.updated`
+//! and `message.deleted` event it receives into a [`MessageFeed`]; each open
+//! channel stream holds one subscription and only sees the events of its own
+//! channel.
+
+use std::sync::Arc;
+
+use futures::{StreamExt, stream::BoxStream};
+use serde::Serialize;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    watch,
+};
+use utoipa::ToSchema;
+
+use crate::domain::message::entities::{AttachmentId, AuthorId, ChannelId, MessageId};
+
+/// Messages a subscriber may fall behind by before it starts skipping some
+pub const DEFAULT_FEED_CAPACITY: usize = 256;
+
+/// New message as announced to live subscribers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, ToSchema)]
+pub struct LiveMessage {
+    pub id: MessageId,
+    pub channel_id: ChannelId,
+    pub author_id: AuthorId,
+    pub content: String,
+    pub reply_to_message_id: Option<MessageId>,
+    pub attachments: Vec<AttachmentId>,
+}
+
+/// Change to a message as announced to live subscribers; absent fields were left unchanged
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, ToSchema)]
+pub struct LiveMessageUpdate {
+    pub id: MessageId,
+    pub channel_id: ChannelId,
+    pub content: Option<String>,
+    pub is_pinned: Option<bool>,
+}
+
+/// Deleted message as announced to live subscribers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, ToSchema)]
+pub struct LiveMessageDeletion {
+    pub id: MessageId,
+    pub channel_id: ChannelId,
+}
+
+/// Anything a live subscriber is told about, serialized as the message it concerns
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum LiveEvent {
+    Created(LiveMessage),
+    Updated(LiveMessageUpdate),
+    Deleted(LiveMessageDeletion),
+}
+
+impl LiveEvent {
+    /// Routing key the event was published with, also used as the SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            LiveEvent::Created(_) => "message.created",
+            LiveEvent::Updated(_) => "message.updated",
+            LiveEvent::Deleted(_) => "message.deleted",
+        }
+    }
+
+    pub fn channel_id(&self) -> ChannelId {
+        match self {
+            LiveEvent::Created(message) => message.channel_id,
+            LiveEvent::Updated(update) => update.channel_id,
+            LiveEvent::Deleted(deletion) => deletion.channel_id,
+        }
+    }
+}
+
+impl From<LiveMessage> for LiveEvent {
+    fn from(message: LiveMessage) -> Self {
+        LiveEvent::Created(message)
+    }
+}
+
+impl From<LiveMessageUpdate> for LiveEvent {
+    fn from(update: LiveMessageUpdate) -> Self {
+        LiveEvent::Updated(update)
+    }
+}
+
+impl From<LiveMessageDeletion> for LiveEvent {
+    fn from(deletion: LiveMessageDeletion) -> Self {
+        LiveEvent::Deleted(deletion)
+    }
+}
+
+/// Message events of one channel, ending when the feed is closed
+pub type LiveEventStream = BoxStream<'static, LiveEvent>;
+
+#[derive(Clone)]
+pub struct MessageFeed {
+    sender: broadcast::Sender<LiveEvent>,
+    closed: Arc<watch::Sender<bool>>,
+}
+
+impl MessageFeed {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        let (closed, _) = watch::channel(false);
+        Self {
+            sender,
+            closed: Arc::new(closed),
+        }
+    }
+
+    /// Hand an event to every current subscriber of its channel
+    pub fn publish(&self, event: impl Into<LiveEvent>) {
+        // Nobody listening is not an error, the event is simply dropped
+        let _ = self.sender.send(event.into());
+    }
+
+    /// Number of open subscriptions, across all channels
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Subscribe to the events of a channel published from now on
+    ///
+    /// Dropping the stream releases the subscription; [`MessageFeed::close`] ends it.
+    pub fn subscribe(&self, channel_id: ChannelId) -> LiveEventStream {
+        let state = (self.sender.subscribe(), self.closed.subscribe());
+        futures::stream::unfold(state, move |(mut receiver, mut closed)| async move {
+            loop {
+                let received = tokio::select! {
+                    received = receiver.recv() => received,
+                    _ = closed.wait_for(|closed| *closed) => return None,
+                };
+                match received {
+                    Ok(event) if event.channel_id() == channel_id => {
+                        return Some((event, (receiver, closed)));
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            channel_id = %channel_id,
+                            skipped,
+                            "Live subscriber fell behind, skipping events"
+                        );
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+        .boxed()
+    }
+
+    /// End every subscription, e.g. so open HTTP streams don't hold up a shutdown
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+}
+
+impl Default for MessageFeed {
+    fn default() -> Self {
+        Self::new(DEFAULT_FEED_CAPACITY)
+    }
+}
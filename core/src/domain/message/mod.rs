@@ -1,4 +1,8 @@
+pub mod duplicates;
 pub mod entities;
 pub mod events;
+pub mod feed;
+pub mod normalization;
 pub mod ports;
+pub mod rate_limit;
 pub mod services;
@@ -0,0 +1,86 @@
+//! Content normalization applied before a message is stored
+//!
+//! Spam often hides behind invisible characters, walls of blank lines or
+//! trailing whitespace. When enabled, [`normalize_content`] cleans those up
+//! while leaving fenced code blocks (```` ``` ````) untouched.
+
+/// Marker opening and closing a fenced code block
+const CODE_FENCE: &str = "```";
+
+/// Whether content is normalized and how many consecutive blank lines are kept
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContentNormalization {
+    pub enabled: bool,
+    pub max_blank_lines: usize,
+}
+
+impl ContentNormalization {
+    pub fn new(enabled: bool, max_blank_lines: usize) -> Self {
+        Self {
+            enabled,
+            max_blank_lines,
+        }
+    }
+
+    /// Normalize `content` if enabled, otherwise return it unchanged
+    pub fn apply(&self, content: &str) -> String {
+        if self.enabled {
+            normalize_content(content, self.max_blank_lines)
+        } else {
+            content.to_string()
+        }
+    }
+}
+
+impl Default for ContentNormalization {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_blank_lines: 2,
+        }
+    }
+}
+
+/// Zero-width characters and control characters other than newline and tab
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}')
+        || (c.is_control() && c != '\n' && c != '\t')
+}
+
+/// Strip invisible characters, trim trailing whitespace on each line, keep at
+/// most `max_blank_lines` consecutive blank lines and trim the whole message
+///
+/// Lines inside fenced code blocks, fences included, are kept verbatim.
+pub fn normalize_content(content: &str, max_blank_lines: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+    let mut blank_run = 0;
+
+    for line in content.split('\n') {
+        let is_fence = line.trim_start().starts_with(CODE_FENCE);
+
+        if in_code_block || is_fence {
+            if is_fence {
+                in_code_block = !in_code_block;
+            }
+            blank_run = 0;
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let cleaned: String = line.chars().filter(|c| !is_invisible(*c)).collect();
+        let cleaned = cleaned.trim_end();
+
+        if cleaned.is_empty() {
+            blank_run += 1;
+            if blank_run > max_blank_lines {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        lines.push(cleaned.to_string());
+    }
+
+    lines.join("\n").trim().to_string()
+}
@@ -1,27 +1,190 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, stream::BoxStream};
 
 use crate::domain::{
-    common::{CoreError, GetPaginated, TotalPaginatedElements},
-    message::entities::{ChannelId, InsertMessageInput, Message, MessageId, ReturnedMessage, UpdateMessageInput},
+    authorization::entities::Actor,
+    common::{BatchResult, CoreError, GetPaginated, MAX_PAGE_LIMIT, TotalPaginatedElements},
+    message::entities::{
+        AuthorId, ChannelId, ChannelMetadata, ChannelParticipant, ChannelReadState, CreatedMessage, ErasureReport, InsertMessageInput,
+        MAX_REACTIONS_PER_MESSAGE, Message, MessageId, MessageSearchCriteria, Reaction, ReactionSummary, ReactionToggle, RecentChannel, ReplyCount, ReturnedMessage,
+        UnreadCount, UpdateMessageInput,
+    },
+    message::feed::LiveEventStream,
 };
 
+/// Messages yielded one at a time, without loading the whole result in memory
+pub type MessageStream = BoxStream<'static, Result<Message, CoreError>>;
+
 #[async_trait::async_trait]
 pub trait MessageRepository: Send + Sync {
     async fn insert(&self, input: InsertMessageInput) -> Result<Message, CoreError>;
+    /// Insert every message or none of them
+    async fn insert_many_atomic(
+        &self,
+        inputs: Vec<InsertMessageInput>,
+    ) -> Result<Vec<Message>, CoreError>;
     async fn find_by_id(&self, id: &MessageId) -> Result<Option<Message>, CoreError>;
     async fn list(
         &self,
         channel_id: &ChannelId,
         pagination: &GetPaginated,
     ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError>;
+    /// Up to `limit` messages older than the `before` message, newest first
+    ///
+    /// Also returns the cursor of the next page, `None` once the oldest message
+    /// was returned. Fails with `InvalidCursor` when the cursor message is not
+    /// in the channel anymore.
+    async fn list_before(
+        &self,
+        channel_id: &ChannelId,
+        before: Option<&MessageId>,
+        limit: u32,
+    ) -> Result<(Vec<Message>, Option<MessageId>), CoreError>;
+    /// Messages created from `from` (inclusive) until `to` (exclusive), oldest first
+    async fn list_in_range(
+        &self,
+        channel_id: &ChannelId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError>;
+    /// Messages of a channel matching every filter of `criteria`, newest first
     async fn search_messages(
         &self,
         channel_id: &ChannelId,
-        query: &str,
+        criteria: &MessageSearchCriteria,
         pagination: &GetPaginated,
     ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError>;
+    /// Replies to a message, oldest first
+    async fn list_replies(
+        &self,
+        message_id: &MessageId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError>;
+    /// Number of replies to a message
+    async fn count_replies(&self, message_id: &MessageId) -> Result<u64, CoreError>;
+    async fn list_recent_channels(
+        &self,
+        author_id: &AuthorId,
+        limit: u32,
+    ) -> Result<Vec<RecentChannel>, CoreError>;
+    async fn list_channel_participants(
+        &self,
+        channel_id: &ChannelId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<ChannelParticipant>, TotalPaginatedElements), CoreError>;
+    /// Newest non-deleted message of each channel; channels without one are absent
+    async fn latest_messages_for_channels(
+        &self,
+        channel_ids: &[ChannelId],
+    ) -> Result<HashMap<ChannelId, Message>, CoreError>;
+    async fn stream_by_author(&self, author_id: &AuthorId) -> Result<MessageStream, CoreError>;
+    /// Every message of the author, soft-deleted ones included, so erasure leaves none behind
+    async fn stream_by_author_including_deleted(
+        &self,
+        author_id: &AuthorId,
+    ) -> Result<MessageStream, CoreError>;
+    async fn count_pinned(&self, channel_id: &ChannelId) -> Result<u64, CoreError>;
+    /// Pinned messages of a channel, most recently pinned first
+    async fn list_pinned(&self, channel_id: &ChannelId) -> Result<Vec<Message>, CoreError>;
+    /// Set each message's `pin_position` to its index in `ordered_ids`
+    async fn set_pin_positions(&self, ordered_ids: &[MessageId]) -> Result<(), CoreError>;
+    /// The channel's sticky message, if one was set
+    async fn find_sticky(&self, channel_id: &ChannelId) -> Result<Option<MessageId>, CoreError>;
+    /// Make the message the channel's sticky, replacing any previous one
+    async fn set_sticky(&self, channel_id: &ChannelId, message_id: &MessageId) -> Result<(), CoreError>;
+    /// Remove the channel's sticky, returning whether there was one
+    async fn clear_sticky(&self, channel_id: &ChannelId) -> Result<bool, CoreError>;
+    /// How far the user has read the channel, if they ever marked it read
+    async fn get_last_read(
+        &self,
+        user_id: &AuthorId,
+        channel_id: &ChannelId,
+    ) -> Result<Option<ChannelReadState>, CoreError>;
+    /// Record how far the user has read the channel, if further than before
+    ///
+    /// `read_up_to` is the creation time of the message marked read. The state
+    /// is only replaced when it is later than the stored one, in a single write
+    /// so concurrent calls can't move the marker back. Returns whether the
+    /// state was replaced.
+    async fn set_last_read(
+        &self,
+        state: &ChannelReadState,
+        read_up_to: DateTime<Utc>,
+    ) -> Result<bool, CoreError>;
+    /// Forget how far the user has read every channel, returning how many states were removed
+    async fn delete_read_states(&self, user_id: &AuthorId) -> Result<u64, CoreError>;
+    /// Number of messages of the channel created strictly after `after`, or all of them
+    async fn count_created_after(
+        &self,
+        channel_id: &ChannelId,
+        after: Option<DateTime<Utc>>,
+    ) -> Result<u64, CoreError>;
+    /// Pin a message, recording who pinned it, when and why
+    ///
+    /// Fails with `CoreError::PinLimitReached` rather than pin a message when
+    /// its channel already has `max_pins` pinned, concurrent pins included.
+    async fn pin(
+        &self,
+        id: &MessageId,
+        pinned_by: &AuthorId,
+        reason: Option<&str>,
+        max_pins: u64,
+    ) -> Result<Message, CoreError>;
+    /// Unpin a message, clearing who pinned it, when, why and its rank among the pins
+    async fn unpin(&self, id: &MessageId) -> Result<Message, CoreError>;
+    /// Apply the given fields
+    ///
+    /// Only persists the change: the `message.updated` event is the service's to write.
     async fn update(&self, input: UpdateMessageInput) -> Result<Message, CoreError>;
+    /// Replace the content and author of the message, deleted or not
+    async fn anonymize(&self, id: &MessageId, content: &str) -> Result<Message, CoreError>;
+    /// Add the reaction if the user doesn't have it yet, remove it otherwise, in one atomic step
+    ///
+    /// Fails with `CoreError::ReactionLimitReached` rather than add a reaction
+    /// to a message that already has `MAX_REACTIONS_PER_MESSAGE`.
+    async fn toggle_reaction(
+        &self,
+        message_id: &MessageId,
+        user_id: &AuthorId,
+        emoji: &str,
+    ) -> Result<ReactionToggle, CoreError>;
+    /// Add the user's reaction, returning whether it was new
+    ///
+    /// Fails with `CoreError::ReactionLimitReached` when the reaction is new
+    /// and the message already has `MAX_REACTIONS_PER_MESSAGE`.
+    async fn add_reaction(
+        &self,
+        message_id: &MessageId,
+        user_id: &AuthorId,
+        emoji: &str,
+    ) -> Result<bool, CoreError>;
+    /// Remove the user's reaction, returning whether there was one
+    async fn remove_reaction(
+        &self,
+        message_id: &MessageId,
+        user_id: &AuthorId,
+        emoji: &str,
+    ) -> Result<bool, CoreError>;
+    /// Reactions on a message grouped by emoji, in the order each emoji was first used
+    async fn list_reactions(&self, message_id: &MessageId) -> Result<Vec<ReactionSummary>, CoreError>;
+    /// Remove every reaction the user left, on any message, deleted or not, returning how many messages changed
+    async fn remove_reactions_by(&self, user_id: &AuthorId) -> Result<u64, CoreError>;
+    /// Forget that the user pinned messages, deleted or not, returning how many messages changed
+    ///
+    /// The messages stay pinned.
+    async fn clear_pinned_by(&self, user_id: &AuthorId) -> Result<u64, CoreError>;
+    /// Like `find_by_id`, but also returns soft-deleted messages
+    async fn find_including_deleted(&self, id: &MessageId) -> Result<Option<Message>, CoreError>;
+    /// Soft-delete: the message is kept but no longer returned by reads
     async fn delete(&self, id: &MessageId) -> Result<(), CoreError>;
+    /// Remove the message for good, deleted or not, e.g. to honour an erasure request
+    async fn purge(&self, id: &MessageId) -> Result<(), CoreError>;
 }
 
 /// A service for managing message operations in the application.
@@ -43,19 +206,47 @@ pub trait MessageService: Send + Sync {
     /// Creates a new message with the provided input.
     ///
     /// This method performs business logic validation before delegating to the repository.
-    /// It ensures that all required fields are present and valid, and that the user
-    /// creating the message has the necessary permissions.
+    /// It ensures that all required fields are present and valid, and that the author
+    /// can send messages to the channel, and attach files when there are attachments.
     ///
     /// # Arguments
     ///
     /// * `input` - The message creation input containing name, owner_id, and optional fields
     ///
+    /// When the message replies to another one, the parent is fetched in the same
+    /// call and returned as a truncated `reply_preview`. A missing or deleted
+    /// parent yields no preview, and so does one from another channel.
+    ///
     /// # Returns
     ///
     /// Returns a `Future` that resolves to:
-    /// - `Ok(Message)` - The newly created message
+    /// - `Ok(CreatedMessage)` - The newly created message and its optional reply preview
+    /// - `Err(CoreError::Forbidden)` - The author may not post, or attach files, in the channel
+    /// - `Err(CoreError::RateLimited)` - The author posted too many messages in the channel
     /// - `Err(CoreError)` - If validation fails or repository operation fails
-    async fn create_message(&self, input: InsertMessageInput) -> Result<Message, CoreError>;
+    async fn create_message(&self, input: InsertMessageInput) -> Result<CreatedMessage, CoreError>;
+
+    /// Creates several related messages in a single transaction.
+    ///
+    /// Either every message is stored or none is. A message may reply to one
+    /// placed before it in the batch or to an existing message; any other
+    /// reply reference rejects the whole batch. Every author must be allowed to
+    /// post in their message's channel. Create events are written only once
+    /// the insert has committed.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The messages to create, in insertion order
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<Message>)` - The created messages, in input order
+    /// - `Err(CoreError::InvalidBatch)` - The batch is inconsistent
+    /// - `Err(CoreError)` - If validation fails or the transaction is aborted
+    async fn create_messages_atomic(
+        &self,
+        inputs: Vec<InsertMessageInput>,
+    ) -> Result<Vec<Message>, CoreError>;
 
     /// Retrieves a message by its unique identifier.
     ///
@@ -65,6 +256,7 @@ pub trait MessageService: Send + Sync {
     ///
     /// # Arguments
     ///
+    /// * `actor` - The user reading the message, who must be able to view its channel
     /// * `message_id` - A reference to the unique identifier of the message to retrieve.
     ///   This should be a valid [`MessageId`] that represents an existing message.
     ///
@@ -73,45 +265,445 @@ pub trait MessageService: Send + Sync {
     /// Returns a `Future` that resolves to:
     /// - `Ok(Message)` - The message was found and the user has permission to access it
     /// - `Err(CoreError::MessageNotFound)` - No message exists with the given ID
-    /// - `Err(CoreError)` - Other errors such as database connectivity issues or authorization failures
-    async fn get_message(&self, message_id: &MessageId) -> Result<Message, CoreError>;
+    /// - `Err(CoreError::Forbidden)` - The actor cannot view the message's channel
+    /// - `Err(CoreError)` - Other errors such as database connectivity issues
+    async fn get_message(&self, actor: &Actor, message_id: &MessageId) -> Result<Message, CoreError>;
+
+    /// Lists the replies to a message, oldest first, so a thread reads in order.
+    ///
+    /// The actor must be able to view the channel of the message replied to.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok((replies, total))` - A page of replies and how many there are in all
+    /// - `Err(CoreError::MessageNotFound)` - No message exists with the given ID
+    /// - `Err(CoreError::Forbidden)` - The actor cannot view the message's channel
+    async fn list_replies(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError>;
+
+    /// Counts the replies to a message, e.g. to show "3 replies" without loading them.
+    ///
+    /// Requires the same permission as [`MessageService::list_replies`].
+    async fn reply_count(&self, actor: &Actor, message_id: &MessageId) -> Result<ReplyCount, CoreError>;
 
     /// Lists messages with pagination support.
     ///
-    /// This method retrieves a paginated list of messages. The implementation should
-    /// apply visibility filters based on user permissions and authorization rules.
+    /// This method retrieves a paginated list of messages of a channel the actor
+    /// can view.
     ///
     /// # Arguments
     ///
+    /// * `actor` - The user listing the messages
     /// * `pagination` - Pagination parameters (page and limit)
     ///
     /// # Returns
     ///
     /// Returns a `Future` that resolves to:
     /// - `Ok((Vec<Message>, TotalPaginatedElements))` - List of messages and total count
+    /// - `Err(CoreError::Forbidden)` - The actor cannot view the channel
     /// - `Err(CoreError)` - If repository operation fails
     async fn list_messages(
         &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<ReturnedMessage>, TotalPaginatedElements), CoreError>;
+
+    /// Lists messages in a channel older than a cursor message, newest first.
+    ///
+    /// Unlike offset pagination, pages stay stable while new messages arrive.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The user listing the messages
+    /// * `before` - Last message of the previous page, `None` for the latest page
+    /// * `limit` - Page size, clamped to `1..=50`
+    ///
+    /// # Returns
+    ///
+    /// - `Ok((Vec<ReturnedMessage>, Option<MessageId>))` - The page and the cursor of the next one
+    /// - `Err(CoreError::InvalidCursor)` - The cursor message was deleted or belongs to another channel
+    /// - `Err(CoreError::Forbidden)` - The actor cannot view the channel
+    async fn list_messages_before(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        before: Option<&MessageId>,
+        limit: u32,
+    ) -> Result<(Vec<ReturnedMessage>, Option<MessageId>), CoreError>;
+
+    /// Lists the messages of a channel created within a time range, oldest first.
+    ///
+    /// The range includes `from` and excludes `to`, so consecutive ranges never
+    /// return a message twice. Soft-deleted messages are left out.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok((Vec<ReturnedMessage>, TotalPaginatedElements))` - The page and the number of messages in the range
+    /// - `Err(CoreError::InvalidTimeRange)` - `from` is after `to`
+    /// - `Err(CoreError::Forbidden)` - The actor cannot view the channel
+    async fn list_messages_in_range(
+        &self,
+        actor: &Actor,
         channel_id: &ChannelId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
         pagination: &GetPaginated,
     ) -> Result<(Vec<ReturnedMessage>, TotalPaginatedElements), CoreError>;
 
+    /// Streams the messages created, updated and deleted in a channel from now on.
+    ///
+    /// The actor must be able to view the channel; the check happens once, when
+    /// subscribing. Dropping the stream ends the subscription.
+    async fn subscribe_channel(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+    ) -> Result<LiveEventStream, CoreError>;
+
+    /// Tells the channel that the actor is typing.
+    ///
+    /// The `channel.typing` event is published straight to the broker rather
+    /// than through the outbox: it is not stored and never replayed.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` - The event was published, or dropped when no publisher is configured
+    /// - `Err(CoreError::Forbidden)` - The actor cannot send messages in the channel
+    /// - `Err(CoreError::RabbitMqError)` - The broker refused the event
+    async fn publish_typing(&self, actor: &Actor, channel_id: &ChannelId) -> Result<(), CoreError>;
+
     /// Searches messages by content with pagination.
+    ///
+    /// The content query can be narrowed to an author and to a time window;
+    /// all filters combine. The actor must be able to view the channel.
+    ///
+    /// # Returns
+    ///
+    /// - `Err(CoreError::InvalidTimeRange)` - `after` is later than `before`
     async fn search_messages(
         &self,
+        actor: &Actor,
         channel_id: &ChannelId,
-        query: &str,
+        criteria: &MessageSearchCriteria,
         pagination: &GetPaginated,
     ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError>;
 
+    /// Lists the channels a user posted in most recently.
+    ///
+    /// Recency is derived from the user's latest message in each channel, and the
+    /// channels are returned most recent first. Channels the user can no longer
+    /// view are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `author_id` - The user whose activity is inspected
+    /// * `limit` - Maximum number of channels to return, clamped to `1..=50`
+    async fn list_recent_channels(
+        &self,
+        author_id: &AuthorId,
+        limit: u32,
+    ) -> Result<Vec<RecentChannel>, CoreError>;
+
+    /// Lists the distinct authors who posted in a channel.
+    ///
+    /// Each participant comes with the number of their messages still in the
+    /// channel; deleted messages are not counted. Participants are ordered by
+    /// message count, most active first.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The user asking, who must be able to view the channel
+    /// * `channel_id` - The channel to inspect
+    /// * `pagination` - Pagination over participants, not messages
+    async fn list_channel_participants(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<ChannelParticipant>, TotalPaginatedElements), CoreError>;
+
+    /// Fetches the latest message of each of the given channels.
+    ///
+    /// Meant for channel lists that preview each channel's last message.
+    /// Deleted messages are skipped, and channels the actor cannot view or
+    /// that have no message are left out of the result.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The user asking
+    /// * `channel_ids` - The channels to inspect, at most [`MAX_LATEST_CHANNELS`](crate::domain::message::entities::MAX_LATEST_CHANNELS)
+    async fn latest_messages_for_channels(
+        &self,
+        actor: &Actor,
+        channel_ids: &[ChannelId],
+    ) -> Result<HashMap<ChannelId, Message>, CoreError>;
+
+    /// Exports every message written by a user, oldest first.
+    ///
+    /// Backs data-subject access requests. The messages are streamed from the
+    /// repository so large histories are never held in memory at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `author_id` - The user whose data is exported
+    async fn export_user_data(&self, author_id: &AuthorId) -> Result<MessageStream, CoreError>;
+
+    /// Erases every message written by a user.
+    ///
+    /// Depending on the configured `ErasureMode`, each message is either
+    /// anonymized (tombstone content, anonymous author) or deleted, and the
+    /// matching update or delete event is written to the outbox per message.
+    /// The user's read positions and reactions are removed, and messages they
+    /// pinned no longer name them.
+    ///
+    /// # Arguments
+    ///
+    /// * `author_id` - The user whose data is erased
+    async fn erase_user_data(&self, author_id: &AuthorId) -> Result<ErasureReport, CoreError>;
+
+    /// Pins a single message, optionally with a note explaining why.
+    ///
+    /// The actor must be able to manage messages in the channel. They are
+    /// stored as the moderator with the pin time and reason, and returned by
+    /// [`MessageService::list_pinned_messages`]. A `message.pinned` event is written.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Message)` - The pinned message
+    /// - `Err(CoreError::Forbidden)` - The actor cannot manage messages in the channel
+    /// - `Err(CoreError::PinReasonTooLong)` - The reason exceeds `MAX_PIN_REASON_CHARS`
+    /// - `Err(CoreError::PinLimitReached)` - The channel already has `MAX_PINS_PER_CHANNEL` pins
+    /// - `Err(CoreError::MessageNotFound)` - No message exists with the given ID
+    async fn pin_message(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+        reason: Option<String>,
+    ) -> Result<Message, CoreError>;
+
+    /// Unpins a single message, clearing its pin details.
+    ///
+    /// Requires the same permission as [`MessageService::pin_message`] and
+    /// writes a `message.unpinned` event.
+    async fn unpin_message(&self, actor: &Actor, message_id: &MessageId) -> Result<Message, CoreError>;
+
+    /// Lists the pinned messages of a channel the actor can view.
+    ///
+    /// Pins follow the order set by [`MessageService::reorder_pins`]. Pins added
+    /// since the last reordering come first, most recently pinned first.
+    async fn list_pinned_messages(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+    ) -> Result<Vec<Message>, CoreError>;
+
+    /// Pins several messages of a channel at once.
+    ///
+    /// The per-channel pin limit is enforced across the whole batch: once it is
+    /// reached, the remaining messages are reported as failed. Messages that
+    /// are missing, belong to another channel or are already pinned are not
+    /// modified. Each newly pinned message is pinned and announced as by
+    /// [`MessageService::pin_message`], with a `message.pinned` event.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The moderator, who must be able to manage messages in the channel
+    /// * `channel_id` - The channel every message must belong to
+    /// * `ids` - The messages to pin, processed in order
+    async fn pin_messages(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        ids: &[MessageId],
+    ) -> Result<BatchResult, CoreError>;
+
+    /// Unpins several messages of a channel at once.
+    ///
+    /// Same reporting as [`MessageService::pin_messages`], without a limit, and
+    /// a `message.unpinned` event per message that was pinned.
+    async fn unpin_messages(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        ids: &[MessageId],
+    ) -> Result<BatchResult, CoreError>;
+
+    /// Sets the manual order of a channel's pinned messages.
+    ///
+    /// `ordered_ids` must list every message currently pinned in the channel
+    /// exactly once. The change is reported as a single `messages.bulk_changed`
+    /// summary whatever the `BulkEventMode`, since no pinned message is edited.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The moderator, who must be able to manage messages in the channel
+    /// * `channel_id` - The channel whose pins are reordered
+    /// * `ordered_ids` - The pinned messages, first to last
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<Message>)` - The pinned messages in their new order
+    /// - `Err(CoreError::InvalidPinOrder)` - An id is repeated, not pinned in the channel, or a pin is missing
+    /// - `Err(CoreError::Forbidden)` - The actor cannot manage messages in the channel
+    async fn reorder_pins(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        ordered_ids: &[MessageId],
+    ) -> Result<Vec<Message>, CoreError>;
+
+    /// Returns the channel's metadata, including its sticky message.
+    ///
+    /// The sticky is reported as unset once its message has been deleted.
+    async fn get_channel_metadata(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+    ) -> Result<ChannelMetadata, CoreError>;
+
+    /// Makes a message the channel's sticky, shown first and apart from the pins.
+    ///
+    /// A channel has at most one sticky; setting another replaces it.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The moderator, who must be able to manage messages in the channel
+    /// * `channel_id` - The channel to set the sticky of
+    /// * `message_id` - The message to show first
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ChannelMetadata)` - The channel's metadata with the new sticky
+    /// - `Err(CoreError::Forbidden)` - The actor cannot manage messages in the channel
+    /// - `Err(CoreError::MessageNotFound)` - No message exists with the given ID
+    /// - `Err(CoreError::MessageNotInChannel)` - The message belongs to another channel
+    async fn set_sticky(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        message_id: &MessageId,
+    ) -> Result<ChannelMetadata, CoreError>;
+
+    /// Removes the channel's sticky, if any.
+    ///
+    /// Requires the same permission as [`MessageService::set_sticky`].
+    async fn clear_sticky(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+    ) -> Result<ChannelMetadata, CoreError>;
+
+    /// Marks the channel as read up to a message.
+    ///
+    /// Read state only moves forward: marking a message older than the one
+    /// already read leaves the state as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The reader, who must be able to view the channel
+    /// * `channel_id` - The channel being read
+    /// * `message_id` - The latest message the reader has seen
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ChannelReadState)` - The reader's state after the call
+    /// - `Err(CoreError::Forbidden)` - The actor cannot view the channel
+    /// - `Err(CoreError::MessageNotFound)` - No message exists with the given ID
+    /// - `Err(CoreError::MessageNotInChannel)` - The message belongs to another channel
+    async fn mark_read(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        message_id: &MessageId,
+    ) -> Result<ChannelReadState, CoreError>;
+
+    /// Counts the messages of the channel created after the actor's last read one.
+    ///
+    /// Every message is unread until the channel is first marked read.
+    /// Requires the permission to view the channel.
+    async fn unread_count(&self, actor: &Actor, channel_id: &ChannelId) -> Result<UnreadCount, CoreError>;
+
+    /// Toggles a user's emoji reaction on a message.
+    ///
+    /// Adds the reaction when the user doesn't have it and removes it when they
+    /// do, as a single atomic update so concurrent toggles never leave
+    /// duplicates behind. Reacting requires the permission to send messages
+    /// in the message's channel.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ReactionToggle)` - Whether the user now has the reaction, and its count
+    /// - `Err(CoreError::InvalidReactionEmoji)` - The emoji is empty, too long or has whitespace
+    /// - `Err(CoreError::MessageNotFound)` - No message exists with the given ID
+    /// - `Err(CoreError::Forbidden)` - The actor cannot send messages in the channel
+    /// - `Err(CoreError::ReactionLimitReached)` - The message already has `MAX_REACTIONS_PER_MESSAGE` reactions
+    async fn toggle_reaction(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+        emoji: &str,
+    ) -> Result<ReactionToggle, CoreError>;
+
+    /// Adds the actor's emoji reaction to a message.
+    ///
+    /// Adding a reaction the actor already has changes nothing. A
+    /// `message.reaction.added` event is written when the reaction is new.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ReactionToggle)` - The reaction's state after the call, and its count
+    /// - `Err(CoreError::InvalidReactionEmoji)` - The emoji is empty, too long or has whitespace
+    /// - `Err(CoreError::MessageNotFound)` - No message exists with the given ID
+    /// - `Err(CoreError::Forbidden)` - The actor cannot send messages in the channel
+    /// - `Err(CoreError::ReactionLimitReached)` - The message already has `MAX_REACTIONS_PER_MESSAGE` reactions
+    async fn add_reaction(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+        emoji: &str,
+    ) -> Result<ReactionToggle, CoreError>;
+
+    /// Removes the actor's emoji reaction from a message.
+    ///
+    /// Removing a reaction the actor doesn't have changes nothing and is not an
+    /// error. A `message.reaction.removed` event is written when one was removed.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ReactionToggle)` - The reaction's state after the call, and its count
+    /// - `Err(CoreError::InvalidReactionEmoji)` - The emoji is empty, too long or has whitespace
+    /// - `Err(CoreError::MessageNotFound)` - No message exists with the given ID
+    /// - `Err(CoreError::Forbidden)` - The actor cannot send messages in the channel
+    async fn remove_reaction(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+        emoji: &str,
+    ) -> Result<ReactionToggle, CoreError>;
+
+    /// Lists the reactions on a message, grouped by emoji.
+    ///
+    /// The actor must be able to view the message's channel.
+    async fn list_reactions(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+    ) -> Result<Vec<ReactionSummary>, CoreError>;
+
     /// Updates an existing message with the provided input.
     ///
-    /// This method validates that the message exists and that the user has permission
-    /// to update it before applying the changes. Only non-None fields in the input
+    /// This method validates that the message exists and that the actor is its
+    /// author before applying the changes. Only non-None fields in the input
     /// will be updated.
     ///
     /// # Arguments
     ///
+    /// * `actor` - The user editing the message
     /// * `input` - The message update input containing the message ID and fields to update
     ///
     /// # Returns
@@ -119,16 +711,22 @@ pub trait MessageService: Send + Sync {
     /// Returns a `Future` that resolves to:
     /// - `Ok(Message)` - The updated message
     /// - `Err(CoreError::MessageNotFound)` - No message exists with the given ID
+    /// - `Err(CoreError::Forbidden)` - The actor is not the message author
     /// - `Err(CoreError)` - If validation fails or repository operation fails
-    async fn update_message(&self, input: UpdateMessageInput) -> Result<Message, CoreError>;
+    async fn update_message(
+        &self,
+        actor: &Actor,
+        input: UpdateMessageInput,
+    ) -> Result<Message, CoreError>;
 
     /// Deletes a message by its unique identifier.
     ///
-    /// This method validates that the message exists and that the user has permission
-    /// to delete it before removing it from the repository.
+    /// This method validates that the message exists and that the actor is its
+    /// author before removing it from the repository.
     ///
     /// # Arguments
     ///
+    /// * `actor` - The user deleting the message
     /// * `message_id` - A reference to the unique identifier of the message to delete
     ///
     /// # Returns
@@ -136,21 +734,80 @@ pub trait MessageService: Send + Sync {
     /// Returns a `Future` that resolves to:
     /// - `Ok(())` - The message was successfully deleted
     /// - `Err(CoreError::MessageNotFound)` - No message exists with the given ID
+    /// - `Err(CoreError::Forbidden)` - The actor is not the message author
     /// - `Err(CoreError)` - If repository operation fails
-    async fn delete_message(&self, message_id: &MessageId) -> Result<(), CoreError>;
+    async fn delete_message(&self, actor: &Actor, message_id: &MessageId) -> Result<(), CoreError>;
+
+    /// Deletes several messages of a channel at once, e.g. to clear spam.
+    ///
+    /// Messages that are missing or belong to another channel, and deletions
+    /// that fail, are reported per message without stopping the batch. The
+    /// deletions are reported like any bulk operation, as one summary or one
+    /// delete event per message depending on the `BulkEventMode`.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The moderator, who must be able to manage messages in the channel
+    /// * `channel_id` - The channel every message must belong to
+    /// * `ids` - The messages to delete, at most the configured maximum
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(BatchResult)` - Which messages were deleted and why the others were not
+    /// - `Err(CoreError::InvalidBatch)` - More messages than the configured maximum
+    /// - `Err(CoreError::Forbidden)` - The actor cannot manage messages in the channel
+    async fn bulk_delete(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        ids: &[MessageId],
+    ) -> Result<BatchResult, CoreError>;
 }
 
 #[derive(Clone)]
 pub struct MockMessageRepository {
     messages: Arc<Mutex<Vec<Message>>>,
+    reactions: Arc<Mutex<Vec<Reaction>>>,
+    /// Soft-deleted messages, out of reach of every read but `find_including_deleted`
+    deleted: Arc<Mutex<Vec<Message>>>,
+    stickies: Arc<Mutex<HashMap<ChannelId, MessageId>>>,
+    read_states: Arc<Mutex<HashMap<(AuthorId, ChannelId), (ChannelReadState, DateTime<Utc>)>>>,
 }
 
 impl MockMessageRepository {
     pub fn new() -> Self {
         Self {
             messages: Arc::new(Mutex::new(Vec::new())),
+            reactions: Arc::new(Mutex::new(Vec::new())),
+            deleted: Arc::new(Mutex::new(Vec::new())),
+            stickies: Arc::new(Mutex::new(HashMap::new())),
+            read_states: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn new_message(input: InsertMessageInput) -> Message {
+        Message {
+            id: input.id,
+            channel_id: input.channel_id,
+            author_id: input.author_id,
+            content: input.content,
+            reply_to_message_id: input.reply_to_message_id,
+            attachments: input.attachments,
+            is_pinned: false,
+            pinned_by: None,
+            pinned_at: None,
+            pin_reason: None,
+            pin_position: None,
+
+            created_at: chrono::Utc::now(),
+            updated_at: None,
         }
     }
+
+    /// Offset and limit clamped the same way as the Mongo repository
+    fn page_bounds(pagination: &GetPaginated) -> (usize, usize) {
+        (pagination.offset() as usize, pagination.normalized().limit as usize)
+    }
 }
 
 #[async_trait::async_trait]
@@ -174,62 +831,446 @@ impl MessageRepository for MockMessageRepository {
         let filtered: Vec<Message> = messages.iter().filter(|m| &m.channel_id == channel_id).cloned().collect();
         let total = filtered.len() as u64;
 
-        let offset = ((pagination.page - 1) * pagination.limit) as usize;
-        let limit = pagination.limit as usize;
+        let (offset, limit) = Self::page_bounds(pagination);
 
         let paginated_messages: Vec<Message> = filtered.into_iter().skip(offset).take(limit).collect();
 
         Ok((paginated_messages, total))
     }
 
+    async fn list_before(
+        &self,
+        channel_id: &ChannelId,
+        before: Option<&MessageId>,
+        limit: u32,
+    ) -> Result<(Vec<Message>, Option<MessageId>), CoreError> {
+        let messages = self.messages.lock().unwrap();
+        let limit = limit.clamp(1, MAX_PAGE_LIMIT) as usize;
+
+        let mut filtered: Vec<&Message> = messages.iter().filter(|m| &m.channel_id == channel_id).collect();
+        filtered.sort_by(|a, b| (b.created_at, b.id.0).cmp(&(a.created_at, a.id.0)));
+
+        // A soft-deleted cursor still marks a position; only a purged one is lost
+        let start = match before {
+            Some(before) => {
+                let deleted = self.deleted.lock().unwrap();
+                let cursor = messages
+                    .iter()
+                    .chain(deleted.iter())
+                    .find(|m| &m.id == before && &m.channel_id == channel_id)
+                    .ok_or(CoreError::InvalidCursor { id: *before })?;
+                filtered
+                    .iter()
+                    .take_while(|m| (m.created_at, m.id.0) > (cursor.created_at, cursor.id.0))
+                    .count()
+            }
+            None => 0,
+        };
+
+        let page: Vec<Message> = filtered.iter().skip(start).take(limit).map(|m| (*m).clone()).collect();
+        let next_cursor = if filtered.len() > start + limit {
+            page.last().map(|m| m.id)
+        } else {
+            None
+        };
+
+        Ok((page, next_cursor))
+    }
+
+    async fn list_in_range(
+        &self,
+        channel_id: &ChannelId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError> {
+        let messages = self.messages.lock().unwrap();
+
+        let mut filtered: Vec<&Message> = messages
+            .iter()
+            .filter(|m| &m.channel_id == channel_id && m.created_at >= from && m.created_at < to)
+            .collect();
+        filtered.sort_by_key(|m| (m.created_at, m.id.0));
+        let total = filtered.len() as u64;
+
+        let (offset, limit) = Self::page_bounds(pagination);
+        let page = filtered.into_iter().skip(offset).take(limit).cloned().collect();
+
+        Ok((page, total))
+    }
+
+    async fn list_replies(
+        &self,
+        message_id: &MessageId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError> {
+        let messages = self.messages.lock().unwrap();
+
+        let mut replies: Vec<&Message> = messages
+            .iter()
+            .filter(|m| m.reply_to_message_id.as_ref() == Some(message_id))
+            .collect();
+        replies.sort_by_key(|m| (m.created_at, m.id.0));
+        let total = replies.len() as u64;
+
+        let (offset, limit) = Self::page_bounds(pagination);
+        let page = replies.into_iter().skip(offset).take(limit).cloned().collect();
+
+        Ok((page, total))
+    }
+
+    async fn count_replies(&self, message_id: &MessageId) -> Result<u64, CoreError> {
+        let messages = self.messages.lock().unwrap();
+
+        let count = messages
+            .iter()
+            .filter(|m| m.reply_to_message_id.as_ref() == Some(message_id))
+            .count();
+
+        Ok(count as u64)
+    }
+
     async fn search_messages(
         &self,
         channel_id: &ChannelId,
-        query: &str,
+        criteria: &MessageSearchCriteria,
         pagination: &GetPaginated,
     ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError> {
         let messages = self.messages.lock().unwrap();
 
-        // Filter by channel and content contains query (case-insensitive)
-        let q = query.to_lowercase();
         let filtered: Vec<Message> = messages
             .iter()
             .filter(|m| &m.channel_id == channel_id)
-            .filter(|m| m.content.to_lowercase().contains(&q))
+            .filter(|m| criteria.matches(m))
             .cloned()
             .collect();
 
         let total = filtered.len() as u64;
 
-        let offset = ((pagination.page - 1) * pagination.limit) as usize;
-        let limit = pagination.limit as usize;
+        let (offset, limit) = Self::page_bounds(pagination);
 
         let paginated_messages: Vec<Message> = filtered.into_iter().skip(offset).take(limit).collect();
 
         Ok((paginated_messages, total))
     }
 
+    async fn list_recent_channels(
+        &self,
+        author_id: &AuthorId,
+        limit: u32,
+    ) -> Result<Vec<RecentChannel>, CoreError> {
+        let messages = self.messages.lock().unwrap();
+
+        let mut latest: Vec<RecentChannel> = Vec::new();
+        for message in messages.iter().filter(|m| &m.author_id == author_id) {
+            match latest.iter_mut().find(|r| r.channel_id == message.channel_id) {
+                Some(recent) if recent.last_message_at < message.created_at => {
+                    recent.last_message_at = message.created_at;
+                }
+                Some(_) => {}
+                None => latest.push(RecentChannel {
+                    channel_id: message.channel_id,
+                    last_message_at: message.created_at,
+                }),
+            }
+        }
+
+        latest.sort_by(|a, b| b.last_message_at.cmp(&a.last_message_at));
+        latest.truncate(limit as usize);
+
+        Ok(latest)
+    }
+
+    async fn list_channel_participants(
+        &self,
+        channel_id: &ChannelId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<ChannelParticipant>, TotalPaginatedElements), CoreError> {
+        let messages = self.messages.lock().unwrap();
+
+        let mut participants: Vec<ChannelParticipant> = Vec::new();
+        for message in messages.iter().filter(|m| &m.channel_id == channel_id) {
+            match participants.iter_mut().find(|p| p.author_id == message.author_id) {
+                Some(participant) => participant.message_count += 1,
+                None => participants.push(ChannelParticipant {
+                    author_id: message.author_id,
+                    message_count: 1,
+                }),
+            }
+        }
+
+        participants.sort_by(|a, b| {
+            b.message_count
+                .cmp(&a.message_count)
+                .then(a.author_id.0.cmp(&b.author_id.0))
+        });
+        let total = participants.len() as u64;
+
+        let (offset, limit) = Self::page_bounds(pagination);
+
+        Ok((participants.into_iter().skip(offset).take(limit).collect(), total))
+    }
+
+    async fn latest_messages_for_channels(
+        &self,
+        channel_ids: &[ChannelId],
+    ) -> Result<HashMap<ChannelId, Message>, CoreError> {
+        let messages = self.messages.lock().unwrap();
+
+        let mut latest: HashMap<ChannelId, Message> = HashMap::new();
+        for message in messages.iter().filter(|m| channel_ids.contains(&m.channel_id)) {
+            let newer = latest.get(&message.channel_id).is_none_or(|current| {
+                (message.created_at, message.id.0) > (current.created_at, current.id.0)
+            });
+            if newer {
+                latest.insert(message.channel_id, message.clone());
+            }
+        }
+
+        Ok(latest)
+    }
+
+    async fn stream_by_author(&self, author_id: &AuthorId) -> Result<MessageStream, CoreError> {
+        let messages = self.messages.lock().unwrap();
+
+        let mut authored: Vec<Message> = messages
+            .iter()
+            .filter(|m| &m.author_id == author_id)
+            .cloned()
+            .collect();
+        authored.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        Ok(futures::stream::iter(authored.into_iter().map(Ok)).boxed())
+    }
+
+    async fn stream_by_author_including_deleted(
+        &self,
+        author_id: &AuthorId,
+    ) -> Result<MessageStream, CoreError> {
+        let messages = self.messages.lock().unwrap();
+        let deleted = self.deleted.lock().unwrap();
+
+        let mut authored: Vec<Message> = messages
+            .iter()
+            .chain(deleted.iter())
+            .filter(|m| &m.author_id == author_id)
+            .cloned()
+            .collect();
+        authored.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        Ok(futures::stream::iter(authored.into_iter().map(Ok)).boxed())
+    }
+
+    async fn count_pinned(&self, channel_id: &ChannelId) -> Result<u64, CoreError> {
+        let messages = self.messages.lock().unwrap();
+
+        let pinned = messages
+            .iter()
+            .filter(|m| &m.channel_id == channel_id && m.is_pinned)
+            .count();
+
+        Ok(pinned as u64)
+    }
+
     async fn insert(&self, input: InsertMessageInput) -> Result<Message, CoreError> {
         let mut messages = self.messages.lock().unwrap();
 
-        let new_message = Message {
-            id: input.id,
-            channel_id: input.channel_id,
-            author_id: input.author_id,
-            content: input.content,
-            reply_to_message_id: input.reply_to_message_id,
-            attachments: input.attachments,
-            is_pinned: false,
-
-            created_at: chrono::Utc::now(),
-            updated_at: None,
-        };
+        // Mirror the unique `_id` index
+        if messages.iter().any(|m| m.id == input.id) {
+            return Err(CoreError::DatabaseError {
+                msg: format!("duplicate key: {}", input.id),
+            });
+        }
+        let new_message = Self::new_message(input);
 
         messages.push(new_message.clone());
 
         Ok(new_message)
     }
 
+    async fn insert_many_atomic(
+        &self,
+        inputs: Vec<InsertMessageInput>,
+    ) -> Result<Vec<Message>, CoreError> {
+        let mut messages = self.messages.lock().unwrap();
+
+        // Mirror the unique `_id` index: one clash aborts the whole batch
+        let now = chrono::Utc::now();
+        let new_messages: Vec<Message> = inputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, input)| Message {
+                created_at: now + chrono::Duration::milliseconds(index as i64),
+                ..Self::new_message(input)
+            })
+            .collect();
+        for (index, message) in new_messages.iter().enumerate() {
+            let clashes = messages.iter().any(|m| m.id == message.id)
+                || new_messages[..index].iter().any(|m| m.id == message.id);
+            if clashes {
+                return Err(CoreError::DatabaseError {
+                    msg: format!("duplicate key: {}", message.id),
+                });
+            }
+        }
+
+        messages.extend(new_messages.iter().cloned());
+
+        Ok(new_messages)
+    }
+
+    async fn list_pinned(&self, channel_id: &ChannelId) -> Result<Vec<Message>, CoreError> {
+        let messages = self.messages.lock().unwrap();
+
+        let mut pinned: Vec<Message> = messages
+            .iter()
+            .filter(|m| &m.channel_id == channel_id && m.is_pinned)
+            .cloned()
+            .collect();
+        // unordered pins (None) first, then by position
+        pinned.sort_by(|a, b| {
+            a.pin_position
+                .cmp(&b.pin_position)
+                .then(b.pinned_at.cmp(&a.pinned_at))
+        });
+
+        Ok(pinned)
+    }
+
+    async fn set_pin_positions(&self, ordered_ids: &[MessageId]) -> Result<(), CoreError> {
+        let mut messages = self.messages.lock().unwrap();
+
+        for (position, id) in ordered_ids.iter().enumerate() {
+            let message = messages
+                .iter_mut()
+                .find(|m| &m.id == id)
+                .ok_or(CoreError::MessageNotFound { id: *id })?;
+            message.pin_position = Some(position as u32);
+        }
+
+        Ok(())
+    }
+
+    async fn find_sticky(&self, channel_id: &ChannelId) -> Result<Option<MessageId>, CoreError> {
+        Ok(self.stickies.lock().unwrap().get(channel_id).copied())
+    }
+
+    async fn set_sticky(&self, channel_id: &ChannelId, message_id: &MessageId) -> Result<(), CoreError> {
+        self.stickies.lock().unwrap().insert(*channel_id, *message_id);
+        Ok(())
+    }
+
+    async fn clear_sticky(&self, channel_id: &ChannelId) -> Result<bool, CoreError> {
+        Ok(self.stickies.lock().unwrap().remove(channel_id).is_some())
+    }
+
+    async fn get_last_read(
+        &self,
+        user_id: &AuthorId,
+        channel_id: &ChannelId,
+    ) -> Result<Option<ChannelReadState>, CoreError> {
+        Ok(self
+            .read_states
+            .lock()
+            .unwrap()
+            .get(&(*user_id, *channel_id))
+            .map(|(state, _)| state.clone()))
+    }
+
+    async fn set_last_read(
+        &self,
+        state: &ChannelReadState,
+        read_up_to: DateTime<Utc>,
+    ) -> Result<bool, CoreError> {
+        let mut read_states = self.read_states.lock().unwrap();
+
+        let key = (state.user_id, state.channel_id);
+        if read_states.get(&key).is_some_and(|(_, stored)| *stored >= read_up_to) {
+            return Ok(false);
+        }
+        read_states.insert(key, (state.clone(), read_up_to));
+        Ok(true)
+    }
+
+    async fn delete_read_states(&self, user_id: &AuthorId) -> Result<u64, CoreError> {
+        let mut read_states = self.read_states.lock().unwrap();
+
+        let before = read_states.len();
+        read_states.retain(|(reader, _), _| reader != user_id);
+        Ok((before - read_states.len()) as u64)
+    }
+
+    async fn count_created_after(
+        &self,
+        channel_id: &ChannelId,
+        after: Option<DateTime<Utc>>,
+    ) -> Result<u64, CoreError> {
+        let messages = self.messages.lock().unwrap();
+
+        let count = messages
+            .iter()
+            .filter(|m| &m.channel_id == channel_id)
+            .filter(|m| after.is_none_or(|after| m.created_at > after))
+            .count();
+
+        Ok(count as u64)
+    }
+
+    async fn pin(
+        &self,
+        id: &MessageId,
+        pinned_by: &AuthorId,
+        reason: Option<&str>,
+        max_pins: u64,
+    ) -> Result<Message, CoreError> {
+        let mut messages = self.messages.lock().unwrap();
+
+        let (channel_id, is_pinned) = messages
+            .iter()
+            .find(|s| &s.id == id)
+            .map(|s| (s.channel_id, s.is_pinned))
+            .ok_or_else(|| CoreError::MessageNotFound { id: *id })?;
+        let pinned = messages
+            .iter()
+            .filter(|s| s.channel_id == channel_id && s.is_pinned)
+            .count() as u64;
+        if !is_pinned && pinned >= max_pins {
+            return Err(CoreError::PinLimitReached);
+        }
+
+        let message = messages
+            .iter_mut()
+            .find(|s| &s.id == id)
+            .ok_or_else(|| CoreError::MessageNotFound { id: *id })?;
+
+        let now = chrono::Utc::now();
+        message.is_pinned = true;
+        message.pinned_by = Some(*pinned_by);
+        message.pinned_at = Some(now);
+        message.pin_reason = reason.map(str::to_string);
+        message.updated_at = Some(now);
+
+        Ok(message.clone())
+    }
+
+    async fn unpin(&self, id: &MessageId) -> Result<Message, CoreError> {
+        let mut messages = self.messages.lock().unwrap();
+
+        let message = messages
+            .iter_mut()
+            .find(|s| &s.id == id)
+            .ok_or_else(|| CoreError::MessageNotFound { id: *id })?;
+
+        message.is_pinned = false;
+        message.pinned_by = None;
+        message.pinned_at = None;
+        message.pin_reason = None;
+        message.pin_position = None;
+        message.updated_at = Some(chrono::Utc::now());
+
+        Ok(message.clone())
+    }
+
     async fn update(&self, input: UpdateMessageInput) -> Result<Message, CoreError> {
         let mut messages = self.messages.lock().unwrap();
 
@@ -243,14 +1284,184 @@ impl MessageRepository for MockMessageRepository {
         if let Some(content) = input.content {
             message.content = content;
         }
-        if let Some(is_pinned) = input.is_pinned {
-            message.is_pinned = is_pinned;
-        }
         message.updated_at = Some(chrono::Utc::now());
 
         Ok(message.clone())
     }
 
+    async fn anonymize(&self, id: &MessageId, content: &str) -> Result<Message, CoreError> {
+        let mut messages = self.messages.lock().unwrap();
+        let mut deleted = self.deleted.lock().unwrap();
+
+        let message = messages
+            .iter_mut()
+            .chain(deleted.iter_mut())
+            .find(|s| &s.id == id)
+            .ok_or_else(|| CoreError::MessageNotFound { id: id.clone() })?;
+
+        message.content = content.to_string();
+        message.author_id = AuthorId::anonymous();
+        message.updated_at = Some(chrono::Utc::now());
+
+        Ok(message.clone())
+    }
+
+    async fn toggle_reaction(
+        &self,
+        message_id: &MessageId,
+        user_id: &AuthorId,
+        emoji: &str,
+    ) -> Result<ReactionToggle, CoreError> {
+        let messages = self.messages.lock().unwrap();
+        if !messages.iter().any(|m| &m.id == message_id) {
+            return Err(CoreError::MessageNotFound { id: *message_id });
+        }
+
+        let mut reactions = self.reactions.lock().unwrap();
+        let existing = reactions.iter().position(|r| {
+            &r.message_id == message_id && &r.user_id == user_id && r.emoji == emoji
+        });
+        let reacted = match existing {
+            Some(index) => {
+                reactions.remove(index);
+                false
+            }
+            None => {
+                let on_message = reactions.iter().filter(|r| &r.message_id == message_id).count();
+                if on_message >= MAX_REACTIONS_PER_MESSAGE {
+                    return Err(CoreError::ReactionLimitReached);
+                }
+                reactions.push(Reaction {
+                    message_id: *message_id,
+                    user_id: *user_id,
+                    emoji: emoji.to_string(),
+                    created_at: chrono::Utc::now(),
+                });
+                true
+            }
+        };
+
+        let count = reactions
+            .iter()
+            .filter(|r| &r.message_id == message_id && r.emoji == emoji)
+            .count() as u64;
+
+        Ok(ReactionToggle {
+            message_id: *message_id,
+            emoji: emoji.to_string(),
+            reacted,
+            count,
+        })
+    }
+
+    async fn add_reaction(
+        &self,
+        message_id: &MessageId,
+        user_id: &AuthorId,
+        emoji: &str,
+    ) -> Result<bool, CoreError> {
+        let messages = self.messages.lock().unwrap();
+        if !messages.iter().any(|m| &m.id == message_id) {
+            return Err(CoreError::MessageNotFound { id: *message_id });
+        }
+
+        let mut reactions = self.reactions.lock().unwrap();
+        if reactions
+            .iter()
+            .any(|r| &r.message_id == message_id && &r.user_id == user_id && r.emoji == emoji)
+        {
+            return Ok(false);
+        }
+        let on_message = reactions.iter().filter(|r| &r.message_id == message_id).count();
+        if on_message >= MAX_REACTIONS_PER_MESSAGE {
+            return Err(CoreError::ReactionLimitReached);
+        }
+        reactions.push(Reaction {
+            message_id: *message_id,
+            user_id: *user_id,
+            emoji: emoji.to_string(),
+            created_at: chrono::Utc::now(),
+        });
+
+        Ok(true)
+    }
+
+    async fn remove_reaction(
+        &self,
+        message_id: &MessageId,
+        user_id: &AuthorId,
+        emoji: &str,
+    ) -> Result<bool, CoreError> {
+        let messages = self.messages.lock().unwrap();
+        if !messages.iter().any(|m| &m.id == message_id) {
+            return Err(CoreError::MessageNotFound { id: *message_id });
+        }
+
+        let mut reactions = self.reactions.lock().unwrap();
+        let before = reactions.len();
+        reactions.retain(|r| !(&r.message_id == message_id && &r.user_id == user_id && r.emoji == emoji));
+
+        Ok(reactions.len() < before)
+    }
+
+    async fn list_reactions(&self, message_id: &MessageId) -> Result<Vec<ReactionSummary>, CoreError> {
+        let messages = self.messages.lock().unwrap();
+        if !messages.iter().any(|m| &m.id == message_id) {
+            return Err(CoreError::MessageNotFound { id: *message_id });
+        }
+
+        let reactions = self.reactions.lock().unwrap();
+        let mut summaries: Vec<ReactionSummary> = Vec::new();
+        for reaction in reactions.iter().filter(|r| &r.message_id == message_id) {
+            match summaries.iter_mut().find(|s| s.emoji == reaction.emoji) {
+                Some(summary) => {
+                    summary.count += 1;
+                    summary.user_ids.push(reaction.user_id);
+                }
+                None => summaries.push(ReactionSummary {
+                    emoji: reaction.emoji.clone(),
+                    count: 1,
+                    user_ids: vec![reaction.user_id],
+                }),
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    async fn remove_reactions_by(&self, user_id: &AuthorId) -> Result<u64, CoreError> {
+        let mut reactions = self.reactions.lock().unwrap();
+
+        let reacted_on: HashSet<MessageId> = reactions
+            .iter()
+            .filter(|r| &r.user_id == user_id)
+            .map(|r| r.message_id)
+            .collect();
+        reactions.retain(|r| &r.user_id != user_id);
+        Ok(reacted_on.len() as u64)
+    }
+
+    async fn clear_pinned_by(&self, user_id: &AuthorId) -> Result<u64, CoreError> {
+        let mut messages = self.messages.lock().unwrap();
+        let mut deleted = self.deleted.lock().unwrap();
+
+        let mut cleared = 0;
+        for message in messages.iter_mut().chain(deleted.iter_mut()) {
+            if message.pinned_by.as_ref() == Some(user_id) {
+                message.pinned_by = None;
+                cleared += 1;
+            }
+        }
+        Ok(cleared)
+    }
+
+    async fn find_including_deleted(&self, id: &MessageId) -> Result<Option<Message>, CoreError> {
+        let messages = self.messages.lock().unwrap();
+        let deleted = self.deleted.lock().unwrap();
+
+        Ok(messages.iter().chain(deleted.iter()).find(|m| &m.id == id).cloned())
+    }
+
     async fn delete(&self, id: &MessageId) -> Result<(), CoreError> {
         let mut messages = self.messages.lock().unwrap();
 
@@ -259,7 +1470,22 @@ impl MessageRepository for MockMessageRepository {
             .position(|s| &s.id == id)
             .ok_or_else(|| CoreError::MessageNotFound { id: id.clone() })?;
 
-        messages.remove(index);
+        let message = messages.remove(index);
+        self.deleted.lock().unwrap().push(message);
+
+        Ok(())
+    }
+
+    async fn purge(&self, id: &MessageId) -> Result<(), CoreError> {
+        let mut messages = self.messages.lock().unwrap();
+        let mut deleted = self.deleted.lock().unwrap();
+
+        let before = messages.len() + deleted.len();
+        messages.retain(|m| &m.id != id);
+        deleted.retain(|m| &m.id != id);
+        if messages.len() + deleted.len() == before {
+            return Err(CoreError::MessageNotFound { id: *id });
+        }
 
         Ok(())
     }
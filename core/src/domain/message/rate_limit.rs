@@ -0,0 +1,200 @@
+//! Throttling of users flooding a channel
+//!
+//! Each user gets a token bucket per channel: every message spends a token
+//! and tokens come back at a steady rate, so short bursts pass while a
+//! sustained flood is rejected until the bucket refills. Buckets live in
+//! memory, per instance, and those full again are dropped every
+//! [`PRUNE_EVERY_CALLS`] checks.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{
+    common::{
+        CoreError,
+        clock::{Clock, SystemClock},
+    },
+    message::entities::{AuthorId, ChannelId},
+};
+
+/// Messages a user may post in a burst before being throttled
+pub const DEFAULT_RATE_LIMIT_CAPACITY: u32 = 10;
+
+/// Tokens given back to a bucket every second
+pub const DEFAULT_RATE_LIMIT_REFILL_PER_SEC: f64 = 1.0;
+
+/// Checks between two sweeps of the buckets that refilled completely
+pub const PRUNE_EVERY_CALLS: u32 = 1_000;
+
+/// Operation a user is throttled on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RateLimitedAction {
+    /// Posting a message in the channel
+    SendMessage(ChannelId),
+}
+
+/// The action was refused; it may be retried after the given delay
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimited {
+    pub retry_after_secs: u64,
+}
+
+pub trait RateLimiter: Send + Sync {
+    /// Spend one of the user's tokens for `action`, or tell when one will be available
+    fn check_and_consume(&self, user_id: &AuthorId, action: RateLimitedAction) -> Result<(), RateLimited>;
+
+    /// Give back a token spent on an action that failed afterwards
+    fn refund(&self, user_id: &AuthorId, action: RateLimitedAction);
+}
+
+pub type DynRateLimiter = Arc<dyn RateLimiter>;
+
+/// How many tokens a bucket holds and how fast it refills
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimitPolicy {
+    capacity: u32,
+    refill_per_sec: f64,
+}
+
+impl RateLimitPolicy {
+    /// Policy holding `capacity` tokens, both it and `refill_per_sec` must be positive
+    ///
+    /// A bucket that never refills would throttle its user forever, so such a
+    /// policy is refused rather than answering with an endless Retry-After.
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Result<Self, CoreError> {
+        if capacity == 0 {
+            return Err(CoreError::InvalidRateLimitPolicy {
+                reason: "capacity must be at least 1".to_string(),
+            });
+        }
+        if !refill_per_sec.is_finite() || refill_per_sec <= 0.0 {
+            return Err(CoreError::InvalidRateLimitPolicy {
+                reason: format!("refill rate must be a positive number, got {}", refill_per_sec),
+            });
+        }
+        Ok(Self {
+            capacity,
+            refill_per_sec,
+        })
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub fn refill_per_sec(&self) -> f64 {
+        self.refill_per_sec
+    }
+}
+
+impl Default for RateLimitPolicy {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_RATE_LIMIT_CAPACITY,
+            refill_per_sec: DEFAULT_RATE_LIMIT_REFILL_PER_SEC,
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct Buckets {
+    by_key: HashMap<(AuthorId, RateLimitedAction), Bucket>,
+    /// Checks since the full buckets were last dropped
+    calls_since_prune: u32,
+}
+
+/// In-memory token buckets keyed by user and action
+pub struct TokenBucketRateLimiter {
+    policy: RateLimitPolicy,
+    clock: Arc<dyn Clock>,
+    buckets: Mutex<Buckets>,
+}
+
+impl TokenBucketRateLimiter {
+    pub fn new(policy: RateLimitPolicy) -> Self {
+        Self {
+            policy,
+            clock: Arc::new(SystemClock),
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// Replace the clock buckets refill against
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn policy(&self) -> &RateLimitPolicy {
+        &self.policy
+    }
+
+    /// Buckets currently tracked, full or not
+    pub fn tracked_buckets(&self) -> usize {
+        self.buckets.lock().unwrap().by_key.len()
+    }
+
+    /// Tokens in `bucket` at `now`, counting what refilled since its last use
+    fn refilled(&self, bucket: &Bucket, now: DateTime<Utc>) -> f64 {
+        let elapsed = (now - bucket.updated_at).num_milliseconds().max(0) as f64 / 1000.0;
+        (bucket.tokens + elapsed * self.policy.refill_per_sec).min(self.policy.capacity as f64)
+    }
+}
+
+impl RateLimiter for TokenBucketRateLimiter {
+    fn check_and_consume(&self, user_id: &AuthorId, action: RateLimitedAction) -> Result<(), RateLimited> {
+        let now = self.clock.now();
+        let capacity = self.policy.capacity as f64;
+        let mut buckets = self.buckets.lock().unwrap();
+
+        // A full bucket is the same as no bucket at all, so those are swept
+        // now and then instead of on every check
+        buckets.calls_since_prune += 1;
+        if buckets.calls_since_prune >= PRUNE_EVERY_CALLS {
+            buckets.calls_since_prune = 0;
+            buckets
+                .by_key
+                .retain(|_, bucket| self.refilled(bucket, now) < capacity);
+        }
+
+        let tokens = buckets
+            .by_key
+            .get(&(*user_id, action))
+            .map_or(capacity, |bucket| self.refilled(bucket, now));
+        if tokens < 1.0 {
+            let retry_after_secs =
+                ((1.0 - tokens) / self.policy.refill_per_sec).ceil().max(1.0) as u64;
+            return Err(RateLimited { retry_after_secs });
+        }
+
+        buckets.by_key.insert(
+            (*user_id, action),
+            Bucket {
+                tokens: tokens - 1.0,
+                updated_at: now,
+            },
+        );
+        Ok(())
+    }
+
+    fn refund(&self, user_id: &AuthorId, action: RateLimitedAction) {
+        let now = self.clock.now();
+        let capacity = self.policy.capacity as f64;
+        let mut buckets = self.buckets.lock().unwrap();
+
+        // Without a bucket it is full already
+        if let Some(bucket) = buckets.by_key.get_mut(&(*user_id, action)) {
+            bucket.tokens = (self.refilled(bucket, now) + 1.0).min(capacity);
+            bucket.updated_at = now;
+        }
+    }
+}
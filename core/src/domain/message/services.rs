@@ -1,20 +1,40 @@
 use crate::{
     domain::{
         attachment::{port::AttachmentRepository},
-        common::{CoreError, GetPaginated, TotalPaginatedElements, services::Service},
+        authorization::entities::{Actor, Permission, Resource},
+        common::{BatchResult, CoreError, GetPaginated, TotalPaginatedElements, services::Service},
         health::port::HealthRepository,
         message::{
-            entities::{Attachment, InsertMessageInput, Message, MessageId, ReturnedMessage, UpdateMessageInput},
-            events::{delete_message_event_from_domain, update_message_event_from_domain},
-            ports::{MessageRepository, MessageService},
+            duplicates::{DuplicateContentAction, content_hash},
+            entities::{
+                Attachment, AttachmentId, AuthorId, BulkEventMode, ChannelId, ChannelMetadata, ChannelParticipant, ChannelReadState, CreatedMessage,
+                ERASED_MESSAGE_CONTENT, ErasureMode, ErasureReport, InsertMessageInput, MAX_LATEST_CHANNELS,
+                MessageSearchCriteria,
+                MAX_PIN_REASON_CHARS, MAX_PINS_PER_CHANNEL, MAX_REACTION_EMOJI_CHARS, Message, MessageId, ReactionSummary, ReactionToggle,
+                RecentChannel, ReplyCount, ReplyPreview, ReturnedMessage, UnreadCount, UpdateMessageInput,
+            },
+            events::{
+                bulk_messages_changed_event_from_domain, channel_typing_event_from_domain,
+                delete_message_event_from_domain,
+                message_pin_event_from_domain, message_reaction_event_from_domain,
+                update_message_event_from_domain,
+            },
+            ports::{MessageRepository, MessageService, MessageStream},
+            rate_limit::RateLimitedAction,
         },
     },
     infrastructure::outbox::entities::MessageOutboxEventRouting,
 };
 
-use crate::domain::message::events::{create_message_event_from_domain, event_to_bytes};
-use crate::domain::outbox::ports::OutboxEventRepository;
-use crate::infrastructure::outbox::{MessageRoutingInfo, OutboxEventRecord};
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+
+use crate::domain::message::events::{created_event_record, event_to_bytes};
+use crate::domain::message::feed::LiveEventStream;
+use crate::domain::outbox::ports::{OutboxEventRepository, OutgoingEvent};
+use crate::infrastructure::outbox::{MessageRoutingInfo, OutboxEventRecord, WireFormat};
 
 #[async_trait::async_trait]
 impl<S, H, A, O> MessageService for Service<S, H, A, O>
@@ -24,143 +44,1102 @@ where
     A: AttachmentRepository,
     O: OutboxEventRepository,
 {
-    async fn create_message(&self, input: InsertMessageInput) -> Result<Message, CoreError> {
+    async fn create_message(&self, mut input: InsertMessageInput) -> Result<CreatedMessage, CoreError> {
+        input.content = self.content_normalization.apply(&input.content);
+
         // Validate message content is not empty
         if input.content.trim().is_empty() {
             return Err(CoreError::InvalidMessageName);
         }
+        self.check_content_length(&input.content)?;
+
+        if input.ephemeral && input.reply_to_message_id.is_some() {
+            return Err(CoreError::EphemeralReply);
+        }
+
+        let actor = Actor::from(input.author_id);
+        self.authorize(&actor, Permission::SendMessages, &input.channel_id)
+            .await?;
+        if !input.attachments.is_empty() {
+            self.authorize(&actor, Permission::AttachFiles, &input.channel_id)
+                .await?;
+        }
+
+        // A parent missing or deleted in the meantime just yields no preview,
+        // and only one from the same channel is previewed, so a reply can't
+        // read another channel
+        let reply_preview = match input.reply_to_message_id {
+            Some(parent_id) => self
+                .message_repository
+                .find_by_id(&parent_id)
+                .await?
+                .filter(|parent| parent.channel_id == input.channel_id)
+                .as_ref()
+                .map(ReplyPreview::from),
+            None => None,
+        };
+
+        let mut duplicate = false;
+        if let Some(detector) = &self.duplicate_detector {
+            if detector.is_duplicate(&input.author_id, &input.content, self.clock.now()) {
+                match detector.policy().action {
+                    DuplicateContentAction::Reject => return Err(CoreError::DuplicateContent),
+                    DuplicateContentAction::Flag => duplicate = true,
+                }
+            }
+        }
+
+        // Only a message that would otherwise be accepted spends a token, so
+        // rejected or unauthorized attempts can't drain the author's bucket;
+        // the token is given back if storing the message fails
+        let rate_limited_action = RateLimitedAction::SendMessage(input.channel_id);
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter
+                .check_and_consume(&input.author_id, rate_limited_action)
+                .map_err(|limited| CoreError::RateLimited {
+                    retry_after_secs: limited.retry_after_secs,
+                })?;
+        }
 
-        // @TODO Authorization: Check if the user has permission to create messages
+        // Ephemeral messages are only broadcast; they never reach the repository,
+        // so they can't be read, pinned or replied to afterwards
+        let ephemeral = input.ephemeral;
+        let message = if ephemeral {
+            Message {
+                id: input.id,
+                channel_id: input.channel_id,
+                author_id: input.author_id,
+                content: input.content,
+                reply_to_message_id: None,
+                attachments: input.attachments,
+                is_pinned: false,
+                pinned_by: None,
+                pinned_at: None,
+                pin_reason: None,
+                pin_position: None,
+                created_at: self.clock.now(),
+                updated_at: None,
+            }
+        } else {
+            let author_id = input.author_id;
+            match self.message_repository.insert(input).await {
+                Ok(message) => message,
+                Err(e) => {
+                    if let Some(rate_limiter) = &self.rate_limiter {
+                        rate_limiter.refund(&author_id, rate_limited_action);
+                    }
+                    return Err(e);
+                }
+            }
+        };
 
-        // Create the message via repository
-        let message = self.message_repository.insert(input).await?;
+        // Recorded only once accepted, so retrying a failed post isn't a repeat
+        if let Some(detector) = &self.duplicate_detector {
+            detector.record(&message.author_id, &message.content, self.clock.now());
+        }
+
+        self.write_created_event(&message).await?;
+
+        Ok(CreatedMessage {
+            message,
+            reply_preview,
+            ephemeral,
+            duplicate,
+        })
+    }
+
+    async fn create_messages_atomic(
+        &self,
+        mut inputs: Vec<InsertMessageInput>,
+    ) -> Result<Vec<Message>, CoreError> {
+        // Channel of every message already checked, for replies within the batch
+        let mut batch_channels: HashMap<MessageId, ChannelId> = HashMap::with_capacity(inputs.len());
+        let mut batch_contents: HashSet<(AuthorId, u64)> = HashSet::with_capacity(inputs.len());
+
+        for input in &mut inputs {
+            input.content = self.content_normalization.apply(&input.content);
+
+            if input.content.trim().is_empty() {
+                return Err(CoreError::InvalidMessageName);
+            }
+            self.check_content_length(&input.content)?;
+            if input.ephemeral {
+                return Err(CoreError::InvalidBatch {
+                    reason: "ephemeral messages cannot be created in a batch".to_string(),
+                });
+            }
+            if batch_channels.contains_key(&input.id) {
+                return Err(CoreError::InvalidBatch {
+                    reason: format!("message {} appears twice in the batch", input.id),
+                });
+            }
+
+            let actor = Actor::from(input.author_id);
+            self.authorize(&actor, Permission::SendMessages, &input.channel_id)
+                .await?;
+            if !input.attachments.is_empty() {
+                self.authorize(&actor, Permission::AttachFiles, &input.channel_id)
+                    .await?;
+            }
+
+            // A reply may target an earlier message of the batch or a stored
+            // one, from the same channel as with a single message
+            if let Some(parent_id) = input.reply_to_message_id {
+                let parent_channel = match batch_channels.get(&parent_id) {
+                    Some(channel_id) => Some(*channel_id),
+                    None => self
+                        .message_repository
+                        .find_by_id(&parent_id)
+                        .await?
+                        .map(|parent| parent.channel_id),
+                };
+                match parent_channel {
+                    Some(channel_id) if channel_id == input.channel_id => {}
+                    Some(_) => return Err(CoreError::InvalidReplyTarget { id: parent_id }),
+                    None => {
+                        return Err(CoreError::InvalidBatch {
+                            reason: format!("reply target {} does not exist", parent_id),
+                        });
+                    }
+                }
+            }
+
+            // Repeats are looked for among recent posts and within the batch;
+            // a flagged batch is stored as is since it has no per-message flag
+            if let Some(detector) = &self.duplicate_detector {
+                let repeated = detector.is_duplicate(&input.author_id, &input.content, self.clock.now())
+                    || !batch_contents.insert((input.author_id, content_hash(&input.content)));
+                if repeated && detector.policy().action == DuplicateContentAction::Reject {
+                    return Err(CoreError::DuplicateContent);
+                }
+            }
+
+            batch_channels.insert(input.id, input.channel_id);
+        }
 
-        // Outbox event logic moved here
-        // Convert Vec<AttachmentId> to Vec<Attachment> with empty URLs (or fetch if needed)
-        let attachments: Vec<crate::domain::message::entities::Attachment> = message
-            .attachments
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Every message spends a token once the whole batch is valid; the
+        // tokens are given back if any is missing or the insert fails
+        let spent: Vec<(AuthorId, RateLimitedAction)> = inputs
             .iter()
-            .cloned()
-            .map(|id| crate::domain::message::entities::Attachment {
-                id,
-                url: String::new(),
-            })
+            .map(|input| (input.author_id, RateLimitedAction::SendMessage(input.channel_id)))
             .collect();
-        let event = create_message_event_from_domain(
-            message.id.0,
-            message.channel_id.0,
-            message.author_id.0,
-            message.content.clone(),
-            message.reply_to_message_id.map(|id| id.0),
-            attachments,
-        );
-        let event_bytes = event_to_bytes(&event)
-            .map_err(|e| CoreError::SerializationError { msg: e.to_string() })?;
-        let outbox_record = OutboxEventRecord::new(
-            MessageRoutingInfo::new("notifications", "message.created"),
-            event_bytes,
-        );
-        self.outbox_repository
-            .write_event(&outbox_record, MessageOutboxEventRouting::Create)
+        if let Some(rate_limiter) = &self.rate_limiter {
+            for (index, (author_id, action)) in spent.iter().enumerate() {
+                if let Err(limited) = rate_limiter.check_and_consume(author_id, *action) {
+                    for (author_id, action) in &spent[..index] {
+                        rate_limiter.refund(author_id, *action);
+                    }
+                    return Err(CoreError::RateLimited {
+                        retry_after_secs: limited.retry_after_secs,
+                    });
+                }
+            }
+        }
+
+        let messages = match self.message_repository.insert_many_atomic(inputs).await {
+            Ok(messages) => messages,
+            Err(e) => {
+                if let Some(rate_limiter) = &self.rate_limiter {
+                    for (author_id, action) in &spent {
+                        rate_limiter.refund(author_id, *action);
+                    }
+                }
+                return Err(e);
+            }
+        };
+
+        if let Some(detector) = &self.duplicate_detector {
+            for message in &messages {
+                detector.record(&message.author_id, &message.content, self.clock.now());
+            }
+        }
+
+        for message in &messages {
+            self.write_created_event(message).await?;
+        }
+
+        Ok(messages)
+    }
+
+    async fn get_message(&self, actor: &Actor, message_id: &MessageId) -> Result<Message, CoreError> {
+        let message = self.find_message(message_id).await?;
+        self.authorize(actor, Permission::ViewChannels, &message.channel_id)
             .await?;
 
         Ok(message)
     }
 
-    async fn get_message(&self, message_id: &MessageId) -> Result<Message, CoreError> {
-        // @TODO Authorization: Check if the user has permission to access the message
+    async fn list_replies(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError> {
+        let parent = self.find_message(message_id).await?;
+        self.authorize(actor, Permission::ViewChannels, &parent.channel_id)
+            .await?;
+
+        self.message_repository
+            .list_replies(message_id, pagination)
+            .await
+    }
+
+    async fn reply_count(&self, actor: &Actor, message_id: &MessageId) -> Result<ReplyCount, CoreError> {
+        let parent = self.find_message(message_id).await?;
+        self.authorize(actor, Permission::ViewChannels, &parent.channel_id)
+            .await?;
 
-        let message = self.message_repository.find_by_id(message_id).await?;
+        let reply_count = self.message_repository.count_replies(message_id).await?;
 
-        match message {
-            Some(message) => Ok(message),
-            None => Err(CoreError::MessageNotFound {
-                id: message_id.clone(),
-            }),
-        }
+        Ok(ReplyCount {
+            message_id: *message_id,
+            reply_count,
+        })
     }
 
     async fn list_messages(
         &self,
+        actor: &Actor,
         channel_id: &crate::domain::message::entities::ChannelId,
         pagination: &GetPaginated,
     ) -> Result<(Vec<ReturnedMessage>, TotalPaginatedElements), CoreError> {
-        // @TODO Authorization: Filter messages by visibility based on user permissions
+        self.authorize(actor, Permission::ViewChannels, channel_id)
+            .await?;
 
-        let (mut messages, total) = self.message_repository.list(channel_id, pagination).await?;
+        let (messages, total) = self.message_repository.list(channel_id, pagination).await?;
 
-        let mut returned_messages = Vec::with_capacity(messages.len());
+        Ok((self.with_attachments(&messages).await, total))
+    }
 
-        for message in &mut messages {
-            let attachments = message
-                .attachments
-                .iter()
-                .map(async |attachment_id| {
-                    self.attachment_repository
-                        .get_attachment(attachment_id.to_string())
-                        .await
-                })
-                .collect::<Vec<_>>();
+    async fn list_messages_before(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        before: Option<&MessageId>,
+        limit: u32,
+    ) -> Result<(Vec<ReturnedMessage>, Option<MessageId>), CoreError> {
+        self.authorize(actor, Permission::ViewChannels, channel_id)
+            .await?;
 
-            let resolved_attachments = futures::future::join_all(attachments)
-                .await
-                .into_iter()
-                .filter_map(Result::ok)
-                .collect::<Vec<Attachment>>();
+        let (messages, next_cursor) = self
+            .message_repository
+            .list_before(channel_id, before, limit)
+            .await?;
 
-            let returned_message = ReturnedMessage {
-                id: message.id.clone(),
-                channel_id: message.channel_id.clone(),
-                author_id: message.author_id.clone(),
-                content: message.content.clone(),
-                reply_to_message_id: message.reply_to_message_id.clone(),
-                attachments: resolved_attachments,
-                is_pinned: message.is_pinned,
-                created_at: message.created_at,
-                updated_at: message.updated_at,
-            };
-            returned_messages.push(returned_message);
+        Ok((self.with_attachments(&messages).await, next_cursor))
+    }
+
+    async fn list_messages_in_range(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<ReturnedMessage>, TotalPaginatedElements), CoreError> {
+        if from > to {
+            return Err(CoreError::InvalidTimeRange);
         }
+        self.authorize(actor, Permission::ViewChannels, channel_id)
+            .await?;
 
-        Ok((returned_messages, total))
+        let (messages, total) = self
+            .message_repository
+            .list_in_range(channel_id, from, to, pagination)
+            .await?;
+
+        Ok((self.with_attachments(&messages).await, total))
+    }
+
+    async fn subscribe_channel(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+    ) -> Result<LiveEventStream, CoreError> {
+        self.authorize(actor, Permission::ViewChannels, channel_id)
+            .await?;
+
+        Ok(self.message_feed.subscribe(*channel_id))
+    }
+
+    async fn publish_typing(&self, actor: &Actor, channel_id: &ChannelId) -> Result<(), CoreError> {
+        self.authorize(actor, Permission::SendMessages, channel_id)
+            .await?;
+
+        let Some(publisher) = &self.event_publisher else {
+            return Ok(());
+        };
+        let event = channel_typing_event_from_domain(*channel_id, actor.author_id(), self.clock.now());
+        let payload = event_to_bytes(&event)
+            .map_err(|e| CoreError::SerializationError { msg: e.to_string() })?;
+        publisher
+            .publish(OutgoingEvent {
+                exchange_name: "notifications".to_string(),
+                routing_key: "channel.typing".to_string(),
+                payload,
+                content_type: WireFormat::Protobuf.content_type().to_string(),
+            })
+            .await
     }
 
     async fn search_messages(
         &self,
+        actor: &Actor,
         channel_id: &crate::domain::message::entities::ChannelId,
-        query: &str,
+        criteria: &MessageSearchCriteria,
         pagination: &GetPaginated,
     ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError> {
-        // @TODO Authorization: Filter messages by visibility based on user permissions
+        if matches!((criteria.after, criteria.before), (Some(after), Some(before)) if after > before) {
+            return Err(CoreError::InvalidTimeRange);
+        }
+        self.authorize(actor, Permission::ViewChannels, channel_id)
+            .await?;
 
         let (messages, total) = self
             .message_repository
-            .search_messages(channel_id, query, pagination)
+            .search_messages(channel_id, criteria, pagination)
             .await?;
 
         Ok((messages, total))
     }
 
-    async fn update_message(&self, input: UpdateMessageInput) -> Result<Message, CoreError> {
-        // Check if message exists
-        let existing_message = self.message_repository.find_by_id(&input.id).await?;
+    async fn list_recent_channels(
+        &self,
+        author_id: &AuthorId,
+        limit: u32,
+    ) -> Result<Vec<RecentChannel>, CoreError> {
+        let recent = self
+            .message_repository
+            .list_recent_channels(author_id, limit.clamp(1, 50))
+            .await?;
+
+        // Drop channels the user can no longer view
+        let actor = Actor::from(*author_id);
+        let mut visible = Vec::with_capacity(recent.len());
+        for channel in recent {
+            if self
+                .authorizer
+                .check(&actor, Permission::ViewChannels, Resource::Channel(channel.channel_id.0))
+                .await?
+            {
+                visible.push(channel);
+            }
+        }
+
+        Ok(visible)
+    }
+
+    async fn list_channel_participants(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<ChannelParticipant>, TotalPaginatedElements), CoreError> {
+        self.authorize(actor, Permission::ViewChannels, channel_id)
+            .await?;
+
+        self.message_repository
+            .list_channel_participants(channel_id, pagination)
+            .await
+    }
 
-        if existing_message.is_none() {
-            return Err(CoreError::MessageNotFound {
-                id: input.id.clone(),
+    async fn latest_messages_for_channels(
+        &self,
+        actor: &Actor,
+        channel_ids: &[ChannelId],
+    ) -> Result<HashMap<ChannelId, Message>, CoreError> {
+        if channel_ids.len() > MAX_LATEST_CHANNELS {
+            return Err(CoreError::InvalidBatch {
+                reason: format!("at most {} channels can be inspected at once", MAX_LATEST_CHANNELS),
             });
         }
 
-        // @TODO Authorization: Verify user is the message owner or has admin privileges
+        // Like recent channels, channels the actor cannot view are dropped
+        let mut visible = Vec::with_capacity(channel_ids.len());
+        for channel_id in channel_ids {
+            if !visible.contains(channel_id)
+                && self
+                    .authorizer
+                    .check(actor, Permission::ViewChannels, Resource::Channel(channel_id.0))
+                    .await?
+            {
+                visible.push(*channel_id);
+            }
+        }
+        if visible.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        self.message_repository
+            .latest_messages_for_channels(&visible)
+            .await
+    }
+
+    async fn export_user_data(&self, author_id: &AuthorId) -> Result<MessageStream, CoreError> {
+        self.message_repository.stream_by_author(author_id).await
+    }
+
+    async fn update_message(
+        &self,
+        actor: &Actor,
+        mut input: UpdateMessageInput,
+    ) -> Result<Message, CoreError> {
+        input.content = input
+            .content
+            .map(|content| self.content_normalization.apply(&content));
+        if let Some(content) = &input.content {
+            self.check_content_length(content)?;
+        }
+
+        // Only the author may edit their message. Erased authors share the
+        // anonymous caller's id, so an anonymous caller never owns a message.
+        let existing_message = self.find_message(&input.id).await?;
+        if actor.is_anonymous() || existing_message.author_id != actor.author_id() {
+            return Err(CoreError::Forbidden);
+        }
 
         // Update the message
         let updated_message = self.message_repository.update(input).await?;
 
+        self.write_updated_event(&updated_message).await?;
+
+        Ok(updated_message)
+    }
+
+    async fn delete_message(&self, actor: &Actor, message_id: &MessageId) -> Result<(), CoreError> {
+        // Only the author may delete their message, never an anonymous caller
+        let existing_message = self.find_message(message_id).await?;
+        if actor.is_anonymous() || existing_message.author_id != actor.author_id() {
+            return Err(CoreError::Forbidden);
+        }
+
+        // Delete the message
+        self.message_repository.delete(message_id).await?;
+
+        self.write_deleted_event(&existing_message).await?;
+
+        Ok(())
+    }
+
+    async fn bulk_delete(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        ids: &[MessageId],
+    ) -> Result<BatchResult, CoreError> {
+        if ids.len() > self.max_bulk_delete {
+            return Err(CoreError::InvalidBatch {
+                reason: format!("at most {} messages can be deleted at once", self.max_bulk_delete),
+            });
+        }
+        self.authorize(actor, Permission::ManageMessages, channel_id)
+            .await?;
+
+        let mut result = BatchResult::default();
+        let mut deleted = Vec::new();
+
+        for id in ids {
+            let message = match self.message_repository.find_by_id(id).await? {
+                Some(message) if &message.channel_id == channel_id => message,
+                _ => {
+                    result.fail(*id, "message not found in channel");
+                    continue;
+                }
+            };
+
+            match self.message_repository.delete(id).await {
+                Ok(()) => {
+                    deleted.push(message);
+                    result.succeed(*id);
+                }
+                Err(error) => result.fail(*id, error.to_string()),
+            }
+        }
+
+        self.write_bulk_events("delete", Some(*channel_id), &deleted, MessageOutboxEventRouting::Delete)
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn pin_message(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+        reason: Option<String>,
+    ) -> Result<Message, CoreError> {
+        let reason = reason
+            .map(|reason| reason.trim().to_string())
+            .filter(|reason| !reason.is_empty());
+        if reason
+            .as_ref()
+            .is_some_and(|reason| reason.chars().count() > MAX_PIN_REASON_CHARS)
+        {
+            return Err(CoreError::PinReasonTooLong {
+                max: MAX_PIN_REASON_CHARS,
+            });
+        }
+
+        let message = self.find_message(message_id).await?;
+        self.authorize(actor, Permission::ManageMessages, &message.channel_id)
+            .await?;
+
+        let pinned = self
+            .message_repository
+            .pin(message_id, &actor.author_id(), reason.as_deref(), MAX_PINS_PER_CHANNEL)
+            .await?;
+        self.write_pin_event(&pinned, actor.author_id()).await?;
+
+        Ok(pinned)
+    }
+
+    async fn unpin_message(&self, actor: &Actor, message_id: &MessageId) -> Result<Message, CoreError> {
+        let message = self.find_message(message_id).await?;
+        self.authorize(actor, Permission::ManageMessages, &message.channel_id)
+            .await?;
+
+        let unpinned = self.message_repository.unpin(message_id).await?;
+        self.write_pin_event(&unpinned, actor.author_id()).await?;
+
+        Ok(unpinned)
+    }
+
+    async fn list_pinned_messages(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+    ) -> Result<Vec<Message>, CoreError> {
+        self.authorize(actor, Permission::ViewChannels, channel_id)
+            .await?;
+
+        self.message_repository.list_pinned(channel_id).await
+    }
+
+    async fn pin_messages(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        ids: &[MessageId],
+    ) -> Result<BatchResult, CoreError> {
+        self.authorize(actor, Permission::ManageMessages, channel_id)
+            .await?;
+
+        let mut result = BatchResult::default();
+        for id in ids {
+            let message = match self.message_repository.find_by_id(id).await? {
+                Some(message) if &message.channel_id == channel_id => message,
+                _ => {
+                    result.fail(*id, "message not found in channel");
+                    continue;
+                }
+            };
+
+            if message.is_pinned {
+                result.succeed(*id);
+                continue;
+            }
+
+            // Each pin goes through the single-message path, so it counts
+            // against the limit and is announced the same way
+            match self.pin_message(actor, id, None).await {
+                Ok(_) => result.succeed(*id),
+                Err(CoreError::PinLimitReached) => result.fail(*id, "channel pin limit reached"),
+                Err(CoreError::MessageNotFound { .. }) => {
+                    result.fail(*id, "message not found in channel")
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn unpin_messages(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        ids: &[MessageId],
+    ) -> Result<BatchResult, CoreError> {
+        self.authorize(actor, Permission::ManageMessages, channel_id)
+            .await?;
+
+        let mut result = BatchResult::default();
+        for id in ids {
+            let message = match self.message_repository.find_by_id(id).await? {
+                Some(message) if &message.channel_id == channel_id => message,
+                _ => {
+                    result.fail(*id, "message not found in channel");
+                    continue;
+                }
+            };
+
+            if message.is_pinned {
+                match self.unpin_message(actor, id).await {
+                    Ok(_) => {}
+                    Err(CoreError::MessageNotFound { .. }) => {
+                        result.fail(*id, "message not found in channel");
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            result.succeed(*id);
+        }
+
+        Ok(result)
+    }
+
+    async fn reorder_pins(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        ordered_ids: &[MessageId],
+    ) -> Result<Vec<Message>, CoreError> {
+        self.authorize(actor, Permission::ManageMessages, channel_id)
+            .await?;
+
+        let pinned = self.message_repository.list_pinned(channel_id).await?;
+        for (index, id) in ordered_ids.iter().enumerate() {
+            if ordered_ids[..index].contains(id) {
+                return Err(CoreError::InvalidPinOrder {
+                    reason: format!("message {} appears twice", id),
+                });
+            }
+            if !pinned.iter().any(|message| &message.id == id) {
+                return Err(CoreError::InvalidPinOrder {
+                    reason: format!("message {} is not pinned in the channel", id),
+                });
+            }
+        }
+        if ordered_ids.len() != pinned.len() {
+            return Err(CoreError::InvalidPinOrder {
+                reason: format!(
+                    "expected all {} pinned messages, got {}",
+                    pinned.len(),
+                    ordered_ids.len()
+                ),
+            });
+        }
+
+        self.message_repository.set_pin_positions(ordered_ids).await?;
+
+        // Always a summary: per-pin update events would announce edits that
+        // never happened, and pin events carry no position
+        let reordered = self.message_repository.list_pinned(channel_id).await?;
+        self.write_bulk_summary("reorder_pins", Some(*channel_id), &reordered)
+            .await?;
+
+        Ok(reordered)
+    }
+
+    async fn get_channel_metadata(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+    ) -> Result<ChannelMetadata, CoreError> {
+        self.authorize(actor, Permission::ViewChannels, channel_id)
+            .await?;
+
+        self.channel_metadata(channel_id).await
+    }
+
+    async fn set_sticky(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        message_id: &MessageId,
+    ) -> Result<ChannelMetadata, CoreError> {
+        self.authorize(actor, Permission::ManageMessages, channel_id)
+            .await?;
+
+        let message = self.find_message(message_id).await?;
+        if &message.channel_id != channel_id {
+            return Err(CoreError::MessageNotInChannel {
+                id: *message_id,
+                channel_id: *channel_id,
+            });
+        }
+
+        self.message_repository.set_sticky(channel_id, message_id).await?;
+
+        Ok(ChannelMetadata {
+            channel_id: *channel_id,
+            sticky_message_id: Some(message.id),
+            sticky_message: Some(message),
+        })
+    }
+
+    async fn clear_sticky(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+    ) -> Result<ChannelMetadata, CoreError> {
+        self.authorize(actor, Permission::ManageMessages, channel_id)
+            .await?;
+
+        self.message_repository.clear_sticky(channel_id).await?;
+
+        Ok(ChannelMetadata {
+            channel_id: *channel_id,
+            sticky_message_id: None,
+            sticky_message: None,
+        })
+    }
+
+    async fn mark_read(
+        &self,
+        actor: &Actor,
+        channel_id: &ChannelId,
+        message_id: &MessageId,
+    ) -> Result<ChannelReadState, CoreError> {
+        self.authorize(actor, Permission::ViewChannels, channel_id)
+            .await?;
+
+        let message = self.find_message(message_id).await?;
+        if &message.channel_id != channel_id {
+            return Err(CoreError::MessageNotInChannel {
+                id: *message_id,
+                channel_id: *channel_id,
+            });
+        }
+
+        let user_id = actor.author_id();
+        let state = ChannelReadState {
+            user_id,
+            channel_id: *channel_id,
+            last_read_message_id: message.id,
+            updated_at: self.clock.now(),
+        };
+        // Marking an older message read must not bring messages back as
+        // unread; the repository only moves the marker forward
+        if self
+            .message_repository
+            .set_last_read(&state, message.created_at)
+            .await?
+        {
+            return Ok(state);
+        }
+
+        let current = self.message_repository.get_last_read(&user_id, channel_id).await?;
+        Ok(current.unwrap_or(state))
+    }
+
+    async fn unread_count(&self, actor: &Actor, channel_id: &ChannelId) -> Result<UnreadCount, CoreError> {
+        self.authorize(actor, Permission::ViewChannels, channel_id)
+            .await?;
+
+        let after = match self
+            .message_repository
+            .get_last_read(&actor.author_id(), channel_id)
+            .await?
+        {
+            Some(state) => Some(self.read_up_to(&state).await?),
+            None => None,
+        };
+        let unread_count = self
+            .message_repository
+            .count_created_after(channel_id, after)
+            .await?;
+
+        Ok(UnreadCount {
+            channel_id: *channel_id,
+            unread_count,
+        })
+    }
+
+    async fn toggle_reaction(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+        emoji: &str,
+    ) -> Result<ReactionToggle, CoreError> {
+        validate_reaction_emoji(emoji)?;
+
+        let message = self.find_message(message_id).await?;
+        self.authorize(actor, Permission::SendMessages, &message.channel_id)
+            .await?;
+
+        let toggle = self
+            .message_repository
+            .toggle_reaction(message_id, &actor.author_id(), emoji)
+            .await?;
+        self.write_reaction_event(&message, actor.author_id(), emoji, toggle.reacted)
+            .await?;
+
+        Ok(toggle)
+    }
+
+    async fn add_reaction(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+        emoji: &str,
+    ) -> Result<ReactionToggle, CoreError> {
+        validate_reaction_emoji(emoji)?;
+
+        let message = self.find_message(message_id).await?;
+        self.authorize(actor, Permission::SendMessages, &message.channel_id)
+            .await?;
+
+        let added = self
+            .message_repository
+            .add_reaction(message_id, &actor.author_id(), emoji)
+            .await?;
+        if added {
+            self.write_reaction_event(&message, actor.author_id(), emoji, true)
+                .await?;
+        }
+
+        self.reaction_state(message_id, emoji, true).await
+    }
+
+    async fn remove_reaction(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+        emoji: &str,
+    ) -> Result<ReactionToggle, CoreError> {
+        validate_reaction_emoji(emoji)?;
+
+        let message = self.find_message(message_id).await?;
+        self.authorize(actor, Permission::SendMessages, &message.channel_id)
+            .await?;
+
+        let removed = self
+            .message_repository
+            .remove_reaction(message_id, &actor.author_id(), emoji)
+            .await?;
+        if removed {
+            self.write_reaction_event(&message, actor.author_id(), emoji, false)
+                .await?;
+        }
+
+        self.reaction_state(message_id, emoji, false).await
+    }
+
+    async fn list_reactions(
+        &self,
+        actor: &Actor,
+        message_id: &MessageId,
+    ) -> Result<Vec<ReactionSummary>, CoreError> {
+        let message = self.find_message(message_id).await?;
+        self.authorize(actor, Permission::ViewChannels, &message.channel_id)
+            .await?;
+
+        self.message_repository.list_reactions(message_id).await
+    }
+
+    async fn erase_user_data(&self, author_id: &AuthorId) -> Result<ErasureReport, CoreError> {
+        // Collect the messages first so the cursor isn't read while its documents
+        // change. Soft-deleted ones still hold the content and must be erased too,
+        // but were already announced as deleted, so only visible ones are reported.
+        let visible: HashSet<MessageId> = self
+            .message_repository
+            .stream_by_author(author_id)
+            .await?
+            .map_ok(|message| message.id)
+            .try_collect()
+            .await?;
+        let messages: Vec<Message> = self
+            .message_repository
+            .stream_by_author_including_deleted(author_id)
+            .await?
+            .try_collect()
+            .await?;
+
+        let per_item = match self.erasure_mode {
+            ErasureMode::Anonymize => MessageOutboxEventRouting::Update,
+            ErasureMode::Delete => MessageOutboxEventRouting::Delete,
+        };
+        let mut affected = 0;
+        let mut changed = Vec::new();
+        let mut erased = Ok(());
+        for message in messages {
+            let outcome = match self.erasure_mode {
+                ErasureMode::Anonymize => self
+                    .message_repository
+                    .anonymize(&message.id, ERASED_MESSAGE_CONTENT)
+                    .await,
+                // Erasure must not leave the content behind, so skip the soft delete
+                ErasureMode::Delete => self
+                    .message_repository
+                    .purge(&message.id)
+                    .await
+                    .map(|()| message),
+            };
+            let message = match outcome {
+                Ok(message) => message,
+                // Removed since the scan, nothing left to erase
+                Err(CoreError::MessageNotFound { .. }) => continue,
+                Err(error) => {
+                    erased = Err(error);
+                    break;
+                }
+            };
+            affected += 1;
+
+            if !visible.contains(&message.id) {
+                continue;
+            }
+            // Per-item events are written as each message changes; a summary is
+            // written below even when erasure stops partway, so that no change
+            // goes unannounced
+            if self.bulk_event_mode == BulkEventMode::PerItem {
+                self.write_bulk_events("erase", None, std::slice::from_ref(&message), per_item)
+                    .await?;
+            } else {
+                changed.push(message);
+            }
+        }
+        self.write_bulk_events("erase", None, &changed, per_item).await?;
+        erased?;
+
+        // How far the user read each channel, the reactions they left and the
+        // pins they made on other people's messages are personal data as well
+        self.message_repository.delete_read_states(author_id).await?;
+        self.message_repository.remove_reactions_by(author_id).await?;
+        self.message_repository.clear_pinned_by(author_id).await?;
+
+        Ok(ErasureReport { affected })
+    }
+}
+
+impl<S, H, A, O> Service<S, H, A, O>
+where
+    S: MessageRepository,
+    H: HealthRepository,
+    A: AttachmentRepository,
+    O: OutboxEventRepository,
+{
+    /// Fail with [`CoreError::Forbidden`] unless the actor holds `permission` on the channel
+    async fn authorize(
+        &self,
+        actor: &Actor,
+        permission: Permission,
+        channel_id: &ChannelId,
+    ) -> Result<(), CoreError> {
+        let resource = Resource::Channel(channel_id.0);
+        let allowed = self.authorizer.check(actor, permission, resource).await?;
+        if !allowed {
+            self.authorizer.record_denial(actor, permission, resource);
+            return Err(CoreError::Forbidden);
+        }
+        Ok(())
+    }
+
+    /// Reject content longer than the configured maximum, counted in bytes
+    fn check_content_length(&self, content: &str) -> Result<(), CoreError> {
+        if content.len() > self.max_content_length {
+            return Err(CoreError::MessageTooLong {
+                max: self.max_content_length,
+            });
+        }
+        Ok(())
+    }
+
+    /// Metadata of a channel, leaving out a sticky whose message is gone
+    async fn channel_metadata(&self, channel_id: &ChannelId) -> Result<ChannelMetadata, CoreError> {
+        let sticky_message = match self.message_repository.find_sticky(channel_id).await? {
+            Some(id) => self.message_repository.find_by_id(&id).await?,
+            None => None,
+        };
+
+        Ok(ChannelMetadata {
+            channel_id: *channel_id,
+            sticky_message_id: sticky_message.as_ref().map(|message| message.id),
+            sticky_message,
+        })
+    }
+
+    /// Creation time of the last message the state marks read
+    ///
+    /// A deleted message still dates the read; one purged since falls back to
+    /// when it was marked read, which it cannot be later than.
+    async fn read_up_to(&self, state: &ChannelReadState) -> Result<DateTime<Utc>, CoreError> {
+        let message = self
+            .message_repository
+            .find_including_deleted(&state.last_read_message_id)
+            .await?;
+        Ok(message.map_or(state.updated_at, |message| message.created_at))
+    }
+
+    async fn find_message(&self, message_id: &MessageId) -> Result<Message, CoreError> {
+        self.message_repository
+            .find_by_id(message_id)
+            .await?
+            .ok_or(CoreError::MessageNotFound { id: *message_id })
+    }
+
+    /// Resolve the attachments of a page of messages in one call, each id once
+    ///
+    /// Attachments that fail to resolve are left out.
+    async fn with_attachments(&self, messages: &[Message]) -> Vec<ReturnedMessage> {
+        let mut attachment_ids: Vec<AttachmentId> = Vec::new();
+        for id in messages.iter().flat_map(|message| &message.attachments) {
+            if !attachment_ids.contains(id) {
+                attachment_ids.push(*id);
+            }
+        }
+        let resolved: HashMap<AttachmentId, Attachment> = if attachment_ids.is_empty() {
+            HashMap::new()
+        } else {
+            self.attachment_repository
+                .get_attachments(&attachment_ids)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to resolve attachments: {}", e);
+                    Vec::new()
+                })
+                .into_iter()
+                .map(|attachment| (attachment.id, attachment))
+                .collect()
+        };
+
+        let mut returned_messages = Vec::with_capacity(messages.len());
+
+        for message in messages {
+            let resolved_attachments = message
+                .attachments
+                .iter()
+                .filter_map(|id| resolved.get(id).cloned())
+                .collect::<Vec<Attachment>>();
+
+            let returned_message = ReturnedMessage {
+                id: message.id.clone(),
+                channel_id: message.channel_id.clone(),
+                author_id: message.author_id.clone(),
+                content: message.content.clone(),
+                reply_to_message_id: message.reply_to_message_id.clone(),
+                attachments: resolved_attachments,
+                is_pinned: message.is_pinned,
+                created_at: message.created_at,
+                updated_at: message.updated_at,
+            };
+            returned_messages.push(returned_message);
+        }
+
+        returned_messages
+    }
+
+    async fn write_created_event(&self, message: &Message) -> Result<(), CoreError> {
+        let outbox_record = created_event_record(message)?;
+        self.outbox_repository
+            .write_event(&outbox_record, MessageOutboxEventRouting::Create)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn write_updated_event(&self, message: &Message) -> Result<(), CoreError> {
         let event = update_message_event_from_domain(
-            updated_message.id,
-            updated_message.channel_id,
-            updated_message.content.clone(),
-            updated_message.is_pinned,
+            message.id,
+            message.channel_id,
+            message.content.clone(),
+            message.is_pinned,
             vec![],
         );
         let event_bytes = event_to_bytes(&event)
@@ -173,28 +1152,136 @@ where
             .write_event(&outbox_record, MessageOutboxEventRouting::Update)
             .await?;
 
-        Ok(updated_message)
+        Ok(())
     }
 
-    async fn delete_message(&self, message_id: &MessageId) -> Result<(), CoreError> {
-        // Check if message exists
-        let existing_message = self.message_repository.find_by_id(message_id).await?;
+    /// Report the messages changed by a bulk operation, either as one
+    /// `messages.bulk_changed` summary or as `per_item` events depending on
+    /// the configured [`BulkEventMode`]
+    async fn write_bulk_events(
+        &self,
+        operation: &str,
+        channel_id: Option<ChannelId>,
+        changed: &[Message],
+        per_item: MessageOutboxEventRouting,
+    ) -> Result<(), CoreError> {
+        if changed.is_empty() {
+            return Ok(());
+        }
 
-        if existing_message.clone().is_none() {
-            return Err(CoreError::MessageNotFound {
-                id: message_id.clone(),
-            });
+        if self.bulk_event_mode == BulkEventMode::PerItem {
+            for message in changed {
+                match per_item {
+                    MessageOutboxEventRouting::Delete => self.write_deleted_event(message).await?,
+                    _ => self.write_updated_event(message).await?,
+                }
+            }
+            return Ok(());
         }
 
-        // Delete the message
-        self.message_repository.delete(message_id).await?;
+        self.write_bulk_summary(operation, channel_id, changed).await
+    }
 
-        let existing_message = existing_message.unwrap();
+    /// Report the messages changed by a bulk operation as one `messages.bulk_changed` event
+    async fn write_bulk_summary(
+        &self,
+        operation: &str,
+        channel_id: Option<ChannelId>,
+        changed: &[Message],
+    ) -> Result<(), CoreError> {
+        if changed.is_empty() {
+            return Ok(());
+        }
 
-        let event = delete_message_event_from_domain(
-            message_id.clone(),
-            existing_message.channel_id.clone(),
+        let message_ids: Vec<MessageId> = changed.iter().map(|message| message.id).collect();
+        let event = bulk_messages_changed_event_from_domain(operation, channel_id, &message_ids);
+        let event_bytes = event_to_bytes(&event)
+            .map_err(|e| CoreError::SerializationError { msg: e.to_string() })?;
+        let outbox_record = OutboxEventRecord::new(
+            MessageRoutingInfo::new("notifications", "messages.bulk_changed"),
+            event_bytes,
         );
+        self.outbox_repository
+            .write_event(&outbox_record, MessageOutboxEventRouting::BulkChanged)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Count of `emoji` on a message once the caller's reaction is known to be `reacted`
+    async fn reaction_state(
+        &self,
+        message_id: &MessageId,
+        emoji: &str,
+        reacted: bool,
+    ) -> Result<ReactionToggle, CoreError> {
+        let count = self
+            .message_repository
+            .list_reactions(message_id)
+            .await?
+            .into_iter()
+            .find(|summary| summary.emoji == emoji)
+            .map_or(0, |summary| summary.count);
+
+        Ok(ReactionToggle {
+            message_id: *message_id,
+            emoji: emoji.to_string(),
+            reacted,
+            count,
+        })
+    }
+
+    async fn write_reaction_event(
+        &self,
+        message: &Message,
+        user_id: AuthorId,
+        emoji: &str,
+        added: bool,
+    ) -> Result<(), CoreError> {
+        let routing = if added {
+            MessageOutboxEventRouting::ReactionAdded
+        } else {
+            MessageOutboxEventRouting::ReactionRemoved
+        };
+        let event = message_reaction_event_from_domain(message.id, message.channel_id, user_id, emoji);
+        let event_bytes = event_to_bytes(&event)
+            .map_err(|e| CoreError::SerializationError { msg: e.to_string() })?;
+        let outbox_record = OutboxEventRecord::new(
+            MessageRoutingInfo::new(routing.get_exchange(), routing.to_routing_key()),
+            event_bytes,
+        )
+        .with_aggregate_id(message.id.0);
+        self.outbox_repository
+            .write_event(&outbox_record, routing)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Write `message.pinned` or `message.unpinned` depending on the message's new state
+    async fn write_pin_event(&self, message: &Message, actor_id: AuthorId) -> Result<(), CoreError> {
+        let routing = if message.is_pinned {
+            MessageOutboxEventRouting::Pinned
+        } else {
+            MessageOutboxEventRouting::Unpinned
+        };
+        let event = message_pin_event_from_domain(message, actor_id);
+        let event_bytes = event_to_bytes(&event)
+            .map_err(|e| CoreError::SerializationError { msg: e.to_string() })?;
+        let outbox_record = OutboxEventRecord::new(
+            MessageRoutingInfo::new(routing.get_exchange(), routing.to_routing_key()),
+            event_bytes,
+        )
+        .with_aggregate_id(message.id.0);
+        self.outbox_repository
+            .write_event(&outbox_record, routing)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn write_deleted_event(&self, message: &Message) -> Result<(), CoreError> {
+        let event = delete_message_event_from_domain(message.id, message.channel_id);
         let event_bytes = event_to_bytes(&event)
             .map_err(|e| CoreError::SerializationError { msg: e.to_string() })?;
         let outbox_record = OutboxEventRecord::new(
@@ -208,3 +1295,14 @@ where
         Ok(())
     }
 }
+
+/// Reject reactions that are empty, longer than [`MAX_REACTION_EMOJI_CHARS`] or contain whitespace
+fn validate_reaction_emoji(emoji: &str) -> Result<(), CoreError> {
+    let length = emoji.chars().count();
+    if length == 0 || length > MAX_REACTION_EMOJI_CHARS || emoji.chars().any(char::is_whitespace) {
+        return Err(CoreError::InvalidReactionEmoji {
+            max: MAX_REACTION_EMOJI_CHARS,
+        });
+    }
+    Ok(())
+}
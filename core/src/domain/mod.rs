@@ -2,4 +2,5 @@ pub mod common;
 pub mod health;
 pub mod message;
 pub mod attachment;
+pub mod authorization;
 pub mod outbox;
\ No newline at end of file
@@ -1,3 +1,5 @@
+use std::sync::{Arc, Mutex};
+
 use async_trait::async_trait;
 
 use crate::domain::common::CoreError;
@@ -15,11 +17,84 @@ pub trait OutboxEventRepository: Send + Sync {
     ) -> Result<(), CoreError>;
 }
 
-pub struct MockOutboxEventRepository;
+/// Event handed to the broker, already encoded in its wire format
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutgoingEvent {
+    pub exchange_name: String,
+    pub routing_key: String,
+    pub payload: Vec<u8>,
+    /// AMQP `content_type` announcing how the payload is encoded
+    pub content_type: String,
+}
+
+/// Broker the outbox relay publishes events to
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    /// Make sure the broker can be reached, reconnecting if the connection dropped
+    ///
+    /// The relay skips its pass while this fails, so an outage doesn't spend
+    /// the rows' publish attempts.
+    async fn ensure_connected(&self) -> Result<(), CoreError> {
+        Ok(())
+    }
+
+    /// Ensure the exchange exists before publishing to it
+    async fn declare_exchange(&self, exchange_name: &str) -> Result<(), CoreError>;
+
+    async fn publish(&self, event: OutgoingEvent) -> Result<(), CoreError>;
+
+    /// Publish several events, one result per event
+    ///
+    /// Results are in the order of `events`. Publishes one at a time unless
+    /// the broker can confirm a whole batch at once.
+    async fn publish_batch(&self, events: Vec<OutgoingEvent>) -> Vec<Result<(), CoreError>> {
+        let mut results = Vec::with_capacity(events.len());
+        for event in events {
+            results.push(self.publish(event).await);
+        }
+        results
+    }
+}
+
+pub type DynEventPublisher = Arc<dyn EventPublisher>;
+
+/// Event written through [`MockOutboxEventRepository`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedOutboxEvent {
+    pub exchange_name: String,
+    pub routing_key: String,
+    pub routing: MessageOutboxEventRouting,
+    pub payload: Vec<u8>,
+    pub priority: u8,
+}
+
+/// In-memory outbox recording every written event, in write order
+#[derive(Clone, Default)]
+pub struct MockOutboxEventRepository {
+    events: Arc<Mutex<Vec<RecordedOutboxEvent>>>,
+}
 
 impl MockOutboxEventRepository {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<RecordedOutboxEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    pub fn routing_keys(&self) -> Vec<String> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| event.routing_key.clone())
+            .collect()
+    }
+
+    /// Forget the events recorded so far, e.g. after seeding fixtures
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
     }
 }
 
@@ -27,9 +102,106 @@ impl MockOutboxEventRepository {
 impl OutboxEventRepository for MockOutboxEventRepository {
     async fn write_event<TRouter: MessageRouter + Send + Sync>(
         &self,
-        _event: &OutboxEventRecord<TRouter>,
-        _routing: MessageOutboxEventRouting,
+        event: &OutboxEventRecord<TRouter>,
+        routing: MessageOutboxEventRouting,
     ) -> Result<(), CoreError> {
+        self.events.lock().unwrap().push(RecordedOutboxEvent {
+            exchange_name: event.router.exchange_name().to_string(),
+            routing_key: event.router.routing_key().to_string(),
+            routing,
+            payload: event.payload.clone(),
+            priority: event.priority,
+        });
+        Ok(())
+    }
+}
+
+
+/// Event published through [`MockEventPublisher`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublishedEvent {
+    pub exchange_name: String,
+    pub routing_key: String,
+    pub payload: Vec<u8>,
+    pub content_type: String,
+}
+
+/// In-memory publisher recording every published event
+#[derive(Clone, Default)]
+pub struct MockEventPublisher {
+    published: Arc<Mutex<Vec<PublishedEvent>>>,
+    failures_left: Arc<Mutex<u32>>,
+    batch_sizes: Arc<Mutex<Vec<usize>>>,
+    disconnected: Arc<Mutex<bool>>,
+}
+
+impl MockEventPublisher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn published(&self) -> Vec<PublishedEvent> {
+        self.published.lock().unwrap().clone()
+    }
+
+    /// Size of every batch handed to [`EventPublisher::publish_batch`]
+    pub fn batch_sizes(&self) -> Vec<usize> {
+        self.batch_sizes.lock().unwrap().clone()
+    }
+
+    /// Make the next `count` publishes fail, as during a broker outage
+    pub fn fail_next(&self, count: u32) {
+        *self.failures_left.lock().unwrap() = count;
+    }
+
+    /// Lose or regain the broker connection
+    pub fn set_connected(&self, connected: bool) {
+        *self.disconnected.lock().unwrap() = !connected;
+    }
+}
+
+#[async_trait]
+impl EventPublisher for MockEventPublisher {
+    async fn ensure_connected(&self) -> Result<(), CoreError> {
+        if *self.disconnected.lock().unwrap() {
+            return Err(CoreError::RabbitMqError {
+                msg: "broker unreachable".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn declare_exchange(&self, _exchange_name: &str) -> Result<(), CoreError> {
+        Ok(())
+    }
+
+    async fn publish(&self, event: OutgoingEvent) -> Result<(), CoreError> {
+        {
+            let mut failures_left = self.failures_left.lock().unwrap();
+            if *failures_left > 0 {
+                *failures_left -= 1;
+                return Err(CoreError::RabbitMqError {
+                    msg: "broker unavailable".to_string(),
+                });
+            }
+        }
+
+        self.published.lock().unwrap().push(PublishedEvent {
+            exchange_name: event.exchange_name,
+            routing_key: event.routing_key,
+            payload: event.payload,
+            content_type: event.content_type,
+        });
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn publish_batch(&self, events: Vec<OutgoingEvent>) -> Vec<Result<(), CoreError>> {
+        self.batch_sizes.lock().unwrap().push(events.len());
+
+        let mut results = Vec::with_capacity(events.len());
+        for event in events {
+            results.push(self.publish(event).await);
+        }
+        results
+    }
+}
@@ -3,7 +3,7 @@ use tracing::{debug, error};
 
 use crate::{
     domain::{
-        attachment::{entities::PresignedUrl, port::AttachmentRepository}, common::CoreError, message::entities::Attachment,
+        attachment::{entities::PresignedUrl, port::AttachmentRepository}, common::CoreError, message::entities::{Attachment, AttachmentId},
     },
     infrastructure::attachments::repositories::entities::{ContentVerb, RequestSignUrl},
 };
@@ -78,4 +78,22 @@ impl AttachmentRepository for ReqwestAttachmentRepository {
             .await?;
         Ok(attachment)
     }
-}
+
+    async fn get_attachments(&self, ids: &[AttachmentId]) -> Result<Vec<Attachment>, CoreError> {
+        // The content service signs one URL per request, so the lookups run
+        // concurrently and each id is only signed once
+        let mut unique: Vec<AttachmentId> = Vec::with_capacity(ids.len());
+        for id in ids {
+            if !unique.contains(id) {
+                unique.push(*id);
+            }
+        }
+
+        let lookups = unique.iter().map(|id| self.get_attachment(id.to_string()));
+        Ok(futures::future::join_all(lookups)
+            .await
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect())
+    }
+}
\ No newline at end of file
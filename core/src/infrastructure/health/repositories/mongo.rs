@@ -16,6 +16,10 @@ impl MongoHealthRepository {
 }
 
 impl HealthRepository for MongoHealthRepository {
+    fn name(&self) -> &'static str {
+        "mongodb"
+    }
+
     fn ping(&self) -> impl Future<Output = IsHealthy> + Send {
         let db = self.db.clone();
 
@@ -1,9 +1,15 @@
-use chrono::Utc;
-use futures::TryStreamExt;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
 use mongodb::{
     Collection, Database,
     bson::Document,
-    bson::{Bson, doc},
+    bson::{Bson, DateTime as BsonDateTime, doc},
+    error::{Error as MongoError, ErrorKind, WriteFailure},
     options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
 };
 
@@ -12,19 +18,110 @@ use mongodb::bson::spec::BinarySubtype;
 
 use crate::{
     domain::{
-        common::{CoreError, GetPaginated, TotalPaginatedElements},
+        common::{CoreError, GetPaginated, MAX_PAGE_LIMIT, TotalPaginatedElements},
         message::{
-            entities::{InsertMessageInput, Message, MessageId, UpdateMessageInput},
-            ports::MessageRepository,
+            entities::{
+                AuthorId, ChannelId, ChannelParticipant, ChannelReadState, InsertMessageInput, MAX_REACTIONS_PER_MESSAGE,
+                Message, MessageId, MessageSearchCriteria, ReactionSummary, ReactionToggle, RecentChannel, UpdateMessageInput,
+            },
+            ports::{MessageRepository, MessageStream},
         },
     },
+    infrastructure::{
+        retry::{RetryPolicy, with_retry},
+        soft_delete::{DELETED_AT_FIELD, exclude_deleted},
+    },
 };
+use serde::Deserialize;
 use uuid::Uuid;
 
+/// Regex matching `text` literally, so user input can't inject a pattern
+///
+/// Every metacharacter is escaped; a search for `a.b` only matches a dot and
+/// nested quantifiers can't trigger catastrophic backtracking.
+pub fn literal_pattern(text: &str) -> String {
+    let mut pattern = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '^' | '$' | '.' | '|' | '?' | '*' | '+' | '(' | ')' | '[' | ']' | '{' | '}'
+        ) {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern
+}
+
+/// Shape of the `$group` stage output used by `list_recent_channels`
+#[derive(Deserialize)]
+struct RecentChannelDocument {
+    #[serde(rename = "_id")]
+    channel_id: ChannelId,
+    last_message_at: DateTime<Utc>,
+}
+
+/// Shape of the `$group` stage output used by `list_channel_participants`
+#[derive(Deserialize)]
+struct ParticipantDocument {
+    #[serde(rename = "_id")]
+    author_id: AuthorId,
+    message_count: i64,
+}
+
+/// Collection holding at most one sticky message per channel, keyed by channel id
+const STICKIES_COLLECTION: &str = "channel_stickies";
+
+/// Collection holding how far each user has read each channel, keyed by user and channel id
+const READ_STATES_COLLECTION: &str = "channel_read_states";
+
+/// `_id` of a user's read state in a channel
+fn read_state_key(user_id: &AuthorId, channel_id: &ChannelId) -> Document {
+    doc! {
+        "user_id": Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: user_id.0.as_bytes().to_vec(),
+        }),
+        "channel_id": Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: channel_id.0.as_bytes().to_vec(),
+        }),
+    }
+}
+
+/// Server code of a write refused by a unique index
+const DUPLICATE_KEY_CODE: i32 = 11000;
+
+/// Whether `error` is a write refused because the key already exists
+fn is_duplicate_key(error: &MongoError) -> bool {
+    matches!(
+        *error.kind,
+        ErrorKind::Write(WriteFailure::WriteError(ref write_error))
+            if write_error.code == DUPLICATE_KEY_CODE
+    )
+}
+
+/// Array path that exists once a message holds `MAX_REACTIONS_PER_MESSAGE` reactions
+fn reactions_full_field() -> String {
+    format!("reactions.{}", MAX_REACTIONS_PER_MESSAGE - 1)
+}
+
+/// Shape of the `$group` stage output used by `latest_messages_for_channels`
+#[derive(Deserialize)]
+struct LatestMessageDocument {
+    message: Message,
+}
+
+#[derive(Deserialize)]
+struct CountDocument {
+    total: i64,
+}
+
 #[derive(Clone)]
 pub struct MongoMessageRepository {
     collection: Collection<Message>,
     pub db: Database,
+    retry_policy: RetryPolicy,
 }
 
 impl MongoMessageRepository {
@@ -32,41 +129,38 @@ impl MongoMessageRepository {
         Self {
             collection: db.collection::<Message>("messages"),
             db: db.clone(),
+            retry_policy: RetryPolicy::default(),
         }
     }
 
-    fn pagination_options(pagination: &GetPaginated) -> FindOptions {
-        let limit = pagination.limit.min(50) as i64;
-        let page = pagination.page.max(1); // Ensure page is at least 1
-        let skip = ((page - 1) * pagination.limit) as u64;
-
-        FindOptions::builder()
-            .sort(doc! { "created_at": -1 })
-            .skip(skip)
-            .limit(limit)
-            .build()
+    /// Override how transient MongoDB errors are retried
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
     }
-}
-
-#[async_trait::async_trait]
-impl MessageRepository for MongoMessageRepository {
-    async fn insert(&self, input: InsertMessageInput) -> Result<Message, CoreError> {
-        let now = Utc::now();
 
-        let message = Message {
+    fn new_message(input: InsertMessageInput, now: DateTime<Utc>) -> Message {
+        Message {
             id: input.id,
             channel_id: input.channel_id,
             author_id: input.author_id,
             content: input.content,
             reply_to_message_id: input.reply_to_message_id,
-            attachments: input.attachments.clone(),
+            attachments: input.attachments,
             is_pinned: false,
+            pinned_by: None,
+            pinned_at: None,
+            pin_reason: None,
+            pin_position: None,
             created_at: now,
             updated_at: None,
-        };
+        }
+    }
 
+    /// Document stored for a new message, with ids as binary UUIDs
+    fn to_document(message: &Message) -> Result<Document, CoreError> {
         // Serialize the message to a BSON document so we can ensure `created_at` is stored as a BSON datetime
-        let bson = mongodb::bson::to_bson(&message)
+        let bson = mongodb::bson::to_bson(message)
             .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
 
         if let Bson::Document(mut doc) = bson {
@@ -105,9 +199,10 @@ impl MessageRepository for MongoMessageRepository {
                 );
             }
 
-            // attachments as array of binary UUIDs
+            // attachments as array of binary UUIDs, converted in place so the
+            // stored array keeps the order the client sent
             if let Some(Bson::Array(arr)) = doc.get_mut("attachments") {
-                for (_, item) in arr.iter_mut().enumerate() {
+                for item in arr.iter_mut() {
                     if let Bson::String(s) = item {
                         if let Ok(u) = Uuid::parse_str(s) {
                             *item = Bson::Binary(Binary {
@@ -131,20 +226,117 @@ impl MessageRepository for MongoMessageRepository {
             }
 
             // store created_at as RFC3339 string to match serde's default chrono serialization
-            doc.insert("created_at", Bson::String(now.to_rfc3339()));
+            doc.insert(
+                "created_at",
+                Bson::String(message.created_at.to_rfc3339()),
+            );
 
-            let raw_coll = self.db.collection::<Document>("messages");
-            raw_coll
-                .insert_one(doc)
-                .await
-                .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+            Ok(doc)
         } else {
-            return Err(CoreError::DatabaseError {
+            Err(CoreError::DatabaseError {
                 msg: "Failed to convert message to BSON document".into(),
-            });
+            })
+        }
+    }
+
+    fn pagination_options(pagination: &GetPaginated) -> FindOptions {
+        let limit = pagination.normalized().limit as i64;
+        let skip = pagination.offset();
+
+        FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .skip(skip)
+            .limit(limit)
+            .build()
+    }
+
+    /// Messages matching `filter`, oldest first, read lazily from a cursor
+    async fn stream_matching(&self, filter: Document) -> Result<MessageStream, CoreError> {
+        let options = FindOptions::builder().sort(doc! { "created_at": 1 }).build();
+
+        let cursor = {
+            let (collection, filter, options) = (&self.collection, &filter, &options);
+            with_retry(&self.retry_policy, || async move {
+                collection
+                    .find(filter.clone())
+                    .with_options(options.clone())
+                    .await
+            })
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        };
+
+        Ok(cursor
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })
+            .boxed())
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageRepository for MongoMessageRepository {
+    async fn insert(&self, input: InsertMessageInput) -> Result<Message, CoreError> {
+        let message = Self::new_message(input, Utc::now());
+        let doc = &Self::to_document(&message)?;
+
+        let raw_coll = &self.db.collection::<Document>("messages");
+        let inserted = with_retry(&self.retry_policy, || async move {
+            raw_coll.insert_one(doc).await
+        })
+        .await;
+
+        match inserted {
+            Ok(_) => Ok(message),
+            // A retry after an insert that landed but whose reply was lost hits
+            // the id it wrote itself; only a different document is a conflict
+            Err(e) if is_duplicate_key(&e) => {
+                let stored = raw_coll
+                    .find_one(doc! { "_id": doc.get("_id").cloned() })
+                    .await
+                    .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+                if stored.as_ref() == Some(doc) {
+                    Ok(message)
+                } else {
+                    Err(CoreError::DatabaseError { msg: e.to_string() })
+                }
+            }
+            Err(e) => Err(CoreError::DatabaseError { msg: e.to_string() }),
+        }
+    }
+
+    async fn insert_many_atomic(
+        &self,
+        inputs: Vec<InsertMessageInput>,
+    ) -> Result<Vec<Message>, CoreError> {
+        // A millisecond apart in batch order, so a reply never sorts before
+        // the parent it was sent with
+        let now = Utc::now();
+        let messages: Vec<Message> = inputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, input)| {
+                Self::new_message(input, now + chrono::Duration::milliseconds(index as i64))
+            })
+            .collect();
+        let docs = messages
+            .iter()
+            .map(Self::to_document)
+            .collect::<Result<Vec<Document>, CoreError>>()?;
+
+        let db_error = |e: mongodb::error::Error| CoreError::DatabaseError { msg: e.to_string() };
+
+        // Transactions need a replica set; the session is not retried as a
+        // whole so a partial failure always surfaces to the caller
+        let mut session = self.db.client().start_session().await.map_err(db_error)?;
+        session.start_transaction().await.map_err(db_error)?;
+
+        let raw_coll = self.db.collection::<Document>("messages");
+        if let Err(e) = raw_coll.insert_many(&docs).session(&mut session).await {
+            let _ = session.abort_transaction().await;
+            return Err(db_error(e));
         }
+        session.commit_transaction().await.map_err(db_error)?;
 
-        Ok(message)
+        Ok(messages)
     }
 
     async fn find_by_id(&self, id: &MessageId) -> Result<Option<Message>, CoreError> {
@@ -156,10 +348,13 @@ impl MessageRepository for MongoMessageRepository {
             bytes: id.0.as_bytes().to_vec(),
         });
 
-        collection
-            .find_one(doc! { "_id": id_bson })
-            .await
-            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })
+        let collection = &collection;
+        let filter = &exclude_deleted(doc! { "_id": id_bson });
+        with_retry(&self.retry_policy, || async move {
+            collection.find_one(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })
     }
 
     async fn list(
@@ -175,18 +370,128 @@ impl MessageRepository for MongoMessageRepository {
             subtype: BinarySubtype::Generic,
             bytes: channel_id.0.as_bytes().to_vec(),
         });
-        let filter = doc! { "channel_id": channel_bson };
+        let filter = exclude_deleted(doc! { "channel_id": channel_bson });
 
-        let total = collection
-            .count_documents(filter.clone())
+        let total = {
+            let (collection, filter) = (&collection, &filter);
+            with_retry(&self.retry_policy, || async move {
+                collection.count_documents(filter.clone()).await
+            })
             .await
-            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        };
 
-        let mut cursor = collection
-            .find(filter)
-            .with_options(options)
+        let mut cursor = {
+            let (collection, filter, options) = (&collection, &filter, &options);
+            with_retry(&self.retry_policy, || async move {
+                collection
+                    .find(filter.clone())
+                    .with_options(options.clone())
+                    .await
+            })
             .await
-            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        };
+
+        let mut messages = Vec::new();
+        while let Some(message) = cursor
+            .try_next()
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        {
+            messages.push(message);
+        }
+
+        Ok((messages, total))
+    }
+
+    async fn list_in_range(
+        &self,
+        channel_id: &ChannelId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError> {
+        let collection = &self.collection;
+        let limit = pagination.normalized().limit as i64;
+        let skip = pagination.offset();
+
+        let channel_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: channel_id.0.as_bytes().to_vec(),
+        });
+        // `created_at` is stored as an RFC3339 string in UTC, which sorts chronologically
+        let filter = &exclude_deleted(doc! {
+            "channel_id": channel_bson,
+            "created_at": { "$gte": from.to_rfc3339(), "$lt": to.to_rfc3339() },
+        });
+
+        let total = with_retry(&self.retry_policy, || async move {
+            collection.count_documents(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        let options = &FindOptions::builder()
+            .sort(doc! { "created_at": 1, "_id": 1 })
+            .skip(skip)
+            .limit(limit)
+            .build();
+        let mut cursor = with_retry(&self.retry_policy, || async move {
+            collection
+                .find(filter.clone())
+                .with_options(options.clone())
+                .await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        let mut messages = Vec::new();
+        while let Some(message) = cursor
+            .try_next()
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        {
+            messages.push(message);
+        }
+
+        Ok((messages, total))
+    }
+
+    async fn list_replies(
+        &self,
+        message_id: &MessageId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError> {
+        let collection = &self.collection;
+        let limit = pagination.normalized().limit as i64;
+        let skip = pagination.offset();
+
+        let parent_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: message_id.0.as_bytes().to_vec(),
+        });
+        let filter = &exclude_deleted(doc! { "reply_to_message_id": parent_bson });
+
+        let total = with_retry(&self.retry_policy, || async move {
+            collection.count_documents(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        let options = &FindOptions::builder()
+            .sort(doc! { "created_at": 1, "_id": 1 })
+            .skip(skip)
+            .limit(limit)
+            .build();
+        let mut cursor = with_retry(&self.retry_policy, || async move {
+            collection
+                .find(filter.clone())
+                .with_options(options.clone())
+                .await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
 
         let mut messages = Vec::new();
         while let Some(message) = cursor
@@ -200,36 +505,163 @@ impl MessageRepository for MongoMessageRepository {
         Ok((messages, total))
     }
 
+    async fn count_replies(&self, message_id: &MessageId) -> Result<u64, CoreError> {
+        let parent_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: message_id.0.as_bytes().to_vec(),
+        });
+        let filter = exclude_deleted(doc! { "reply_to_message_id": parent_bson });
+
+        let (collection, filter) = (&self.collection, &filter);
+        with_retry(&self.retry_policy, || async move {
+            collection.count_documents(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })
+    }
+
+    async fn list_before(
+        &self,
+        channel_id: &ChannelId,
+        before: Option<&MessageId>,
+        limit: u32,
+    ) -> Result<(Vec<Message>, Option<MessageId>), CoreError> {
+        let collection = self.collection.clone();
+        let limit = limit.clamp(1, MAX_PAGE_LIMIT) as usize;
+
+        let channel_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: channel_id.0.as_bytes().to_vec(),
+        });
+        let mut filter = doc! { "channel_id": channel_bson.clone() };
+
+        if let Some(before) = before {
+            let cursor_bson = Bson::Binary(Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: before.0.as_bytes().to_vec(),
+            });
+
+            // Soft-deleted cursors still carry their timestamp, so they are looked
+            // up without the soft-delete predicate. Only a removed message is lost.
+            let raw_coll = &self.db.collection::<Document>("messages");
+            let cursor_filter = &doc! { "_id": cursor_bson.clone(), "channel_id": channel_bson };
+            let cursor = with_retry(&self.retry_policy, || async move {
+                raw_coll.find_one(cursor_filter.clone()).await
+            })
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+            let Some(created_at) = cursor.and_then(|c| c.get("created_at").cloned()) else {
+                return Err(CoreError::InvalidCursor { id: *before });
+            };
+
+            // Messages sharing the cursor's timestamp are ordered by id so none is skipped
+            filter.insert(
+                "$or",
+                vec![
+                    Bson::Document(doc! { "created_at": { "$lt": created_at.clone() } }),
+                    Bson::Document(doc! { "created_at": created_at, "_id": { "$lt": cursor_bson } }),
+                ],
+            );
+        }
+        let filter = &exclude_deleted(filter);
+
+        // One extra message tells whether there is a next page
+        let options = &FindOptions::builder()
+            .sort(doc! { "created_at": -1, "_id": -1 })
+            .limit(limit as i64 + 1)
+            .build();
+
+        let collection = &collection;
+        let mut cursor = with_retry(&self.retry_policy, || async move {
+            collection
+                .find(filter.clone())
+                .with_options(options.clone())
+                .await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        let mut messages = Vec::with_capacity(limit + 1);
+        while let Some(message) = cursor
+            .try_next()
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        {
+            messages.push(message);
+        }
+
+        let next_cursor = if messages.len() > limit {
+            messages.truncate(limit);
+            messages.last().map(|m| m.id)
+        } else {
+            None
+        };
+
+        Ok((messages, next_cursor))
+    }
+
     async fn search_messages(
         &self,
         channel_id: &crate::domain::message::entities::ChannelId,
-        query: &str,
+        criteria: &MessageSearchCriteria,
         pagination: &GetPaginated,
     ) -> Result<(Vec<Message>, TotalPaginatedElements), CoreError> {
         let collection = self.collection.clone();
         let options = Self::pagination_options(pagination);
 
-        // build filter by channel_id and content regex (case-insensitive)
+        // build filter by channel_id and content substring (case-insensitive)
         let channel_bson = Bson::Binary(Binary {
             subtype: BinarySubtype::Generic,
             bytes: channel_id.0.as_bytes().to_vec(),
         });
 
-        let filter = doc! {
+        let mut filter = doc! {
             "channel_id": channel_bson,
-            "content": { "$regex": query, "$options": "i" }
+            "content": { "$regex": literal_pattern(&criteria.query), "$options": "i" }
         };
+        if let Some(author_id) = criteria.author_id {
+            filter.insert(
+                "author_id",
+                Bson::Binary(Binary {
+                    subtype: BinarySubtype::Generic,
+                    bytes: author_id.0.as_bytes().to_vec(),
+                }),
+            );
+        }
+        // `created_at` is stored as an RFC3339 string in UTC, which sorts chronologically
+        let mut created_at = Document::new();
+        if let Some(after) = criteria.after {
+            created_at.insert("$gt", after.to_rfc3339());
+        }
+        if let Some(before) = criteria.before {
+            created_at.insert("$lt", before.to_rfc3339());
+        }
+        if !created_at.is_empty() {
+            filter.insert("created_at", created_at);
+        }
+        let filter = exclude_deleted(filter);
 
-        let total = collection
-            .count_documents(filter.clone())
+        let total = {
+            let (collection, filter) = (&collection, &filter);
+            with_retry(&self.retry_policy, || async move {
+                collection.count_documents(filter.clone()).await
+            })
             .await
-            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        };
 
-        let mut cursor = collection
-            .find(filter)
-            .with_options(options)
+        let mut cursor = {
+            let (collection, filter, options) = (&collection, &filter, &options);
+            with_retry(&self.retry_policy, || async move {
+                collection
+                    .find(filter.clone())
+                    .with_options(options.clone())
+                    .await
+            })
             .await
-            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        };
 
         let mut messages = Vec::new();
         while let Some(message) = cursor
@@ -243,55 +675,993 @@ impl MessageRepository for MongoMessageRepository {
         Ok((messages, total))
     }
 
-    async fn update(&self, input: UpdateMessageInput) -> Result<Message, CoreError> {
+    async fn list_recent_channels(
+        &self,
+        author_id: &AuthorId,
+        limit: u32,
+    ) -> Result<Vec<RecentChannel>, CoreError> {
         let collection = self.collection.clone();
 
-        let mut set = doc! {
-            // store updated_at as RFC3339 string to match how `created_at` is serialized
-            "updated_at": Utc::now().to_rfc3339()
+        let author_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: author_id.0.as_bytes().to_vec(),
+        });
+
+        // latest message per channel for this author, most recent channel first
+        let pipeline = vec![
+            doc! { "$match": exclude_deleted(doc! { "author_id": author_bson }) },
+            doc! { "$group": { "_id": "$channel_id", "last_message_at": { "$max": "$created_at" } } },
+            doc! { "$sort": { "last_message_at": -1 } },
+            doc! { "$limit": limit as i64 },
+        ];
+
+        let mut cursor = {
+            let (collection, pipeline) = (&collection, &pipeline);
+            with_retry(&self.retry_policy, || async move {
+                collection.aggregate(pipeline.clone()).await
+            })
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
         };
 
-        if let Some(ref content) = input.content {
-            set.insert("content", content);
+        let mut channels = Vec::new();
+        while let Some(document) = cursor
+            .try_next()
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        {
+            let recent: RecentChannelDocument = mongodb::bson::from_document(document)
+                .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+            channels.push(RecentChannel {
+                channel_id: recent.channel_id,
+                last_message_at: recent.last_message_at,
+            });
         }
 
-        if let Some(is_pinned) = input.is_pinned {
-            set.insert("is_pinned", is_pinned);
-        }
+        Ok(channels)
+    }
 
-        let options = FindOneAndUpdateOptions::builder()
-            .return_document(ReturnDocument::After)
-            .build();
+    async fn list_channel_participants(
+        &self,
+        channel_id: &ChannelId,
+        pagination: &GetPaginated,
+    ) -> Result<(Vec<ChannelParticipant>, TotalPaginatedElements), CoreError> {
+        let collection = self.collection.clone();
 
-        let id_bson = Bson::Binary(Binary {
+        let channel_bson = Bson::Binary(Binary {
             subtype: BinarySubtype::Generic,
-            bytes: input.id.0.as_bytes().to_vec(),
+            bytes: channel_id.0.as_bytes().to_vec(),
         });
+        let match_stage = doc! { "$match": exclude_deleted(doc! { "channel_id": channel_bson }) };
 
-        let updated = collection
-            .find_one_and_update(doc! { "_id": id_bson }, doc! { "$set": set })
-            .with_options(options)
+        let limit = pagination.normalized().limit as i64;
+        let skip = pagination.offset() as i64;
+
+        // one row per author, most active first, ties broken by id for stable pages
+        let pipeline = vec![
+            match_stage.clone(),
+            doc! { "$group": { "_id": "$author_id", "message_count": { "$sum": 1 } } },
+            doc! { "$sort": { "message_count": -1, "_id": 1 } },
+            doc! { "$skip": skip },
+            doc! { "$limit": limit },
+        ];
+        let count_pipeline = vec![
+            match_stage,
+            doc! { "$group": { "_id": "$author_id" } },
+            doc! { "$count": "total" },
+        ];
+
+        let total = {
+            let (collection, count_pipeline) = (&collection, &count_pipeline);
+            let mut cursor = with_retry(&self.retry_policy, || async move {
+                collection.aggregate(count_pipeline.clone()).await
+            })
             .await
             .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
 
-        updated.ok_or(CoreError::MessageNotFound { id: input.id })
-    }
-
-    async fn delete(&self, id: &MessageId) -> Result<(), CoreError> {
-        let collection = self.collection.clone();
-        let id = *id;
+            // `$count` yields no document at all when nothing matched
+            match cursor
+                .try_next()
+                .await
+                .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+            {
+                Some(document) => {
+                    let count: CountDocument = mongodb::bson::from_document(document)
+                        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+                    count.total as u64
+                }
+                None => 0,
+            }
+        };
 
-        let id_bson = Bson::Binary(Binary {
-            subtype: BinarySubtype::Generic,
-            bytes: id.0.as_bytes().to_vec(),
-        });
+        let mut cursor = {
+            let (collection, pipeline) = (&collection, &pipeline);
+            with_retry(&self.retry_policy, || async move {
+                collection.aggregate(pipeline.clone()).await
+            })
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        };
 
-        let result = collection
-            .delete_one(doc! { "_id": id_bson })
+        let mut participants = Vec::new();
+        while let Some(document) = cursor
+            .try_next()
             .await
-            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        {
+            let participant: ParticipantDocument = mongodb::bson::from_document(document)
+                .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+            participants.push(ChannelParticipant {
+                author_id: participant.author_id,
+                message_count: participant.message_count as u64,
+            });
+        }
+
+        Ok((participants, total))
+    }
+
+    async fn latest_messages_for_channels(
+        &self,
+        channel_ids: &[ChannelId],
+    ) -> Result<HashMap<ChannelId, Message>, CoreError> {
+        let collection = self.collection.clone();
+
+        let channel_bsons: Vec<Bson> = channel_ids
+            .iter()
+            .map(|channel_id| {
+                Bson::Binary(Binary {
+                    subtype: BinarySubtype::Generic,
+                    bytes: channel_id.0.as_bytes().to_vec(),
+                })
+            })
+            .collect();
+
+        // newest first, so the first message of each group is the channel's latest
+        let pipeline = vec![
+            doc! { "$match": exclude_deleted(doc! { "channel_id": { "$in": channel_bsons } }) },
+            doc! { "$sort": { "created_at": -1, "_id": -1 } },
+            doc! { "$group": { "_id": "$channel_id", "message": { "$first": "$$ROOT" } } },
+        ];
+
+        let mut cursor = {
+            let (collection, pipeline) = (&collection, &pipeline);
+            with_retry(&self.retry_policy, || async move {
+                collection.aggregate(pipeline.clone()).await
+            })
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        };
+
+        let mut latest = HashMap::with_capacity(channel_ids.len());
+        while let Some(document) = cursor
+            .try_next()
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        {
+            let group: LatestMessageDocument = mongodb::bson::from_document(document)
+                .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+            latest.insert(group.message.channel_id, group.message);
+        }
+
+        Ok(latest)
+    }
+
+    async fn stream_by_author(&self, author_id: &AuthorId) -> Result<MessageStream, CoreError> {
+        let author_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: author_id.0.as_bytes().to_vec(),
+        });
+        self.stream_matching(exclude_deleted(doc! { "author_id": author_bson }))
+            .await
+    }
+
+    async fn stream_by_author_including_deleted(
+        &self,
+        author_id: &AuthorId,
+    ) -> Result<MessageStream, CoreError> {
+        let author_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: author_id.0.as_bytes().to_vec(),
+        });
+        self.stream_matching(doc! { "author_id": author_bson }).await
+    }
+
+    async fn count_pinned(&self, channel_id: &ChannelId) -> Result<u64, CoreError> {
+        let channel_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: channel_id.0.as_bytes().to_vec(),
+        });
+        let filter = exclude_deleted(doc! { "channel_id": channel_bson, "is_pinned": true });
+
+        let (collection, filter) = (&self.collection, &filter);
+        with_retry(&self.retry_policy, || async move {
+            collection.count_documents(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })
+    }
+
+    async fn list_pinned(&self, channel_id: &ChannelId) -> Result<Vec<Message>, CoreError> {
+        let channel_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: channel_id.0.as_bytes().to_vec(),
+        });
+        let filter = exclude_deleted(doc! { "channel_id": channel_bson, "is_pinned": true });
+        // pins without a position (added since the last reordering) sort
+        // first, most recently pinned first
+        let options = FindOptions::builder()
+            .sort(doc! { "pin_position": 1, "pinned_at": -1, "created_at": -1 })
+            .build();
+
+        let mut cursor = {
+            let (collection, filter, options) = (&self.collection, &filter, &options);
+            with_retry(&self.retry_policy, || async move {
+                collection
+                    .find(filter.clone())
+                    .with_options(options.clone())
+                    .await
+            })
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        };
+
+        let mut messages = Vec::new();
+        while let Some(message) = cursor
+            .try_next()
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        {
+            messages.push(message);
+        }
+
+        Ok(messages)
+    }
+
+    async fn set_pin_positions(&self, ordered_ids: &[MessageId]) -> Result<(), CoreError> {
+        for (position, id) in ordered_ids.iter().enumerate() {
+            let id_bson = Bson::Binary(Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: id.0.as_bytes().to_vec(),
+            });
+
+            let (collection, filter, update) = (
+                &self.collection,
+                &exclude_deleted(doc! { "_id": id_bson }),
+                &doc! { "$set": { "pin_position": position as i64 } },
+            );
+            let result = with_retry(&self.retry_policy, || async move {
+                collection.update_one(filter.clone(), update.clone()).await
+            })
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+            if result.matched_count == 0 {
+                return Err(CoreError::MessageNotFound { id: *id });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn find_sticky(&self, channel_id: &ChannelId) -> Result<Option<MessageId>, CoreError> {
+        let channel_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: channel_id.0.as_bytes().to_vec(),
+        });
+
+        let (collection, filter) = (
+            &self.db.collection::<Document>(STICKIES_COLLECTION),
+            &doc! { "_id": channel_bson },
+        );
+        let sticky = with_retry(&self.retry_policy, || async move {
+            collection.find_one(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        let Some(sticky) = sticky else {
+            return Ok(None);
+        };
+        let bytes = sticky
+            .get_binary_generic("message_id")
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+        let id = Uuid::from_slice(bytes).map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        Ok(Some(MessageId(id)))
+    }
+
+    async fn set_sticky(&self, channel_id: &ChannelId, message_id: &MessageId) -> Result<(), CoreError> {
+        let channel_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: channel_id.0.as_bytes().to_vec(),
+        });
+        let message_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: message_id.0.as_bytes().to_vec(),
+        });
+
+        let (collection, filter, replacement) = (
+            &self.db.collection::<Document>(STICKIES_COLLECTION),
+            &doc! { "_id": channel_bson.clone() },
+            &doc! {
+                "_id": channel_bson,
+                "message_id": message_bson,
+                "set_at": Utc::now().to_rfc3339(),
+            },
+        );
+        with_retry(&self.retry_policy, || async move {
+            collection
+                .replace_one(filter.clone(), replacement.clone())
+                .upsert(true)
+                .await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        Ok(())
+    }
+
+    async fn clear_sticky(&self, channel_id: &ChannelId) -> Result<bool, CoreError> {
+        let channel_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: channel_id.0.as_bytes().to_vec(),
+        });
+
+        let (collection, filter) = (
+            &self.db.collection::<Document>(STICKIES_COLLECTION),
+            &doc! { "_id": channel_bson },
+        );
+        let attempts = &AtomicU32::new(0);
+        let result = with_retry(&self.retry_policy, || async move {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            collection.delete_one(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        // Same as in `purge`: a retry finding nothing follows a removal
+        Ok(result.deleted_count > 0 || attempts.load(Ordering::Relaxed) > 1)
+    }
+
+    async fn get_last_read(
+        &self,
+        user_id: &AuthorId,
+        channel_id: &ChannelId,
+    ) -> Result<Option<ChannelReadState>, CoreError> {
+        let key = read_state_key(user_id, channel_id);
+
+        let (collection, filter) = (
+            &self.db.collection::<Document>(READ_STATES_COLLECTION),
+            &doc! { "_id": key },
+        );
+        let state = with_retry(&self.retry_policy, || async move {
+            collection.find_one(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        let Some(state) = state else {
+            return Ok(None);
+        };
+        let bytes = state
+            .get_binary_generic("last_read_message_id")
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+        let last_read_message_id =
+            Uuid::from_slice(bytes).map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+        let updated_at = state
+            .get_str("updated_at")
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+        let updated_at = DateTime::parse_from_rfc3339(updated_at)
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+            .with_timezone(&Utc);
+
+        Ok(Some(ChannelReadState {
+            user_id: *user_id,
+            channel_id: *channel_id,
+            last_read_message_id: MessageId(last_read_message_id),
+            updated_at,
+        }))
+    }
+
+    async fn set_last_read(
+        &self,
+        state: &ChannelReadState,
+        read_up_to: DateTime<Utc>,
+    ) -> Result<bool, CoreError> {
+        let key = read_state_key(&state.user_id, &state.channel_id);
+        let message_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: state.last_read_message_id.0.as_bytes().to_vec(),
+        });
+        let read_up_to = BsonDateTime::from_millis(read_up_to.timestamp_millis());
+
+        // Only a state read less far matches; otherwise the upsert collides
+        // with the stored state's `_id` and nothing changes. States written
+        // before `read_up_to` was stored are replaced.
+        let (collection, filter, replacement) = (
+            &self.db.collection::<Document>(READ_STATES_COLLECTION),
+            &doc! {
+                "_id": key.clone(),
+                "$or": [
+                    { "read_up_to": { "$lt": read_up_to } },
+                    { "read_up_to": { "$exists": false } },
+                ],
+            },
+            &doc! {
+                "_id": key,
+                "last_read_message_id": message_bson,
+                "read_up_to": read_up_to,
+                "updated_at": state.updated_at.to_rfc3339(),
+            },
+        );
+        let result = with_retry(&self.retry_policy, || async move {
+            collection
+                .replace_one(filter.clone(), replacement.clone())
+                .upsert(true)
+                .await
+        })
+        .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(e) if is_duplicate_key(&e) => Ok(false),
+            Err(e) => Err(CoreError::DatabaseError { msg: e.to_string() }),
+        }
+    }
+
+    async fn delete_read_states(&self, user_id: &AuthorId) -> Result<u64, CoreError> {
+        let user_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: user_id.0.as_bytes().to_vec(),
+        });
+
+        let (collection, filter) = (
+            &self.db.collection::<Document>(READ_STATES_COLLECTION),
+            &doc! { "_id.user_id": user_bson },
+        );
+        let result = with_retry(&self.retry_policy, || async move {
+            collection.delete_many(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        Ok(result.deleted_count)
+    }
+
+    async fn count_created_after(
+        &self,
+        channel_id: &ChannelId,
+        after: Option<DateTime<Utc>>,
+    ) -> Result<u64, CoreError> {
+        let channel_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: channel_id.0.as_bytes().to_vec(),
+        });
+        let mut filter = doc! { "channel_id": channel_bson };
+        if let Some(after) = after {
+            filter.insert("created_at", doc! { "$gt": after.to_rfc3339() });
+        }
+        let filter = exclude_deleted(filter);
+
+        let (collection, filter) = (&self.collection, &filter);
+        with_retry(&self.retry_policy, || async move {
+            collection.count_documents(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })
+    }
+
+    async fn pin(
+        &self,
+        id: &MessageId,
+        pinned_by: &AuthorId,
+        reason: Option<&str>,
+        max_pins: u64,
+    ) -> Result<Message, CoreError> {
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: id.0.as_bytes().to_vec(),
+        });
+        let pinned_by_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: pinned_by.0.as_bytes().to_vec(),
+        });
+        let now = Utc::now().to_rfc3339();
+
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        let update = doc! { "$set": {
+            "is_pinned": true,
+            "pinned_by": pinned_by_bson,
+            "pinned_at": &now,
+            "pin_reason": reason,
+            "updated_at": &now,
+        } };
+
+        // Re-pinning only refreshes the details and can't cross the limit
+        let (collection, filter, update, options) = (
+            &self.collection,
+            &exclude_deleted(doc! { "_id": id_bson.clone(), "is_pinned": true }),
+            &update,
+            &options,
+        );
+        let repinned = with_retry(&self.retry_policy, || async move {
+            collection
+                .find_one_and_update(filter.clone(), update.clone())
+                .with_options(options.clone())
+                .await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+        if let Some(message) = repinned {
+            return Ok(message);
+        }
+
+        // MongoDB can't make the pin conditional on the other rows, so the pin
+        // is taken first and the channel counted after it. Whichever pins went
+        // over the limit back out, so concurrent pins can never leave the
+        // channel with more than `max_pins`.
+        let filter = &exclude_deleted(doc! { "_id": id_bson.clone(), "is_pinned": { "$ne": true } });
+        let pinned = with_retry(&self.retry_policy, || async move {
+            collection
+                .find_one_and_update(filter.clone(), update.clone())
+                .with_options(options.clone())
+                .await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        .ok_or(CoreError::MessageNotFound { id: *id })?;
+
+        if self.count_pinned(&pinned.channel_id).await? > max_pins {
+            let (filter, update) = (
+                &doc! { "_id": id_bson, "pinned_at": &now },
+                &doc! {
+                    "$set": { "is_pinned": false },
+                    "$unset": { "pinned_by": "", "pinned_at": "", "pin_reason": "" },
+                },
+            );
+            with_retry(&self.retry_policy, || async move {
+                collection.update_one(filter.clone(), update.clone()).await
+            })
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+            return Err(CoreError::PinLimitReached);
+        }
+
+        Ok(pinned)
+    }
+
+    async fn unpin(&self, id: &MessageId) -> Result<Message, CoreError> {
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: id.0.as_bytes().to_vec(),
+        });
+
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        let (collection, filter, update, options) = (
+            &self.collection,
+            &exclude_deleted(doc! { "_id": id_bson }),
+            &doc! {
+                "$set": { "is_pinned": false, "updated_at": Utc::now().to_rfc3339() },
+                "$unset": { "pinned_by": "", "pinned_at": "", "pin_reason": "", "pin_position": "" },
+            },
+            &options,
+        );
+        let updated = with_retry(&self.retry_policy, || async move {
+            collection
+                .find_one_and_update(filter.clone(), update.clone())
+                .with_options(options.clone())
+                .await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        updated.ok_or(CoreError::MessageNotFound { id: *id })
+    }
+
+    async fn update(&self, input: UpdateMessageInput) -> Result<Message, CoreError> {
+        let collection = self.collection.clone();
+
+        let mut set = doc! {
+            // store updated_at as RFC3339 string to match how `created_at` is serialized
+            "updated_at": Utc::now().to_rfc3339()
+        };
+
+        if let Some(ref content) = input.content {
+            set.insert("content", content);
+        }
+        let update = doc! { "$set": set };
+
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: input.id.0.as_bytes().to_vec(),
+        });
+
+        let (collection, filter, update, options) = (
+            &collection,
+            &exclude_deleted(doc! { "_id": id_bson }),
+            &update,
+            &options,
+        );
+        let updated = with_retry(&self.retry_policy, || async move {
+            collection
+                .find_one_and_update(filter.clone(), update.clone())
+                .with_options(options.clone())
+                .await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        updated.ok_or(CoreError::MessageNotFound { id: input.id })
+    }
+
+    async fn anonymize(&self, id: &MessageId, content: &str) -> Result<Message, CoreError> {
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: id.0.as_bytes().to_vec(),
+        });
+        let anonymous_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: AuthorId::anonymous().0.as_bytes().to_vec(),
+        });
+
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        let (collection, filter, update, options) = (
+            &self.collection,
+            &doc! { "_id": id_bson },
+            &doc! { "$set": {
+                "content": content,
+                "author_id": anonymous_bson,
+                "updated_at": Utc::now().to_rfc3339(),
+            } },
+            &options,
+        );
+        let updated = with_retry(&self.retry_policy, || async move {
+            collection
+                .find_one_and_update(filter.clone(), update.clone())
+                .with_options(options.clone())
+                .await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        updated.ok_or(CoreError::MessageNotFound { id: *id })
+    }
+
+    async fn toggle_reaction(
+        &self,
+        message_id: &MessageId,
+        user_id: &AuthorId,
+        emoji: &str,
+    ) -> Result<ReactionToggle, CoreError> {
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: message_id.0.as_bytes().to_vec(),
+        });
+        let user_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: user_id.0.as_bytes().to_vec(),
+        });
+
+        // Reactions live in a `reactions` array on the message document. The
+        // add-or-remove decision is made by the server inside a pipeline
+        // update, so concurrent toggles are applied one after the other.
+        let current = doc! { "$ifNull": ["$reactions", []] };
+        let is_mine = doc! { "$and": [
+            { "$eq": ["$$this.user_id", user_bson.clone()] },
+            { "$eq": ["$$this.emoji", { "$literal": emoji }] },
+        ] };
+        let update = vec![doc! { "$set": { "reactions": { "$cond": [
+            { "$gt": [{ "$size": { "$filter": { "input": current.clone(), "cond": is_mine.clone() } } }, 0] },
+            { "$filter": { "input": current.clone(), "cond": { "$not": [is_mine] } } },
+            { "$concatArrays": [current, [{
+                "user_id": user_bson.clone(),
+                "emoji": { "$literal": emoji },
+                "created_at": Utc::now().to_rfc3339(),
+            }]] },
+        ] } } }];
+
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        // Matches while there is room for one more reaction, or when the
+        // toggle removes one, so a full message can still lose reactions
+        let full = reactions_full_field();
+        let filter = exclude_deleted(doc! {
+            "_id": id_bson,
+            "$or": [
+                { full: { "$exists": false } },
+                { "reactions": { "$elemMatch": { "user_id": user_bson.clone(), "emoji": emoji } } },
+            ],
+        });
+
+        // Not retried: replaying a toggle that did reach the server would undo it
+        let updated = self
+            .db
+            .collection::<Document>("messages")
+            .find_one_and_update(filter, update)
+            .with_options(options)
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+        let Some(updated) = updated else {
+            self.find_by_id(message_id)
+                .await?
+                .ok_or(CoreError::MessageNotFound { id: *message_id })?;
+            return Err(CoreError::ReactionLimitReached);
+        };
+
+        let reactions = updated.get_array("reactions").cloned().unwrap_or_default();
+        let with_emoji: Vec<&Document> = reactions
+            .iter()
+            .filter_map(Bson::as_document)
+            .filter(|reaction| reaction.get_str("emoji").is_ok_and(|e| e == emoji))
+            .collect();
+
+        Ok(ReactionToggle {
+            message_id: *message_id,
+            emoji: emoji.to_string(),
+            reacted: with_emoji
+                .iter()
+                .any(|reaction| reaction.get("user_id") == Some(&user_bson)),
+            count: with_emoji.len() as u64,
+        })
+    }
+
+    async fn add_reaction(
+        &self,
+        message_id: &MessageId,
+        user_id: &AuthorId,
+        emoji: &str,
+    ) -> Result<bool, CoreError> {
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: message_id.0.as_bytes().to_vec(),
+        });
+        let user_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: user_id.0.as_bytes().to_vec(),
+        });
+
+        // Only matches while the user doesn't have the reaction yet, so adding
+        // it again (or retrying) leaves the array untouched, and while the
+        // message has room for it
+        let (mine, full) = (doc! { "user_id": user_bson.clone(), "emoji": emoji }, reactions_full_field());
+        let filter = &exclude_deleted(doc! {
+            "_id": id_bson.clone(),
+            "reactions": { "$not": { "$elemMatch": mine.clone() } },
+            full: { "$exists": false },
+        });
+        let update = &doc! { "$push": { "reactions": {
+            "user_id": user_bson,
+            "emoji": emoji,
+            "created_at": Utc::now().to_rfc3339(),
+        } } };
+
+        let collection = &self.db.collection::<Document>("messages");
+        let result = with_retry(&self.retry_policy, || async move {
+            collection.update_one(filter.clone(), update.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        if result.matched_count == 0 {
+            let (filter, mine) = (&exclude_deleted(doc! { "_id": id_bson }), &mine);
+            let message = with_retry(&self.retry_policy, || async move {
+                collection
+                    .find_one(filter.clone())
+                    .projection(doc! { "reactions": { "$elemMatch": mine.clone() } })
+                    .await
+            })
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+            .ok_or(CoreError::MessageNotFound { id: *message_id })?;
+
+            // Either the user already had the reaction, or the message is full
+            if message.get_array("reactions").is_ok_and(|reactions| !reactions.is_empty()) {
+                return Ok(false);
+            }
+            return Err(CoreError::ReactionLimitReached);
+        }
+
+        Ok(true)
+    }
+
+    async fn remove_reaction(
+        &self,
+        message_id: &MessageId,
+        user_id: &AuthorId,
+        emoji: &str,
+    ) -> Result<bool, CoreError> {
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: message_id.0.as_bytes().to_vec(),
+        });
+        let user_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: user_id.0.as_bytes().to_vec(),
+        });
+
+        let filter = &exclude_deleted(doc! { "_id": id_bson });
+        let update = &doc! { "$pull": { "reactions": { "user_id": user_bson, "emoji": emoji } } };
+
+        let collection = &self.db.collection::<Document>("messages");
+        let result = with_retry(&self.retry_policy, || async move {
+            collection.update_one(filter.clone(), update.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        if result.matched_count == 0 {
+            return Err(CoreError::MessageNotFound { id: *message_id });
+        }
+
+        Ok(result.modified_count > 0)
+    }
+
+    async fn list_reactions(&self, message_id: &MessageId) -> Result<Vec<ReactionSummary>, CoreError> {
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: message_id.0.as_bytes().to_vec(),
+        });
+
+        let filter = &exclude_deleted(doc! { "_id": id_bson });
+        let collection = &self.db.collection::<Document>("messages");
+        let message = with_retry(&self.retry_policy, || async move {
+            collection
+                .find_one(filter.clone())
+                .projection(doc! { "reactions": 1 })
+                .await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+        .ok_or(CoreError::MessageNotFound { id: *message_id })?;
+
+        let mut summaries: Vec<ReactionSummary> = Vec::new();
+        let reactions = message.get_array("reactions").cloned().unwrap_or_default();
+        for reaction in reactions.iter().filter_map(Bson::as_document) {
+            let (Ok(emoji), Ok(user)) = (reaction.get_str("emoji"), reaction.get_binary_generic("user_id"))
+            else {
+                continue;
+            };
+            let Ok(user_id) = Uuid::from_slice(user) else {
+                continue;
+            };
+            match summaries.iter_mut().find(|s| s.emoji == emoji) {
+                Some(summary) => {
+                    summary.count += 1;
+                    summary.user_ids.push(AuthorId(user_id));
+                }
+                None => summaries.push(ReactionSummary {
+                    emoji: emoji.to_string(),
+                    count: 1,
+                    user_ids: vec![AuthorId(user_id)],
+                }),
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    async fn remove_reactions_by(&self, user_id: &AuthorId) -> Result<u64, CoreError> {
+        let user_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: user_id.0.as_bytes().to_vec(),
+        });
+
+        // Deleted messages included: they may still be restored or exported
+        let (collection, filter, update) = (
+            &self.collection,
+            &doc! { "reactions.user_id": user_bson.clone() },
+            &doc! { "$pull": { "reactions": { "user_id": user_bson } } },
+        );
+        let result = with_retry(&self.retry_policy, || async move {
+            collection.update_many(filter.clone(), update.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        Ok(result.modified_count)
+    }
+
+    async fn clear_pinned_by(&self, user_id: &AuthorId) -> Result<u64, CoreError> {
+        let user_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: user_id.0.as_bytes().to_vec(),
+        });
+
+        let (collection, filter, update) = (
+            &self.collection,
+            &doc! { "pinned_by": user_bson },
+            &doc! { "$unset": { "pinned_by": "" } },
+        );
+        let result = with_retry(&self.retry_policy, || async move {
+            collection.update_many(filter.clone(), update.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        Ok(result.modified_count)
+    }
+
+    async fn find_including_deleted(&self, id: &MessageId) -> Result<Option<Message>, CoreError> {
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: id.0.as_bytes().to_vec(),
+        });
+
+        let collection = &self.collection;
+        let filter = &doc! { "_id": id_bson };
+        with_retry(&self.retry_policy, || async move {
+            collection.find_one(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })
+    }
+
+    async fn delete(&self, id: &MessageId) -> Result<(), CoreError> {
+        let collection = self.collection.clone();
+        let id = *id;
+
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: id.0.as_bytes().to_vec(),
+        });
+
+        // The document stays for audit and reply references; reads skip it.
+        // A retry also matches the deletion stamped by an earlier attempt, so
+        // one that landed before its reply was lost isn't reported as missing.
+        let deleted_at = Utc::now().to_rfc3339();
+        let filter = &doc! {
+            "_id": id_bson,
+            "$or": [{ DELETED_AT_FIELD: Bson::Null }, { DELETED_AT_FIELD: &deleted_at }],
+        };
+        let update = &doc! { "$set": { DELETED_AT_FIELD: &deleted_at } };
+        let collection = &collection;
+        let result = with_retry(&self.retry_policy, || async move {
+            collection.update_one(filter.clone(), update.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        if result.matched_count == 0 {
+            return Err(CoreError::MessageNotFound { id });
+        }
+
+        Ok(())
+    }
+
+    async fn purge(&self, id: &MessageId) -> Result<(), CoreError> {
+        let collection = self.collection.clone();
+        let id = *id;
+
+        let id_bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: id.0.as_bytes().to_vec(),
+        });
+
+        let (collection, filter) = (&collection, &doc! { "_id": id_bson });
+        let attempts = &AtomicU32::new(0);
+        let result = with_retry(&self.retry_policy, || async move {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            collection.delete_one(filter.clone()).await
+        })
+        .await
+        .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
 
-        if result.deleted_count == 0 {
+        // Nothing left to delete on a retry means the failed attempt removed it
+        if result.deleted_count == 0 && attempts.load(Ordering::Relaxed) == 1 {
             return Err(CoreError::MessageNotFound { id });
         }
 
@@ -2,8 +2,10 @@ pub mod health;
 pub mod message;
 pub mod outbox;
 pub mod rabbitmq;
+pub mod retry;
+pub mod soft_delete;
 pub mod attachments;
 
 pub use outbox::MessageRoutingInfo;
 pub use outbox::write_outbox_event;
-pub use rabbitmq::{OutboxRelayService, RabbitMqPublisher};
+pub use rabbitmq::{MessageFeedConsumer, OutboxRelayService, RabbitMqConsumer, RabbitMqPublisher};
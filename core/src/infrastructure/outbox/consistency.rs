@@ -0,0 +1,205 @@
+//! Periodic repair of message and outbox state left inconsistent by crashes
+//!
+//! A message insert and its outbox write are separate operations, so a crash
+//! in between stores a message nobody is told about. Likewise a relay that
+//! dies mid-batch leaves rows `PROCESSING`. The checker finds both and puts
+//! them back on the normal publishing path.
+
+use std::collections::HashSet;
+
+use chrono::{TimeDelta, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    Collection, Database,
+    bson::{Binary, Bson, Document, doc, spec::BinarySubtype},
+    options::FindOptions,
+};
+use tokio::{
+    sync::watch,
+    time::{Duration, interval},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    domain::{
+        common::CoreError,
+        message::{entities::Message, events::created_event_record},
+        outbox::ports::OutboxEventRepository,
+    },
+    infrastructure::{
+        outbox::{entities::MessageOutboxEventRouting, mongo::MongoOutboxEventRepository},
+        rabbitmq::relay::{DEFAULT_OUTBOX_LEASE, release_stale_claims},
+        soft_delete::exclude_deleted,
+    },
+};
+
+/// What a consistency pass repaired
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    /// Messages whose missing create event was written to the outbox
+    pub reenqueued_events: u64,
+    /// Stale `PROCESSING` claims handed back to the relay
+    pub released_claims: u64,
+}
+
+/// Background job re-enqueueing lost create events and releasing stale claims
+pub struct OutboxConsistencyChecker {
+    db: Database,
+    outbox: MongoOutboxEventRepository,
+    interval: Duration,
+    /// How far back messages are inspected
+    window: Duration,
+    /// Messages younger than this may still be writing their event and are skipped
+    grace_period: Duration,
+    lease_duration: Duration,
+}
+
+impl OutboxConsistencyChecker {
+    pub fn new(db: Database) -> Self {
+        Self {
+            outbox: MongoOutboxEventRepository::new(db.clone()),
+            db,
+            interval: Duration::from_secs(300),
+            window: Duration::from_secs(3600),
+            grace_period: Duration::from_secs(60),
+            lease_duration: DEFAULT_OUTBOX_LEASE,
+        }
+    }
+
+    /// Configure the time between two passes
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Configure how far back each pass looks for messages without an event
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Configure how old a message must be before its event is considered missing
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Configure after how long a `PROCESSING` claim is considered abandoned
+    pub fn with_lease_duration(mut self, lease_duration: Duration) -> Self {
+        self.lease_duration = lease_duration;
+        self
+    }
+
+    /// Run a pass every interval until `shutdown` turns `true` or its sender is dropped
+    pub async fn start(&self, mut shutdown: watch::Receiver<bool>) {
+        info!("Starting outbox consistency checker");
+        let mut ticker = interval(self.interval);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.wait_for(|stop| *stop) => break,
+            }
+
+            match self.run_once().await {
+                Ok(report) if report != ConsistencyReport::default() => {
+                    warn!(
+                        "Outbox consistency pass re-enqueued {} events and released {} claims",
+                        report.reenqueued_events, report.released_claims
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => error!("Error checking outbox consistency: {}", e),
+            }
+        }
+
+        info!("Outbox consistency checker stopped");
+    }
+
+    /// Run a single pass
+    pub async fn run_once(&self) -> Result<ConsistencyReport, CoreError> {
+        let released_claims = release_stale_claims(&self.db, self.lease_duration).await?;
+        let reenqueued_events = self.reenqueue_missing_created_events().await?;
+
+        Ok(ConsistencyReport {
+            reenqueued_events,
+            released_claims,
+        })
+    }
+
+    /// Write a create event for every recent message that has none in the outbox
+    ///
+    /// An event counts as present whatever its status; failed rows are the
+    /// relay's concern. Events written before outbox rows carried an
+    /// `aggregate_id` cannot be matched, so messages from that time are
+    /// announced again on the first pass.
+    async fn reenqueue_missing_created_events(&self) -> Result<u64, CoreError> {
+        let now = Utc::now();
+        let from = now - TimeDelta::from_std(self.window).unwrap_or(TimeDelta::zero());
+        let until = now - TimeDelta::from_std(self.grace_period).unwrap_or(TimeDelta::zero());
+
+        // created_at is stored as an RFC3339 string, which sorts chronologically
+        let messages: Collection<Message> = self.db.collection("messages");
+        let recent: Vec<Message> = messages
+            .find(exclude_deleted(doc! {
+                "created_at": { "$gte": from.to_rfc3339(), "$lt": until.to_rfc3339() },
+            }))
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+            .try_collect()
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+        if recent.is_empty() {
+            return Ok(0);
+        }
+
+        let ids: Vec<Bson> = recent
+            .iter()
+            .map(|message| {
+                Bson::Binary(Binary {
+                    subtype: BinarySubtype::Generic,
+                    bytes: message.id.0.as_bytes().to_vec(),
+                })
+            })
+            .collect();
+        let outbox: Collection<Document> = self.db.collection("outbox_messages");
+        let options = FindOptions::builder()
+            .projection(doc! { "aggregate_id": 1 })
+            .build();
+        let announced: HashSet<Vec<u8>> = outbox
+            .find(doc! {
+                "routing_key": MessageOutboxEventRouting::Create.to_routing_key(),
+                "aggregate_id": { "$in": ids },
+            })
+            .with_options(options)
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?
+            .try_filter_map(|doc| async move {
+                Ok(match doc.get("aggregate_id") {
+                    Some(Bson::Binary(bin)) => Some(bin.bytes.clone()),
+                    _ => None,
+                })
+            })
+            .try_collect()
+            .await
+            .map_err(|e| CoreError::DatabaseError { msg: e.to_string() })?;
+
+        let mut reenqueued = 0;
+        for message in recent
+            .iter()
+            .filter(|message| !announced.contains(message.id.0.as_bytes().as_slice()))
+        {
+            warn!(
+                "Message {} in channel {} has no create event in the outbox, re-enqueueing it",
+                message.id, message.channel_id
+            );
+            let record = created_event_record(message)?;
+            self.outbox
+                .write_event(&record, MessageOutboxEventRouting::Create)
+                .await?;
+            reenqueued += 1;
+        }
+
+        Ok(reenqueued)
+    }
+}
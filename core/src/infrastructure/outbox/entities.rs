@@ -3,14 +3,35 @@ pub enum MessageOutboxEventRouting {
     Create,
     Update,
     Delete,
+    BulkChanged,
+    ReactionAdded,
+    ReactionRemoved,
+    Pinned,
+    Unpinned,
 }
 
 impl MessageOutboxEventRouting {
+    pub const ALL: [MessageOutboxEventRouting; 8] = [
+        MessageOutboxEventRouting::Create,
+        MessageOutboxEventRouting::Update,
+        MessageOutboxEventRouting::Delete,
+        MessageOutboxEventRouting::BulkChanged,
+        MessageOutboxEventRouting::ReactionAdded,
+        MessageOutboxEventRouting::ReactionRemoved,
+        MessageOutboxEventRouting::Pinned,
+        MessageOutboxEventRouting::Unpinned,
+    ];
+
     pub fn to_event_type(&self) -> &str {
         match self {
             MessageOutboxEventRouting::Create => "message.create",
             MessageOutboxEventRouting::Update => "message.update",
             MessageOutboxEventRouting::Delete => "message.delete",
+            MessageOutboxEventRouting::BulkChanged => "messages.bulk_change",
+            MessageOutboxEventRouting::ReactionAdded => "message.reaction.add",
+            MessageOutboxEventRouting::ReactionRemoved => "message.reaction.remove",
+            MessageOutboxEventRouting::Pinned => "message.pin",
+            MessageOutboxEventRouting::Unpinned => "message.unpin",
         }
     }
 
@@ -19,6 +40,11 @@ impl MessageOutboxEventRouting {
             MessageOutboxEventRouting::Create => "message.created",
             MessageOutboxEventRouting::Update => "message.updated",
             MessageOutboxEventRouting::Delete => "message.deleted",
+            MessageOutboxEventRouting::BulkChanged => "messages.bulk_changed",
+            MessageOutboxEventRouting::ReactionAdded => "message.reaction.added",
+            MessageOutboxEventRouting::ReactionRemoved => "message.reaction.removed",
+            MessageOutboxEventRouting::Pinned => "message.pinned",
+            MessageOutboxEventRouting::Unpinned => "message.unpinned",
         }
     }
 
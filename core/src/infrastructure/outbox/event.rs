@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Priority of ordinary events; the relay publishes them in FIFO order
+pub const DEFAULT_EVENT_PRIORITY: u8 = 0;
+
 /// Outbox event record (domain-level abstraction)
 #[derive(Debug, Clone)]
 pub struct OutboxEventRecord<TRouter>
@@ -10,6 +13,10 @@ where
     pub id: Uuid,
     pub router: TRouter,
     pub payload: Vec<u8>, // protobuf bytes
+    /// Higher priorities are published first, even under backlog
+    pub priority: u8,
+    /// Entity the event is about, so missing events can be detected later
+    pub aggregate_id: Option<Uuid>,
 }
 
 impl<TRouter> OutboxEventRecord<TRouter>
@@ -21,8 +28,22 @@ where
             id: Uuid::new_v4(),
             router,
             payload,
+            priority: DEFAULT_EVENT_PRIORITY,
+            aggregate_id: None,
         }
     }
+
+    /// Publish ahead of lower priority events, e.g. for announcements or security notices
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Record which entity, e.g. which message, the event is about
+    pub fn with_aggregate_id(mut self, aggregate_id: Uuid) -> Self {
+        self.aggregate_id = Some(aggregate_id);
+        self
+    }
 }
 
 /// Routing info (infrastructure-friendly, domain-safe)
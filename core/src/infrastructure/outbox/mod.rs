@@ -7,8 +7,13 @@
 
 mod event;
 mod writer;
+pub mod consistency;
 pub mod mongo;
 pub mod entities;
+pub mod routing;
+pub mod wire_format;
 
-pub use event::{MessageRouter, MessageRoutingInfo, OutboxEventRecord};
+pub use event::{DEFAULT_EVENT_PRIORITY, MessageRouter, MessageRoutingInfo, OutboxEventRecord};
+pub use routing::{EventRouting, RoutingConfig};
+pub use wire_format::{WireFormat, WireFormats};
 pub use writer::write_outbox_event;
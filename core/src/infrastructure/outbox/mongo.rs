@@ -1,16 +1,26 @@
 use async_trait::async_trait;
 use mongodb::Database;
 
-use crate::{domain::{common::CoreError, outbox::ports::OutboxEventRepository}, infrastructure::outbox::{MessageRouter, OutboxEventRecord, entities::MessageOutboxEventRouting}, write_outbox_event};
+use crate::{domain::{common::CoreError, outbox::ports::OutboxEventRepository}, infrastructure::outbox::{EventRouting, MessageRouter, OutboxEventRecord, entities::MessageOutboxEventRouting}, write_outbox_event};
 
 #[derive(Clone)]
 pub struct MongoOutboxEventRepository {
     db: Database,
+    routing: EventRouting,
 }
 
 impl MongoOutboxEventRepository {
     pub fn new(db: Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            routing: EventRouting::default(),
+        }
+    }
+
+    /// Pick each event's exchange from `routing`, which may be reloaded later
+    pub fn with_event_routing(mut self, routing: EventRouting) -> Self {
+        self.routing = routing;
+        self
     }
 }
 
@@ -21,7 +31,8 @@ impl OutboxEventRepository for MongoOutboxEventRepository {
         event: &OutboxEventRecord<TRouter>,
         routing: MessageOutboxEventRouting
     ) -> Result<(), CoreError> {
-        write_outbox_event(&self.db, routing.get_exchange(), routing.to_routing_key(), event)
+        let exchange = self.routing.exchange_for(routing);
+        write_outbox_event(&self.db, &exchange, routing.to_routing_key(), event)
             .await
             .map(|_| ())
     }
@@ -0,0 +1,93 @@
+//! Exchange each outbox event is written for, replaceable while running
+//!
+//! Events go to their default exchange unless the routing config re-points
+//! their routing key, e.g. `message.created: audit`. Operators reload the
+//! config without a redeploy; writes made after the reload use the new table.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use serde::Deserialize;
+
+use crate::{domain::common::CoreError, infrastructure::outbox::entities::MessageOutboxEventRouting};
+
+/// Exchange overrides keyed by routing key
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct RoutingConfig {
+    #[serde(default)]
+    pub exchanges: HashMap<String, String>,
+}
+
+impl RoutingConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write events with `routing_key` for `exchange` instead of their default one
+    pub fn with_exchange(mut self, routing_key: impl Into<String>, exchange: impl Into<String>) -> Self {
+        self.exchanges.insert(routing_key.into(), exchange.into());
+        self
+    }
+
+    /// Reject overrides for unknown routing keys or to an unnamed exchange
+    pub fn validate(&self) -> Result<(), CoreError> {
+        for (routing_key, exchange) in &self.exchanges {
+            let known = MessageOutboxEventRouting::ALL
+                .iter()
+                .any(|routing| routing.to_routing_key() == routing_key);
+            if !known {
+                return Err(CoreError::InvalidRouting {
+                    reason: format!("unknown routing key '{}'", routing_key),
+                });
+            }
+            if exchange.trim().is_empty() {
+                return Err(CoreError::InvalidRouting {
+                    reason: format!("routing key '{}' has an empty exchange", routing_key),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn exchange_for(&self, routing: MessageOutboxEventRouting) -> &str {
+        self.exchanges
+            .get(routing.to_routing_key())
+            .map(String::as_str)
+            .unwrap_or_else(|| routing.get_exchange())
+    }
+}
+
+/// Shared handle on the active [`RoutingConfig`]
+///
+/// Clones see the same table, so a reload through any of them applies to
+/// every repository holding one.
+#[derive(Clone, Debug, Default)]
+pub struct EventRouting {
+    active: Arc<RwLock<Arc<RoutingConfig>>>,
+}
+
+impl EventRouting {
+    pub fn new(config: RoutingConfig) -> Result<Self, CoreError> {
+        config.validate()?;
+        Ok(Self {
+            active: Arc::new(RwLock::new(Arc::new(config))),
+        })
+    }
+
+    /// Swap in `config` once it is valid; an invalid one leaves the active table untouched
+    pub fn reload(&self, config: RoutingConfig) -> Result<(), CoreError> {
+        config.validate()?;
+        *self.active.write().unwrap() = Arc::new(config);
+        Ok(())
+    }
+
+    pub fn current(&self) -> Arc<RoutingConfig> {
+        self.active.read().unwrap().clone()
+    }
+
+    pub fn exchange_for(&self, routing: MessageOutboxEventRouting) -> String {
+        self.current().exchange_for(routing).to_string()
+    }
+}
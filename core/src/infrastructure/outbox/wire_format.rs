@@ -0,0 +1,117 @@
+//! Format events are published in, chosen per exchange or route
+//!
+//! Events are always stored as protobuf; the relay converts them at publish
+//! time for consumers that want another format.
+
+use std::{collections::HashMap, str::FromStr};
+
+use crate::domain::{common::CoreError, message::events::event_to_json};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WireFormat {
+    /// The stored bytes, unchanged
+    #[default]
+    Protobuf,
+    Json,
+}
+
+impl WireFormat {
+    /// AMQP `content_type` of messages published in this format
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            WireFormat::Protobuf => "application/x-protobuf",
+            WireFormat::Json => "application/json",
+        }
+    }
+
+    /// Format of a delivery announcing `content_type`
+    ///
+    /// Anything but JSON is read as protobuf, like events published before
+    /// the content type was set.
+    pub fn from_content_type(content_type: &str) -> Self {
+        if content_type == WireFormat::Json.content_type() {
+            WireFormat::Json
+        } else {
+            WireFormat::Protobuf
+        }
+    }
+
+    /// Convert a stored protobuf payload to this format
+    pub fn encode(&self, routing_key: &str, payload: Vec<u8>) -> Result<Vec<u8>, CoreError> {
+        match self {
+            WireFormat::Protobuf => Ok(payload),
+            WireFormat::Json => event_to_json(routing_key, &payload),
+        }
+    }
+}
+
+impl FromStr for WireFormat {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "protobuf" | "proto" => Ok(WireFormat::Protobuf),
+            "json" => Ok(WireFormat::Json),
+            _ => Err(CoreError::InvalidWireFormat { rule: s.to_string() }),
+        }
+    }
+}
+
+/// Wire format of every route, protobuf unless configured otherwise
+///
+/// A rule for an exchange and routing key wins over one for the whole exchange.
+#[derive(Clone, Debug, Default)]
+pub struct WireFormats {
+    by_exchange: HashMap<String, WireFormat>,
+    by_route: HashMap<(String, String), WireFormat>,
+}
+
+impl WireFormats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish everything sent to `exchange` in `format`
+    pub fn with_exchange(mut self, exchange: impl Into<String>, format: WireFormat) -> Self {
+        self.by_exchange.insert(exchange.into(), format);
+        self
+    }
+
+    /// Publish events sent to `exchange` with `routing_key` in `format`
+    pub fn with_route(
+        mut self,
+        exchange: impl Into<String>,
+        routing_key: impl Into<String>,
+        format: WireFormat,
+    ) -> Self {
+        self.by_route.insert((exchange.into(), routing_key.into()), format);
+        self
+    }
+
+    pub fn for_route(&self, exchange: &str, routing_key: &str) -> WireFormat {
+        self.by_route
+            .get(&(exchange.to_string(), routing_key.to_string()))
+            .or_else(|| self.by_exchange.get(exchange))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Parse comma-separated `exchange=format` or `exchange/routing_key=format` rules
+impl FromStr for WireFormats {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut formats = WireFormats::new();
+        for rule in s.split(',').map(str::trim).filter(|rule| !rule.is_empty()) {
+            let invalid = || CoreError::InvalidWireFormat { rule: rule.to_string() };
+            let (target, format) = rule.split_once('=').ok_or_else(invalid)?;
+            let format = format.parse::<WireFormat>().map_err(|_| invalid())?;
+            formats = match target.trim().split_once('/') {
+                Some((exchange, routing_key)) => formats.with_route(exchange, routing_key, format),
+                None => formats.with_exchange(target.trim(), format),
+            };
+        }
+        Ok(formats)
+    }
+}
@@ -21,6 +21,9 @@ struct OutboxDocument {
     routing_key: String,
     payload: Binary, // store as BSON binary
     status: String,
+    priority: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aggregate_id: Option<Binary>,
     created_at: BsonDateTime,
 }
 
@@ -42,6 +45,11 @@ where
             bytes: event.payload.clone(),
         },
         status: "READY".to_string(),
+        priority: event.priority as i32,
+        aggregate_id: event.aggregate_id.map(|id| Binary {
+            subtype: mongodb::bson::spec::BinarySubtype::Generic,
+            bytes: id.as_bytes().to_vec(),
+        }),
         created_at: BsonDateTime::now(),
     };
 
@@ -0,0 +1,646 @@
+use std::{collections::HashSet, future::Future, sync::Arc, time::Duration};
+
+use events_protobuf::messages_events::{CreateMessageEvent, DeleteMessageEvent, UpdateMessageEvent};
+use futures::StreamExt;
+use lapin::{
+    Channel, Connection, ExchangeKind,
+    options::{
+        BasicAckOptions, BasicConsumeOptions, BasicNackOptions, BasicQosOptions,
+        ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions,
+    },
+    types::FieldTable,
+};
+use prost::Message as _;
+use serde::Deserialize;
+use tokio::{
+    sync::{RwLock, watch},
+    time::interval,
+};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        common::CoreError,
+        message::{
+            entities::{AttachmentId, AuthorId, ChannelId, MessageId},
+            feed::{LiveEvent, LiveMessage, LiveMessageDeletion, LiveMessageUpdate, MessageFeed},
+        },
+    },
+    infrastructure::{
+        outbox::{EventRouting, WireFormat, entities::MessageOutboxEventRouting},
+        rabbitmq::connection_properties,
+    },
+};
+
+/// Events feeding live channel streams
+const LIVE_ROUTINGS: [MessageOutboxEventRouting; 3] = [
+    MessageOutboxEventRouting::Create,
+    MessageOutboxEventRouting::Update,
+    MessageOutboxEventRouting::Delete,
+];
+
+/// How often the feed consumer picks up a reloaded routing config
+const ROUTING_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Pause before reconnecting after the broker connection was lost
+pub const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Deliveries a consumer may hold unacknowledged at once
+const DEFAULT_PREFETCH: u16 = 16;
+
+/// Event received from another service
+#[derive(Debug, Clone)]
+pub struct IncomingEvent {
+    pub routing_key: String,
+    pub payload: Vec<u8>,
+}
+
+/// RabbitMQ consumer for reacting to events published by other services
+///
+/// Deliveries are acknowledged once the handler succeeds and requeued when it
+/// fails, so a handler must tolerate seeing the same event more than once.
+#[derive(Clone)]
+pub struct RabbitMqConsumer {
+    connection: Arc<RwLock<Option<Connection>>>,
+    url: String,
+    connection_name: Option<String>,
+    exchange: String,
+    reconnect_delay: Duration,
+    prefetch: u16,
+}
+
+impl RabbitMqConsumer {
+    /// Create a new RabbitMQ consumer
+    pub fn new(url: String) -> Self {
+        Self {
+            connection: Arc::new(RwLock::new(None)),
+            url,
+            connection_name: None,
+            exchange: "notifications".to_string(),
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+            prefetch: DEFAULT_PREFETCH,
+        }
+    }
+
+    /// Name the connection as shown by the broker
+    pub fn with_connection_name(mut self, connection_name: impl Into<String>) -> Self {
+        self.connection_name = Some(connection_name.into());
+        self
+    }
+
+    /// Consume from another exchange than `notifications`
+    pub fn with_exchange(mut self, exchange: impl Into<String>) -> Self {
+        self.exchange = exchange.into();
+        self
+    }
+
+    pub fn with_reconnect_delay(mut self, reconnect_delay: Duration) -> Self {
+        self.reconnect_delay = reconnect_delay;
+        self
+    }
+
+    /// Limit how many deliveries are handed out before earlier ones are acknowledged
+    pub fn with_prefetch(mut self, prefetch: u16) -> Self {
+        self.prefetch = prefetch.max(1);
+        self
+    }
+
+    /// Connect to RabbitMQ
+    pub async fn connect(&self) -> Result<(), CoreError> {
+        info!("Connecting consumer to RabbitMQ at {}", self.url);
+
+        let properties = connection_properties(self.connection_name.as_deref());
+        let conn = Connection::connect(&self.url, properties)
+            .await
+            .map_err(|e| CoreError::RabbitMqError {
+                msg: format!("Failed to connect to RabbitMQ: {}", e),
+            })?;
+
+        *self.connection.write().await = Some(conn);
+
+        info!("Consumer connected to RabbitMQ");
+        Ok(())
+    }
+
+    /// Check if the connection is alive
+    pub async fn is_connected(&self) -> bool {
+        let conn_guard = self.connection.read().await;
+        if let Some(conn) = conn_guard.as_ref() {
+            conn.status().connected()
+        } else {
+            false
+        }
+    }
+
+    /// Reconnect if connection is lost
+    pub async fn ensure_connected(&self) -> Result<(), CoreError> {
+        if !self.is_connected().await {
+            warn!("RabbitMQ consumer connection lost. Reconnecting...");
+            self.connect().await?;
+        }
+        Ok(())
+    }
+
+    /// Consume `queue` until `shutdown` turns `true` (long-running task)
+    ///
+    /// The durable queue is declared and bound to every routing key pattern
+    /// before consuming. `handler` is called once per delivery; an error
+    /// requeues the delivery. When the connection drops, the consumer waits
+    /// for the reconnect delay and starts over.
+    pub async fn consume<F, Fut>(
+        &self,
+        queue: &str,
+        routing_keys: &[&str],
+        handler: F,
+        mut shutdown: watch::Receiver<bool>,
+    ) where
+        F: Fn(IncomingEvent) -> Fut,
+        Fut: Future<Output = Result<(), CoreError>>,
+    {
+        info!("Starting RabbitMQ consumer on queue {}", queue);
+
+        loop {
+            tokio::select! {
+                result = self.consume_once(queue, routing_keys, &handler) => {
+                    if let Err(e) = result {
+                        error!("RabbitMQ consumer on queue {} failed: {}", queue, e);
+                    }
+                }
+                _ = shutdown.wait_for(|stop| *stop) => break,
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(self.reconnect_delay) => {}
+                _ = shutdown.wait_for(|stop| *stop) => break,
+            }
+        }
+
+        info!("RabbitMQ consumer on queue {} stopped", queue);
+    }
+
+    /// Consume on the current connection until the broker closes it
+    async fn consume_once<F, Fut>(
+        &self,
+        queue: &str,
+        routing_keys: &[&str],
+        handler: &F,
+    ) -> Result<(), CoreError>
+    where
+        F: Fn(IncomingEvent) -> Fut,
+        Fut: Future<Output = Result<(), CoreError>>,
+    {
+        let rabbitmq_error = |e: lapin::Error| CoreError::RabbitMqError { msg: e.to_string() };
+
+        self.ensure_connected().await?;
+        let channel = {
+            let conn_guard = self.connection.read().await;
+            let connection = conn_guard.as_ref().ok_or_else(|| CoreError::RabbitMqError {
+                msg: "Connection not initialized".to_string(),
+            })?;
+            connection.create_channel().await.map_err(rabbitmq_error)?
+        };
+
+        channel
+            .exchange_declare(
+                &self.exchange,
+                ExchangeKind::Topic,
+                ExchangeDeclareOptions {
+                    durable: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await
+            .map_err(rabbitmq_error)?;
+
+        channel
+            .queue_declare(
+                queue,
+                QueueDeclareOptions {
+                    durable: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await
+            .map_err(rabbitmq_error)?;
+
+        for routing_key in routing_keys {
+            channel
+                .queue_bind(
+                    queue,
+                    &self.exchange,
+                    routing_key,
+                    QueueBindOptions::default(),
+                    FieldTable::default(),
+                )
+                .await
+                .map_err(rabbitmq_error)?;
+        }
+
+        channel
+            .basic_qos(self.prefetch, BasicQosOptions::default())
+            .await
+            .map_err(rabbitmq_error)?;
+
+        let mut consumer = channel
+            .basic_consume(
+                queue,
+                "message-service",
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            )
+            .await
+            .map_err(rabbitmq_error)?;
+
+        info!("Consuming {} bound to {}", queue, self.exchange);
+
+        while let Some(delivery) = consumer.next().await {
+            let delivery = delivery.map_err(rabbitmq_error)?;
+            let event = IncomingEvent {
+                routing_key: delivery.routing_key.to_string(),
+                payload: delivery.data.clone(),
+            };
+
+            match handler(event).await {
+                Ok(()) => delivery
+                    .ack(BasicAckOptions::default())
+                    .await
+                    .map_err(rabbitmq_error)?,
+                Err(e) => {
+                    warn!(
+                        "Handler failed for {} on queue {}, requeuing: {}",
+                        delivery.routing_key, queue, e
+                    );
+                    delivery
+                        .nack(BasicNackOptions {
+                            requeue: true,
+                            ..Default::default()
+                        })
+                        .await
+                        .map_err(rabbitmq_error)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Consumes `message.created`, `message.updated` and `message.deleted` events
+/// and hands them to a [`MessageFeed`]
+///
+/// Every instance binds its own exclusive queue, so each one sees every
+/// message no matter which instance relayed it. The queue follows the
+/// exchanges the events are routed to, reloads included, and each delivery
+/// is decoded according to its `content_type`.
+pub struct MessageFeedConsumer {
+    url: String,
+    connection_name: Option<String>,
+    routing: EventRouting,
+    feed: MessageFeed,
+    reconnect_delay: Duration,
+}
+
+impl MessageFeedConsumer {
+    pub fn new(url: String, feed: MessageFeed) -> Self {
+        Self {
+            url,
+            connection_name: None,
+            routing: EventRouting::default(),
+            feed,
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+        }
+    }
+
+    /// Name the connection as shown by the broker
+    pub fn with_connection_name(mut self, connection_name: impl Into<String>) -> Self {
+        self.connection_name = Some(connection_name.into());
+        self
+    }
+
+    /// Consume from the exchanges `routing` sends the live events to
+    ///
+    /// Pass the outbox's handle so a reloaded routing config moves the
+    /// bindings along with the events.
+    pub fn with_event_routing(mut self, routing: EventRouting) -> Self {
+        self.routing = routing;
+        self
+    }
+
+    pub fn with_reconnect_delay(mut self, reconnect_delay: Duration) -> Self {
+        self.reconnect_delay = reconnect_delay;
+        self
+    }
+
+    /// Consume until `shutdown` turns `true`, reconnecting whenever the broker goes away
+    pub async fn start(&self, mut shutdown: watch::Receiver<bool>) {
+        info!("Starting message feed consumer");
+
+        loop {
+            tokio::select! {
+                result = self.consume() => {
+                    if let Err(e) = result {
+                        error!("Message feed consumer failed: {}", e);
+                    }
+                }
+                _ = shutdown.wait_for(|stop| *stop) => break,
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(self.reconnect_delay) => {}
+                _ = shutdown.wait_for(|stop| *stop) => break,
+            }
+        }
+
+        info!("Message feed consumer stopped");
+    }
+
+    /// Consume on a fresh connection until it is closed
+    async fn consume(&self) -> Result<(), CoreError> {
+        let rabbitmq_error = |e: lapin::Error| CoreError::RabbitMqError { msg: e.to_string() };
+
+        let properties = connection_properties(self.connection_name.as_deref());
+        let connection = Connection::connect(&self.url, properties)
+            .await
+            .map_err(rabbitmq_error)?;
+        let channel = connection.create_channel().await.map_err(rabbitmq_error)?;
+
+        // Server-named and exclusive, so the queue disappears with the connection
+        let queue = channel
+            .queue_declare(
+                "",
+                QueueDeclareOptions {
+                    exclusive: true,
+                    auto_delete: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await
+            .map_err(rabbitmq_error)?;
+
+        let mut bound = HashSet::new();
+        self.sync_bindings(&channel, queue.name().as_str(), &mut bound)
+            .await?;
+
+        // Live streams are best effort, a message lost on the way is not redelivered
+        let mut consumer = channel
+            .basic_consume(
+                queue.name().as_str(),
+                "message-feed",
+                BasicConsumeOptions {
+                    no_ack: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await
+            .map_err(rabbitmq_error)?;
+
+        info!("Message feed consumer started");
+
+        let mut routing_check = interval(ROUTING_CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                delivery = consumer.next() => {
+                    let Some(delivery) = delivery else { break };
+                    let delivery = delivery.map_err(rabbitmq_error)?;
+                    let format = delivery
+                        .properties
+                        .content_type()
+                        .as_ref()
+                        .map(|content_type| WireFormat::from_content_type(content_type.as_str()))
+                        .unwrap_or_default();
+                    match live_event_from_wire(delivery.routing_key.as_str(), format, &delivery.data) {
+                        Ok(event) => self.feed.publish(event),
+                        Err(e) => warn!("Skipping undecodable {} event: {}", delivery.routing_key, e),
+                    }
+                }
+                _ = routing_check.tick() => {
+                    self.sync_bindings(&channel, queue.name().as_str(), &mut bound)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Bind `queue` to where the live events are routed now and drop stale bindings
+    ///
+    /// `bound` holds the `(exchange, routing_key)` pairs bound so far and is
+    /// updated in place.
+    async fn sync_bindings(
+        &self,
+        channel: &Channel,
+        queue: &str,
+        bound: &mut HashSet<(String, String)>,
+    ) -> Result<(), CoreError> {
+        let rabbitmq_error = |e: lapin::Error| CoreError::RabbitMqError { msg: e.to_string() };
+
+        let routing = self.routing.current();
+        let wanted: HashSet<(String, String)> = LIVE_ROUTINGS
+            .iter()
+            .map(|event| {
+                (
+                    routing.exchange_for(*event).to_string(),
+                    event.to_routing_key().to_string(),
+                )
+            })
+            .collect();
+
+        for (exchange, routing_key) in wanted.difference(bound) {
+            channel
+                .exchange_declare(
+                    exchange,
+                    ExchangeKind::Topic,
+                    ExchangeDeclareOptions {
+                        durable: true,
+                        ..Default::default()
+                    },
+                    FieldTable::default(),
+                )
+                .await
+                .map_err(rabbitmq_error)?;
+            channel
+                .queue_bind(
+                    queue,
+                    exchange,
+                    routing_key,
+                    QueueBindOptions::default(),
+                    FieldTable::default(),
+                )
+                .await
+                .map_err(rabbitmq_error)?;
+            info!("Message feed consumer bound to {}/{}", exchange, routing_key);
+        }
+
+        for (exchange, routing_key) in bound.difference(&wanted) {
+            channel
+                .queue_unbind(queue, exchange, routing_key, FieldTable::default())
+                .await
+                .map_err(rabbitmq_error)?;
+            info!("Message feed consumer unbound from {}/{}", exchange, routing_key);
+        }
+
+        *bound = wanted;
+        Ok(())
+    }
+}
+
+/// Decode an event feeding live streams that was published in `format`
+pub fn live_event_from_wire(
+    routing_key: &str,
+    format: WireFormat,
+    payload: &[u8],
+) -> Result<LiveEvent, CoreError> {
+    match format {
+        WireFormat::Protobuf => live_event_from_payload(routing_key, payload),
+        WireFormat::Json => live_event_from_json(routing_key, payload),
+    }
+}
+
+/// Decode an event feeding live streams, whose type is found from its routing key
+pub fn live_event_from_payload(routing_key: &str, payload: &[u8]) -> Result<LiveEvent, CoreError> {
+    let decode_error = |e: prost::DecodeError| CoreError::SerializationError { msg: e.to_string() };
+
+    match routing_key {
+        "message.created" => live_message_from_payload(payload).map(LiveEvent::from),
+        "message.updated" => {
+            let event = UpdateMessageEvent::decode(payload).map_err(decode_error)?;
+            Ok(LiveEvent::from(LiveMessageUpdate {
+                id: MessageId(parse_id("message_id", &event.message_id)?),
+                channel_id: ChannelId(parse_id("channel_id", &event.channel_id)?),
+                content: event.content,
+                is_pinned: event.is_pinned,
+            }))
+        }
+        "message.deleted" => {
+            let event = DeleteMessageEvent::decode(payload).map_err(decode_error)?;
+            Ok(LiveEvent::from(LiveMessageDeletion {
+                id: MessageId(parse_id("message_id", &event.message_id)?),
+                channel_id: ChannelId(parse_id("channel_id", &event.channel_id)?),
+            }))
+        }
+        other => Err(CoreError::SerializationError {
+            msg: format!("no live event for routing key {}", other),
+        }),
+    }
+}
+
+fn parse_id(field: &str, value: &str) -> Result<Uuid, CoreError> {
+    Uuid::parse_str(value).map_err(|e| CoreError::SerializationError {
+        msg: format!("invalid {}: {}", field, e),
+    })
+}
+
+/// Decode a `message.created` event payload as written to the outbox
+pub fn live_message_from_payload(payload: &[u8]) -> Result<LiveMessage, CoreError> {
+    let event = CreateMessageEvent::decode(payload)
+        .map_err(|e| CoreError::SerializationError { msg: e.to_string() })?;
+
+    let reply_to_message_id = if event.reply_to_message_id.is_empty() {
+        None
+    } else {
+        Some(MessageId(parse_id("reply_to_message_id", &event.reply_to_message_id)?))
+    };
+
+    let attachments = event
+        .attachments
+        .iter()
+        .map(|attachment| parse_id("attachment id", &attachment.id).map(AttachmentId))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(LiveMessage {
+        id: MessageId(parse_id("message_id", &event.message_id)?),
+        channel_id: ChannelId(parse_id("channel_id", &event.channel_id)?),
+        author_id: AuthorId(parse_id("author_id", &event.author_id)?),
+        content: event.content,
+        reply_to_message_id,
+        attachments,
+    })
+}
+
+/// `message.created` as published in JSON
+#[derive(Deserialize)]
+struct JsonCreatedEvent {
+    message_id: String,
+    channel_id: String,
+    author_id: String,
+    content: String,
+    reply_to_message_id: Option<String>,
+    #[serde(default)]
+    attachments: Vec<JsonAttachment>,
+}
+
+#[derive(Deserialize)]
+struct JsonAttachment {
+    id: String,
+}
+
+/// `message.updated` as published in JSON
+#[derive(Deserialize)]
+struct JsonUpdatedEvent {
+    message_id: String,
+    channel_id: String,
+    content: Option<String>,
+    is_pinned: Option<bool>,
+}
+
+/// `message.deleted` as published in JSON
+#[derive(Deserialize)]
+struct JsonDeletedEvent {
+    message_id: String,
+    channel_id: String,
+}
+
+/// Decode a live event converted to JSON by the relay
+fn live_event_from_json(routing_key: &str, payload: &[u8]) -> Result<LiveEvent, CoreError> {
+    let json_error = |e: serde_json::Error| CoreError::SerializationError { msg: e.to_string() };
+
+    match routing_key {
+        "message.created" => {
+            let event: JsonCreatedEvent = serde_json::from_slice(payload).map_err(json_error)?;
+            let reply_to_message_id = event
+                .reply_to_message_id
+                .as_deref()
+                .map(|id| parse_id("reply_to_message_id", id).map(MessageId))
+                .transpose()?;
+            let attachments = event
+                .attachments
+                .iter()
+                .map(|attachment| parse_id("attachment id", &attachment.id).map(AttachmentId))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(LiveEvent::from(LiveMessage {
+                id: MessageId(parse_id("message_id", &event.message_id)?),
+                channel_id: ChannelId(parse_id("channel_id", &event.channel_id)?),
+                author_id: AuthorId(parse_id("author_id", &event.author_id)?),
+                content: event.content,
+                reply_to_message_id,
+                attachments,
+            }))
+        }
+        "message.updated" => {
+            let event: JsonUpdatedEvent = serde_json::from_slice(payload).map_err(json_error)?;
+            Ok(LiveEvent::from(LiveMessageUpdate {
+                id: MessageId(parse_id("message_id", &event.message_id)?),
+                channel_id: ChannelId(parse_id("channel_id", &event.channel_id)?),
+                content: event.content,
+                is_pinned: event.is_pinned,
+            }))
+        }
+        "message.deleted" => {
+            let event: JsonDeletedEvent = serde_json::from_slice(payload).map_err(json_error)?;
+            Ok(LiveEvent::from(LiveMessageDeletion {
+                id: MessageId(parse_id("message_id", &event.message_id)?),
+                channel_id: ChannelId(parse_id("channel_id", &event.channel_id)?),
+            }))
+        }
+        other => Err(CoreError::SerializationError {
+            msg: format!("no live event for routing key {}", other),
+        }),
+    }
+}
@@ -1,5 +1,20 @@
+use lapin::ConnectionProperties;
+
+pub mod consumer;
 pub mod publisher;
 pub mod relay;
 
+pub use consumer::{IncomingEvent, MessageFeedConsumer, RabbitMqConsumer};
 pub use publisher::RabbitMqPublisher;
 pub use relay::OutboxRelayService;
+
+/// Connection properties carrying `connection_name`, if any
+///
+/// The name is what the RabbitMQ management UI lists the connection under.
+pub(crate) fn connection_properties(connection_name: Option<&str>) -> ConnectionProperties {
+    let properties = ConnectionProperties::default();
+    match connection_name {
+        Some(name) => properties.with_connection_name(name.into()),
+        None => properties,
+    }
+}
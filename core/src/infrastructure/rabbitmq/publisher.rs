@@ -1,13 +1,22 @@
+use futures::future::join_all;
 use lapin::{
-    BasicProperties, Channel, Connection, ConnectionProperties, ExchangeKind,
-    options::{BasicPublishOptions, ExchangeDeclareOptions},
+    BasicProperties, Channel, Connection, ExchangeKind,
+    options::{BasicPublishOptions, ConfirmSelectOptions, ExchangeDeclareOptions},
+    publisher_confirm::Confirmation,
     types::FieldTable,
 };
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, warn};
 
-use crate::domain::common::CoreError;
+use crate::{
+    domain::{
+        common::CoreError,
+        health::{entities::IsHealthy, port::DependencyProbe},
+        outbox::ports::{EventPublisher, OutgoingEvent},
+    },
+    infrastructure::rabbitmq::connection_properties,
+};
 
 /// RabbitMQ publisher for publishing domain events
 #[derive(Clone)]
@@ -15,6 +24,7 @@ pub struct RabbitMqPublisher {
     connection: Arc<RwLock<Option<Connection>>>,
     channel: Arc<RwLock<Option<Channel>>>,
     url: String,
+    connection_name: Option<String>,
 }
 
 impl RabbitMqPublisher {
@@ -24,14 +34,22 @@ impl RabbitMqPublisher {
             connection: Arc::new(RwLock::new(None)),
             channel: Arc::new(RwLock::new(None)),
             url,
+            connection_name: None,
         }
     }
 
+    /// Name the connection as shown by the broker
+    pub fn with_connection_name(mut self, connection_name: impl Into<String>) -> Self {
+        self.connection_name = Some(connection_name.into());
+        self
+    }
+
     /// Connect to RabbitMQ and create a channel
     pub async fn connect(&self) -> Result<(), CoreError> {
         info!("Connecting to RabbitMQ at {}", self.url);
 
-        let conn = Connection::connect(&self.url, ConnectionProperties::default())
+        let properties = connection_properties(self.connection_name.as_deref());
+        let conn = Connection::connect(&self.url, properties)
             .await
             .map_err(|e| CoreError::RabbitMqError {
                 msg: format!("Failed to connect to RabbitMQ: {}", e),
@@ -44,6 +62,15 @@ impl RabbitMqPublisher {
                 msg: format!("Failed to create channel: {}", e),
             })?;
 
+        // Without confirm mode every confirm resolves at once, whether the
+        // broker took the message or not
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await
+            .map_err(|e| CoreError::RabbitMqError {
+                msg: format!("Failed to enable publisher confirms: {}", e),
+            })?;
+
         *self.connection.write().await = Some(conn);
         *self.channel.write().await = Some(channel);
 
@@ -79,13 +106,8 @@ impl RabbitMqPublisher {
         Ok(())
     }
 
-    /// Publish a message to an exchange with a routing key
-    pub async fn publish(
-        &self,
-        exchange_name: &str,
-        routing_key: &str,
-        payload: Vec<u8>,
-    ) -> Result<(), CoreError> {
+    /// Publish an event to its exchange with its routing key
+    pub async fn publish(&self, event: OutgoingEvent) -> Result<(), CoreError> {
         let channel_guard = self.channel.read().await;
         let channel = channel_guard
             .as_ref()
@@ -93,17 +115,20 @@ impl RabbitMqPublisher {
                 msg: "Channel not initialized. Call connect() first.".to_string(),
             })?;
 
-        let properties = BasicProperties::default()
-            .with_content_type("application/json".into())
-            .with_delivery_mode(2); // persistent
+        let OutgoingEvent {
+            exchange_name,
+            routing_key,
+            payload,
+            content_type,
+        } = event;
 
-        channel
+        let confirmation = channel
             .basic_publish(
-                exchange_name,
-                routing_key,
+                &exchange_name,
+                &routing_key,
                 BasicPublishOptions::default(),
                 &payload,
-                properties,
+                Self::properties(&content_type),
             )
             .await
             .map_err(|e| CoreError::RabbitMqError {
@@ -119,6 +144,7 @@ impl RabbitMqPublisher {
                     exchange_name, routing_key, e
                 ),
             })?;
+        Self::accepted(confirmation, &exchange_name, &routing_key)?;
 
         info!(
             "Published message to exchange: {}, routing_key: {}",
@@ -127,6 +153,93 @@ impl RabbitMqPublisher {
         Ok(())
     }
 
+    /// Publish several events, then wait for all their confirms together
+    ///
+    /// Returns one result per event, in order; a failed publish or confirm
+    /// only fails its own entry.
+    pub async fn publish_batch(&self, events: Vec<OutgoingEvent>) -> Vec<Result<(), CoreError>> {
+        if let Err(e) = self.ensure_connected().await {
+            return events.iter().map(|_| Err(e.clone())).collect();
+        }
+
+        let channel_guard = self.channel.read().await;
+        let Some(channel) = channel_guard.as_ref() else {
+            return events
+                .iter()
+                .map(|_| {
+                    Err(CoreError::RabbitMqError {
+                        msg: "Channel not initialized. Call connect() first.".to_string(),
+                    })
+                })
+                .collect();
+        };
+
+        // Everything is sent before the first confirm is awaited, so the
+        // batch costs one round-trip instead of one per message
+        let mut confirms = Vec::with_capacity(events.len());
+        for event in &events {
+            let confirm = channel
+                .basic_publish(
+                    &event.exchange_name,
+                    &event.routing_key,
+                    BasicPublishOptions::default(),
+                    &event.payload,
+                    Self::properties(&event.content_type),
+                )
+                .await
+                .map_err(|e| CoreError::RabbitMqError {
+                    msg: format!(
+                        "Failed to publish message to {}/{}: {}",
+                        event.exchange_name, event.routing_key, e
+                    ),
+                });
+            confirms.push(confirm);
+        }
+
+        let results = join_all(confirms.into_iter().zip(&events).map(
+            |(confirm, event)| async move {
+                let confirmation = confirm?.await.map_err(|e| CoreError::RabbitMqError {
+                    msg: format!(
+                        "Failed to confirm publish to {}/{}: {}",
+                        event.exchange_name, event.routing_key, e
+                    ),
+                })?;
+                Self::accepted(confirmation, &event.exchange_name, &event.routing_key)
+            },
+        ))
+        .await;
+
+        info!("Published batch of {} messages", events.len());
+        results
+    }
+
+    /// Only an ack means the broker took responsibility for the message
+    fn accepted(
+        confirmation: Confirmation,
+        exchange_name: &str,
+        routing_key: &str,
+    ) -> Result<(), CoreError> {
+        match confirmation {
+            Confirmation::Ack(_) => Ok(()),
+            Confirmation::Nack(_) => Err(CoreError::RabbitMqError {
+                msg: format!("Broker refused message to {}/{}", exchange_name, routing_key),
+            }),
+            Confirmation::NotRequested => Err(CoreError::RabbitMqError {
+                msg: format!(
+                    "Publish to {}/{} was not confirmed, the channel is not in confirm mode",
+                    exchange_name, routing_key
+                ),
+            }),
+        }
+    }
+
+    /// Persistent delivery, announcing how the payload is encoded
+    fn properties(content_type: &str) -> BasicProperties {
+        BasicProperties::default()
+            .with_content_type(content_type.into())
+            .with_delivery_mode(2) // persistent
+    }
+
     /// Check if the connection is alive
     pub async fn is_connected(&self) -> bool {
         let conn_guard = self.connection.read().await;
@@ -160,3 +273,33 @@ impl RabbitMqPublisher {
         Ok(())
     }
 }
+
+#[async_trait::async_trait]
+impl EventPublisher for RabbitMqPublisher {
+    async fn ensure_connected(&self) -> Result<(), CoreError> {
+        RabbitMqPublisher::ensure_connected(self).await
+    }
+
+    async fn declare_exchange(&self, exchange_name: &str) -> Result<(), CoreError> {
+        RabbitMqPublisher::declare_exchange(self, exchange_name).await
+    }
+
+    async fn publish(&self, event: OutgoingEvent) -> Result<(), CoreError> {
+        RabbitMqPublisher::publish(self, event).await
+    }
+
+    async fn publish_batch(&self, events: Vec<OutgoingEvent>) -> Vec<Result<(), CoreError>> {
+        RabbitMqPublisher::publish_batch(self, events).await
+    }
+}
+
+#[async_trait::async_trait]
+impl DependencyProbe for RabbitMqPublisher {
+    fn name(&self) -> &'static str {
+        "rabbitmq"
+    }
+
+    async fn ping(&self) -> IsHealthy {
+        IsHealthy::new(self.is_connected().await)
+    }
+}
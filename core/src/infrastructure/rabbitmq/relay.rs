@@ -1,66 +1,276 @@
 use futures::TryStreamExt;
 use mongodb::{
     Collection, Database,
-    bson::{Bson, Document, doc},
+    bson::{Binary, Bson, DateTime as BsonDateTime, Document, doc, spec::BinarySubtype},
     options::FindOptions,
 };
-use std::sync::Arc;
-use tokio::time::{Duration, interval};
+use std::{collections::HashSet, sync::Arc};
+use tokio::{
+    sync::watch,
+    time::{Duration, interval},
+};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use crate::{domain::common::CoreError, infrastructure::rabbitmq::publisher::RabbitMqPublisher};
+use crate::{
+    domain::{
+        common::CoreError,
+        outbox::ports::{EventPublisher, OutgoingEvent},
+    },
+    infrastructure::outbox::WireFormats,
+};
 
 /// Service that relays messages from the outbox to RabbitMQ
 pub struct OutboxRelayService {
     db: Database,
-    publisher: Arc<RabbitMqPublisher>,
+    publisher: Arc<dyn EventPublisher>,
     poll_interval: Duration,
+    /// Exchanges the relay may publish to; `None` allows any exchange
+    allowed_exchanges: Option<HashSet<String>>,
+    /// Format each route is published in
+    wire_formats: WireFormats,
+    /// Identifies this instance in the `claimed_by` field of the rows it claims
+    instance_id: String,
+    /// How long a claim holds before the reaper hands the row back
+    lease_duration: Duration,
+    /// Publish attempts after which a row is moved to the dead letters
+    max_retries: u32,
+    /// Delay before the first retry, doubled on every further failure
+    base_backoff: Duration,
+    /// Longest delay between two attempts
+    max_backoff: Duration,
 }
 
+/// Claimed row ready to be handed to the publisher
+struct PendingPublish {
+    id: Uuid,
+    id_bson: Bson,
+    row: Document,
+    exchange_name: String,
+    routing_key: String,
+    /// Already converted to the route's wire format
+    payload: Vec<u8>,
+    content_type: &'static str,
+}
+
+/// Time a relay may hold a claimed batch before other instances can take it over
+pub const DEFAULT_OUTBOX_LEASE: Duration = Duration::from_secs(30);
+
+/// Rows that exhausted their publish attempts, out of the relay's reach
+const DEAD_LETTER_COLLECTION: &str = "outbox_dead_letters";
+
+/// Maximum number of rows claimed in one pass
+const CLAIM_BATCH_SIZE: i64 = 100;
+
+/// Publish attempts made for a row before it is dead-lettered
+pub const DEFAULT_OUTBOX_MAX_RETRIES: u32 = 5;
+
+/// Delay before the first retry of a failed publish
+pub const DEFAULT_OUTBOX_BASE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Upper bound of the delay between two publish attempts
+pub const DEFAULT_OUTBOX_MAX_BACKOFF: Duration = Duration::from_secs(300);
+
 impl OutboxRelayService {
     /// Create a new outbox relay service
-    pub fn new(db: Database, publisher: Arc<RabbitMqPublisher>) -> Self {
+    pub fn new(db: Database, publisher: Arc<dyn EventPublisher>) -> Self {
         Self {
             db,
             publisher,
             poll_interval: Duration::from_secs(1),
+            allowed_exchanges: None,
+            wire_formats: WireFormats::default(),
+            instance_id: Uuid::new_v4().to_string(),
+            lease_duration: DEFAULT_OUTBOX_LEASE,
+            max_retries: DEFAULT_OUTBOX_MAX_RETRIES,
+            base_backoff: DEFAULT_OUTBOX_BASE_BACKOFF,
+            max_backoff: DEFAULT_OUTBOX_MAX_BACKOFF,
         }
     }
 
+    /// Configure how many publish attempts a row gets before it is dead-lettered
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries.max(1);
+        self
+    }
+
+    /// Configure the delay before the first retry; each further failure doubles it
+    pub fn with_base_backoff(mut self, base_backoff: Duration) -> Self {
+        self.base_backoff = base_backoff;
+        self
+    }
+
+    /// Cap the delay between two attempts
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Name this instance in the claims it takes, e.g. with the host name
+    pub fn with_instance_id(mut self, instance_id: impl Into<String>) -> Self {
+        self.instance_id = instance_id.into();
+        self
+    }
+
+    /// Configure how long claimed rows stay reserved for this instance
+    ///
+    /// Must comfortably exceed the time it takes to publish a batch, or a slow
+    /// batch is handed to another instance and published twice.
+    pub fn with_lease_duration(mut self, lease_duration: Duration) -> Self {
+        self.lease_duration = lease_duration;
+        self
+    }
+
+    /// Only publish to these exchanges; anything else is quarantined as `FAILED`
+    ///
+    /// An empty list keeps every exchange allowed.
+    pub fn with_allowed_exchanges<I>(mut self, exchanges: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let exchanges: HashSet<String> = exchanges.into_iter().collect();
+        self.allowed_exchanges = (!exchanges.is_empty()).then_some(exchanges);
+        self
+    }
+
+    /// Choose the format events are published in, per exchange or route
+    ///
+    /// Routes without a rule keep the stored protobuf bytes.
+    pub fn with_wire_formats(mut self, wire_formats: WireFormats) -> Self {
+        self.wire_formats = wire_formats;
+        self
+    }
+
+    fn is_exchange_allowed(&self, exchange_name: &str) -> bool {
+        self.allowed_exchanges
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(exchange_name))
+    }
+
     /// Start the relay service (long-running task)
-    pub async fn start(&self) {
+    ///
+    /// Runs until `shutdown` turns `true` or its sender is dropped. A batch that
+    /// is already being processed is always finished before returning.
+    pub async fn start(&self, mut shutdown: watch::Receiver<bool>) {
         info!("Starting outbox relay service");
         let mut ticker = interval(self.poll_interval);
 
         loop {
-            ticker.tick().await;
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.wait_for(|stop| *stop) => break,
+            }
 
+            if let Err(e) = self.reap_stale_claims().await {
+                error!("Error releasing stale outbox claims: {}", e);
+            }
             if let Err(e) = self.process_pending_messages().await {
                 error!("Error processing outbox messages: {}", e);
             }
         }
+
+        info!("Outbox relay service stopped");
     }
 
-    /// Process all pending messages in the outbox
-    async fn process_pending_messages(&self) -> Result<(), CoreError> {
+    /// Hand claims older than the lease back to `READY`
+    ///
+    /// A relay that crashed mid-batch leaves its rows `PROCESSING`; once the
+    /// lease expires any instance may claim them again. Returns the number of
+    /// rows released.
+    pub async fn reap_stale_claims(&self) -> Result<u64, CoreError> {
+        release_stale_claims(&self.db, self.lease_duration).await
+    }
+
+    /// Filter matching the row `id` only while this instance still holds its claim
+    ///
+    /// Every write after the claim goes through it, so once the lease expired
+    /// and another relay took the row over, the stale outcome matches nothing
+    /// instead of overwriting the new owner's.
+    fn still_claimed(&self, id: &Bson) -> Document {
+        doc! {
+            "_id": id.clone(),
+            "status": "PROCESSING",
+            "claimed_by": self.instance_id.as_str(),
+        }
+    }
+
+    /// Claim a batch of pending messages and publish it
+    ///
+    /// Rows move from `READY` to `PROCESSING` with this instance's id before
+    /// anything is published, so concurrent relays never publish the same row.
+    pub async fn process_pending_messages(&self) -> Result<(), CoreError> {
+        // Nothing is claimed while the broker is out of reach, so an outage
+        // leaves the rows READY instead of spending their publish attempts
+        if let Err(e) = self.publisher.ensure_connected().await {
+            warn!("Skipping outbox pass, the broker is unreachable: {}", e);
+            return Ok(());
+        }
+
         let collection: Collection<Document> = self.db.collection("outbox_messages");
 
-        // Find all READY messages
-        let filter = doc! { "status": "READY" };
-        let options = FindOptions::builder()
-            .sort(doc! { "created_at": 1 })
-            .limit(100)
+        // Highest priority first and FIFO within a priority. Events written
+        // before priorities existed have none and sort last.
+        let sort = doc! { "priority": -1, "created_at": 1 };
+
+        let candidates = FindOptions::builder()
+            .sort(sort.clone())
+            .limit(CLAIM_BATCH_SIZE)
+            .projection(doc! { "_id": 1 })
             .build();
+        // Rows waiting out a retry backoff are left alone until it expires
+        let now = BsonDateTime::now();
+        let ready = doc! {
+            "status": "READY",
+            "$or": [{ "next_retry_at": Bson::Null }, { "next_retry_at": { "$lte": now } }],
+        };
+        let ids: Vec<Bson> = collection
+            .find(ready)
+            .with_options(candidates)
+            .await
+            .map_err(|e| CoreError::DatabaseError {
+                msg: format!("Failed to query outbox: {}", e),
+            })?
+            .try_filter_map(|doc| async move { Ok(doc.get("_id").cloned()) })
+            .try_collect()
+            .await
+            .map_err(|e| CoreError::DatabaseError {
+                msg: format!("Failed to read outbox document: {}", e),
+            })?;
+        if ids.is_empty() {
+            return Ok(());
+        }
 
+        // Only rows still READY are claimed, so a row another instance took
+        // in the meantime is skipped
+        let claimed_at = BsonDateTime::now();
+        collection
+            .update_many(
+                doc! { "_id": { "$in": ids }, "status": "READY" },
+                doc! { "$set": {
+                    "status": "PROCESSING",
+                    "claimed_by": self.instance_id.as_str(),
+                    "claimed_at": claimed_at,
+                } },
+            )
+            .await
+            .map_err(|e| CoreError::DatabaseError {
+                msg: format!("Failed to claim outbox messages: {}", e),
+            })?;
+
+        let claimed = FindOptions::builder().sort(sort).build();
         let mut cursor = collection
-            .find(filter)
-            .with_options(options)
+            .find(doc! {
+                "status": "PROCESSING",
+                "claimed_by": self.instance_id.as_str(),
+                "claimed_at": claimed_at,
+            })
+            .with_options(claimed)
             .await
             .map_err(|e| CoreError::DatabaseError {
                 msg: format!("Failed to query outbox: {}", e),
             })?;
 
+        let mut batch = Vec::new();
         while let Some(doc) = cursor
             .try_next()
             .await
@@ -68,27 +278,34 @@ impl OutboxRelayService {
                 msg: format!("Failed to read outbox document: {}", e),
             })?
         {
-            if let Err(e) = self.process_single_message(&collection, doc).await {
-                error!("Failed to process outbox message: {}", e);
+            match self.prepare_message(&collection, doc).await {
+                Ok(Some(pending)) => batch.push(pending),
+                Ok(None) => {}
+                Err(e) => error!("Failed to process outbox message: {}", e),
             }
         }
 
+        self.publish_claimed(&collection, batch).await;
+
         Ok(())
     }
 
-    /// Process a single outbox message
-    async fn process_single_message(
+    /// Read a claimed row into something publishable
+    ///
+    /// Rows that can never be published are quarantined as `FAILED` and
+    /// yield `None` or an error.
+    async fn prepare_message(
         &self,
         collection: &Collection<Document>,
         doc: Document,
-    ) -> Result<(), CoreError> {
+    ) -> Result<Option<PendingPublish>, CoreError> {
         // Extract _id which is stored as UUID (Binary in MongoDB)
-        let id_bson = doc.get("_id").ok_or_else(|| CoreError::DatabaseError {
+        let id_bson = doc.get("_id").cloned().ok_or_else(|| CoreError::DatabaseError {
             msg: "Missing _id in outbox document".to_string(),
         })?;
 
         // Convert BSON to UUID - handle both Binary and String representations
-        let id = match id_bson {
+        let id = match &id_bson {
             Bson::Binary(bin) => {
                 Uuid::from_slice(&bin.bytes).map_err(|e| CoreError::DatabaseError {
                     msg: format!("Invalid UUID in _id: {}", e),
@@ -109,21 +326,23 @@ impl OutboxRelayService {
             .and_then(|v| v.as_str())
             .ok_or_else(|| CoreError::DatabaseError {
                 msg: format!("Missing exchange_name in outbox document {}", id),
-            })?;
+            })?
+            .to_string();
 
         let routing_key = doc
             .get("routing_key")
             .and_then(|v| v.as_str())
             .ok_or_else(|| CoreError::DatabaseError {
                 msg: format!("Missing routing_key in outbox document {}", id),
-            })?;
+            })?
+            .to_string();
 
         let payload = doc.get("payload").ok_or_else(|| CoreError::DatabaseError {
             msg: format!("Missing payload in outbox document {}", id),
         })?;
 
         // Extract protobuf bytes from BSON binary
-        let payload_bytes = match payload {
+        let payload = match payload {
             Bson::Binary(bin) => bin.bytes.clone(),
             _ => {
                 // Prevent endless retries for legacy/non-binary payloads
@@ -131,63 +350,342 @@ impl OutboxRelayService {
                     "$set": {
                         "status": "FAILED",
                         "failed_at": mongodb::bson::DateTime::now(),
+                        "failure_reason": "payload is not binary",
                     }
                 };
-                let _ = collection.update_one(doc! { "_id": id_bson }, update).await;
+                let _ = collection.update_one(self.still_claimed(&id_bson), update).await;
                 return Err(CoreError::SerializationError {
                     msg: format!("Expected binary payload in outbox document {}", id),
                 });
             }
         };
 
-        // Ensure exchange exists
-        if let Err(e) = self.publisher.declare_exchange(exchange_name).await {
-            warn!("Failed to declare exchange {}: {}", exchange_name, e);
-        }
-
-        // Publish to RabbitMQ
-        match self
-            .publisher
-            .publish(exchange_name, routing_key, payload_bytes)
-            .await
-        {
-            Ok(_) => {
-                // Mark as SENT - use the original _id value for the query
-                let update = doc! {
-                    "$set": {
-                        "status": "SENT",
-                    }
-                };
+        // Quarantine events aimed at exchanges operators did not allow
+        if !self.is_exchange_allowed(&exchange_name) {
+            let reason = format!("exchange '{}' is not in the relay allowlist", exchange_name);
+            let update = doc! {
+                "$set": {
+                    "status": "FAILED",
+                    "failed_at": mongodb::bson::DateTime::now(),
+                    "failure_reason": &reason,
+                }
+            };
+            let result = collection
+                .update_one(self.still_claimed(&id_bson), update)
+                .await
+                .map_err(|e| CoreError::DatabaseError {
+                    msg: format!("Failed to quarantine outbox message: {}", e),
+                })?;
+            if result.matched_count == 0 {
+                warn!("Lost the claim on outbox message {} before quarantining it", id);
+                return Ok(None);
+            }
 
-                collection
-                    .update_one(doc! { "_id": id_bson }, update)
-                    .await
-                    .map_err(|e| CoreError::DatabaseError {
-                        msg: format!("Failed to update outbox status: {}", e),
-                    })?;
+            warn!("Quarantined outbox message {}: {}", id, reason);
+            return Ok(None);
+        }
 
-                info!("Successfully published outbox message {}", id);
-            }
+        // A payload that can't be converted never will be, so don't retry it
+        let wire_format = self.wire_formats.for_route(&exchange_name, &routing_key);
+        let payload = match wire_format.encode(&routing_key, payload) {
+            Ok(payload) => payload,
             Err(e) => {
-                // Mark as FAILED - use the original _id value for the query
+                let reason = format!("payload cannot be published as {:?}: {}", wire_format, e);
                 let update = doc! {
                     "$set": {
                         "status": "FAILED",
                         "failed_at": mongodb::bson::DateTime::now(),
+                        "failure_reason": &reason,
                     }
                 };
-
-                collection
-                    .update_one(doc! { "_id": id_bson }, update)
+                let result = collection
+                    .update_one(self.still_claimed(&id_bson), update)
                     .await
                     .map_err(|e| CoreError::DatabaseError {
-                        msg: format!("Failed to update outbox status to FAILED: {}", e),
+                        msg: format!("Failed to quarantine outbox message: {}", e),
                     })?;
+                if result.matched_count == 0 {
+                    warn!("Lost the claim on outbox message {} before quarantining it", id);
+                    return Ok(None);
+                }
 
-                error!("Failed to publish outbox message {}: {}", id, e);
+                warn!("Quarantined outbox message {}: {}", id, reason);
+                return Ok(None);
             }
+        };
+
+        Ok(Some(PendingPublish {
+            id,
+            id_bson,
+            row: doc,
+            exchange_name,
+            routing_key,
+            payload,
+            content_type: wire_format.content_type(),
+        }))
+    }
+
+    /// Publish a claimed batch at once and record the outcome of every row
+    ///
+    /// Each row is marked on its own confirm, so one failure in the batch
+    /// never affects the rows that were published.
+    async fn publish_claimed(&self, collection: &Collection<Document>, batch: Vec<PendingPublish>) {
+        if batch.is_empty() {
+            return;
         }
 
+        // Ensure exchanges exist
+        let exchanges: HashSet<&str> = batch.iter().map(|p| p.exchange_name.as_str()).collect();
+        for exchange_name in exchanges {
+            if let Err(e) = self.publisher.declare_exchange(exchange_name).await {
+                warn!("Failed to declare exchange {}: {}", exchange_name, e);
+            }
+        }
+
+        let events = batch
+            .iter()
+            .map(|p| OutgoingEvent {
+                exchange_name: p.exchange_name.clone(),
+                routing_key: p.routing_key.clone(),
+                payload: p.payload.clone(),
+                content_type: p.content_type.to_string(),
+            })
+            .collect();
+        let results = self.publisher.publish_batch(events).await;
+
+        for (pending, result) in batch.iter().zip(results) {
+            let recorded = match result {
+                Ok(()) => self.mark_sent(collection, pending).await,
+                Err(e) => self.record_failure(collection, pending, e).await,
+            };
+            if let Err(e) = recorded {
+                error!("Failed to process outbox message {}: {}", pending.id, e);
+            }
+        }
+    }
+
+    async fn mark_sent(
+        &self,
+        collection: &Collection<Document>,
+        pending: &PendingPublish,
+    ) -> Result<(), CoreError> {
+        let result = collection
+            .update_one(self.still_claimed(&pending.id_bson), doc! { "$set": { "status": "SENT" } })
+            .await
+            .map_err(|e| CoreError::DatabaseError {
+                msg: format!("Failed to update outbox status: {}", e),
+            })?;
+        if result.matched_count == 0 {
+            warn!(
+                "Published outbox message {} after losing its claim, the new owner may publish it again",
+                pending.id
+            );
+            return Ok(());
+        }
+
+        info!("Successfully published outbox message {}", pending.id);
+        Ok(())
+    }
+
+    /// Hand the row back for a later attempt, or dead-letter it once the cap is reached
+    async fn record_failure(
+        &self,
+        collection: &Collection<Document>,
+        pending: &PendingPublish,
+        error: CoreError,
+    ) -> Result<(), CoreError> {
+        let attempts = pending
+            .row
+            .get_i32("retry_count")
+            .unwrap_or(0)
+            .saturating_add(1);
+
+        if attempts as u32 >= self.max_retries {
+            if !self
+                .move_to_dead_letter(collection, &pending.row, attempts, &error.to_string())
+                .await?
+            {
+                warn!("Lost the claim on outbox message {} before dead-lettering it", pending.id);
+                return Ok(());
+            }
+        } else {
+            let backoff = retry_backoff(self.base_backoff, self.max_backoff, attempts as u32);
+            let backoff_ms = i64::try_from(backoff.as_millis()).unwrap_or(i64::MAX);
+            let next_retry_at = BsonDateTime::from_millis(
+                BsonDateTime::now().timestamp_millis().saturating_add(backoff_ms),
+            );
+            let update = doc! {
+                "$set": {
+                    "status": "READY",
+                    "retry_count": attempts,
+                    "next_retry_at": next_retry_at,
+                    "failure_reason": error.to_string(),
+                },
+                "$unset": { "claimed_by": "", "claimed_at": "" },
+            };
+
+            let result = collection
+                .update_one(self.still_claimed(&pending.id_bson), update)
+                .await
+                .map_err(|e| CoreError::DatabaseError {
+                    msg: format!("Failed to record outbox publish failure: {}", e),
+                })?;
+            if result.matched_count == 0 {
+                warn!("Lost the claim on outbox message {} before recording its failure", pending.id);
+                return Ok(());
+            }
+        }
+
+        error!(
+            "Failed to publish outbox message {} (attempt {}/{}): {}",
+            pending.id, attempts, self.max_retries, error
+        );
         Ok(())
     }
+
+    /// Move a row that exhausted its retries to the dead-letter collection
+    ///
+    /// The dead letter keeps the original id, payload and routing so it can be
+    /// replayed as is. It is written before the row is removed, so a crash in
+    /// between leaves a duplicate rather than losing the event. Returns `false`
+    /// without writing anything when this instance no longer holds the claim.
+    async fn move_to_dead_letter(
+        &self,
+        collection: &Collection<Document>,
+        row: &Document,
+        attempts: i32,
+        error: &str,
+    ) -> Result<bool, CoreError> {
+        let id = row.get("_id").cloned().unwrap_or(Bson::Null);
+
+        // Renewing the claim first leaves a whole lease to move the row in
+        let renewed = collection
+            .update_one(
+                self.still_claimed(&id),
+                doc! { "$set": { "claimed_at": BsonDateTime::now() } },
+            )
+            .await
+            .map_err(|e| CoreError::DatabaseError {
+                msg: format!("Failed to renew outbox claim: {}", e),
+            })?;
+        if renewed.matched_count == 0 {
+            return Ok(false);
+        }
+
+        let mut dead_letter = row.clone();
+        for field in [
+            "status",
+            "retry_count",
+            "next_retry_at",
+            "claimed_by",
+            "claimed_at",
+            "failure_reason",
+        ] {
+            dead_letter.remove(field);
+        }
+        dead_letter.insert("error", error);
+        dead_letter.insert("attempts", attempts);
+        dead_letter.insert("dead_lettered_at", BsonDateTime::now());
+
+        self.db
+            .collection::<Document>(DEAD_LETTER_COLLECTION)
+            .replace_one(doc! { "_id": id.clone() }, dead_letter)
+            .upsert(true)
+            .await
+            .map_err(|e| CoreError::DatabaseError {
+                msg: format!("Failed to write outbox dead letter: {}", e),
+            })?;
+
+        collection
+            .delete_one(self.still_claimed(&id))
+            .await
+            .map_err(|e| CoreError::DatabaseError {
+                msg: format!("Failed to remove dead-lettered outbox message: {}", e),
+            })?;
+
+        Ok(true)
+    }
+
+    /// Hand a dead letter back to the relay with a fresh retry budget
+    ///
+    /// Meant for operators once the cause of the failures is fixed. Returns
+    /// `false` when there is no dead letter with this id.
+    pub async fn replay_dead_letter(&self, id: Uuid) -> Result<bool, CoreError> {
+        let dead_letters: Collection<Document> = self.db.collection(DEAD_LETTER_COLLECTION);
+        let id = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: id.as_bytes().to_vec(),
+        });
+
+        let Some(mut row) = dead_letters
+            .find_one(doc! { "_id": id.clone() })
+            .await
+            .map_err(|e| CoreError::DatabaseError {
+                msg: format!("Failed to query outbox dead letters: {}", e),
+            })?
+        else {
+            return Ok(false);
+        };
+
+        for field in ["error", "attempts", "dead_lettered_at"] {
+            row.remove(field);
+        }
+        row.insert("status", "READY");
+
+        self.db
+            .collection::<Document>("outbox_messages")
+            .replace_one(doc! { "_id": id.clone() }, row)
+            .upsert(true)
+            .await
+            .map_err(|e| CoreError::DatabaseError {
+                msg: format!("Failed to requeue outbox dead letter: {}", e),
+            })?;
+
+        dead_letters
+            .delete_one(doc! { "_id": id })
+            .await
+            .map_err(|e| CoreError::DatabaseError {
+                msg: format!("Failed to remove replayed outbox dead letter: {}", e),
+            })?;
+
+        Ok(true)
+    }
+}
+
+/// Delay before the attempt following the `attempt`-th failure
+///
+/// Doubles from `base` with every failure and never exceeds `max`.
+pub fn retry_backoff(base: Duration, max: Duration, attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    base.saturating_mul(factor).min(max)
+}
+
+/// Reset `PROCESSING` outbox rows claimed longer than `lease` ago back to `READY`
+pub async fn release_stale_claims(db: &Database, lease: Duration) -> Result<u64, CoreError> {
+    let collection: Collection<Document> = db.collection("outbox_messages");
+
+    let lease_ms = i64::try_from(lease.as_millis()).unwrap_or(i64::MAX);
+    let expired_before =
+        BsonDateTime::from_millis(BsonDateTime::now().timestamp_millis().saturating_sub(lease_ms));
+    let result = collection
+        .update_many(
+            doc! { "status": "PROCESSING", "claimed_at": { "$lt": expired_before } },
+            doc! {
+                "$set": { "status": "READY" },
+                "$unset": { "claimed_by": "", "claimed_at": "" },
+            },
+        )
+        .await
+        .map_err(|e| CoreError::DatabaseError {
+            msg: format!("Failed to release stale outbox claims: {}", e),
+        })?;
+
+    if result.modified_count > 0 {
+        warn!(
+            "Released {} outbox messages whose claim expired",
+            result.modified_count
+        );
+    }
+
+    Ok(result.modified_count)
 }
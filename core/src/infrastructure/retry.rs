@@ -0,0 +1,86 @@
+//! Retry helper for transient MongoDB errors
+//!
+//! Operations such as a primary stepdown surface as errors carrying the
+//! `RetryableWriteError` or `TransientTransactionError` labels. Those are safe
+//! to replay, so repositories wrap their calls with [`with_retry`] instead of
+//! failing the request outright.
+//!
+//! The failed attempt may still have been applied, so a replayed write has to
+//! accept finding its own earlier effect: an insert hitting its own id or a
+//! delete finding nothing left is a success, not an error.
+
+use std::{fmt::Display, future::Future, time::Duration};
+
+use mongodb::error::{Error as MongoError, RETRYABLE_WRITE_ERROR, TRANSIENT_TRANSACTION_ERROR};
+use tracing::warn;
+
+/// How many times a transient error is retried and how long to wait in between
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, base_backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            base_backoff,
+        }
+    }
+
+    /// Exponential backoff: `base_backoff * 2^attempt`
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.base_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_backoff: Duration::from_millis(50),
+        }
+    }
+}
+
+/// Errors that can tell whether replaying the operation may succeed
+pub trait RetryableError {
+    fn is_retryable(&self) -> bool;
+}
+
+impl RetryableError for MongoError {
+    fn is_retryable(&self) -> bool {
+        self.contains_label(RETRYABLE_WRITE_ERROR) || self.contains_label(TRANSIENT_TRANSACTION_ERROR)
+    }
+}
+
+/// Run `operation`, retrying it with backoff while it fails with a retryable error
+///
+/// Non-retryable errors and the last error once `max_retries` is reached are
+/// returned unchanged.
+pub async fn with_retry<T, E, F, Fut>(policy: &RetryPolicy, mut operation: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: RetryableError + Display,
+{
+    let mut attempt = 0;
+
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) if e.is_retryable() && attempt < policy.max_retries => {
+                let delay = policy.backoff(attempt);
+                attempt += 1;
+                warn!(
+                    "Transient MongoDB error (attempt {}/{}), retrying in {:?}: {}",
+                    attempt, policy.max_retries, delay, e
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Soft-delete predicate shared by MongoDB read paths
+//!
+//! Soft-deleted documents keep their data but carry a non-null `deleted_at`.
+//! Every read query goes through [`exclude_deleted`] so the predicate is
+//! applied consistently instead of being repeated (and forgotten) per query.
+
+use mongodb::bson::{Bson, Document, doc};
+
+/// Field set when a document is soft-deleted
+pub const DELETED_AT_FIELD: &str = "deleted_at";
+
+/// Merge the "not deleted" predicate into `filter`
+///
+/// `deleted_at: null` matches both documents where the field is null and
+/// documents written before the field existed. If the base filter already
+/// constrains `deleted_at`, both predicates are combined with `$and`.
+pub fn exclude_deleted(filter: Document) -> Document {
+    if filter.contains_key(DELETED_AT_FIELD) {
+        doc! { "$and": [filter, { DELETED_AT_FIELD: Bson::Null }] }
+    } else {
+        let mut filter = filter;
+        filter.insert(DELETED_AT_FIELD, Bson::Null);
+        filter
+    }
+}
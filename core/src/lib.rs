@@ -3,7 +3,7 @@ pub mod domain;
 pub mod infrastructure;
 
 // Re-export commonly used types for convenience
-pub use application::{MessagesService, create_repositories};
+pub use application::{MessagesService, create_named_repositories, create_repositories};
 pub use domain::common::services::Service;
 pub use infrastructure::health::repositories::mongo::MongoHealthRepository;
 pub use infrastructure::message::repositories::mongo::MongoMessageRepository;
@@ -0,0 +1,337 @@
+use std::sync::Arc;
+
+use messages_core::domain::attachment::port::MockAttachmentRepository;
+use messages_core::domain::authorization::entities::{Actor, Permission, Resource};
+use messages_core::domain::authorization::ports::MockAuthorizer;
+use messages_core::domain::common::services::Service;
+use messages_core::domain::common::{CoreError, GetPaginated};
+use messages_core::domain::health::port::MockHealthRepository;
+use messages_core::domain::message::entities::{
+    AttachmentId, ChannelId, InsertMessageInput, MessageId, UpdateMessageInput,
+};
+use messages_core::domain::message::ports::{MessageService, MockMessageRepository};
+use messages_core::domain::outbox::ports::MockOutboxEventRepository;
+use uuid::Uuid;
+
+type TestService =
+    Service<MockMessageRepository, MockHealthRepository, MockAttachmentRepository, MockOutboxEventRepository>;
+
+fn service(authorizer: &MockAuthorizer) -> TestService {
+    Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(authorizer.clone()))
+}
+
+fn input(actor: Actor, channel: ChannelId) -> InsertMessageInput {
+    InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: channel,
+        author_id: actor.author_id(),
+        content: "hello".into(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    }
+}
+
+/// A channel member who can read and post but not moderate
+fn member(authorizer: &MockAuthorizer, channel: ChannelId) -> Actor {
+    let actor = Actor::from(Uuid::new_v4());
+    for permission in [Permission::ViewChannels, Permission::SendMessages] {
+        authorizer.grant(actor, permission, Resource::Channel(channel.0));
+    }
+    actor
+}
+
+#[tokio::test]
+async fn create_requires_send_permission() {
+    let authorizer = MockAuthorizer::new();
+    let service = service(&authorizer);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let outsider = Actor::from(Uuid::new_v4());
+
+    let message = input(outsider, channel);
+    let id = message.id;
+    let res = service.create_message(message).await;
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+
+    let reader = member(&authorizer, channel);
+    assert!(matches!(
+        service.get_message(&reader, &id).await,
+        Err(CoreError::MessageNotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn a_service_without_an_authorizer_refuses_everything() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    );
+    let actor = Actor::from(Uuid::new_v4());
+
+    let res = service.create_message(input(actor, ChannelId::from(Uuid::new_v4()))).await;
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+}
+
+#[tokio::test]
+async fn attachments_require_attach_permission() {
+    let authorizer = MockAuthorizer::new();
+    let service = service(&authorizer);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = member(&authorizer, channel);
+
+    let mut message = input(author, channel);
+    message.attachments = vec![AttachmentId::from(Uuid::new_v4())];
+    let res = service.create_message(message.clone()).await;
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+
+    authorizer.grant(author, Permission::AttachFiles, Resource::Channel(channel.0));
+    service
+        .create_message(message)
+        .await
+        .expect("create should work once attaching is allowed");
+}
+
+#[tokio::test]
+async fn reads_require_view_permission() {
+    let authorizer = MockAuthorizer::new();
+    let service = service(&authorizer);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = member(&authorizer, channel);
+    let outsider = Actor::from(Uuid::new_v4());
+
+    let created = service
+        .create_message(input(author, channel))
+        .await
+        .expect("create should work");
+    let id = created.message.id;
+    let pagination = GetPaginated::default();
+
+    assert!(service.get_message(&author, &id).await.is_ok());
+    assert!(matches!(
+        service.get_message(&outsider, &id).await,
+        Err(CoreError::Forbidden)
+    ));
+    assert!(matches!(
+        service.list_messages(&outsider, &channel, &pagination).await,
+        Err(CoreError::Forbidden)
+    ));
+    assert!(matches!(
+        service
+            .search_messages(&outsider, &channel, &"hello".into(), &pagination)
+            .await,
+        Err(CoreError::Forbidden)
+    ));
+    assert!(matches!(
+        service.list_pinned_messages(&outsider, &channel).await,
+        Err(CoreError::Forbidden)
+    ));
+}
+
+#[tokio::test]
+async fn only_the_author_can_edit_or_delete() {
+    let authorizer = MockAuthorizer::new();
+    let service = service(&authorizer);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = member(&authorizer, channel);
+    let other = member(&authorizer, channel);
+
+    let id = service
+        .create_message(input(author, channel))
+        .await
+        .expect("create should work")
+        .message
+        .id;
+
+    let res = service
+        .update_message(
+            &other,
+            UpdateMessageInput {
+                id,
+                content: Some("hijacked".into()),
+            },
+        )
+        .await;
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+    assert!(matches!(
+        service.delete_message(&other, &id).await,
+        Err(CoreError::Forbidden)
+    ));
+
+    let kept = service.get_message(&author, &id).await.expect("message is kept");
+    assert_eq!(kept.content, "hello");
+}
+
+#[tokio::test]
+async fn moderation_requires_manage_permission() {
+    let authorizer = MockAuthorizer::new();
+    let service = service(&authorizer);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = member(&authorizer, channel);
+
+    let id = service
+        .create_message(input(author, channel))
+        .await
+        .expect("create should work")
+        .message
+        .id;
+
+    assert!(matches!(
+        service.pin_message(&author, &id, None).await,
+        Err(CoreError::Forbidden)
+    ));
+    assert!(matches!(
+        service.pin_messages(&author, &channel, &[id]).await,
+        Err(CoreError::Forbidden)
+    ));
+    assert!(matches!(
+        service.bulk_delete(&author, &channel, &[id]).await,
+        Err(CoreError::Forbidden)
+    ));
+    assert!(!service.get_message(&author, &id).await.unwrap().is_pinned);
+
+    let moderator = Actor::from(Uuid::new_v4());
+    authorizer.grant(moderator, Permission::ManageMessages, Resource::Channel(channel.0));
+    let pinned = service
+        .pin_message(&moderator, &id, None)
+        .await
+        .expect("moderator can pin");
+    assert_eq!(pinned.pinned_by, Some(moderator.author_id()));
+}
+
+#[tokio::test]
+async fn sticky_requires_manage_permission() {
+    let authorizer = MockAuthorizer::new();
+    let service = service(&authorizer);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = member(&authorizer, channel);
+
+    let id = service
+        .create_message(input(author, channel))
+        .await
+        .expect("create should work")
+        .message
+        .id;
+
+    assert!(matches!(
+        service.set_sticky(&author, &channel, &id).await,
+        Err(CoreError::Forbidden)
+    ));
+    assert!(matches!(
+        service.clear_sticky(&author, &channel).await,
+        Err(CoreError::Forbidden)
+    ));
+
+    let moderator = Actor::from(Uuid::new_v4());
+    authorizer.grant(moderator, Permission::ManageMessages, Resource::Channel(channel.0));
+    service
+        .set_sticky(&moderator, &channel, &id)
+        .await
+        .expect("moderator can set the sticky");
+    let metadata = service.get_channel_metadata(&author, &channel).await.unwrap();
+    assert_eq!(metadata.sticky_message_id, Some(id));
+}
+
+#[tokio::test]
+async fn reacting_requires_send_permission() {
+    let authorizer = MockAuthorizer::new();
+    let service = service(&authorizer);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = member(&authorizer, channel);
+    let outsider = Actor::from(Uuid::new_v4());
+
+    let id = service
+        .create_message(input(author, channel))
+        .await
+        .expect("create should work")
+        .message
+        .id;
+
+    assert!(matches!(
+        service.toggle_reaction(&outsider, &id, "🎉").await,
+        Err(CoreError::Forbidden)
+    ));
+    assert!(service.toggle_reaction(&author, &id, "🎉").await.unwrap().reacted);
+}
+
+#[tokio::test]
+async fn recent_channels_hide_channels_no_longer_visible() {
+    let authorizer = MockAuthorizer::new();
+    let service = service(&authorizer);
+    let (visible, revoked) = (ChannelId::from(Uuid::new_v4()), ChannelId::from(Uuid::new_v4()));
+    let author = member(&authorizer, visible);
+    authorizer.grant(author, Permission::SendMessages, Resource::Channel(revoked.0));
+
+    for channel in [visible, revoked] {
+        service
+            .create_message(input(author, channel))
+            .await
+            .expect("create should work");
+    }
+
+    let recent = service
+        .list_recent_channels(&author.author_id(), 10)
+        .await
+        .expect("recent channels should work");
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].channel_id, visible);
+}
+
+#[tokio::test]
+async fn latest_messages_leave_out_channels_not_visible() {
+    let authorizer = MockAuthorizer::new();
+    let service = service(&authorizer);
+    let (visible, hidden) = (ChannelId::from(Uuid::new_v4()), ChannelId::from(Uuid::new_v4()));
+    let viewer = member(&authorizer, visible);
+    let other = member(&authorizer, hidden);
+
+    for (author, channel) in [(viewer, visible), (other, hidden)] {
+        service
+            .create_message(input(author, channel))
+            .await
+            .expect("create should work");
+    }
+
+    let latest = service
+        .latest_messages_for_channels(&viewer, &[visible, hidden])
+        .await
+        .expect("latest messages should work");
+    assert_eq!(latest.len(), 1);
+    assert!(latest.contains_key(&visible));
+}
+
+#[tokio::test]
+async fn replies_require_viewing_the_parent_channel() {
+    let authorizer = MockAuthorizer::new();
+    let service = service(&authorizer);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = member(&authorizer, channel);
+    let parent = service
+        .create_message(input(author, channel))
+        .await
+        .expect("member can post")
+        .message
+        .id;
+
+    let outsider = Actor::from(Uuid::new_v4());
+    let res = service
+        .list_replies(&outsider, &parent, &GetPaginated::default())
+        .await;
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+    let res = service.reply_count(&outsider, &parent).await;
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+
+    let (replies, total) = service
+        .list_replies(&author, &parent, &GetPaginated::default())
+        .await
+        .expect("member can read the thread");
+    assert!(replies.is_empty());
+    assert_eq!(total, 0);
+}
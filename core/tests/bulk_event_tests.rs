@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use messages_core::domain::attachment::port::MockAttachmentRepository;
+use messages_core::domain::authorization::entities::Actor;
+use messages_core::domain::authorization::ports::AllowAllAuthorizer;
+use messages_core::domain::common::services::Service;
+use messages_core::domain::health::port::MockHealthRepository;
+use messages_core::domain::message::entities::{
+    AuthorId, ChannelId, InsertMessageInput, MessageId,
+};
+use messages_core::domain::message::events::{BulkMessagesChangedEvent, MessagePinEvent};
+use messages_core::domain::message::ports::{MessageService, MockMessageRepository};
+use messages_core::domain::outbox::ports::MockOutboxEventRepository;
+use prost::Message as _;
+use uuid::Uuid;
+
+fn service(
+    outbox: MockOutboxEventRepository,
+) -> Service<MockMessageRepository, MockHealthRepository, MockAttachmentRepository, MockOutboxEventRepository> {
+    Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox,
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+}
+
+fn moderator() -> Actor {
+    Actor::from(Uuid::new_v4())
+}
+
+async fn seed_channel(service: &impl MessageService, channel: ChannelId, count: usize) -> Vec<MessageId> {
+    let mut ids = Vec::with_capacity(count);
+    for i in 0..count {
+        let id = MessageId::from(Uuid::new_v4());
+        service
+            .create_message(InsertMessageInput {
+                id,
+                channel_id: channel,
+                author_id: AuthorId::from(Uuid::new_v4()),
+                content: format!("message {i}"),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("create should work");
+        ids.push(id);
+    }
+    ids
+}
+
+#[tokio::test]
+async fn bulk_pin_announces_each_pin_like_a_single_pin() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = service(outbox.clone());
+    let channel = ChannelId::from(Uuid::new_v4());
+    let ids = seed_channel(&service, channel, 3).await;
+    outbox.clear();
+
+    let moderator = moderator();
+    service
+        .pin_messages(&moderator, &channel, &ids)
+        .await
+        .expect("pin should work");
+
+    assert_eq!(outbox.routing_keys(), vec!["message.pinned"; 3]);
+    for (event, id) in outbox.events().iter().zip(&ids) {
+        let pin = MessagePinEvent::decode(event.payload.as_slice()).unwrap();
+        assert_eq!(pin.message_id, id.to_string());
+        assert_eq!(pin.actor_id, moderator.author_id().to_string());
+    }
+    let pinned = service.get_message(&moderator, &ids[0]).await.unwrap();
+    assert_eq!(pinned.pinned_by, Some(moderator.author_id()));
+}
+
+#[tokio::test]
+async fn bulk_unpin_announces_each_unpin() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = service(outbox.clone());
+    let channel = ChannelId::from(Uuid::new_v4());
+    let ids = seed_channel(&service, channel, 2).await;
+    service
+        .pin_messages(&moderator(), &channel, &ids[..1])
+        .await
+        .expect("pin should work");
+    outbox.clear();
+
+    service
+        .unpin_messages(&moderator(), &channel, &ids)
+        .await
+        .expect("unpin should work");
+
+    assert_eq!(outbox.routing_keys(), vec!["message.unpinned"]);
+}
+
+#[tokio::test]
+async fn bulk_unpin_without_changes_emits_nothing() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = service(outbox.clone());
+    let channel = ChannelId::from(Uuid::new_v4());
+    let ids = seed_channel(&service, channel, 2).await;
+    outbox.clear();
+
+    // none of the messages are pinned, so nothing changes
+    service
+        .unpin_messages(&moderator(), &channel, &ids)
+        .await
+        .expect("unpin should work");
+
+    assert!(outbox.routing_keys().is_empty());
+}
+
+#[tokio::test]
+async fn bulk_delete_emits_single_summary_event() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = service(outbox.clone());
+    let channel = ChannelId::from(Uuid::new_v4());
+    let ids = seed_channel(&service, channel, 3).await;
+    outbox.clear();
+
+    service
+        .bulk_delete(&moderator(), &channel, &ids)
+        .await
+        .expect("bulk delete should work");
+
+    let events = outbox.events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].routing_key, "messages.bulk_changed");
+
+    let summary = BulkMessagesChangedEvent::decode(events[0].payload.as_slice()).unwrap();
+    assert_eq!(summary.operation, "delete");
+    assert_eq!(summary.channel_id, channel.to_string());
+    let expected: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    assert_eq!(summary.message_ids, expected);
+}
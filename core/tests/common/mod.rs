@@ -0,0 +1,146 @@
+#![allow(dead_code)]
+
+//! Shared MongoDB bootstrap for integration tests.
+//!
+//! Uses `MONGO_TEST_URI` when set, otherwise starts a throwaway `mongo:6.0`
+//! container through the docker CLI. When neither is available the caller is
+//! expected to skip the test.
+
+use messages_core::infrastructure::outbox::{MessageRoutingInfo, OutboxEventRecord};
+use messages_core::infrastructure::write_outbox_event;
+use mongodb::{Client, Database, bson::doc, options::ClientOptions};
+use uuid::Uuid;
+
+pub struct TestMongo {
+    pub db: Database,
+    container: Option<String>,
+}
+
+impl TestMongo {
+    /// Connect to a fresh, uniquely named database, or `None` if MongoDB is unavailable
+    pub async fn start() -> Option<Self> {
+        let env_uri = std::env::var("MONGO_TEST_URI")
+            .ok()
+            .filter(|u| !u.is_empty());
+
+        let (uri, container) = match env_uri {
+            Some(uri) => (uri, None),
+            None => match try_start_docker_mongo().await {
+                Ok((uri, cid)) => (uri, Some(cid)),
+                Err(e) => {
+                    eprintln!("Skipping Mongo integration test: {}", e);
+                    return None;
+                }
+            },
+        };
+
+        let mut opts = ClientOptions::parse(&uri).await.expect("parse options");
+        opts.app_name = Some("messages_core_integration_test".to_string());
+        let client = Client::with_options(opts).expect("create client");
+        let db_name = format!("message_test_{}", Uuid::new_v4().simple());
+        let db = client.database(&db_name);
+
+        // Wait for mongo to be ready (it may take a few seconds after container start)
+        for _ in 0..40 {
+            if db.run_command(doc! { "ping": 1 }).await.is_ok() {
+                return Some(Self { db, container });
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        }
+
+        if let Some(cid) = container {
+            let _ = stop_docker_container(&cid);
+        }
+        panic!("MongoDB did not become ready in time");
+    }
+
+    /// Drop the test database and stop the container if one was started
+    pub async fn teardown(self) {
+        let _ = self.db.drop().await;
+        if let Some(cid) = self.container {
+            let _ = stop_docker_container(&cid);
+        }
+    }
+}
+
+/// Outbox event for `exchange` and `routing_key` with a placeholder payload
+pub fn outbox_event(exchange: &str, routing_key: &str) -> OutboxEventRecord<MessageRoutingInfo> {
+    OutboxEventRecord::new(MessageRoutingInfo::new(exchange, routing_key), vec![1, 2, 3])
+}
+
+/// Store `record` in the outbox under its own exchange and routing key, for the relay to pick up
+pub async fn write_event(db: &Database, record: OutboxEventRecord<MessageRoutingInfo>) {
+    let MessageRoutingInfo { exchange, routing_key } = &record.router;
+    write_outbox_event(db, exchange, routing_key, &record)
+        .await
+        .expect("outbox write should succeed");
+}
+
+pub fn stop_docker_container(container_id: &str) -> Result<(), String> {
+    use std::process::Command;
+    let out = Command::new("docker")
+        .args(["rm", "-f", container_id])
+        .output()
+        .map_err(|e| format!("failed to stop docker container: {}", e))?;
+
+    if !out.status.success() {
+        return Err(format!(
+            "docker rm failed: {}",
+            String::from_utf8_lossy(&out.stderr)
+        ));
+    }
+    Ok(())
+}
+
+pub async fn try_start_docker_mongo() -> Result<(String, String), String> {
+    // Start a docker mongo container via the `docker` CLI and return (uri, container_id).
+    use std::process::Command;
+
+    // Ensure `docker` is available
+    let docker_check = Command::new("docker").arg("version").output();
+    if docker_check.is_err() {
+        return Err("docker CLI not found".into());
+    }
+
+    // Start container with random host port mapping (-P) and capture container id
+    let name = format!("test-mongo-{}", Uuid::new_v4().to_string());
+    let run = Command::new("docker")
+        .args(["run", "-d", "-P", "--rm", "--name", &name, "mongo:6.0"])
+        .output()
+        .map_err(|e| format!("failed to run docker: {}", e))?;
+
+    if !run.status.success() {
+        let stderr = String::from_utf8_lossy(&run.stderr);
+        return Err(format!("docker run failed: {}", stderr));
+    }
+
+    let container_id = String::from_utf8_lossy(&run.stdout).trim().to_string();
+
+    // Get mapped port for 27017, retrying once while docker publishes it
+    let mut port_out = None;
+    for _ in 0..2 {
+        let out = Command::new("docker")
+            .args(["port", &container_id, "27017"])
+            .output()
+            .map_err(|e| format!("failed to query docker port: {}", e))?;
+        if out.status.success() {
+            port_out = Some(out);
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    }
+
+    let Some(port_out) = port_out else {
+        let _ = stop_docker_container(&container_id);
+        return Err("docker port query failed".into());
+    };
+
+    let out = String::from_utf8_lossy(&port_out.stdout);
+    let host_port = out
+        .trim()
+        .rsplit(':')
+        .next()
+        .ok_or_else(|| "failed to parse docker port output".to_string())?;
+    let uri = format!("mongodb://127.0.0.1:{}", host_port);
+    Ok((uri, container_id))
+}
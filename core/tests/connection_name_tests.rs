@@ -0,0 +1,19 @@
+use messages_core::application::{default_connection_name, mongo_client_options};
+
+#[tokio::test]
+async fn mongo_options_carry_the_application_name() {
+    // Parsing a plain mongodb:// URI never touches the network
+    let options = mongo_client_options("mongodb://127.0.0.1:1/messages", "messages-test")
+        .await
+        .expect("uri should parse");
+
+    assert_eq!(options.app_name.as_deref(), Some("messages-test"));
+}
+
+#[test]
+fn default_connection_name_is_unique_per_instance() {
+    let (first, second) = (default_connection_name(), default_connection_name());
+
+    assert!(first.starts_with(env!("CARGO_PKG_NAME")));
+    assert_ne!(first, second);
+}
@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use messages_core::domain::attachment::port::MockAttachmentRepository;
+use messages_core::domain::authorization::ports::AllowAllAuthorizer;
+use messages_core::domain::common::CoreError;
+use messages_core::domain::common::clock::MockClock;
+use messages_core::domain::common::services::Service;
+use messages_core::domain::health::port::MockHealthRepository;
+use messages_core::domain::message::duplicates::{
+    DuplicateContentAction, DuplicateContentPolicy, DuplicateDetector, PRUNE_EVERY_RECORDS,
+    content_hash,
+};
+use messages_core::domain::message::entities::{AuthorId, ChannelId, InsertMessageInput, MessageId};
+use messages_core::domain::message::ports::{MessageService, MockMessageRepository};
+use messages_core::domain::outbox::ports::MockOutboxEventRepository;
+use uuid::Uuid;
+
+fn service_with(
+    action: DuplicateContentAction,
+    clock: MockClock,
+) -> Service<MockMessageRepository, MockHealthRepository, MockAttachmentRepository, MockOutboxEventRepository>
+{
+    Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_duplicate_detection(DuplicateContentPolicy::new(action, Duration::seconds(30)))
+    .with_clock(Arc::new(clock))
+}
+
+fn message(author_id: AuthorId, content: &str) -> InsertMessageInput {
+    InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: ChannelId::from(Uuid::new_v4()),
+        author_id,
+        content: content.into(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    }
+}
+
+#[test]
+fn hash_ignores_case_and_whitespace() {
+    assert_eq!(content_hash("Buy  NOW\n"), content_hash("buy now"));
+    assert_ne!(content_hash("buy now"), content_hash("buy later"));
+}
+
+#[tokio::test]
+async fn repeated_content_within_window_is_rejected() {
+    let clock = MockClock::new(Utc::now());
+    let service = service_with(DuplicateContentAction::Reject, clock.clone());
+    let author = AuthorId::from(Uuid::new_v4());
+
+    service
+        .create_message(message(author, "spam spam"))
+        .await
+        .expect("first post should work");
+
+    clock.advance(Duration::seconds(5));
+    let res = service.create_message(message(author, "SPAM  spam")).await;
+    assert!(matches!(res, Err(CoreError::DuplicateContent)));
+}
+
+#[tokio::test]
+async fn repeated_content_is_flagged_when_configured() {
+    let clock = MockClock::new(Utc::now());
+    let service = service_with(DuplicateContentAction::Flag, clock.clone());
+    let author = AuthorId::from(Uuid::new_v4());
+
+    let first = service
+        .create_message(message(author, "hello"))
+        .await
+        .expect("first post should work");
+    assert!(!first.duplicate);
+
+    let second = service
+        .create_message(message(author, "hello"))
+        .await
+        .expect("flagged post is still created");
+    assert!(second.duplicate);
+}
+
+#[tokio::test]
+async fn distinct_content_other_authors_and_expired_window_pass() {
+    let clock = MockClock::new(Utc::now());
+    let service = service_with(DuplicateContentAction::Reject, clock.clone());
+    let author = AuthorId::from(Uuid::new_v4());
+
+    service
+        .create_message(message(author, "hello"))
+        .await
+        .expect("first post should work");
+    service
+        .create_message(message(author, "something else"))
+        .await
+        .expect("distinct content should pass");
+    service
+        .create_message(message(AuthorId::from(Uuid::new_v4()), "hello"))
+        .await
+        .expect("another author may post the same content");
+
+    clock.advance(Duration::seconds(31));
+    service
+        .create_message(message(author, "hello"))
+        .await
+        .expect("content may be reposted once the window has passed");
+}
+
+#[tokio::test]
+async fn retrying_a_failed_post_is_not_a_duplicate() {
+    let clock = MockClock::new(Utc::now());
+    let service = service_with(DuplicateContentAction::Reject, clock.clone());
+    let author = AuthorId::from(Uuid::new_v4());
+
+    let stored = service
+        .create_message(message(author, "first"))
+        .await
+        .expect("first post should work");
+
+    // Reusing a stored id makes the insert fail
+    let mut failing = message(author, "hello");
+    failing.id = stored.message.id;
+    let res = service.create_message(failing).await;
+    assert!(matches!(res, Err(CoreError::DatabaseError { .. })));
+
+    service
+        .create_message(message(author, "hello"))
+        .await
+        .expect("the retry should be accepted");
+}
+
+#[test]
+fn expired_hashes_are_dropped_on_the_periodic_sweep() {
+    let detector = DuplicateDetector::new(DuplicateContentPolicy::new(
+        DuplicateContentAction::Reject,
+        Duration::seconds(30),
+    ));
+    let start = Utc::now();
+    let author = AuthorId::from(Uuid::new_v4());
+
+    detector.record(&author, "old", start);
+    assert!(detector.is_duplicate(&author, "old", start + Duration::seconds(10)));
+    assert!(!detector.is_duplicate(&author, "old", start + Duration::seconds(31)));
+
+    // An expired hash lingers until the sweep comes round
+    let later = start + Duration::seconds(60);
+    for i in 1..PRUNE_EVERY_RECORDS - 1 {
+        detector.record(&author, &format!("message {i}"), later);
+    }
+    assert_eq!(detector.tracked_hashes(), PRUNE_EVERY_RECORDS as usize - 1);
+
+    // The sweep drops the expired hash as the new one comes in
+    detector.record(&author, "last", later);
+    assert_eq!(detector.tracked_hashes(), PRUNE_EVERY_RECORDS as usize - 1);
+}
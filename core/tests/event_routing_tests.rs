@@ -0,0 +1,56 @@
+use messages_core::domain::outbox::ports::OutboxEventRepository;
+use messages_core::infrastructure::outbox::entities::MessageOutboxEventRouting;
+use messages_core::infrastructure::outbox::mongo::MongoOutboxEventRepository;
+use messages_core::infrastructure::outbox::{EventRouting, MessageRoutingInfo, OutboxEventRecord, RoutingConfig};
+use mongodb::bson::{Document, doc};
+
+mod common;
+use common::TestMongo;
+
+#[test]
+fn invalid_config_is_rejected_and_the_active_one_kept() {
+    let routing = EventRouting::new(RoutingConfig::new().with_exchange("message.created", "audit"))
+        .expect("config should be valid");
+
+    assert!(routing
+        .reload(RoutingConfig::new().with_exchange("message.exploded", "audit"))
+        .is_err());
+    assert!(routing
+        .reload(RoutingConfig::new().with_exchange("message.deleted", " "))
+        .is_err());
+
+    assert_eq!(routing.exchange_for(MessageOutboxEventRouting::Create), "audit");
+    assert_eq!(routing.exchange_for(MessageOutboxEventRouting::Delete), "notifications");
+}
+
+#[tokio::test]
+async fn reload_changes_the_exchange_of_later_writes() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let routing = EventRouting::default();
+    let repository = MongoOutboxEventRepository::new(mongo.db.clone()).with_event_routing(routing.clone());
+    let record = || OutboxEventRecord::new(MessageRoutingInfo::new("notifications", "message.created"), vec![1]);
+
+    repository
+        .write_event(&record(), MessageOutboxEventRouting::Create)
+        .await
+        .expect("write should succeed");
+    routing
+        .reload(RoutingConfig::new().with_exchange("message.created", "audit"))
+        .expect("reload should succeed");
+    repository
+        .write_event(&record(), MessageOutboxEventRouting::Create)
+        .await
+        .expect("write should succeed");
+
+    let outbox = mongo.db.collection::<Document>("outbox_messages");
+    let count = |exchange: &'static str| {
+        let outbox = outbox.clone();
+        async move { outbox.count_documents(doc! { "exchange_name": exchange }).await.unwrap() }
+    };
+    assert_eq!(count("notifications").await, 1);
+    assert_eq!(count("audit").await, 1);
+
+    mongo.teardown().await;
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use messages_core::domain::attachment::port::MockAttachmentRepository;
+use messages_core::domain::authorization::ports::AllowAllAuthorizer;
+use messages_core::domain::common::services::Service;
+use messages_core::domain::health::entities::IsHealthy;
+use messages_core::domain::health::port::{DependencyProbe, HealthRepository, HealthService};
+use messages_core::domain::message::ports::MockMessageRepository;
+use messages_core::domain::outbox::ports::MockOutboxEventRepository;
+
+/// Health repository answering healthy after a fixed delay
+struct SlowHealthRepository {
+    delay: Duration,
+}
+
+impl HealthRepository for SlowHealthRepository {
+    fn name(&self) -> &'static str {
+        "slow-db"
+    }
+
+    async fn ping(&self) -> IsHealthy {
+        tokio::time::sleep(self.delay).await;
+        IsHealthy::new(true)
+    }
+}
+
+/// Extra dependency answering with a fixed status
+struct FixedProbe {
+    healthy: bool,
+}
+
+#[async_trait::async_trait]
+impl DependencyProbe for FixedProbe {
+    fn name(&self) -> &'static str {
+        "broker"
+    }
+
+    async fn ping(&self) -> IsHealthy {
+        IsHealthy::new(self.healthy)
+    }
+}
+
+fn service(
+    delay: Duration,
+) -> Service<MockMessageRepository, SlowHealthRepository, MockAttachmentRepository, MockOutboxEventRepository>
+{
+    Service::new(
+        MockMessageRepository::new(),
+        SlowHealthRepository { delay },
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+}
+
+#[tokio::test]
+async fn readiness_reports_latency_per_dependency() {
+    let service = service(Duration::from_millis(20)).with_health_check_timeout(Duration::from_secs(1));
+
+    let checks = service.check_readiness().await;
+
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0].name, "slow-db");
+    assert!(checks[0].ok);
+    assert!(checks[0].latency_ms >= 20);
+}
+
+#[tokio::test]
+async fn readiness_marks_timed_out_check_failed() {
+    let service = service(Duration::from_secs(5)).with_health_check_timeout(Duration::from_millis(20));
+
+    let checks = service.check_readiness().await;
+
+    assert_eq!(checks.len(), 1);
+    assert!(!checks[0].ok);
+    assert!(checks[0].latency_ms < 5_000);
+}
+
+#[tokio::test]
+async fn check_all_reports_every_dependency() {
+    let service = service(Duration::from_millis(20))
+        .with_health_check_timeout(Duration::from_secs(1))
+        .with_dependency_probe(Arc::new(FixedProbe { healthy: true }));
+
+    let checks = service.check_all().await;
+
+    let names: Vec<&str> = checks.iter().map(|check| check.name.as_str()).collect();
+    assert_eq!(names, vec!["slow-db", "broker"]);
+    assert!(checks.iter().all(|check| check.healthy && check.detail.is_none()));
+    assert!(checks[0].latency_ms >= 20);
+}
+
+#[tokio::test]
+async fn check_all_explains_which_dependency_is_down() {
+    let service = service(Duration::from_secs(5))
+        .with_health_check_timeout(Duration::from_millis(20))
+        .with_dependency_probe(Arc::new(FixedProbe { healthy: false }));
+
+    let checks = service.check_all().await;
+
+    assert_eq!(checks.len(), 2);
+    assert!(!checks[0].healthy);
+    assert!(checks[0].detail.as_deref().unwrap().contains("20ms"));
+    assert!(!checks[1].healthy);
+    assert_eq!(checks[1].detail.as_deref(), Some("ping failed"));
+}
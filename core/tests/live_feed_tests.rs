@@ -0,0 +1,268 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use messages_core::domain::attachment::port::MockAttachmentRepository;
+use messages_core::domain::authorization::entities::Actor;
+use messages_core::domain::authorization::ports::{AllowAllAuthorizer, MockAuthorizer};
+use messages_core::domain::common::CoreError;
+use messages_core::domain::common::services::Service;
+use messages_core::domain::health::port::MockHealthRepository;
+use messages_core::domain::message::entities::{
+    AttachmentId, AuthorId, ChannelId, InsertMessageInput, MessageId, UpdateMessageRequest,
+};
+use messages_core::domain::message::feed::{LiveEvent, LiveEventStream, LiveMessageDeletion, LiveMessageUpdate, MessageFeed};
+use messages_core::domain::message::ports::{MessageService, MockMessageRepository};
+use messages_core::domain::outbox::ports::MockOutboxEventRepository;
+use messages_core::infrastructure::outbox::WireFormat;
+use messages_core::infrastructure::rabbitmq::consumer::{
+    live_event_from_payload, live_event_from_wire, live_message_from_payload,
+};
+use uuid::Uuid;
+
+fn input(channel: ChannelId) -> InsertMessageInput {
+    InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: channel,
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: "live".into(),
+        reply_to_message_id: None,
+        attachments: vec![AttachmentId::from(Uuid::new_v4())],
+        ephemeral: false,
+    }
+}
+
+async fn next_event(stream: &mut LiveEventStream) -> LiveEvent {
+    tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .expect("event should arrive")
+        .expect("stream should be open")
+}
+
+#[tokio::test]
+async fn created_message_appears_on_the_channel_stream() {
+    let feed = MessageFeed::default();
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_message_feed(feed.clone());
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let mut stream = service
+        .subscribe_channel(&Actor::from(Uuid::new_v4()), &channel)
+        .await
+        .expect("subscribe should work");
+
+    // a message in another channel must not show up
+    service
+        .create_message(input(ChannelId::from(Uuid::new_v4())))
+        .await
+        .expect("create should work");
+    let message = input(channel);
+    service
+        .create_message(message.clone())
+        .await
+        .expect("create should work");
+
+    // play the broker consumer: decode what was written to the outbox
+    for event in outbox.events() {
+        feed.publish(live_message_from_payload(&event.payload).expect("payload should decode"));
+    }
+
+    let received = tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .expect("message should arrive")
+        .expect("stream should be open");
+    let LiveEvent::Created(received) = received else {
+        panic!("expected a created event, got {:?}", received);
+    };
+    assert_eq!(received.id, message.id);
+    assert_eq!(received.channel_id, channel);
+    assert_eq!(received.content, "live");
+    assert_eq!(received.attachments, message.attachments);
+}
+
+#[tokio::test]
+async fn subscribing_requires_view_permission() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(MockAuthorizer::new()));
+
+    let res = service
+        .subscribe_channel(&Actor::from(Uuid::new_v4()), &ChannelId::from(Uuid::new_v4()))
+        .await;
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+}
+
+#[tokio::test]
+async fn dropped_and_closed_streams_release_their_subscription() {
+    let feed = MessageFeed::default();
+    let channel = ChannelId::from(Uuid::new_v4());
+
+    let dropped = feed.subscribe(channel);
+    let mut open = feed.subscribe(channel);
+    assert_eq!(feed.subscriber_count(), 2);
+
+    // a client disconnecting drops its stream
+    drop(dropped);
+    assert_eq!(feed.subscriber_count(), 1);
+
+    feed.close();
+    let next = tokio::time::timeout(Duration::from_secs(1), open.next())
+        .await
+        .expect("closing should end the stream");
+    assert!(next.is_none());
+}
+
+#[tokio::test]
+async fn updates_and_deletions_reach_the_channel_stream() {
+    let feed = MessageFeed::default();
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_message_feed(feed.clone());
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let message = input(channel);
+    let author = Actor::from(message.author_id);
+    service
+        .create_message(message.clone())
+        .await
+        .expect("create should work");
+
+    let mut stream = service
+        .subscribe_channel(&author, &channel)
+        .await
+        .expect("subscribe should work");
+    let already_sent = outbox.events().len();
+
+    service
+        .update_message(
+            &author,
+            UpdateMessageRequest {
+                content: Some("edited".into()),
+            }
+            .into_input(message.id),
+        )
+        .await
+        .expect("update should work");
+    service
+        .delete_message(&author, &message.id)
+        .await
+        .expect("delete should work");
+
+    // play the broker consumer for the events written since subscribing
+    for event in outbox.events().into_iter().skip(already_sent) {
+        feed.publish(live_event_from_payload(&event.routing_key, &event.payload).expect("payload should decode"));
+    }
+
+    let updated = next_event(&mut stream).await;
+    assert_eq!(updated.name(), "message.updated");
+    assert_eq!(
+        updated,
+        LiveEvent::Updated(LiveMessageUpdate {
+            id: message.id,
+            channel_id: channel,
+            content: Some("edited".into()),
+            is_pinned: Some(false),
+        })
+    );
+    let deleted = next_event(&mut stream).await;
+    assert_eq!(deleted.name(), "message.deleted");
+    assert_eq!(
+        deleted,
+        LiveEvent::Deleted(LiveMessageDeletion {
+            id: message.id,
+            channel_id: channel,
+        })
+    );
+}
+
+#[tokio::test]
+async fn events_of_other_channels_are_filtered_out() {
+    let feed = MessageFeed::default();
+    let (channel, other) = (ChannelId::from(Uuid::new_v4()), ChannelId::from(Uuid::new_v4()));
+    let mut stream = feed.subscribe(channel);
+
+    let deletion = |channel_id| LiveMessageDeletion {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id,
+    };
+    feed.publish(deletion(other));
+    let ours = deletion(channel);
+    feed.publish(ours.clone());
+
+    assert_eq!(next_event(&mut stream).await, LiveEvent::Deleted(ours));
+}
+
+#[test]
+fn unknown_routing_keys_are_not_live_events() {
+    let res = live_event_from_payload("message.pinned", &[]);
+    assert!(matches!(res, Err(CoreError::SerializationError { .. })));
+}
+
+#[tokio::test]
+async fn events_published_as_json_decode_like_protobuf() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    let message = input(ChannelId::from(Uuid::new_v4()));
+    let author = Actor::from(message.author_id);
+    service
+        .create_message(message.clone())
+        .await
+        .expect("create should work");
+    service
+        .update_message(
+            &author,
+            UpdateMessageRequest {
+                content: Some("edited".into()),
+            }
+            .into_input(message.id),
+        )
+        .await
+        .expect("update should work");
+    service
+        .delete_message(&author, &message.id)
+        .await
+        .expect("delete should work");
+
+    let events = outbox.events();
+    assert_eq!(events.len(), 3);
+    for event in events {
+        let json = WireFormat::Json
+            .encode(&event.routing_key, event.payload.clone())
+            .expect("payload should convert");
+        let format = WireFormat::from_content_type(WireFormat::Json.content_type());
+        assert_eq!(
+            live_event_from_wire(&event.routing_key, format, &json).expect("json should decode"),
+            live_event_from_payload(&event.routing_key, &event.payload).expect("payload should decode"),
+        );
+    }
+}
+
+#[test]
+fn deliveries_without_a_json_content_type_are_read_as_protobuf() {
+    assert_eq!(WireFormat::from_content_type("application/x-protobuf"), WireFormat::Protobuf);
+    assert_eq!(WireFormat::from_content_type(""), WireFormat::Protobuf);
+}
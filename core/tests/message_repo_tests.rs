@@ -1,4 +1,4 @@
-use messages_core::domain::common::{CoreError, GetPaginated};
+use messages_core::domain::common::{CoreError, GetPaginated, MAX_PAGE_LIMIT};
 use messages_core::domain::message::entities::{
     AttachmentId, AuthorId, ChannelId, InsertMessageInput, MessageId,
     UpdateMessageInput,
@@ -21,6 +21,7 @@ async fn mock_repo_crud_flow() {
         content: "hello world".to_string(),
         reply_to_message_id: None,
         attachments: vec![AttachmentId::from(Uuid::new_v4())],
+        ephemeral: false,
     };
 
     // Insert
@@ -49,14 +50,13 @@ async fn mock_repo_crud_flow() {
     let update_input = UpdateMessageInput {
         id,
         content: Some("updated".into()),
-        is_pinned: Some(true),
     };
     let updated = repo
         .update(update_input)
         .await
         .expect("update should succeed");
     assert_eq!(updated.content, "updated");
-    assert!(updated.is_pinned);
+    assert!(!updated.is_pinned);
 
     // Delete
     repo.delete(&id).await.expect("delete should succeed");
@@ -71,3 +71,85 @@ async fn mock_repo_crud_flow() {
     let res = repo.delete(&missing_id).await;
     assert!(matches!(res, Err(CoreError::MessageNotFound { .. })));
 }
+
+#[tokio::test]
+async fn mock_repo_clamps_page_zero_and_large_limits() {
+    let repo = MockMessageRepository::new();
+    let channel = ChannelId::from(Uuid::new_v4());
+
+    for i in 0..60 {
+        repo.insert(InsertMessageInput {
+            id: MessageId::from(Uuid::new_v4()),
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: format!("message {i}"),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("insert should succeed");
+    }
+
+    let page_zero = GetPaginated { page: 0, limit: 10 };
+    let (list, total) = repo.list(&channel, &page_zero).await.expect("list should succeed");
+    assert_eq!(list.len(), 10);
+    assert_eq!(total, 60);
+
+    let (found, total) = repo
+        .search_messages(&channel, &"message".into(), &page_zero)
+        .await
+        .expect("search should succeed");
+    assert_eq!(found.len(), 10);
+    assert_eq!(total, 60);
+
+    let too_large = GetPaginated { page: 1, limit: 500 };
+    let (list, _) = repo.list(&channel, &too_large).await.expect("list should succeed");
+    assert_eq!(list.len(), 50);
+}
+
+#[test]
+fn pagination_is_normalized_without_overflow() {
+    let zero = GetPaginated { page: 0, limit: 0 }.normalized();
+    assert_eq!((zero.page, zero.limit), (1, 1));
+    assert_eq!(GetPaginated { page: 0, limit: 0 }.offset(), 0);
+
+    let huge = GetPaginated { page: u32::MAX, limit: u32::MAX };
+    assert_eq!(huge.normalized().limit, MAX_PAGE_LIMIT);
+    assert_eq!(huge.offset(), (u32::MAX as u64 - 1) * MAX_PAGE_LIMIT as u64);
+}
+
+#[tokio::test]
+async fn mock_repo_lists_one_item_for_page_and_limit_zero() {
+    let repo = MockMessageRepository::new();
+    let channel = ChannelId::from(Uuid::new_v4());
+    for i in 0..3 {
+        repo.insert(InsertMessageInput {
+            id: MessageId::from(Uuid::new_v4()),
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: format!("message {i}"),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("insert should succeed");
+    }
+    let zero = GetPaginated { page: 0, limit: 0 };
+
+    let (list, total) = repo.list(&channel, &zero).await.expect("list should succeed");
+    assert_eq!((list.len(), total), (1, 3));
+
+    let (found, total) = repo
+        .search_messages(&channel, &"message".into(), &zero)
+        .await
+        .expect("search should succeed");
+    assert_eq!((found.len(), total), (1, 3));
+
+    let (participants, total) = repo
+        .list_channel_participants(&channel, &zero)
+        .await
+        .expect("participants should succeed");
+    assert_eq!((participants.len(), total), (1, 3));
+}
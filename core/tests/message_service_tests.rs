@@ -1,15 +1,33 @@
+use std::sync::Arc;
+
+use events_protobuf::messages_events::UpdateMessageEvent;
 use messages_core::domain::attachment::port::MockAttachmentRepository;
-use messages_core::domain::common::CoreError;
+use messages_core::domain::authorization::entities::Actor;
+use messages_core::domain::authorization::ports::AllowAllAuthorizer;
+use messages_core::domain::common::{CoreError, GetPaginated};
 use messages_core::domain::common::services::Service;
 use messages_core::domain::health::port::MockHealthRepository;
 use messages_core::domain::message::entities::{
-    AttachmentId, AuthorId, ChannelId, InsertMessageInput, MessageId,
-    UpdateMessageInput,
+    AttachmentId, AuthorId, BulkEventMode, ChannelId, ERASED_MESSAGE_CONTENT, ErasureMode, InsertMessageInput,
+    MAX_LATEST_CHANNELS, MAX_PIN_REASON_CHARS, MAX_PINS_PER_CHANNEL, MAX_REACTIONS_PER_MESSAGE, Message, MessageId,
+    MessageSearchCriteria, REPLY_PREVIEW_MAX_CHARS, UpdateMessageInput, truncate_content,
+};
+use messages_core::domain::message::duplicates::{DuplicateContentAction, DuplicateContentPolicy};
+use messages_core::domain::message::events::MessagePinEvent;
+use messages_core::domain::message::ports::{MessageRepository, MessageService, MockMessageRepository};
+use messages_core::domain::message::rate_limit::{
+    RateLimitPolicy, RateLimitedAction, RateLimiter, TokenBucketRateLimiter,
 };
-use messages_core::domain::message::ports::{MessageService, MockMessageRepository};
 use messages_core::domain::outbox::ports::MockOutboxEventRepository;
+use messages_core::infrastructure::outbox::entities::MessageOutboxEventRouting;
+use prost::Message as _;
 use uuid::Uuid;
 
+/// Caller for operations whose outcome doesn't depend on who asks
+fn any_actor() -> Actor {
+    Actor::from(Uuid::new_v4())
+}
+
 #[tokio::test]
 async fn service_create_get_update_delete_flow() {
     let repo = MockMessageRepository::new();
@@ -18,11 +36,13 @@ async fn service_create_get_update_delete_flow() {
     let outbox = MockOutboxEventRepository::new();
 
 
-    let service = Service::new(repo.clone(), health, attachment, outbox);
+    let service = Service::new(repo.clone(), health, attachment, outbox)
+        .with_authorizer(Arc::new(AllowAllAuthorizer));
 
     let id = MessageId::from(Uuid::new_v4());
     let channel = ChannelId::from(Uuid::new_v4());
     let author = AuthorId::from(Uuid::new_v4());
+    let actor = Actor::from(author);
 
     let input = InsertMessageInput {
         id,
@@ -31,6 +51,7 @@ async fn service_create_get_update_delete_flow() {
         content: "service message".into(),
         reply_to_message_id: None,
         attachments: vec![AttachmentId::from(Uuid::new_v4())],
+        ephemeral: false,
     };
 
     // create
@@ -38,32 +59,32 @@ async fn service_create_get_update_delete_flow() {
         .create_message(input.clone())
         .await
         .expect("create should work");
-    assert_eq!(created.id, id);
+    assert_eq!(created.message.id, id);
+    assert!(created.reply_preview.is_none());
 
     // get
-    let got = service.get_message(&id).await.expect("get should work");
+    let got = service.get_message(&actor, &id).await.expect("get should work");
     assert_eq!(got.content, "service message");
 
     // update
     let update = UpdateMessageInput {
         id,
         content: Some("changed".into()),
-        is_pinned: Some(false),
     };
     let updated = service
-        .update_message(update)
+        .update_message(&actor, update)
         .await
         .expect("update should work");
     assert_eq!(updated.content, "changed");
 
     // delete
     service
-        .delete_message(&id)
+        .delete_message(&actor, &id)
         .await
         .expect("delete should work");
 
     // get after delete -> not found
-    let res = service.get_message(&id).await;
+    let res = service.get_message(&actor, &id).await;
     assert!(matches!(res, Err(CoreError::MessageNotFound { .. })));
 }
 
@@ -73,7 +94,8 @@ async fn create_invalid_message_name_rejected() {
     let health = MockHealthRepository::new();
     let attachment = MockAttachmentRepository::new();
     let outbox = MockOutboxEventRepository::new();
-    let service = Service::new(repo, health, attachment, outbox);
+    let service = Service::new(repo, health, attachment, outbox)
+        .with_authorizer(Arc::new(AllowAllAuthorizer));
 
     let input = InsertMessageInput {
         id: MessageId::from(Uuid::new_v4()),
@@ -82,8 +104,1986 @@ async fn create_invalid_message_name_rejected() {
         content: "  ".into(),
         reply_to_message_id: None,
         attachments: vec![],
+        ephemeral: false,
     };
 
     let res = service.create_message(input).await;
     assert!(matches!(res, Err(CoreError::InvalidMessageName)));
 }
+
+#[tokio::test]
+async fn create_reply_includes_parent_preview() {
+    let repo = MockMessageRepository::new();
+    let health = MockHealthRepository::new();
+    let attachment = MockAttachmentRepository::new();
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(repo, health, attachment, outbox)
+        .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let parent_author = AuthorId::from(Uuid::new_v4());
+    let parent_content = "é".repeat(REPLY_PREVIEW_MAX_CHARS + 10);
+
+    let parent = service
+        .create_message(InsertMessageInput {
+            id: MessageId::from(Uuid::new_v4()),
+            channel_id: channel,
+            author_id: parent_author,
+            content: parent_content.clone(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("parent create should work");
+
+    let reply = service
+        .create_message(InsertMessageInput {
+            id: MessageId::from(Uuid::new_v4()),
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: "a reply".into(),
+            reply_to_message_id: Some(parent.message.id),
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("reply create should work");
+
+    let preview = reply.reply_preview.expect("reply should carry a preview");
+    assert_eq!(preview.message_id, parent.message.id);
+    assert_eq!(preview.author_id, parent_author);
+    assert_eq!(
+        preview.content,
+        truncate_content(&parent_content, REPLY_PREVIEW_MAX_CHARS)
+    );
+    assert_eq!(preview.content.chars().count(), REPLY_PREVIEW_MAX_CHARS + 1);
+}
+
+#[tokio::test]
+async fn create_reply_to_missing_or_deleted_parent_has_no_preview() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let (channel, author) = (ChannelId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+    let post = |reply_to_message_id| InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: channel,
+        author_id: author,
+        content: "reply to nothing".into(),
+        reply_to_message_id,
+        attachments: vec![],
+        ephemeral: false,
+    };
+    let deleted = service.create_message(post(None)).await.unwrap().message.id;
+    service.delete_message(&Actor::from(author), &deleted).await.unwrap();
+
+    for parent in [MessageId::from(Uuid::new_v4()), deleted] {
+        let reply = service
+            .create_message(post(Some(parent)))
+            .await
+            .expect("reply create should work");
+        assert_eq!(reply.message.reply_to_message_id, Some(parent));
+        assert!(reply.reply_preview.is_none());
+    }
+}
+
+#[tokio::test]
+async fn create_reply_to_another_channel_has_no_preview() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let post = |channel_id, reply_to_message_id| InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id,
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: "secret plans".into(),
+        reply_to_message_id,
+        attachments: vec![],
+        ephemeral: false,
+    };
+    let private = service
+        .create_message(post(ChannelId::from(Uuid::new_v4()), None))
+        .await
+        .expect("create should work");
+
+    let reply = service
+        .create_message(post(ChannelId::from(Uuid::new_v4()), Some(private.message.id)))
+        .await
+        .expect("reply create should work");
+
+    assert!(reply.reply_preview.is_none());
+}
+
+#[tokio::test]
+async fn recent_channels_ordered_by_latest_activity() {
+    let repo = MockMessageRepository::new();
+    let health = MockHealthRepository::new();
+    let attachment = MockAttachmentRepository::new();
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(repo, health, attachment, outbox)
+        .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    let author = AuthorId::from(Uuid::new_v4());
+    let first = ChannelId::from(Uuid::new_v4());
+    let second = ChannelId::from(Uuid::new_v4());
+
+    // post in `first`, then `second`, then `first` again
+    for channel in [first, second, first] {
+        service
+            .create_message(InsertMessageInput {
+                id: MessageId::from(Uuid::new_v4()),
+                channel_id: channel,
+                author_id: author,
+                content: "activity".into(),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("create should work");
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+
+    // someone else's activity must not count
+    service
+        .create_message(InsertMessageInput {
+            id: MessageId::from(Uuid::new_v4()),
+            channel_id: ChannelId::from(Uuid::new_v4()),
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: "other user".into(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("create should work");
+
+    let recent = service
+        .list_recent_channels(&author, 10)
+        .await
+        .expect("list recent channels should work");
+
+    let channels: Vec<ChannelId> = recent.iter().map(|r| r.channel_id).collect();
+    assert_eq!(channels, vec![first, second]);
+    assert!(recent[0].last_message_at > recent[1].last_message_at);
+
+    let limited = service
+        .list_recent_channels(&author, 1)
+        .await
+        .expect("list recent channels should work");
+    assert_eq!(limited.len(), 1);
+    assert_eq!(limited[0].channel_id, first);
+}
+
+#[tokio::test]
+async fn latest_messages_returns_newest_message_per_channel() {
+    let repo = MockMessageRepository::new();
+    let health = MockHealthRepository::new();
+    let attachment = MockAttachmentRepository::new();
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(repo, health, attachment, outbox)
+        .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    let (first, second, empty) = (
+        ChannelId::from(Uuid::new_v4()),
+        ChannelId::from(Uuid::new_v4()),
+        ChannelId::from(Uuid::new_v4()),
+    );
+    let author = Uuid::new_v4();
+    let mut newest = std::collections::HashMap::new();
+    for (channel, content) in [(first, "a"), (second, "b"), (first, "c"), (second, "d"), (second, "e")] {
+        let id = MessageId::from(Uuid::new_v4());
+        service
+            .create_message(InsertMessageInput {
+                id,
+                channel_id: channel,
+                author_id: AuthorId::from(author),
+                content: content.into(),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("create should work");
+        newest.insert(channel, id);
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+
+    // the newest message of `second` is deleted, so the one before it is its latest
+    service
+        .delete_message(&Actor::from(author), &newest[&second])
+        .await
+        .expect("delete should work");
+
+    let latest = service
+        .latest_messages_for_channels(&any_actor(), &[first, second, empty])
+        .await
+        .expect("latest messages should work");
+
+    assert_eq!(latest.len(), 2);
+    assert_eq!(latest[&first].id, newest[&first]);
+    assert_eq!(latest[&first].content, "c");
+    assert_eq!(latest[&second].content, "d");
+    assert!(!latest.contains_key(&empty));
+}
+
+#[tokio::test]
+async fn latest_messages_rejects_too_many_channels() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channels: Vec<ChannelId> = (0..=MAX_LATEST_CHANNELS)
+        .map(|_| ChannelId::from(Uuid::new_v4()))
+        .collect();
+
+    let res = service.latest_messages_for_channels(&any_actor(), &channels).await;
+
+    assert!(matches!(res, Err(CoreError::InvalidBatch { .. })));
+}
+
+#[tokio::test]
+async fn export_user_data_contains_only_the_users_messages() {
+    use futures::TryStreamExt;
+
+    let repo = MockMessageRepository::new();
+    let health = MockHealthRepository::new();
+    let attachment = MockAttachmentRepository::new();
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(repo, health, attachment, outbox)
+        .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    let author = AuthorId::from(Uuid::new_v4());
+    let mine = MessageId::from(Uuid::new_v4());
+
+    for (id, author_id, content) in [
+        (mine, author, "my message"),
+        (MessageId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()), "not mine"),
+    ] {
+        service
+            .create_message(InsertMessageInput {
+                id,
+                channel_id: ChannelId::from(Uuid::new_v4()),
+                author_id,
+                content: content.into(),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("create should work");
+    }
+
+    let exported: Vec<_> = service
+        .export_user_data(&author)
+        .await
+        .expect("export should work")
+        .try_collect()
+        .await
+        .expect("stream should not fail");
+
+    assert_eq!(exported.len(), 1);
+    assert_eq!(exported[0].id, mine);
+    assert_eq!(exported[0].content, "my message");
+}
+
+async fn seed_messages_for(
+    service: &impl MessageService,
+    author: AuthorId,
+    other: AuthorId,
+) -> (MessageId, MessageId) {
+    let (mine, theirs) = (MessageId::from(Uuid::new_v4()), MessageId::from(Uuid::new_v4()));
+    for (id, author_id) in [(mine, author), (theirs, other)] {
+        service
+            .create_message(InsertMessageInput {
+                id,
+                channel_id: ChannelId::from(Uuid::new_v4()),
+                author_id,
+                content: "personal content".into(),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("create should work");
+    }
+    (mine, theirs)
+}
+
+#[tokio::test]
+async fn erase_user_data_anonymizes_messages_by_default() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let (author, other) = (AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+    let (mine, theirs) = seed_messages_for(&service, author, other).await;
+
+    let report = service.erase_user_data(&author).await.expect("erase should work");
+    assert_eq!(report.affected, 1);
+
+    let anonymized = service.get_message(&any_actor(), &mine).await.expect("message is kept");
+    assert_eq!(anonymized.content, ERASED_MESSAGE_CONTENT);
+    assert_eq!(anonymized.author_id, AuthorId::anonymous());
+
+    let untouched = service
+        .get_message(&any_actor(), &theirs)
+        .await
+        .expect("other message is kept");
+    assert_eq!(untouched.content, "personal content");
+    assert_eq!(untouched.author_id, other);
+}
+
+#[tokio::test]
+async fn erase_user_data_forgets_how_far_the_user_read() {
+    let repo = MockMessageRepository::new();
+    let service = Service::new(
+        repo.clone(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let (author, other) = (AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+    let (_, theirs) = seed_messages_for(&service, author, other).await;
+    let channel = service.get_message(&any_actor(), &theirs).await.unwrap().channel_id;
+    for reader in [author, other] {
+        service
+            .mark_read(&Actor::from(reader), &channel, &theirs)
+            .await
+            .expect("mark read should work");
+    }
+
+    service.erase_user_data(&author).await.expect("erase should work");
+
+    assert!(repo.get_last_read(&author, &channel).await.unwrap().is_none());
+    assert!(repo.get_last_read(&other, &channel).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn erase_user_data_removes_reactions_and_pins_on_other_messages() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let (author, other) = (AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+    let (_, theirs) = seed_messages_for(&service, author, other).await;
+    for reactor in [author, other] {
+        service
+            .add_reaction(&Actor::from(reactor), &theirs, "🎉")
+            .await
+            .expect("reaction should work");
+    }
+    service
+        .pin_message(&Actor::from(author), &theirs, None)
+        .await
+        .expect("pin should work");
+
+    service.erase_user_data(&author).await.expect("erase should work");
+
+    let reactions = service.list_reactions(&any_actor(), &theirs).await.unwrap();
+    assert_eq!(reactions.len(), 1);
+    assert_eq!(reactions[0].user_ids, vec![other]);
+    let kept = service.get_message(&any_actor(), &theirs).await.expect("message is kept");
+    assert!(kept.is_pinned);
+    assert!(kept.pinned_by.is_none());
+}
+
+#[tokio::test]
+async fn anonymous_callers_cannot_edit_or_delete_erased_messages() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let (author, other) = (AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+    let (mine, _) = seed_messages_for(&service, author, other).await;
+    service.erase_user_data(&author).await.expect("erase should work");
+
+    // the erased author and the anonymous caller share the nil id
+    let edit = UpdateMessageInput {
+        id: mine,
+        content: Some("taken over".to_string()),
+    };
+    let res = service.update_message(&Actor::anonymous(), edit).await;
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+    let res = service.delete_message(&Actor::anonymous(), &mine).await;
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+
+    let kept = service.get_message(&any_actor(), &mine).await.expect("message is kept");
+    assert_eq!(kept.content, ERASED_MESSAGE_CONTENT);
+}
+
+#[tokio::test]
+async fn erase_user_data_deletes_messages_when_configured() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_erasure_mode(ErasureMode::Delete);
+    let (author, other) = (AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+    let (mine, theirs) = seed_messages_for(&service, author, other).await;
+
+    let report = service.erase_user_data(&author).await.expect("erase should work");
+    assert_eq!(report.affected, 1);
+
+    let res = service.get_message(&any_actor(), &mine).await;
+    assert!(matches!(res, Err(CoreError::MessageNotFound { .. })));
+    assert!(service.get_message(&any_actor(), &theirs).await.is_ok());
+}
+
+#[tokio::test]
+async fn erase_user_data_scrubs_soft_deleted_messages_without_announcing_them() {
+    let repo = MockMessageRepository::new();
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        repo.clone(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_bulk_event_mode(BulkEventMode::PerItem);
+    let (author, other) = (AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+    let (mine, _) = seed_messages_for(&service, author, other).await;
+    let (deleted, _) = seed_messages_for(&service, author, other).await;
+    service
+        .delete_message(&Actor::from(author), &deleted)
+        .await
+        .expect("delete should work");
+    outbox.clear();
+
+    let report = service.erase_user_data(&author).await.expect("erase should work");
+    assert_eq!(report.affected, 2);
+
+    let scrubbed = repo
+        .find_including_deleted(&deleted)
+        .await
+        .unwrap()
+        .expect("soft-deleted message is kept");
+    assert_eq!(scrubbed.content, ERASED_MESSAGE_CONTENT);
+    assert_ne!(scrubbed.author_id, author);
+
+    // only the visible message is announced, it was the only one readers could see
+    let events = outbox.events();
+    assert_eq!(events.len(), 1);
+    let event = UpdateMessageEvent::decode(events[0].payload.as_slice()).unwrap();
+    assert_eq!(event.message_id, mine.to_string());
+}
+
+#[tokio::test]
+async fn erase_user_data_purges_soft_deleted_messages_when_configured() {
+    let repo = MockMessageRepository::new();
+    let service = Service::new(
+        repo.clone(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_erasure_mode(ErasureMode::Delete);
+    let (author, other) = (AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+    let (deleted, _) = seed_messages_for(&service, author, other).await;
+    service
+        .delete_message(&Actor::from(author), &deleted)
+        .await
+        .expect("delete should work");
+
+    let report = service.erase_user_data(&author).await.expect("erase should work");
+    assert_eq!(report.affected, 1);
+    assert!(repo.find_including_deleted(&deleted).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn ephemeral_message_is_not_persisted() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let id = MessageId::from(Uuid::new_v4());
+    let channel = ChannelId::from(Uuid::new_v4());
+
+    let created = service
+        .create_message(InsertMessageInput {
+            id,
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: "only you can see this".into(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: true,
+        })
+        .await
+        .expect("ephemeral create should work");
+    assert!(created.ephemeral);
+    assert_eq!(created.message.id, id);
+
+    let res = service.get_message(&any_actor(), &id).await;
+    assert!(matches!(res, Err(CoreError::MessageNotFound { .. })));
+
+    let (messages, total) = service
+        .list_messages(&any_actor(), &channel, &Default::default())
+        .await
+        .expect("list should work");
+    assert!(messages.is_empty());
+    assert_eq!(total, 0);
+
+    // nothing was stored, so it can't be pinned either
+    let pin = service.pin_message(&any_actor(), &id, None).await;
+    assert!(matches!(pin, Err(CoreError::MessageNotFound { .. })));
+}
+
+#[tokio::test]
+async fn update_writes_one_event_with_the_stored_content() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let author = AuthorId::from(Uuid::new_v4());
+    let created = service
+        .create_message(InsertMessageInput {
+            id: MessageId::from(Uuid::new_v4()),
+            channel_id: ChannelId::from(Uuid::new_v4()),
+            author_id: author,
+            content: "original".into(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("create should work");
+    outbox.clear();
+
+    // the input carries no content, the event still reports what is stored
+    service
+        .update_message(&Actor::from(author), UpdateMessageInput {
+            id: created.message.id,
+            content: None,
+        })
+        .await
+        .expect("update should work");
+
+    let events = outbox.events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].routing_key, "message.updated");
+    let event = UpdateMessageEvent::decode(events[0].payload.as_slice()).unwrap();
+    assert_eq!(event.content.as_deref(), Some("original"));
+    assert_eq!(event.is_pinned, Some(false));
+}
+
+#[tokio::test]
+async fn editing_a_message_leaves_its_pin_alone() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let author = AuthorId::from(Uuid::new_v4());
+    let moderator = Actor::from(Uuid::new_v4());
+    let created = service
+        .create_message(InsertMessageInput {
+            id: MessageId::from(Uuid::new_v4()),
+            channel_id: ChannelId::from(Uuid::new_v4()),
+            author_id: author,
+            content: "rules".into(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("create should work");
+    service
+        .pin_message(&moderator, &created.message.id, Some("read me".into()))
+        .await
+        .expect("pin should work");
+
+    let edited = service
+        .update_message(&Actor::from(author), UpdateMessageInput {
+            id: created.message.id,
+            content: Some("rules, v2".into()),
+        })
+        .await
+        .expect("update should work");
+
+    assert!(edited.is_pinned);
+    assert_eq!(edited.pinned_by, Some(moderator.author_id()));
+    assert_eq!(edited.pin_reason.as_deref(), Some("read me"));
+}
+
+#[tokio::test]
+async fn ephemeral_reply_rejected() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    let res = service
+        .create_message(InsertMessageInput {
+            id: MessageId::from(Uuid::new_v4()),
+            channel_id: ChannelId::from(Uuid::new_v4()),
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: "ephemeral reply".into(),
+            reply_to_message_id: Some(MessageId::from(Uuid::new_v4())),
+            attachments: vec![],
+            ephemeral: true,
+        })
+        .await;
+    assert!(matches!(res, Err(CoreError::EphemeralReply)));
+}
+
+async fn seed_channel(service: &impl MessageService, channel: ChannelId, count: usize) -> Vec<MessageId> {
+    let mut ids = Vec::with_capacity(count);
+    for i in 0..count {
+        let id = MessageId::from(Uuid::new_v4());
+        service
+            .create_message(InsertMessageInput {
+                id,
+                channel_id: channel,
+                author_id: AuthorId::from(Uuid::new_v4()),
+                content: format!("message {i}"),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("create should work");
+        ids.push(id);
+    }
+    ids
+}
+
+#[tokio::test]
+async fn list_messages_in_range_includes_start_and_excludes_end() {
+    let repo = MockMessageRepository::new();
+    let service = Service::new(
+        repo.clone(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        ids.extend(seed_channel(&service, channel, 1).await);
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+    let mut created_at = Vec::new();
+    for id in &ids {
+        created_at.push(repo.find_by_id(id).await.unwrap().unwrap().created_at);
+    }
+
+    let (messages, total) = service
+        .list_messages_in_range(&any_actor(), &channel, created_at[0], created_at[2], &GetPaginated::default())
+        .await
+        .expect("range listing should work");
+
+    let listed: Vec<MessageId> = messages.iter().map(|m| m.id).collect();
+    assert_eq!(listed, ids[..2]);
+    assert_eq!(total, 2);
+
+    // an empty range is valid and matches nothing
+    let (messages, _) = service
+        .list_messages_in_range(&any_actor(), &channel, created_at[1], created_at[1], &GetPaginated::default())
+        .await
+        .unwrap();
+    assert!(messages.is_empty());
+}
+
+#[tokio::test]
+async fn list_messages_in_range_rejects_an_inverted_range() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let now = chrono::Utc::now();
+
+    let res = service
+        .list_messages_in_range(&any_actor(), &channel, now, now - chrono::Duration::seconds(1), &GetPaginated::default())
+        .await;
+    assert!(matches!(res, Err(CoreError::InvalidTimeRange)));
+}
+
+/// Post "hello <i>" messages alternating between two authors, returning them oldest first
+async fn seed_search_channel(
+    service: &impl MessageService,
+    repo: &MockMessageRepository,
+    channel: ChannelId,
+    authors: [AuthorId; 2],
+) -> Vec<Message> {
+    let mut messages = Vec::new();
+    for i in 0..4 {
+        let id = MessageId::from(Uuid::new_v4());
+        service
+            .create_message(InsertMessageInput {
+                id,
+                channel_id: channel,
+                author_id: authors[i % 2],
+                content: format!("hello {i}"),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("create should work");
+        messages.push(repo.find_by_id(&id).await.unwrap().unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+    messages
+}
+
+#[tokio::test]
+async fn search_messages_filters_by_author_and_time_window() {
+    let repo = MockMessageRepository::new();
+    let service = Service::new(
+        repo.clone(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let authors = [AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4())];
+    let messages = seed_search_channel(&service, &repo, channel, authors).await;
+    let search = |criteria: MessageSearchCriteria| {
+        let service = &service;
+        async move {
+            let (found, total) = service
+                .search_messages(&any_actor(), &channel, &criteria, &GetPaginated::default())
+                .await
+                .expect("search should work");
+            assert_eq!(found.len() as u64, total);
+            let mut contents: Vec<String> = found.into_iter().map(|m| m.content).collect();
+            contents.sort();
+            contents
+        }
+    };
+
+    // author only
+    assert_eq!(
+        search(MessageSearchCriteria::new("hello").with_author(authors[0])).await,
+        vec!["hello 0", "hello 2"]
+    );
+
+    // time window only, both bounds excluded
+    assert_eq!(
+        search(
+            MessageSearchCriteria::new("hello")
+                .with_after(messages[0].created_at)
+                .with_before(messages[3].created_at)
+        )
+        .await,
+        vec!["hello 1", "hello 2"]
+    );
+
+    // every filter must hold
+    assert_eq!(
+        search(
+            MessageSearchCriteria::new("hello")
+                .with_author(authors[1])
+                .with_after(messages[0].created_at)
+                .with_before(messages[3].created_at)
+        )
+        .await,
+        vec!["hello 1"]
+    );
+}
+
+#[tokio::test]
+async fn search_messages_rejects_an_inverted_time_window() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let now = chrono::Utc::now();
+    let criteria = MessageSearchCriteria::new("hello")
+        .with_after(now)
+        .with_before(now - chrono::Duration::seconds(1));
+
+    let res = service
+        .search_messages(&any_actor(), &channel, &criteria, &GetPaginated::default())
+        .await;
+    assert!(matches!(res, Err(CoreError::InvalidTimeRange)));
+}
+
+#[tokio::test]
+async fn list_replies_returns_the_thread_oldest_first() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let post = |content: &str, reply_to: Option<MessageId>| InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: channel,
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: content.into(),
+        reply_to_message_id: reply_to,
+        attachments: vec![],
+        ephemeral: false,
+    };
+
+    let parent = service.create_message(post("parent", None)).await.unwrap().message.id;
+    let other = service.create_message(post("other", None)).await.unwrap().message.id;
+    for content in ["first", "second", "third"] {
+        service.create_message(post(content, Some(parent))).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+    service.create_message(post("elsewhere", Some(other))).await.unwrap();
+
+    let (replies, total) = service
+        .list_replies(&any_actor(), &parent, &GetPaginated { page: 1, limit: 2 })
+        .await
+        .expect("list replies should work");
+    assert_eq!(total, 3);
+    let contents: Vec<&str> = replies.iter().map(|m| m.content.as_str()).collect();
+    assert_eq!(contents, vec!["first", "second"]);
+
+    let count = service.reply_count(&any_actor(), &parent).await.unwrap();
+    assert_eq!(count.message_id, parent);
+    assert_eq!(count.reply_count, 3);
+
+    let missing = MessageId::from(Uuid::new_v4());
+    let res = service.reply_count(&any_actor(), &missing).await;
+    assert!(matches!(res, Err(CoreError::MessageNotFound { .. })));
+}
+
+#[tokio::test]
+async fn mark_read_only_moves_forward() {
+    let repo = MockMessageRepository::new();
+    let service = Service::new(
+        repo.clone(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let authors = [AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4())];
+    let messages = seed_search_channel(&service, &repo, channel, authors).await;
+    let reader = any_actor();
+
+    let unread = service.unread_count(&reader, &channel).await.unwrap();
+    assert_eq!(unread.unread_count, 4);
+
+    let state = service
+        .mark_read(&reader, &channel, &messages[2].id)
+        .await
+        .expect("mark read should work");
+    assert_eq!(state.last_read_message_id, messages[2].id);
+    assert_eq!(state.user_id, reader.author_id());
+    assert_eq!(service.unread_count(&reader, &channel).await.unwrap().unread_count, 1);
+
+    // an older message leaves the state where it was
+    let state = service
+        .mark_read(&reader, &channel, &messages[0].id)
+        .await
+        .expect("mark read should work");
+    assert_eq!(state.last_read_message_id, messages[2].id);
+    assert_eq!(service.unread_count(&reader, &channel).await.unwrap().unread_count, 1);
+
+    service
+        .mark_read(&reader, &channel, &messages[3].id)
+        .await
+        .expect("mark read should work");
+    assert_eq!(service.unread_count(&reader, &channel).await.unwrap().unread_count, 0);
+
+    // read state is per user
+    assert_eq!(service.unread_count(&any_actor(), &channel).await.unwrap().unread_count, 4);
+}
+
+#[tokio::test]
+async fn mark_read_rejects_a_message_of_another_channel() {
+    let repo = MockMessageRepository::new();
+    let service = Service::new(
+        repo.clone(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let authors = [AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4())];
+    let messages = seed_search_channel(&service, &repo, channel, authors).await;
+
+    let other = ChannelId::from(Uuid::new_v4());
+    let res = service.mark_read(&any_actor(), &other, &messages[0].id).await;
+    assert!(matches!(res, Err(CoreError::MessageNotInChannel { .. })));
+
+    let res = service
+        .mark_read(&any_actor(), &channel, &MessageId::from(Uuid::new_v4()))
+        .await;
+    assert!(matches!(res, Err(CoreError::MessageNotFound { .. })));
+}
+
+#[tokio::test]
+async fn pin_messages_stops_at_channel_pin_limit() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let limit = MAX_PINS_PER_CHANNEL as usize;
+    let ids = seed_channel(&service, channel, limit + 2).await;
+
+    // fill the channel up to one below the limit
+    let result = service
+        .pin_messages(&any_actor(), &channel, &ids[..limit - 1])
+        .await
+        .expect("pin should work");
+    assert_eq!(result.succeeded.len(), limit - 1);
+    assert!(result.failed.is_empty());
+
+    // the batch crosses the limit after its first item
+    let batch = &ids[limit - 1..];
+    let result = service
+        .pin_messages(&any_actor(), &channel, batch)
+        .await
+        .expect("pin should work");
+    assert_eq!(result.succeeded, vec![batch[0]]);
+    let failed: Vec<MessageId> = result.failed.iter().map(|f| f.id).collect();
+    assert_eq!(failed, batch[1..].to_vec());
+
+    assert!(service.get_message(&any_actor(), &batch[0]).await.unwrap().is_pinned);
+    assert!(!service.get_message(&any_actor(), &batch[1]).await.unwrap().is_pinned);
+}
+
+#[tokio::test]
+async fn bulk_delete_reports_messages_it_could_not_delete() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let ids = seed_channel(&service, channel, 3).await;
+    let elsewhere = seed_channel(&service, ChannelId::from(Uuid::new_v4()), 1).await;
+    let missing = MessageId::from(Uuid::new_v4());
+
+    let result = service
+        .bulk_delete(&any_actor(), &channel, &[ids[0], elsewhere[0], ids[1], missing])
+        .await
+        .expect("bulk delete should work");
+    assert_eq!(result.succeeded, vec![ids[0], ids[1]]);
+    let failed: Vec<MessageId> = result.failed.iter().map(|f| f.id).collect();
+    assert_eq!(failed, vec![elsewhere[0], missing]);
+
+    assert!(matches!(
+        service.get_message(&any_actor(), &ids[0]).await,
+        Err(CoreError::MessageNotFound { .. })
+    ));
+    assert!(service.get_message(&any_actor(), &ids[2]).await.is_ok());
+    assert!(service.get_message(&any_actor(), &elsewhere[0]).await.is_ok());
+}
+
+#[tokio::test]
+async fn bulk_delete_rejects_batches_over_the_limit() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_max_bulk_delete(2);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let ids = seed_channel(&service, channel, 3).await;
+
+    let res = service.bulk_delete(&any_actor(), &channel, &ids).await;
+    assert!(matches!(res, Err(CoreError::InvalidBatch { .. })));
+    assert!(service.get_message(&any_actor(), &ids[0]).await.is_ok());
+}
+
+#[tokio::test]
+async fn unpin_messages_reports_messages_from_other_channels() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let ids = seed_channel(&service, channel, 2).await;
+    let elsewhere = seed_channel(&service, ChannelId::from(Uuid::new_v4()), 1).await;
+
+    service
+        .pin_messages(&any_actor(), &channel, &ids)
+        .await
+        .expect("pin should work");
+
+    let result = service
+        .unpin_messages(&any_actor(), &channel, &[ids[0], elsewhere[0]])
+        .await
+        .expect("unpin should work");
+    assert_eq!(result.succeeded, vec![ids[0]]);
+    assert_eq!(result.failed.len(), 1);
+    assert_eq!(result.failed[0].id, elsewhere[0]);
+
+    assert!(!service.get_message(&any_actor(), &ids[0]).await.unwrap().is_pinned);
+    assert!(service.get_message(&any_actor(), &ids[1]).await.unwrap().is_pinned);
+}
+
+fn batch_input(channel: ChannelId, content: &str, reply_to: Option<MessageId>) -> InsertMessageInput {
+    InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: channel,
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: content.to_string(),
+        reply_to_message_id: reply_to,
+        attachments: vec![],
+        ephemeral: false,
+    }
+}
+
+#[tokio::test]
+async fn create_messages_atomic_stores_parent_and_reply() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let parent = batch_input(channel, "parent", None);
+    let reply = batch_input(channel, "reply", Some(parent.id));
+    let (parent_id, reply_id) = (parent.id, reply.id);
+
+    let created = service
+        .create_messages_atomic(vec![parent, reply])
+        .await
+        .expect("batch should commit");
+
+    assert_eq!(created.len(), 2);
+    assert!(service.get_message(&any_actor(), &parent_id).await.is_ok());
+    let stored_reply = service.get_message(&any_actor(), &reply_id).await.unwrap();
+    assert_eq!(stored_reply.reply_to_message_id, Some(parent_id));
+}
+
+#[tokio::test]
+async fn create_messages_atomic_stores_nothing_when_one_insert_fails() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let existing = seed_channel(&service, channel, 1).await[0];
+
+    let first = batch_input(channel, "first", None);
+    let first_id = first.id;
+    // reuses an id that is already stored, so the second insert fails
+    let mut second = batch_input(channel, "second", None);
+    second.id = existing;
+
+    let res = service.create_messages_atomic(vec![first, second]).await;
+    assert!(matches!(res, Err(CoreError::DatabaseError { .. })));
+
+    assert!(matches!(
+        service.get_message(&any_actor(), &first_id).await,
+        Err(CoreError::MessageNotFound { .. })
+    ));
+    assert_eq!(service.get_message(&any_actor(), &existing).await.unwrap().content, "message 0");
+}
+
+#[tokio::test]
+async fn create_messages_atomic_rejects_unknown_reply_target() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let first = batch_input(channel, "first", None);
+    let first_id = first.id;
+    let orphan = batch_input(channel, "orphan", Some(MessageId::from(Uuid::new_v4())));
+
+    let res = service.create_messages_atomic(vec![first, orphan]).await;
+    assert!(matches!(res, Err(CoreError::InvalidBatch { .. })));
+    assert!(service.get_message(&any_actor(), &first_id).await.is_err());
+}
+
+#[tokio::test]
+async fn create_messages_atomic_rejects_replies_across_channels() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let (channel, other) = (ChannelId::from(Uuid::new_v4()), ChannelId::from(Uuid::new_v4()));
+    let stored = seed_channel(&service, other, 1).await[0];
+
+    let res = service
+        .create_messages_atomic(vec![batch_input(channel, "reply", Some(stored))])
+        .await;
+    assert!(matches!(res, Err(CoreError::InvalidReplyTarget { id }) if id == stored));
+
+    let parent = batch_input(other, "parent", None);
+    let reply = batch_input(channel, "reply", Some(parent.id));
+    let parent_id = parent.id;
+    let res = service.create_messages_atomic(vec![parent, reply]).await;
+    assert!(matches!(res, Err(CoreError::InvalidReplyTarget { id }) if id == parent_id));
+    assert!(service.get_message(&any_actor(), &parent_id).await.is_err());
+}
+
+#[tokio::test]
+async fn create_messages_atomic_orders_replies_after_their_parent() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let parent = batch_input(channel, "parent", None);
+    let reply = batch_input(channel, "reply", Some(parent.id));
+
+    let created = service
+        .create_messages_atomic(vec![parent, reply])
+        .await
+        .expect("batch should commit");
+    assert!(created[0].created_at < created[1].created_at);
+}
+
+#[tokio::test]
+async fn create_messages_atomic_is_rate_limited_and_checked_for_duplicates() {
+    let limiter = Arc::new(TokenBucketRateLimiter::new(
+        RateLimitPolicy::new(2, 0.01).expect("valid policy"),
+    ));
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_rate_limiter(limiter.clone())
+    .with_duplicate_detection(DuplicateContentPolicy::new(
+        DuplicateContentAction::Reject,
+        chrono::Duration::seconds(30),
+    ));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = AuthorId::from(Uuid::new_v4());
+    let input = |content: &str| InsertMessageInput {
+        author_id: author,
+        ..batch_input(channel, content, None)
+    };
+
+    let res = service
+        .create_messages_atomic(vec![input("same"), input("same")])
+        .await;
+    assert!(matches!(res, Err(CoreError::DuplicateContent)));
+
+    let res = service
+        .create_messages_atomic(vec![input("one"), input("two"), input("three")])
+        .await;
+    assert!(matches!(res, Err(CoreError::RateLimited { .. })));
+
+    // Neither refused batch kept a token
+    service
+        .create_messages_atomic(vec![input("one"), input("two")])
+        .await
+        .expect("batch within the limit should commit");
+    let res = service.create_messages_atomic(vec![input("one")]).await;
+    assert!(matches!(res, Err(CoreError::DuplicateContent)));
+    assert!(
+        limiter
+            .check_and_consume(&author, RateLimitedAction::SendMessage(channel))
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn create_message_records_one_create_event() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    service
+        .create_message(batch_input(ChannelId::from(Uuid::new_v4()), "hello", None))
+        .await
+        .expect("create should work");
+
+    let events = outbox.events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].exchange_name, "notifications");
+    assert_eq!(events[0].routing_key, "message.created");
+    assert_eq!(events[0].routing, MessageOutboxEventRouting::Create);
+}
+
+#[tokio::test]
+async fn list_channel_participants_counts_messages_per_author() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let (alice, bob) = (AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+
+    for author in [alice, bob, alice, alice] {
+        let mut input = batch_input(channel, "hi", None);
+        input.author_id = author;
+        service.create_message(input).await.expect("create should work");
+    }
+    // other channels don't count
+    service
+        .create_message(batch_input(ChannelId::from(Uuid::new_v4()), "elsewhere", None))
+        .await
+        .expect("create should work");
+
+    let (participants, total) = service
+        .list_channel_participants(&any_actor(), &channel, &GetPaginated::default())
+        .await
+        .expect("participants should work");
+
+    assert_eq!(total, 2);
+    assert_eq!(participants[0].author_id, alice);
+    assert_eq!(participants[0].message_count, 3);
+    assert_eq!(participants[1].author_id, bob);
+    assert_eq!(participants[1].message_count, 1);
+}
+
+#[tokio::test]
+async fn toggle_reaction_adds_then_removes() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let message = seed_channel(&service, ChannelId::from(Uuid::new_v4()), 1).await[0];
+    let user = Actor::from(Uuid::new_v4());
+
+    let on = service.toggle_reaction(&user, &message, "🎉").await.unwrap();
+    assert!(on.reacted);
+    assert_eq!(on.count, 1);
+
+    let off = service.toggle_reaction(&user, &message, "🎉").await.unwrap();
+    assert!(!off.reacted);
+    assert_eq!(off.count, 0);
+}
+
+#[tokio::test]
+async fn toggle_reaction_rejects_invalid_emoji_and_missing_message() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let message = seed_channel(&service, ChannelId::from(Uuid::new_v4()), 1).await[0];
+    let user = Actor::from(Uuid::new_v4());
+
+    for emoji in ["", "thumbs up", &"x".repeat(65)] {
+        let res = service.toggle_reaction(&user, &message, emoji).await;
+        assert!(matches!(res, Err(CoreError::InvalidReactionEmoji { .. })));
+    }
+
+    let missing = MessageId::from(Uuid::new_v4());
+    let res = service.toggle_reaction(&user, &missing, "🎉").await;
+    assert!(matches!(res, Err(CoreError::MessageNotFound { .. })));
+}
+
+#[tokio::test]
+async fn add_reaction_is_idempotent_and_emits_once() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let message = seed_channel(&service, ChannelId::from(Uuid::new_v4()), 1).await[0];
+    let (alice, bob) = (Actor::from(Uuid::new_v4()), Actor::from(Uuid::new_v4()));
+    outbox.clear();
+
+    let first = service.add_reaction(&alice, &message, "🎉").await.unwrap();
+    let again = service.add_reaction(&alice, &message, "🎉").await.unwrap();
+    assert!(first.reacted && again.reacted);
+    assert_eq!(again.count, 1);
+    service.add_reaction(&bob, &message, "🎉").await.unwrap();
+    service.add_reaction(&bob, &message, "👍").await.unwrap();
+
+    assert_eq!(outbox.routing_keys(), vec!["message.reaction.added"; 3]);
+
+    let reactions = service.list_reactions(&alice, &message).await.unwrap();
+    assert_eq!(reactions.len(), 2);
+    assert_eq!(reactions[0].emoji, "🎉");
+    assert_eq!(reactions[0].count, 2);
+    assert_eq!(reactions[0].user_ids, vec![alice.author_id(), bob.author_id()]);
+    assert_eq!(reactions[1].emoji, "👍");
+    assert_eq!(reactions[1].count, 1);
+}
+
+#[tokio::test]
+async fn reactions_stop_at_the_per_message_limit() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let message = seed_channel(&service, ChannelId::from(Uuid::new_v4()), 1).await[0];
+    let first = Actor::from(Uuid::new_v4());
+    service.add_reaction(&first, &message, "🎉").await.unwrap();
+    for _ in 1..MAX_REACTIONS_PER_MESSAGE {
+        service
+            .add_reaction(&Actor::from(Uuid::new_v4()), &message, "🎉")
+            .await
+            .unwrap();
+    }
+
+    let latecomer = Actor::from(Uuid::new_v4());
+    let res = service.add_reaction(&latecomer, &message, "🎉").await;
+    assert!(matches!(res, Err(CoreError::ReactionLimitReached)));
+    let res = service.toggle_reaction(&latecomer, &message, "🎉").await;
+    assert!(matches!(res, Err(CoreError::ReactionLimitReached)));
+
+    // a full message can still lose reactions, and has room again after
+    let off = service.toggle_reaction(&first, &message, "🎉").await.unwrap();
+    assert!(!off.reacted);
+    let on = service.add_reaction(&latecomer, &message, "🎉").await.unwrap();
+    assert_eq!(on.count as usize, MAX_REACTIONS_PER_MESSAGE);
+}
+
+#[tokio::test]
+async fn remove_reaction_without_one_is_a_no_op() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let message = seed_channel(&service, ChannelId::from(Uuid::new_v4()), 1).await[0];
+    let user = Actor::from(Uuid::new_v4());
+    outbox.clear();
+
+    let absent = service.remove_reaction(&user, &message, "🎉").await.unwrap();
+    assert!(!absent.reacted);
+    assert_eq!(absent.count, 0);
+    assert!(outbox.routing_keys().is_empty());
+
+    service.add_reaction(&user, &message, "🎉").await.unwrap();
+    let removed = service.remove_reaction(&user, &message, "🎉").await.unwrap();
+    assert_eq!(removed.count, 0);
+    assert_eq!(
+        outbox.routing_keys(),
+        vec!["message.reaction.added", "message.reaction.removed"]
+    );
+
+    let missing = MessageId::from(Uuid::new_v4());
+    let res = service.remove_reaction(&user, &missing, "🎉").await;
+    assert!(matches!(res, Err(CoreError::MessageNotFound { .. })));
+}
+
+#[tokio::test]
+async fn pin_message_stores_reason_and_unpin_clears_it() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let id = seed_channel(&service, channel, 1).await[0];
+    let moderator = Actor::from(Uuid::new_v4());
+
+    let pinned = service
+        .pin_message(&moderator, &id, Some("  rules of the channel ".into()))
+        .await
+        .expect("pin should work");
+    assert!(pinned.is_pinned);
+    assert_eq!(pinned.pinned_by, Some(moderator.author_id()));
+    assert!(pinned.pinned_at.is_some());
+    assert_eq!(pinned.pin_reason.as_deref(), Some("rules of the channel"));
+
+    let listed = service.list_pinned_messages(&moderator, &channel).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].pin_reason.as_deref(), Some("rules of the channel"));
+
+    let unpinned = service.unpin_message(&moderator, &id).await.expect("unpin should work");
+    assert!(!unpinned.is_pinned);
+    assert!(unpinned.pinned_by.is_none());
+    assert!(unpinned.pinned_at.is_none());
+    assert!(unpinned.pin_reason.is_none());
+    assert!(service.list_pinned_messages(&moderator, &channel).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn pin_and_unpin_emit_dedicated_events() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let id = seed_channel(&service, channel, 1).await[0];
+    let moderator = Actor::from(Uuid::new_v4());
+    outbox.clear();
+
+    service.pin_message(&moderator, &id, Some("keep".into())).await.unwrap();
+    service.unpin_message(&moderator, &id).await.unwrap();
+
+    assert_eq!(outbox.routing_keys(), vec!["message.pinned", "message.unpinned"]);
+    let events = outbox.events();
+    let pinned = MessagePinEvent::decode(events[0].payload.as_slice()).unwrap();
+    assert_eq!(pinned.message_id, id.to_string());
+    assert_eq!(pinned.channel_id, channel.to_string());
+    assert_eq!(pinned.actor_id, moderator.author_id().to_string());
+    assert_eq!(pinned.reason, "keep");
+    let unpinned = MessagePinEvent::decode(events[1].payload.as_slice()).unwrap();
+    assert!(unpinned.reason.is_empty());
+}
+
+#[tokio::test]
+async fn pin_message_rejects_overlong_reason() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let id = seed_channel(&service, ChannelId::from(Uuid::new_v4()), 1).await[0];
+
+    let reason = "x".repeat(MAX_PIN_REASON_CHARS + 1);
+    let res = service
+        .pin_message(&any_actor(), &id, Some(reason))
+        .await;
+    assert!(matches!(res, Err(CoreError::PinReasonTooLong { .. })));
+    assert!(!service.get_message(&any_actor(), &id).await.unwrap().is_pinned);
+}
+
+#[tokio::test]
+async fn reorder_pins_changes_pinned_list_order() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let moderator = any_actor();
+    let ids = seed_channel(&service, channel, 3).await;
+    for id in &ids {
+        service
+            .pin_message(&moderator, id, None)
+            .await
+            .expect("pin should work");
+    }
+
+    let order = vec![ids[2], ids[0], ids[1]];
+    let reordered = service
+        .reorder_pins(&moderator, &channel, &order)
+        .await
+        .expect("reorder should work");
+    let returned: Vec<MessageId> = reordered.iter().map(|m| m.id).collect();
+    assert_eq!(returned, order);
+
+    let listed: Vec<MessageId> = service
+        .list_pinned_messages(&moderator, &channel)
+        .await
+        .unwrap()
+        .iter()
+        .map(|m| m.id)
+        .collect();
+    assert_eq!(listed, order);
+
+    // unpinning drops the position, so a later pin starts unordered again
+    service.unpin_message(&moderator, &ids[0]).await.unwrap();
+    let repinned = service.pin_message(&moderator, &ids[0], None).await.unwrap();
+    assert_eq!(repinned.pin_position, None);
+}
+
+#[tokio::test]
+async fn reorder_pins_is_announced_as_one_summary_not_as_edits() {
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_bulk_event_mode(BulkEventMode::PerItem);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let moderator = any_actor();
+    let ids = seed_channel(&service, channel, 2).await;
+    service
+        .pin_messages(&moderator, &channel, &ids)
+        .await
+        .expect("pin should work");
+    let before = outbox.routing_keys().len();
+
+    service
+        .reorder_pins(&moderator, &channel, &[ids[1], ids[0]])
+        .await
+        .expect("reorder should work");
+
+    assert_eq!(outbox.routing_keys()[before..], ["messages.bulk_changed"]);
+}
+
+#[tokio::test]
+async fn reorder_pins_rejects_ids_that_are_not_the_channel_pins() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let moderator = any_actor();
+    let ids = seed_channel(&service, channel, 3).await;
+    service
+        .pin_messages(&moderator, &channel, &ids[..2])
+        .await
+        .expect("pin should work");
+
+    for order in [
+        vec![ids[0], ids[2]],
+        vec![ids[0], ids[0]],
+        vec![ids[0]],
+    ] {
+        let res = service.reorder_pins(&moderator, &channel, &order).await;
+        assert!(matches!(res, Err(CoreError::InvalidPinOrder { .. })));
+    }
+}
+
+#[tokio::test]
+async fn set_sticky_is_reported_in_channel_metadata_apart_from_pins() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let moderator = any_actor();
+    let ids = seed_channel(&service, channel, 2).await;
+
+    let metadata = service.get_channel_metadata(&moderator, &channel).await.unwrap();
+    assert_eq!(metadata.sticky_message_id, None);
+
+    let set = service
+        .set_sticky(&moderator, &channel, &ids[0])
+        .await
+        .expect("set sticky should work");
+    assert_eq!(set.sticky_message_id, Some(ids[0]));
+
+    let metadata = service.get_channel_metadata(&moderator, &channel).await.unwrap();
+    assert_eq!(metadata.sticky_message_id, Some(ids[0]));
+    assert_eq!(metadata.sticky_message.map(|m| m.id), Some(ids[0]));
+    assert!(service.list_pinned_messages(&moderator, &channel).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn setting_another_sticky_replaces_the_previous_one() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let moderator = any_actor();
+    let ids = seed_channel(&service, channel, 2).await;
+
+    service.set_sticky(&moderator, &channel, &ids[0]).await.unwrap();
+    service.set_sticky(&moderator, &channel, &ids[1]).await.unwrap();
+
+    let metadata = service.get_channel_metadata(&moderator, &channel).await.unwrap();
+    assert_eq!(metadata.sticky_message_id, Some(ids[1]));
+}
+
+#[tokio::test]
+async fn clear_sticky_leaves_the_channel_without_one() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let moderator = any_actor();
+    let ids = seed_channel(&service, channel, 1).await;
+    service.set_sticky(&moderator, &channel, &ids[0]).await.unwrap();
+
+    let cleared = service
+        .clear_sticky(&moderator, &channel)
+        .await
+        .expect("clear sticky should work");
+    assert_eq!(cleared.sticky_message_id, None);
+
+    let metadata = service.get_channel_metadata(&moderator, &channel).await.unwrap();
+    assert_eq!(metadata.sticky_message_id, None);
+    assert!(metadata.sticky_message.is_none());
+
+    // clearing twice is not an error
+    service.clear_sticky(&moderator, &channel).await.unwrap();
+}
+
+#[tokio::test]
+async fn set_sticky_rejects_a_message_from_another_channel() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+    let channel = ChannelId::from(Uuid::new_v4());
+    let other = ChannelId::from(Uuid::new_v4());
+    let moderator = any_actor();
+    let ids = seed_channel(&service, other, 1).await;
+
+    let res = service.set_sticky(&moderator, &channel, &ids[0]).await;
+    assert!(matches!(res, Err(CoreError::MessageNotInChannel { .. })));
+
+    let metadata = service.get_channel_metadata(&moderator, &channel).await.unwrap();
+    assert_eq!(metadata.sticky_message_id, None);
+}
+
+#[tokio::test]
+async fn list_messages_resolves_shared_attachments_once() {
+    let attachment = MockAttachmentRepository::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        attachment.clone(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let shared = AttachmentId::from(Uuid::new_v4());
+    let own = AttachmentId::from(Uuid::new_v4());
+    let first = MessageId::from(Uuid::new_v4());
+    let second = MessageId::from(Uuid::new_v4());
+
+    for (id, attachments) in [(first, vec![shared]), (second, vec![own, shared])] {
+        service
+            .create_message(InsertMessageInput {
+                id,
+                channel_id: channel,
+                author_id: AuthorId::from(Uuid::new_v4()),
+                content: "with attachments".into(),
+                reply_to_message_id: None,
+                attachments,
+                ephemeral: false,
+            })
+            .await
+            .expect("create should work");
+    }
+
+    let (messages, _) = service
+        .list_messages(&any_actor(), &channel, &Default::default())
+        .await
+        .expect("list should work");
+
+    let mut requested = attachment.requested_ids();
+    assert_eq!(requested.len(), 2);
+    requested.sort_by_key(|id| id.0);
+    let mut expected = vec![shared, own];
+    expected.sort_by_key(|id| id.0);
+    assert_eq!(requested, expected);
+
+    let by_id = |id: MessageId| messages.iter().find(|m| m.id == id).expect("message listed");
+    let first_ids: Vec<AttachmentId> = by_id(first).attachments.iter().map(|a| a.id).collect();
+    let second_ids: Vec<AttachmentId> = by_id(second).attachments.iter().map(|a| a.id).collect();
+    assert_eq!(first_ids, vec![shared]);
+    assert_eq!(second_ids, vec![own, shared]);
+}
+
+#[tokio::test]
+async fn list_messages_before_pages_until_the_oldest_message() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = AuthorId::from(Uuid::new_v4());
+    for i in 0..3 {
+        service
+            .create_message(InsertMessageInput {
+                id: MessageId::from(Uuid::new_v4()),
+                channel_id: channel,
+                author_id: author,
+                content: format!("message {i}"),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("create should work");
+    }
+
+    let (first, cursor) = service
+        .list_messages_before(&any_actor(), &channel, None, 2)
+        .await
+        .expect("first page");
+    assert_eq!(first.len(), 2);
+    assert_eq!(cursor, Some(first[1].id));
+
+    let (second, cursor) = service
+        .list_messages_before(&any_actor(), &channel, cursor.as_ref(), 2)
+        .await
+        .expect("second page");
+    assert_eq!(second.len(), 1);
+    assert!(first.iter().all(|m| m.id != second[0].id));
+    assert_eq!(cursor, None);
+}
+
+#[tokio::test]
+async fn list_messages_before_pages_past_a_deleted_cursor() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = AuthorId::from(Uuid::new_v4());
+    let mut ids = Vec::new();
+    for content in ["older", "soon gone"] {
+        let id = MessageId::from(Uuid::new_v4());
+        service
+            .create_message(InsertMessageInput {
+                id,
+                channel_id: channel,
+                author_id: author,
+                content: content.into(),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("create should work");
+        ids.push(id);
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+    service
+        .delete_message(&Actor::from(author), &ids[1])
+        .await
+        .expect("delete should work");
+
+    // soft-deleted messages keep their place, so the cursor still works
+    let (page, _) = service
+        .list_messages_before(&any_actor(), &channel, Some(&ids[1]), 20)
+        .await
+        .expect("paging past a deleted cursor should work");
+    assert_eq!(page.iter().map(|m| m.id).collect::<Vec<_>>(), vec![ids[0]]);
+}
+
+#[tokio::test]
+async fn list_messages_before_rejects_a_purged_cursor() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_erasure_mode(ErasureMode::Delete);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = AuthorId::from(Uuid::new_v4());
+    let id = MessageId::from(Uuid::new_v4());
+    service
+        .create_message(InsertMessageInput {
+            id,
+            channel_id: channel,
+            author_id: author,
+            content: "soon gone".into(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("create should work");
+    service
+        .erase_user_data(&author)
+        .await
+        .expect("erase should work");
+
+    let res = service
+        .list_messages_before(&any_actor(), &channel, Some(&id), 20)
+        .await;
+    assert!(matches!(res, Err(CoreError::InvalidCursor { id: cursor }) if cursor == id));
+}
+
+#[tokio::test]
+async fn deleting_a_message_twice_emits_one_deleted_event() {
+    let repo = MockMessageRepository::new();
+    let outbox = MockOutboxEventRepository::new();
+    let service = Service::new(
+        repo.clone(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer));
+
+    let author = AuthorId::from(Uuid::new_v4());
+    let id = MessageId::from(Uuid::new_v4());
+    service
+        .create_message(InsertMessageInput {
+            id,
+            channel_id: ChannelId::from(Uuid::new_v4()),
+            author_id: author,
+            content: "kept for audit".into(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("create should work");
+    outbox.clear();
+
+    service
+        .delete_message(&Actor::from(author), &id)
+        .await
+        .expect("delete should work");
+    let again = service.delete_message(&Actor::from(author), &id).await;
+    assert!(matches!(again, Err(CoreError::MessageNotFound { .. })));
+
+    assert_eq!(outbox.routing_keys(), vec!["message.deleted"]);
+    let kept = repo
+        .find_including_deleted(&id)
+        .await
+        .expect("find should work")
+        .expect("soft-deleted message should still exist");
+    assert_eq!(kept.content, "kept for audit");
+}
+
+#[tokio::test]
+async fn content_at_the_length_limit_is_accepted_and_one_byte_over_rejected() {
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_max_content_length(10);
+    let author = Uuid::new_v4();
+    let input = |content: &str| InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: ChannelId::from(Uuid::new_v4()),
+        author_id: AuthorId::from(author),
+        content: content.into(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    };
+
+    let created = service
+        .create_message(input("0123456789"))
+        .await
+        .expect("content at the limit should be accepted");
+
+    let res = service.create_message(input("0123456789a")).await;
+    assert!(matches!(res, Err(CoreError::MessageTooLong { max: 10 })));
+
+    // the limit counts bytes, so a multi-byte character can tip it over
+    let res = service.create_message(input("012345678é")).await;
+    assert!(matches!(res, Err(CoreError::MessageTooLong { max: 10 })));
+
+    let res = service
+        .update_message(
+            &Actor::from(author),
+            UpdateMessageInput {
+                id: created.message.id,
+                content: Some("0123456789a".into()),
+            },
+        )
+        .await;
+    assert!(matches!(res, Err(CoreError::MessageTooLong { max: 10 })));
+}
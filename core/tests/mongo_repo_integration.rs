@@ -1,13 +1,16 @@
-use messages_core::domain::common::GetPaginated;
+use messages_core::domain::common::{CoreError, GetPaginated};
 use messages_core::domain::message::entities::{
-    AttachmentId, AuthorId, ChannelId, InsertMessageInput, MessageId,
-    UpdateMessageInput,
+    AttachmentId, AuthorId, ChannelId, ChannelReadState, InsertMessageInput, MAX_PINS_PER_CHANNEL,
+    MessageId, UpdateMessageInput,
 };
 use messages_core::domain::message::ports::MessageRepository;
-use messages_core::infrastructure::message::repositories::mongo::MongoMessageRepository;
+use messages_core::infrastructure::message::repositories::mongo::{MongoMessageRepository, literal_pattern};
 use mongodb::{Client, options::ClientOptions};
 use uuid::Uuid;
 
+mod common;
+use common::{TestMongo, stop_docker_container, try_start_docker_mongo};
+
 // Optional testcontainers integration to allow `cargo test` without env vars.
 // If Docker is available, spin up a temporary MongoDB container.
 #[tokio::test]
@@ -90,6 +93,7 @@ async fn mongo_repository_crud_flow() {
         content: "mongo hello".to_string(),
         reply_to_message_id: None,
         attachments: vec![AttachmentId::from(Uuid::new_v4())],
+        ephemeral: false,
     };
 
     // Insert
@@ -135,7 +139,6 @@ async fn mongo_repository_crud_flow() {
     let update_input = UpdateMessageInput {
         id,
         content: Some("updated mongo".into()),
-        is_pinned: Some(true),
     };
     let updated = repo
         .update(update_input)
@@ -160,83 +163,548 @@ async fn mongo_repository_crud_flow() {
     }
 }
 
-fn stop_docker_container(container_id: &str) -> Result<(), String> {
-    use std::process::Command;
-    let out = Command::new("docker")
-        .args(["rm", "-f", container_id])
-        .output()
-        .map_err(|e| format!("failed to stop docker container: {}", e))?;
-
-    if !out.status.success() {
-        return Err(format!(
-            "docker rm failed: {}",
-            String::from_utf8_lossy(&out.stderr)
-        ));
+#[tokio::test]
+async fn mongo_repository_still_refuses_real_conflicts() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let input = InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: ChannelId::from(Uuid::new_v4()),
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: "first".to_string(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    };
+    repo.insert(input.clone()).await.expect("insert should succeed");
+
+    // another message reusing the id is not mistaken for a replayed insert
+    let res = repo
+        .insert(InsertMessageInput {
+            content: "second".to_string(),
+            ..input.clone()
+        })
+        .await;
+    assert!(matches!(res, Err(CoreError::DatabaseError { .. })));
+
+    // a second deletion isn't mistaken for a replay of the first either
+    repo.delete(&input.id).await.expect("delete should succeed");
+    let res = repo.delete(&input.id).await;
+    assert!(matches!(res, Err(CoreError::MessageNotFound { .. })));
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn mongo_repository_preserves_attachment_order() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let id = MessageId::from(Uuid::new_v4());
+    let channel = ChannelId::from(Uuid::new_v4());
+    let attachments: Vec<AttachmentId> = (0..3)
+        .map(|_| AttachmentId::from(Uuid::new_v4()))
+        .collect();
+
+    repo.insert(InsertMessageInput {
+        id,
+        channel_id: channel,
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: "three attachments".to_string(),
+        reply_to_message_id: None,
+        attachments: attachments.clone(),
+        ephemeral: false,
+    })
+    .await
+    .expect("insert should succeed");
+
+    let found = repo
+        .find_by_id(&id)
+        .await
+        .expect("find should succeed")
+        .expect("message should exist");
+    assert_eq!(found.attachments, attachments);
+
+    let (list, _) = repo
+        .list(&channel, &GetPaginated::default())
+        .await
+        .expect("list should succeed");
+    assert_eq!(list[0].attachments, attachments);
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn mongo_repository_toggles_reaction_on_then_off() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let id = MessageId::from(Uuid::new_v4());
+    repo.insert(InsertMessageInput {
+        id,
+        channel_id: ChannelId::from(Uuid::new_v4()),
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: "react to me".to_string(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    })
+    .await
+    .expect("insert should succeed");
+
+    let (alice, bob) = (AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+
+    let on = repo.toggle_reaction(&id, &alice, "👍").await.expect("toggle on");
+    assert!(on.reacted);
+    assert_eq!(on.count, 1);
+
+    let other = repo.toggle_reaction(&id, &bob, "👍").await.expect("second user");
+    assert!(other.reacted);
+    assert_eq!(other.count, 2);
+
+    let off = repo.toggle_reaction(&id, &alice, "👍").await.expect("toggle off");
+    assert!(!off.reacted);
+    assert_eq!(off.count, 1);
+
+    // the document still reads back as a message
+    assert!(repo.find_by_id(&id).await.expect("find should succeed").is_some());
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn mongo_repository_adds_and_removes_reactions() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let id = MessageId::from(Uuid::new_v4());
+    repo.insert(InsertMessageInput {
+        id,
+        channel_id: ChannelId::from(Uuid::new_v4()),
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: "react to me".to_string(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    })
+    .await
+    .expect("insert should succeed");
+
+    let (alice, bob) = (AuthorId::from(Uuid::new_v4()), AuthorId::from(Uuid::new_v4()));
+
+    assert!(repo.add_reaction(&id, &alice, "👍").await.expect("add"));
+    assert!(!repo.add_reaction(&id, &alice, "👍").await.expect("add again"));
+    assert!(repo.add_reaction(&id, &bob, "👍").await.expect("second user"));
+
+    let reactions = repo.list_reactions(&id).await.expect("list");
+    assert_eq!(reactions.len(), 1);
+    assert_eq!(reactions[0].count, 2);
+    assert_eq!(reactions[0].user_ids, vec![alice, bob]);
+
+    assert!(repo.remove_reaction(&id, &alice, "👍").await.expect("remove"));
+    assert!(!repo.remove_reaction(&id, &alice, "👍").await.expect("remove again"));
+    assert_eq!(repo.list_reactions(&id).await.expect("list")[0].user_ids, vec![bob]);
+
+    let missing = MessageId::from(Uuid::new_v4());
+    let res = repo.add_reaction(&missing, &alice, "👍").await;
+    assert!(matches!(res, Err(CoreError::MessageNotFound { .. })));
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn mongo_repository_stores_pin_reason_until_unpinned() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let id = MessageId::from(Uuid::new_v4());
+    let channel = ChannelId::from(Uuid::new_v4());
+    repo.insert(InsertMessageInput {
+        id,
+        channel_id: channel,
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: "read this first".to_string(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    })
+    .await
+    .expect("insert should succeed");
+
+    let moderator = AuthorId::from(Uuid::new_v4());
+    repo.pin(&id, &moderator, Some("onboarding"), MAX_PINS_PER_CHANNEL)
+        .await
+        .expect("pin should succeed");
+
+    let pinned = repo.list_pinned(&channel).await.expect("list pinned");
+    assert_eq!(pinned.len(), 1);
+    assert_eq!(pinned[0].pinned_by, Some(moderator));
+    assert!(pinned[0].pinned_at.is_some());
+    assert_eq!(pinned[0].pin_reason.as_deref(), Some("onboarding"));
+
+    let unpinned = repo.unpin(&id).await.expect("unpin should succeed");
+    assert!(unpinned.pin_reason.is_none());
+    assert!(unpinned.pinned_by.is_none());
+    assert!(repo.list_pinned(&channel).await.unwrap().is_empty());
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn mongo_repository_refuses_pins_over_the_limit() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let moderator = AuthorId::from(Uuid::new_v4());
+    let mut ids = Vec::new();
+    for i in 0..2 {
+        let id = MessageId::from(Uuid::new_v4());
+        repo.insert(InsertMessageInput {
+            id,
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: format!("pin {i}"),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("insert should succeed");
+        ids.push(id);
     }
-    Ok(())
+
+    repo.pin(&ids[0], &moderator, None, 1)
+        .await
+        .expect("pin should succeed");
+    let res = repo.pin(&ids[1], &moderator, None, 1).await;
+    assert!(matches!(res, Err(CoreError::PinLimitReached)));
+
+    // re-pinning what is already pinned doesn't count twice
+    repo.pin(&ids[0], &moderator, Some("still here"), 1)
+        .await
+        .expect("re-pin should succeed");
+    let pinned = repo.list_pinned(&channel).await.expect("list pinned");
+    assert_eq!(pinned.len(), 1);
+    assert_eq!(pinned[0].id, ids[0]);
+    assert_eq!(pinned[0].pin_reason.as_deref(), Some("still here"));
+
+    mongo.teardown().await;
 }
 
-async fn try_start_docker_mongo() -> Result<(String, String), String> {
-    // Start a docker mongo container via the `docker` CLI and return (uri, container_id).
-    use std::process::Command;
+#[tokio::test]
+async fn mongo_repository_lists_pins_by_position() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let moderator = AuthorId::from(Uuid::new_v4());
+    let mut ids = Vec::new();
+    for i in 0..3 {
+        let id = MessageId::from(Uuid::new_v4());
+        repo.insert(InsertMessageInput {
+            id,
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: format!("pin {i}"),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("insert should succeed");
+        repo.pin(&id, &moderator, None, MAX_PINS_PER_CHANNEL)
+            .await
+            .expect("pin should succeed");
+        ids.push(id);
+    }
+
+    let order = [ids[1], ids[2], ids[0]];
+    repo.set_pin_positions(&order)
+        .await
+        .expect("positions should be stored");
+
+    let pinned = repo.list_pinned(&channel).await.expect("list pinned");
+    let listed: Vec<MessageId> = pinned.iter().map(|m| m.id).collect();
+    assert_eq!(listed, order.to_vec());
+    assert_eq!(pinned[0].pin_position, Some(0));
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn mongo_repository_pages_with_a_cursor() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let mut ids = Vec::new();
+    for i in 0..5 {
+        let id = MessageId::from(Uuid::new_v4());
+        repo.insert(InsertMessageInput {
+            id,
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: format!("message {i}"),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("insert should succeed");
+        ids.push(id);
+    }
+    ids.reverse();
+
+    let (first, cursor) = repo.list_before(&channel, None, 2).await.expect("first page");
+    assert_eq!(first.iter().map(|m| m.id).collect::<Vec<_>>(), ids[..2].to_vec());
+    assert_eq!(cursor, Some(ids[1]));
+
+    let (second, cursor) = repo
+        .list_before(&channel, cursor.as_ref(), 2)
+        .await
+        .expect("second page");
+    assert_eq!(second.iter().map(|m| m.id).collect::<Vec<_>>(), ids[2..4].to_vec());
+
+    let (last, cursor) = repo
+        .list_before(&channel, cursor.as_ref(), 2)
+        .await
+        .expect("last page");
+    assert_eq!(last.iter().map(|m| m.id).collect::<Vec<_>>(), ids[4..].to_vec());
+    assert_eq!(cursor, None);
+
+    // a soft-deleted cursor keeps its place in the channel
+    repo.delete(&ids[1]).await.expect("delete should succeed");
+    let (after_deleted, _) = repo
+        .list_before(&channel, Some(&ids[1]), 2)
+        .await
+        .expect("page after a deleted cursor");
+    assert_eq!(after_deleted.iter().map(|m| m.id).collect::<Vec<_>>(), ids[2..4].to_vec());
+
+    // a purged cursor has no timestamp left to page from
+    repo.purge(&ids[1]).await.expect("purge should succeed");
+    let res = repo.list_before(&channel, Some(&ids[1]), 2).await;
+    assert!(matches!(res, Err(CoreError::InvalidCursor { .. })));
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn mongo_repository_lists_a_time_range_oldest_first() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let mut messages = Vec::new();
+    for i in 0..4 {
+        let message = repo
+            .insert(InsertMessageInput {
+                id: MessageId::from(Uuid::new_v4()),
+                channel_id: channel,
+                author_id: AuthorId::from(Uuid::new_v4()),
+                content: format!("message {i}"),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("insert should succeed");
+        messages.push(message);
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+    let ids: Vec<MessageId> = messages.iter().map(|m| m.id).collect();
+
+    // the start is included, the end excluded
+    let (listed, total) = repo
+        .list_in_range(&channel, messages[0].created_at, messages[3].created_at, &GetPaginated::default())
+        .await
+        .expect("range listing should succeed");
+    assert_eq!(listed.iter().map(|m| m.id).collect::<Vec<_>>(), ids[..3].to_vec());
+    assert_eq!(total, 3);
+
+    // soft-deleted messages are left out
+    repo.delete(&ids[1]).await.expect("delete should succeed");
+    let (listed, total) = repo
+        .list_in_range(&channel, messages[0].created_at, messages[3].created_at, &GetPaginated::default())
+        .await
+        .unwrap();
+    assert_eq!(listed.iter().map(|m| m.id).collect::<Vec<_>>(), vec![ids[0], ids[2]]);
+    assert_eq!(total, 2);
 
-    // Ensure `docker` is available
-    let docker_check = Command::new("docker").arg("version").output();
-    if docker_check.is_err() {
-        return Err("docker CLI not found".into());
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn mongo_repository_lists_one_item_for_page_and_limit_zero() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    for i in 0..3 {
+        repo.insert(InsertMessageInput {
+            id: MessageId::from(Uuid::new_v4()),
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: format!("message {i}"),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("insert should succeed");
     }
+    let zero = GetPaginated { page: 0, limit: 0 };
 
-    // Start container with random host port mapping (-P) and capture container id
-    let name = format!("test-mongo-{}", Uuid::new_v4().to_string());
-    let run = Command::new("docker")
-        .args(["run", "-d", "-P", "--rm", "--name", &name, "mongo:6.0"])
-        .output()
-        .map_err(|e| format!("failed to run docker: {}", e))?;
+    // a limit of 0 would mean "no limit" to MongoDB
+    let (list, total) = repo.list(&channel, &zero).await.expect("list should succeed");
+    assert_eq!((list.len(), total), (1, 3));
 
-    if !run.status.success() {
-        let stderr = String::from_utf8_lossy(&run.stderr);
-        return Err(format!("docker run failed: {}", stderr));
+    let (found, total) = repo
+        .search_messages(&channel, &"message".into(), &zero)
+        .await
+        .expect("search should succeed");
+    assert_eq!((found.len(), total), (1, 3));
+
+    let (participants, total) = repo
+        .list_channel_participants(&channel, &zero)
+        .await
+        .expect("participants should succeed");
+    assert_eq!((participants.len(), total), (1, 3));
+
+    mongo.teardown().await;
+}
+
+#[test]
+fn literal_pattern_escapes_regex_metacharacters() {
+    assert_eq!(literal_pattern("a.b*(c)"), r"a\.b\*\(c\)");
+    assert_eq!(literal_pattern("plain text"), "plain text");
+}
+
+#[tokio::test]
+async fn mongo_repository_search_matches_metacharacters_literally() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    for content in ["price (USD)", "a*b", "aab", "v1.2", "v132"] {
+        repo.insert(InsertMessageInput {
+            id: MessageId::from(Uuid::new_v4()),
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: content.to_string(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("insert should succeed");
     }
 
-    let container_id = String::from_utf8_lossy(&run.stdout).trim().to_string();
-
-    // Get mapped port for 27017
-    let port_out = Command::new("docker")
-        .args(["port", &container_id, "27017"])
-        .output()
-        .map_err(|e| format!("failed to query docker port: {}", e))?;
-
-    if !port_out.status.success() {
-        // Try a short wait and retry once
-        std::thread::sleep(std::time::Duration::from_millis(200));
-        let port_out = Command::new("docker")
-            .args(["port", &container_id, "27017"])
-            .output()
-            .map_err(|e| format!("failed to query docker port: {}", e))?;
-        if !port_out.status.success() {
-            let stderr = String::from_utf8_lossy(&port_out.stderr);
-            // cleanup container
-            let _ = Command::new("docker")
-                .args(["rm", "-f", &container_id])
-                .output();
-            return Err(format!("docker port query failed: {}", stderr));
+    let search = |query: &'static str| {
+        let repo = &repo;
+        async move {
+            let (found, _) = repo
+                .search_messages(&channel, &query.into(), &GetPaginated::default())
+                .await
+                .expect("search should succeed");
+            let mut contents: Vec<String> = found.into_iter().map(|m| m.content).collect();
+            contents.sort();
+            contents
         }
-        let out = String::from_utf8_lossy(&port_out.stdout);
-        let host_port = out
-            .trim()
-            .rsplit(':')
-            .next()
-            .ok_or_else(|| "failed to parse docker port output".to_string())?;
-        let uri = format!("mongodb://127.0.0.1:{}", host_port);
-        return Ok((uri, container_id));
+    };
+
+    assert_eq!(search("(usd").await, vec!["price (USD)"]);
+    assert_eq!(search("a*b").await, vec!["a*b"]);
+    assert_eq!(search("1.2").await, vec!["v1.2"]);
+    // an unbalanced pattern is plain text, not a regex error
+    assert!(search("(.*").await.is_empty());
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn mongo_repository_keeps_read_state_and_counts_later_messages() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+    let channel = ChannelId::from(Uuid::new_v4());
+    let user = AuthorId::from(Uuid::new_v4());
+
+    let mut messages = Vec::new();
+    for i in 0..3 {
+        let message = repo
+            .insert(InsertMessageInput {
+                id: MessageId::from(Uuid::new_v4()),
+                channel_id: channel,
+                author_id: AuthorId::from(Uuid::new_v4()),
+                content: format!("message {i}"),
+                reply_to_message_id: None,
+                attachments: vec![],
+                ephemeral: false,
+            })
+            .await
+            .expect("insert should succeed");
+        messages.push(message);
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
     }
 
-    let out = String::from_utf8_lossy(&port_out.stdout);
-    let host_port = out
-        .trim()
-        .rsplit(':')
-        .next()
-        .ok_or_else(|| "failed to parse docker port output".to_string())?;
-    let uri = format!("mongodb://127.0.0.1:{}", host_port);
-    Ok((uri, container_id))
+    assert!(repo.get_last_read(&user, &channel).await.unwrap().is_none());
+    assert_eq!(repo.count_created_after(&channel, None).await.unwrap(), 3);
+
+    let state = ChannelReadState {
+        user_id: user,
+        channel_id: channel,
+        last_read_message_id: messages[0].id,
+        updated_at: chrono::Utc::now(),
+    };
+    assert!(repo.set_last_read(&state, messages[0].created_at).await.expect("set should succeed"));
+    let state = ChannelReadState {
+        last_read_message_id: messages[1].id,
+        ..state
+    };
+    assert!(repo.set_last_read(&state, messages[1].created_at).await.expect("set should succeed"));
+
+    // A read less far, e.g. from a concurrent call, leaves the state alone
+    let older = ChannelReadState {
+        last_read_message_id: messages[0].id,
+        ..state.clone()
+    };
+    assert!(!repo.set_last_read(&older, messages[0].created_at).await.expect("set should succeed"));
+
+    let stored = repo.get_last_read(&user, &channel).await.unwrap().unwrap();
+    assert_eq!(stored.last_read_message_id, messages[1].id);
+    assert_eq!(stored.updated_at.timestamp(), state.updated_at.timestamp());
+    assert_eq!(
+        repo.count_created_after(&channel, Some(messages[1].created_at)).await.unwrap(),
+        1
+    );
+
+    assert_eq!(repo.delete_read_states(&user).await.unwrap(), 1);
+    assert!(repo.get_last_read(&user, &channel).await.unwrap().is_none());
+
+    mongo.teardown().await;
 }
@@ -0,0 +1,74 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use messages_core::infrastructure::retry::{RetryPolicy, RetryableError, with_retry};
+
+#[derive(Debug)]
+struct StubError {
+    retryable: bool,
+}
+
+impl std::fmt::Display for StubError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "stub error (retryable: {})", self.retryable)
+    }
+}
+
+impl RetryableError for StubError {
+    fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+}
+
+fn fast_policy(max_retries: u32) -> RetryPolicy {
+    RetryPolicy::new(max_retries, Duration::from_millis(1))
+}
+
+#[tokio::test]
+async fn transient_error_is_retried_until_success() {
+    let attempts = AtomicU32::new(0);
+
+    let result = with_retry(&fast_policy(3), || {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+        async move {
+            if attempt == 0 {
+                Err(StubError { retryable: true })
+            } else {
+                Ok("done")
+            }
+        }
+    })
+    .await;
+
+    assert_eq!(result.expect("operation should eventually succeed"), "done");
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn non_retryable_error_fails_immediately() {
+    let attempts = AtomicU32::new(0);
+
+    let result: Result<(), StubError> = with_retry(&fast_policy(3), || {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        async { Err(StubError { retryable: false }) }
+    })
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn retries_stop_at_configured_count() {
+    let attempts = AtomicU32::new(0);
+
+    let result: Result<(), StubError> = with_retry(&fast_policy(2), || {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        async { Err(StubError { retryable: true }) }
+    })
+    .await;
+
+    assert!(result.is_err());
+    // first attempt + 2 retries
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use messages_core::domain::attachment::port::MockAttachmentRepository;
+use messages_core::domain::authorization::entities::Actor;
+use messages_core::domain::authorization::ports::AllowAllAuthorizer;
+use messages_core::domain::common::services::Service;
+use messages_core::domain::health::port::MockHealthRepository;
+use messages_core::domain::message::entities::{
+    AuthorId, ChannelId, InsertMessageInput, MessageId, UpdateMessageInput,
+};
+use messages_core::domain::message::normalization::{ContentNormalization, normalize_content};
+use messages_core::domain::message::ports::{MessageService, MockMessageRepository};
+use messages_core::domain::outbox::ports::MockOutboxEventRepository;
+use uuid::Uuid;
+
+#[test]
+fn collapses_blank_line_runs() {
+    assert_eq!(normalize_content("a\n\n\n\n\nb", 2), "a\n\n\nb");
+    assert_eq!(normalize_content("a\n\nb", 2), "a\n\nb");
+    assert_eq!(normalize_content("a\n \n\t\n\nb", 1), "a\n\nb");
+}
+
+#[test]
+fn strips_zero_width_and_control_characters() {
+    assert_eq!(normalize_content("he\u{200B}llo\u{FEFF} wo\u{0007}rld", 2), "hello world");
+    assert_eq!(normalize_content("tab\tkept", 2), "tab\tkept");
+}
+
+#[test]
+fn trims_message_and_trailing_whitespace() {
+    assert_eq!(normalize_content("  \n hello   \nworld\t \n\n", 2), "hello\nworld");
+}
+
+#[test]
+fn preserves_code_blocks_verbatim() {
+    let code = "```rust\nfn main() {   \n\n\n\n    let a = \"\u{200B}\";\n}\n```";
+    let content = format!("look:   \n\n\n\n{code}\n\n\n\ndone");
+
+    let normalized = normalize_content(&content, 1);
+
+    assert_eq!(normalized, format!("look:\n\n{code}\n\ndone"));
+}
+
+#[test]
+fn disabled_normalization_is_a_no_op() {
+    let content = " a\u{200B}\n\n\n\nb ";
+    assert_eq!(ContentNormalization::default().apply(content), content);
+}
+
+#[tokio::test]
+async fn service_normalizes_content_on_create_and_update() {
+    let repo = MockMessageRepository::new();
+    let service = Service::new(
+        repo,
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_content_normalization(ContentNormalization::new(true, 1));
+
+    let id = MessageId::from(Uuid::new_v4());
+    let author = AuthorId::from(Uuid::new_v4());
+    let created = service
+        .create_message(InsertMessageInput {
+            id,
+            channel_id: ChannelId::from(Uuid::new_v4()),
+            author_id: author,
+            content: "hi\u{200B}\n\n\n\nthere  ".into(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("create should work");
+    assert_eq!(created.message.content, "hi\n\nthere");
+
+    let updated = service
+        .update_message(&Actor::from(author), UpdateMessageInput {
+            id,
+            content: Some("  edited\u{2060}  ".into()),
+        })
+        .await
+        .expect("update should work");
+    assert_eq!(updated.content, "edited");
+}
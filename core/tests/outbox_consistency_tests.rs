@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use messages_core::domain::message::entities::{
+    AuthorId, ChannelId, InsertMessageInput, Message, MessageId,
+};
+use messages_core::domain::message::events::created_event_record;
+use messages_core::domain::message::ports::MessageRepository;
+use messages_core::infrastructure::message::repositories::mongo::MongoMessageRepository;
+use messages_core::infrastructure::outbox::consistency::OutboxConsistencyChecker;
+use messages_core::write_outbox_event;
+use mongodb::bson::{Binary, Bson, Document, doc, spec::BinarySubtype};
+use uuid::Uuid;
+
+mod common;
+use common::TestMongo;
+
+async fn insert_message(repo: &MongoMessageRepository) -> Message {
+    repo.insert(InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: ChannelId::from(Uuid::new_v4()),
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: "hello".into(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    })
+    .await
+    .expect("insert should succeed")
+}
+
+#[tokio::test]
+async fn message_missing_its_event_gets_one_reenqueued() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+    let checker = OutboxConsistencyChecker::new(mongo.db.clone()).with_grace_period(Duration::ZERO);
+
+    let announced = insert_message(&repo).await;
+    write_outbox_event(
+        &mongo.db,
+        "notifications",
+        "message.created",
+        &created_event_record(&announced).unwrap(),
+    )
+    .await
+    .expect("outbox write should succeed");
+    // crashed between the insert and the outbox write
+    let lost = insert_message(&repo).await;
+
+    let report = checker.run_once().await.expect("pass should succeed");
+    assert_eq!(report.reenqueued_events, 1);
+
+    let outbox = mongo.db.collection::<Document>("outbox_messages");
+    let lost_id = Bson::Binary(Binary {
+        subtype: BinarySubtype::Generic,
+        bytes: lost.id.0.as_bytes().to_vec(),
+    });
+    let reenqueued = outbox
+        .find_one(doc! { "aggregate_id": lost_id })
+        .await
+        .expect("query should succeed")
+        .expect("lost message has an outbox record");
+    assert_eq!(reenqueued.get_str("routing_key").unwrap(), "message.created");
+    assert_eq!(reenqueued.get_str("status").unwrap(), "READY");
+
+    // the next pass finds nothing left to repair
+    let report = checker.run_once().await.expect("pass should succeed");
+    assert_eq!(report.reenqueued_events, 0);
+    assert_eq!(outbox.count_documents(doc! {}).await.unwrap(), 2);
+
+    mongo.teardown().await;
+}
@@ -0,0 +1,45 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::time::Duration;
+
+use messages_core::infrastructure::RabbitMqConsumer;
+use tokio::sync::watch;
+
+#[tokio::test]
+async fn consumer_keeps_retrying_an_unreachable_broker_until_shutdown() {
+    // Nothing listens on port 1, so every connection attempt fails
+    let consumer = RabbitMqConsumer::new("amqp://127.0.0.1:1".to_string())
+        .with_reconnect_delay(Duration::from_millis(10));
+    let handled = Arc::new(AtomicUsize::new(0));
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+    let counter = handled.clone();
+    let task = tokio::spawn(async move {
+        consumer
+            .consume(
+                "message-service.server-events",
+                &["server.deleted"],
+                move |_event| {
+                    let counter = counter.clone();
+                    async move {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        Ok(())
+                    }
+                },
+                shutdown_rx,
+            )
+            .await
+    });
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!task.is_finished());
+
+    shutdown_tx.send(true).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("consumer should stop on shutdown")
+        .unwrap();
+    assert_eq!(handled.load(Ordering::SeqCst), 0);
+}
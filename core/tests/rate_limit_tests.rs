@@ -0,0 +1,219 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use messages_core::domain::attachment::port::MockAttachmentRepository;
+use messages_core::domain::authorization::entities::{Actor, Permission, Resource};
+use messages_core::domain::authorization::ports::{AllowAllAuthorizer, MockAuthorizer};
+use messages_core::domain::common::CoreError;
+use messages_core::domain::common::clock::MockClock;
+use messages_core::domain::common::services::Service;
+use messages_core::domain::health::port::MockHealthRepository;
+use messages_core::domain::message::duplicates::{DuplicateContentAction, DuplicateContentPolicy};
+use messages_core::domain::message::entities::{AuthorId, ChannelId, InsertMessageInput, MessageId};
+use messages_core::domain::message::ports::{MessageService, MockMessageRepository};
+use messages_core::domain::message::rate_limit::{
+    PRUNE_EVERY_CALLS, RateLimitPolicy, RateLimited, RateLimitedAction, RateLimiter,
+    TokenBucketRateLimiter,
+};
+use messages_core::domain::outbox::ports::MockOutboxEventRepository;
+use uuid::Uuid;
+
+fn limiter(capacity: u32, refill_per_sec: f64) -> (TokenBucketRateLimiter, MockClock) {
+    let clock = MockClock::new(Utc::now());
+    let limiter = TokenBucketRateLimiter::new(RateLimitPolicy::new(capacity, refill_per_sec).expect("valid policy"))
+        .with_clock(Arc::new(clock.clone()));
+    (limiter, clock)
+}
+
+#[test]
+fn burst_beyond_capacity_is_rejected_until_refilled() {
+    let (limiter, clock) = limiter(3, 0.5);
+    let user = AuthorId::from(Uuid::new_v4());
+    let action = RateLimitedAction::SendMessage(ChannelId::from(Uuid::new_v4()));
+
+    for _ in 0..3 {
+        assert!(limiter.check_and_consume(&user, action).is_ok());
+    }
+    assert_eq!(
+        limiter.check_and_consume(&user, action),
+        Err(RateLimited { retry_after_secs: 2 })
+    );
+
+    // half a token back is not enough
+    clock.advance(Duration::seconds(1));
+    assert!(limiter.check_and_consume(&user, action).is_err());
+
+    clock.advance(Duration::seconds(1));
+    assert!(limiter.check_and_consume(&user, action).is_ok());
+    assert!(limiter.check_and_consume(&user, action).is_err());
+}
+
+#[test]
+fn buckets_are_per_user_and_channel() {
+    let (limiter, _clock) = limiter(1, 1.0);
+    let user = AuthorId::from(Uuid::new_v4());
+    let channel = ChannelId::from(Uuid::new_v4());
+    let action = RateLimitedAction::SendMessage(channel);
+
+    assert!(limiter.check_and_consume(&user, action).is_ok());
+    assert!(limiter.check_and_consume(&user, action).is_err());
+
+    let other_channel = RateLimitedAction::SendMessage(ChannelId::from(Uuid::new_v4()));
+    assert!(limiter.check_and_consume(&user, other_channel).is_ok());
+    let other_user = AuthorId::from(Uuid::new_v4());
+    assert!(limiter.check_and_consume(&other_user, action).is_ok());
+}
+
+#[test]
+fn policies_that_never_refill_are_refused() {
+    for (capacity, refill_per_sec) in [(0, 1.0), (5, 0.0), (5, -1.0), (5, f64::NAN)] {
+        assert!(matches!(
+            RateLimitPolicy::new(capacity, refill_per_sec),
+            Err(CoreError::InvalidRateLimitPolicy { .. })
+        ));
+    }
+}
+
+#[test]
+fn full_buckets_are_dropped_on_the_periodic_sweep() {
+    let (limiter, clock) = limiter(5, 1.0);
+    let action = RateLimitedAction::SendMessage(ChannelId::from(Uuid::new_v4()));
+
+    let first = AuthorId::from(Uuid::new_v4());
+    limiter.check_and_consume(&first, action).unwrap();
+    clock.advance(Duration::seconds(10));
+
+    // A full bucket lingers until the sweep comes round
+    for _ in 1..PRUNE_EVERY_CALLS - 1 {
+        limiter
+            .check_and_consume(&AuthorId::from(Uuid::new_v4()), action)
+            .unwrap();
+    }
+    assert_eq!(limiter.tracked_buckets(), PRUNE_EVERY_CALLS as usize - 1);
+
+    clock.advance(Duration::seconds(10));
+    limiter
+        .check_and_consume(&AuthorId::from(Uuid::new_v4()), action)
+        .unwrap();
+    assert_eq!(limiter.tracked_buckets(), 1);
+}
+
+#[tokio::test]
+async fn refused_messages_do_not_spend_tokens() {
+    let (limiter, _clock) = limiter(1, 1.0);
+    let limiter = Arc::new(limiter);
+    let authorizer = MockAuthorizer::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(authorizer.clone()))
+    .with_rate_limiter(limiter.clone());
+    let author = AuthorId::from(Uuid::new_v4());
+    let channel = ChannelId::from(Uuid::new_v4());
+    let input = |content: &str| InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: channel,
+        author_id: author,
+        content: content.into(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    };
+
+    let res = service.create_message(input("   ")).await;
+    assert!(matches!(res, Err(CoreError::InvalidMessageName)));
+    let res = service.create_message(input("hello")).await;
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+
+    authorizer.grant(Actor::from(author), Permission::SendMessages, Resource::Channel(channel.0));
+    service
+        .create_message(input("hello"))
+        .await
+        .expect("the token is still there");
+    let action = RateLimitedAction::SendMessage(channel);
+    assert!(limiter.check_and_consume(&author, action).is_err());
+}
+
+#[tokio::test]
+async fn create_message_is_rate_limited() {
+    let (limiter, clock) = limiter(2, 1.0);
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_rate_limiter(Arc::new(limiter));
+    let author = AuthorId::from(Uuid::new_v4());
+    let channel = ChannelId::from(Uuid::new_v4());
+    let input = || InsertMessageInput {
+        id: MessageId::from(Uuid::new_v4()),
+        channel_id: channel,
+        author_id: author,
+        content: "spam".into(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    };
+
+    service.create_message(input()).await.expect("first message passes");
+    service.create_message(input()).await.expect("second message passes");
+    let res = service.create_message(input()).await;
+    assert!(matches!(res, Err(CoreError::RateLimited { retry_after_secs: 1 })));
+
+    clock.advance(Duration::seconds(1));
+    service
+        .create_message(input())
+        .await
+        .expect("a refilled token lets the message through");
+}
+
+#[tokio::test]
+async fn rejected_duplicates_and_failed_inserts_do_not_spend_tokens() {
+    let (limiter, _clock) = limiter(2, 1.0);
+    let limiter = Arc::new(limiter);
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(AllowAllAuthorizer))
+    .with_duplicate_detection(DuplicateContentPolicy::new(
+        DuplicateContentAction::Reject,
+        Duration::seconds(30),
+    ))
+    .with_rate_limiter(limiter.clone());
+    let author = AuthorId::from(Uuid::new_v4());
+    let channel = ChannelId::from(Uuid::new_v4());
+    let input = |id: MessageId, content: &str| InsertMessageInput {
+        id,
+        channel_id: channel,
+        author_id: author,
+        content: content.into(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    };
+
+    let first = MessageId::from(Uuid::new_v4());
+    service
+        .create_message(input(first, "hello"))
+        .await
+        .expect("first message passes");
+    let res = service
+        .create_message(input(MessageId::from(Uuid::new_v4()), "hello"))
+        .await;
+    assert!(matches!(res, Err(CoreError::DuplicateContent)));
+    // Reusing a stored id makes the insert fail
+    let res = service.create_message(input(first, "other")).await;
+    assert!(matches!(res, Err(CoreError::DatabaseError { .. })));
+
+    let action = RateLimitedAction::SendMessage(channel);
+    assert!(limiter.check_and_consume(&author, action).is_ok());
+    assert!(limiter.check_and_consume(&author, action).is_err());
+}
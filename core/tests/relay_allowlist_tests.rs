@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use messages_core::domain::outbox::ports::MockEventPublisher;
+use messages_core::infrastructure::OutboxRelayService;
+use mongodb::bson::{Document, doc};
+
+mod common;
+use common::{TestMongo, outbox_event, write_event};
+
+#[tokio::test]
+async fn event_for_unlisted_exchange_is_quarantined() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_allowed_exchanges(["notifications".to_string()]);
+
+    write_event(&mongo.db, outbox_event("notifications", "message.created")).await;
+    write_event(&mongo.db, outbox_event("rogue", "message.created")).await;
+
+    relay
+        .process_pending_messages()
+        .await
+        .expect("relay pass should succeed");
+
+    let published = publisher.published();
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0].exchange_name, "notifications");
+
+    let quarantined = mongo
+        .db
+        .collection::<Document>("outbox_messages")
+        .find_one(doc! { "exchange_name": "rogue" })
+        .await
+        .expect("find should succeed")
+        .expect("rogue event should still be stored");
+    assert_eq!(quarantined.get_str("status").unwrap(), "FAILED");
+    assert!(
+        quarantined
+            .get_str("failure_reason")
+            .unwrap()
+            .contains("not in the relay allowlist")
+    );
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn empty_allowlist_publishes_to_any_exchange() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_allowed_exchanges(Vec::new());
+
+    write_event(&mongo.db, outbox_event("anything", "message.created")).await;
+    relay
+        .process_pending_messages()
+        .await
+        .expect("relay pass should succeed");
+
+    assert_eq!(publisher.published().len(), 1);
+
+    mongo.teardown().await;
+}
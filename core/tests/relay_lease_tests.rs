@@ -0,0 +1,182 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use messages_core::domain::common::CoreError;
+use messages_core::domain::outbox::ports::{EventPublisher, MockEventPublisher, OutgoingEvent};
+use messages_core::infrastructure::OutboxRelayService;
+use mongodb::bson::{DateTime as BsonDateTime, Document, doc};
+
+mod common;
+use common::{TestMongo, outbox_event, write_event};
+
+#[tokio::test]
+async fn two_relays_publish_each_event_exactly_once() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let (first, second) = (MockEventPublisher::new(), MockEventPublisher::new());
+    let relay_a = OutboxRelayService::new(mongo.db.clone(), Arc::new(first.clone()))
+        .with_instance_id("relay-a");
+    let relay_b = OutboxRelayService::new(mongo.db.clone(), Arc::new(second.clone()))
+        .with_instance_id("relay-b");
+
+    let total = 250;
+    for i in 0..total {
+        write_event(&mongo.db, outbox_event("notifications", &format!("message.{i}"))).await;
+    }
+
+    // each pass claims at most one batch, so keep both relays going until the backlog is drained
+    for _ in 0..5 {
+        let (a, b) = tokio::join!(
+            relay_a.process_pending_messages(),
+            relay_b.process_pending_messages()
+        );
+        a.expect("relay a pass should succeed");
+        b.expect("relay b pass should succeed");
+    }
+
+    let published: Vec<String> = first
+        .published()
+        .into_iter()
+        .chain(second.published())
+        .map(|event| event.routing_key)
+        .collect();
+    let unique: HashSet<&String> = published.iter().collect();
+    assert_eq!(published.len(), total);
+    assert_eq!(unique.len(), total);
+
+    let outbox = mongo.db.collection::<Document>("outbox_messages");
+    let sent = outbox
+        .count_documents(doc! { "status": "SENT" })
+        .await
+        .expect("count should succeed");
+    assert_eq!(sent, total as u64);
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn stale_claim_is_released_and_published() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_lease_duration(Duration::from_secs(30));
+
+    write_event(&mongo.db, outbox_event("notifications", "message.orphaned")).await;
+    write_event(&mongo.db, outbox_event("notifications", "message.in_flight")).await;
+
+    // one row was claimed by a relay that crashed a minute ago, the other is still leased
+    let outbox = mongo.db.collection::<Document>("outbox_messages");
+    let minute_ago = BsonDateTime::from_millis(BsonDateTime::now().timestamp_millis() - 60_000);
+    for (routing_key, claimed_at) in [
+        ("message.orphaned", minute_ago),
+        ("message.in_flight", BsonDateTime::now()),
+    ] {
+        outbox
+            .update_one(
+                doc! { "routing_key": routing_key },
+                doc! { "$set": {
+                    "status": "PROCESSING",
+                    "claimed_by": "crashed-relay",
+                    "claimed_at": claimed_at,
+                } },
+            )
+            .await
+            .expect("raw update should succeed");
+    }
+
+    let released = relay.reap_stale_claims().await.expect("reap should succeed");
+    assert_eq!(released, 1);
+
+    relay
+        .process_pending_messages()
+        .await
+        .expect("relay pass should succeed");
+
+    let published: Vec<String> = publisher
+        .published()
+        .into_iter()
+        .map(|event| event.routing_key)
+        .collect();
+    assert_eq!(published, vec!["message.orphaned"]);
+
+    mongo.teardown().await;
+}
+
+/// Publisher under which the lease expires mid-publish and another relay claims the rows
+struct LeaseLosingPublisher {
+    db: mongodb::Database,
+    fail: bool,
+}
+
+#[async_trait]
+impl EventPublisher for LeaseLosingPublisher {
+    async fn declare_exchange(&self, _exchange_name: &str) -> Result<(), CoreError> {
+        Ok(())
+    }
+
+    async fn publish(&self, _event: OutgoingEvent) -> Result<(), CoreError> {
+        self.db
+            .collection::<Document>("outbox_messages")
+            .update_many(
+                doc! { "status": "PROCESSING" },
+                doc! { "$set": { "claimed_by": "relay-b", "claimed_at": BsonDateTime::now() } },
+            )
+            .await
+            .expect("raw update should succeed");
+        if self.fail {
+            return Err(CoreError::RabbitMqError {
+                msg: "broker unavailable".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn publish_batch(&self, events: Vec<OutgoingEvent>) -> Vec<Result<(), CoreError>> {
+        let mut results = Vec::with_capacity(events.len());
+        for event in events {
+            results.push(self.publish(event).await);
+        }
+        results
+    }
+}
+
+#[tokio::test]
+async fn outcome_is_dropped_once_the_claim_is_lost() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let outbox = mongo.db.collection::<Document>("outbox_messages");
+
+    for fail in [false, true] {
+        outbox.delete_many(doc! {}).await.expect("clear outbox");
+        write_event(&mongo.db, outbox_event("notifications", "message.contended")).await;
+
+        let publisher = LeaseLosingPublisher {
+            db: mongo.db.clone(),
+            fail,
+        };
+        let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher))
+            .with_instance_id("relay-a");
+        relay
+            .process_pending_messages()
+            .await
+            .expect("relay pass should succeed");
+
+        // The row stays with the relay that took it over, untouched
+        let row = outbox
+            .find_one(doc! { "routing_key": "message.contended" })
+            .await
+            .expect("query should succeed")
+            .expect("row should still exist");
+        assert_eq!(row.get_str("status").unwrap(), "PROCESSING");
+        assert_eq!(row.get_str("claimed_by").unwrap(), "relay-b");
+        assert!(row.get("retry_count").is_none());
+    }
+
+    mongo.teardown().await;
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use messages_core::domain::outbox::ports::MockEventPublisher;
+use messages_core::infrastructure::OutboxRelayService;
+
+mod common;
+use common::{TestMongo, outbox_event, write_event};
+
+#[tokio::test]
+async fn high_priority_event_is_published_before_older_backlog() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()));
+
+    write_event(&mongo.db, outbox_event("notifications", "message.first")).await;
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    write_event(&mongo.db, outbox_event("notifications", "message.second")).await;
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    write_event(&mongo.db, outbox_event("notifications", "security.alert").with_priority(10)).await;
+
+    relay
+        .process_pending_messages()
+        .await
+        .expect("relay pass should succeed");
+
+    let order: Vec<String> = publisher
+        .published()
+        .into_iter()
+        .map(|event| event.routing_key)
+        .collect();
+    assert_eq!(order, vec!["security.alert", "message.first", "message.second"]);
+
+    mongo.teardown().await;
+}
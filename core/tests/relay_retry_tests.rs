@@ -0,0 +1,271 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use messages_core::domain::common::CoreError;
+use messages_core::domain::outbox::ports::{EventPublisher, MockEventPublisher, OutgoingEvent};
+use messages_core::infrastructure::rabbitmq::relay::retry_backoff;
+use messages_core::infrastructure::OutboxRelayService;
+use mongodb::bson::{Bson, Document, doc};
+use uuid::Uuid;
+
+mod common;
+use common::{TestMongo, outbox_event, write_event};
+
+/// Broker that refuses the second event of every batch, as a nack would
+struct NackingSecondPublisher;
+
+#[async_trait]
+impl EventPublisher for NackingSecondPublisher {
+    async fn declare_exchange(&self, _exchange_name: &str) -> Result<(), CoreError> {
+        Ok(())
+    }
+
+    async fn publish(&self, _event: OutgoingEvent) -> Result<(), CoreError> {
+        Ok(())
+    }
+
+    async fn publish_batch(&self, events: Vec<OutgoingEvent>) -> Vec<Result<(), CoreError>> {
+        (0..events.len())
+            .map(|i| match i {
+                1 => Err(CoreError::RabbitMqError {
+                    msg: "Broker refused message".to_string(),
+                }),
+                _ => Ok(()),
+            })
+            .collect()
+    }
+}
+
+async fn only_row(db: &mongodb::Database) -> Document {
+    db.collection::<Document>("outbox_messages")
+        .find_one(doc! {})
+        .await
+        .expect("query should succeed")
+        .expect("row should exist")
+}
+
+async fn dead_letter(db: &mongodb::Database) -> Document {
+    db.collection::<Document>("outbox_dead_letters")
+        .find_one(doc! {})
+        .await
+        .expect("query should succeed")
+        .expect("dead letter should exist")
+}
+
+#[test]
+fn backoff_doubles_and_is_capped() {
+    let (base, max) = (Duration::from_secs(1), Duration::from_secs(10));
+
+    assert_eq!(retry_backoff(base, max, 1), Duration::from_secs(1));
+    assert_eq!(retry_backoff(base, max, 2), Duration::from_secs(2));
+    assert_eq!(retry_backoff(base, max, 4), Duration::from_secs(8));
+    assert_eq!(retry_backoff(base, max, 5), max);
+    assert_eq!(retry_backoff(base, max, 64), max);
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried_until_published() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    publisher.fail_next(2);
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_base_backoff(Duration::ZERO);
+
+    write_event(&mongo.db, outbox_event("notifications", "message.created")).await;
+
+    for attempt in 1..=2 {
+        relay.process_pending_messages().await.expect("pass should succeed");
+        let row = only_row(&mongo.db).await;
+        assert_eq!(row.get_str("status").unwrap(), "READY");
+        assert_eq!(row.get_i32("retry_count").unwrap(), attempt);
+    }
+
+    relay.process_pending_messages().await.expect("pass should succeed");
+    assert_eq!(publisher.published().len(), 1);
+    assert_eq!(only_row(&mongo.db).await.get_str("status").unwrap(), "SENT");
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn row_waits_out_its_backoff() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    publisher.fail_next(1);
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_base_backoff(Duration::from_secs(3600));
+
+    write_event(&mongo.db, outbox_event("notifications", "message.created")).await;
+
+    relay.process_pending_messages().await.expect("pass should succeed");
+    relay.process_pending_messages().await.expect("pass should succeed");
+
+    assert!(publisher.published().is_empty());
+    let row = only_row(&mongo.db).await;
+    assert_eq!(row.get_i32("retry_count").unwrap(), 1);
+    assert!(row.get_datetime("next_retry_at").is_ok());
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn row_is_dead_lettered_once_retries_are_exhausted() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    publisher.fail_next(10);
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_base_backoff(Duration::ZERO)
+        .with_max_retries(3);
+
+    write_event(&mongo.db, outbox_event("notifications", "message.created")).await;
+
+    for _ in 0..5 {
+        relay.process_pending_messages().await.expect("pass should succeed");
+    }
+
+    let remaining = mongo
+        .db
+        .collection::<Document>("outbox_messages")
+        .count_documents(doc! {})
+        .await
+        .expect("count should succeed");
+    assert_eq!(remaining, 0);
+
+    let dead_letter = dead_letter(&mongo.db).await;
+    assert_eq!(dead_letter.get_i32("attempts").unwrap(), 3);
+    assert_eq!(dead_letter.get_str("exchange_name").unwrap(), "notifications");
+    assert_eq!(dead_letter.get_str("routing_key").unwrap(), "message.created");
+    assert_eq!(dead_letter.get_binary_generic("payload").unwrap(), &vec![1, 2, 3]);
+    assert!(!dead_letter.get_str("error").unwrap().is_empty());
+    assert!(publisher.published().is_empty());
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn replayed_dead_letter_is_published() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    publisher.fail_next(1);
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_max_retries(1);
+
+    write_event(&mongo.db, outbox_event("notifications", "message.created")).await;
+    relay.process_pending_messages().await.expect("pass should succeed");
+
+    let id = match dead_letter(&mongo.db).await.get("_id") {
+        Some(Bson::Binary(bin)) => Uuid::from_slice(&bin.bytes).unwrap(),
+        other => panic!("unexpected dead letter id {other:?}"),
+    };
+    assert!(relay.replay_dead_letter(id).await.expect("replay should succeed"));
+    assert!(!relay.replay_dead_letter(id).await.expect("replay should succeed"));
+
+    relay.process_pending_messages().await.expect("pass should succeed");
+    assert_eq!(publisher.published().len(), 1);
+    assert_eq!(only_row(&mongo.db).await.get_str("status").unwrap(), "SENT");
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn one_failure_in_a_batch_leaves_the_others_sent() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    publisher.fail_next(1);
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_base_backoff(Duration::from_secs(3600));
+
+    for _ in 0..3 {
+        write_event(&mongo.db, outbox_event("notifications", "message.created")).await;
+    }
+
+    relay.process_pending_messages().await.expect("pass should succeed");
+
+    assert_eq!(publisher.batch_sizes(), vec![3]);
+    assert_eq!(publisher.published().len(), 2);
+
+    let rows = mongo.db.collection::<Document>("outbox_messages");
+    let count = |status: &'static str| {
+        let rows = rows.clone();
+        async move {
+            rows.count_documents(doc! { "status": status })
+                .await
+                .expect("count should succeed")
+        }
+    };
+    assert_eq!(count("SENT").await, 2);
+    assert_eq!(count("READY").await, 1);
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn nacked_events_are_not_marked_sent() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(NackingSecondPublisher))
+        .with_base_backoff(Duration::from_secs(3600));
+
+    for _ in 0..3 {
+        write_event(&mongo.db, outbox_event("notifications", "message.created")).await;
+    }
+
+    relay.process_pending_messages().await.expect("pass should succeed");
+
+    let rows = mongo.db.collection::<Document>("outbox_messages");
+    let nacked = rows
+        .find_one(doc! { "status": "READY" })
+        .await
+        .expect("query should succeed")
+        .expect("nacked row should be handed back");
+    assert_eq!(nacked.get_i32("retry_count").unwrap(), 1);
+    assert!(nacked.get_str("failure_reason").unwrap().contains("refused"));
+    assert_eq!(
+        rows.count_documents(doc! { "status": "SENT" })
+            .await
+            .expect("count should succeed"),
+        2
+    );
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn passes_made_while_disconnected_spend_no_attempt() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    publisher.set_connected(false);
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_base_backoff(Duration::ZERO)
+        .with_max_retries(1);
+
+    write_event(&mongo.db, outbox_event("notifications", "message.created")).await;
+
+    for _ in 0..3 {
+        relay.process_pending_messages().await.expect("pass should succeed");
+    }
+    let row = only_row(&mongo.db).await;
+    assert_eq!(row.get_str("status").unwrap(), "READY");
+    assert!(row.get_i32("retry_count").is_err());
+
+    publisher.set_connected(true);
+    relay.process_pending_messages().await.expect("pass should succeed");
+    assert_eq!(publisher.published().len(), 1);
+    assert_eq!(only_row(&mongo.db).await.get_str("status").unwrap(), "SENT");
+
+    mongo.teardown().await;
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use messages_core::infrastructure::{OutboxRelayService, RabbitMqPublisher};
+use mongodb::Client;
+use tokio::sync::watch;
+
+#[tokio::test]
+async fn relay_task_completes_on_shutdown() {
+    // Nothing listens on these addresses: each poll fails fast and the relay keeps looping
+    let client = Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=50")
+        .await
+        .expect("create client");
+    let publisher = Arc::new(RabbitMqPublisher::new("amqp://127.0.0.1:1".into()));
+    let relay = OutboxRelayService::new(client.database("relay_shutdown_test"), publisher);
+
+    let (shutdown, receiver) = watch::channel(false);
+    let task = tokio::spawn(async move { relay.start(receiver).await });
+
+    // let it go through at least one poll
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!task.is_finished());
+
+    shutdown.send_replace(true);
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("relay should stop after shutdown")
+        .expect("relay task should not panic");
+}
+
+#[tokio::test]
+async fn relay_task_completes_when_shutdown_sender_is_dropped() {
+    let client = Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=50")
+        .await
+        .expect("create client");
+    let publisher = Arc::new(RabbitMqPublisher::new("amqp://127.0.0.1:1".into()));
+    let relay = OutboxRelayService::new(client.database("relay_shutdown_test"), publisher);
+
+    let (shutdown, receiver) = watch::channel(false);
+    let task = tokio::spawn(async move { relay.start(receiver).await });
+
+    drop(shutdown);
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("relay should stop once the sender is gone")
+        .expect("relay task should not panic");
+}
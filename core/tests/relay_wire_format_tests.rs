@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use messages_core::domain::message::entities::{ChannelId, MessageId};
+use messages_core::domain::message::events::{delete_message_event_from_domain, event_to_bytes};
+use messages_core::domain::outbox::ports::MockEventPublisher;
+use messages_core::infrastructure::outbox::{MessageRoutingInfo, OutboxEventRecord, WireFormat, WireFormats};
+use messages_core::infrastructure::OutboxRelayService;
+use mongodb::bson::{Document, doc};
+use uuid::Uuid;
+
+mod common;
+use common::{TestMongo, outbox_event, write_event};
+
+#[test]
+fn rules_for_a_route_win_over_rules_for_its_exchange() {
+    let formats: WireFormats = "audit=json, notifications/message.deleted=json, audit/message.created=protobuf"
+        .parse()
+        .expect("rules should parse");
+
+    assert_eq!(formats.for_route("audit", "message.deleted"), WireFormat::Json);
+    assert_eq!(formats.for_route("audit", "message.created"), WireFormat::Protobuf);
+    assert_eq!(formats.for_route("notifications", "message.deleted"), WireFormat::Json);
+    assert_eq!(formats.for_route("notifications", "message.created"), WireFormat::Protobuf);
+    assert!("".parse::<WireFormats>().is_ok());
+    assert!("audit=xml".parse::<WireFormats>().is_err());
+    assert!("audit".parse::<WireFormats>().is_err());
+}
+
+#[tokio::test]
+async fn same_event_publishes_as_json_for_one_exchange_and_protobuf_for_another() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_wire_formats(WireFormats::new().with_exchange("audit", WireFormat::Json));
+
+    let (message_id, channel_id) = (MessageId::from(Uuid::new_v4()), ChannelId::from(Uuid::new_v4()));
+    let stored = event_to_bytes(&delete_message_event_from_domain(message_id, channel_id)).unwrap();
+    let deleted_on = |exchange: &str| OutboxEventRecord::new(MessageRoutingInfo::new(exchange, "message.deleted"), stored.clone());
+    write_event(&mongo.db, deleted_on("notifications")).await;
+    write_event(&mongo.db, deleted_on("audit")).await;
+
+    relay
+        .process_pending_messages()
+        .await
+        .expect("relay pass should succeed");
+
+    let published = publisher.published();
+    assert_eq!(published.len(), 2);
+
+    let protobuf = published.iter().find(|e| e.exchange_name == "notifications").unwrap();
+    assert_eq!(protobuf.content_type, "application/x-protobuf");
+    assert_eq!(protobuf.payload, stored);
+
+    let json = published.iter().find(|e| e.exchange_name == "audit").unwrap();
+    assert_eq!(json.content_type, "application/json");
+    let body: serde_json::Value = serde_json::from_slice(&json.payload).expect("payload should be JSON");
+    assert_eq!(body["message_id"], message_id.to_string());
+    assert_eq!(body["channel_id"], channel_id.to_string());
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn event_that_cannot_be_converted_is_quarantined() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let publisher = MockEventPublisher::new();
+    let relay = OutboxRelayService::new(mongo.db.clone(), Arc::new(publisher.clone()))
+        .with_wire_formats(WireFormats::new().with_exchange("audit", WireFormat::Json));
+
+    write_event(&mongo.db, outbox_event("audit", "server.created")).await;
+    relay
+        .process_pending_messages()
+        .await
+        .expect("relay pass should succeed");
+
+    assert!(publisher.published().is_empty());
+    let row = mongo
+        .db
+        .collection::<Document>("outbox_messages")
+        .find_one(doc! {})
+        .await
+        .expect("find should succeed")
+        .expect("event should still be stored");
+    assert_eq!(row.get_str("status").unwrap(), "FAILED");
+
+    mongo.teardown().await;
+}
@@ -0,0 +1,274 @@
+use messages_core::domain::common::{CoreError, GetPaginated};
+use messages_core::domain::message::entities::{AuthorId, ChannelId, InsertMessageInput, MessageId, UpdateMessageInput};
+use messages_core::domain::message::ports::MessageRepository;
+use messages_core::infrastructure::message::repositories::mongo::MongoMessageRepository;
+use messages_core::infrastructure::soft_delete::{DELETED_AT_FIELD, exclude_deleted};
+use mongodb::Database;
+use mongodb::bson::{Binary, Bson, Document, doc, spec::BinarySubtype};
+use uuid::Uuid;
+
+mod common;
+use common::TestMongo;
+
+/// Mark a stored message as soft-deleted directly in the collection
+async fn mark_deleted(db: &Database, id: &MessageId) {
+    let id_bson = Bson::Binary(Binary {
+        subtype: BinarySubtype::Generic,
+        bytes: id.0.as_bytes().to_vec(),
+    });
+    db.collection::<Document>("messages")
+        .update_one(
+            doc! { "_id": id_bson },
+            doc! { "$set": { DELETED_AT_FIELD: chrono::Utc::now().to_rfc3339() } },
+        )
+        .await
+        .expect("raw update should succeed");
+}
+
+#[test]
+fn exclude_deleted_adds_predicate_to_base_filter() {
+    let filter = exclude_deleted(doc! { "channel_id": "abc" });
+
+    assert_eq!(filter.get_str("channel_id").unwrap(), "abc");
+    assert_eq!(filter.get(DELETED_AT_FIELD), Some(&Bson::Null));
+}
+
+#[test]
+fn exclude_deleted_keeps_existing_deleted_at_constraint() {
+    let filter = exclude_deleted(doc! { DELETED_AT_FIELD: { "$exists": true } });
+
+    let clauses = filter.get_array("$and").expect("predicates combined with $and");
+    assert_eq!(clauses.len(), 2);
+}
+
+#[tokio::test]
+async fn soft_deleted_message_is_excluded_from_every_read_path() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let author = AuthorId::from(Uuid::new_v4());
+    let id = MessageId::from(Uuid::new_v4());
+
+    repo.insert(InsertMessageInput {
+        id,
+        channel_id: channel,
+        author_id: author,
+        content: "soon deleted".into(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    })
+    .await
+    .expect("insert should succeed");
+
+    mark_deleted(&mongo.db, &id).await;
+
+    let found = repo.find_by_id(&id).await.expect("find should succeed");
+    assert!(found.is_none());
+
+    let (listed, total) = repo
+        .list(&channel, &GetPaginated::default())
+        .await
+        .expect("list should succeed");
+    assert!(listed.is_empty());
+    assert_eq!(total, 0);
+
+    let (matches, total) = repo
+        .search_messages(&channel, &"deleted".into(), &GetPaginated::default())
+        .await
+        .expect("search should succeed");
+    assert!(matches.is_empty());
+    assert_eq!(total, 0);
+
+    let recent = repo
+        .list_recent_channels(&author, 10)
+        .await
+        .expect("recent channels should succeed");
+    assert!(recent.is_empty());
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn search_total_matches_returned_set_when_a_match_is_deleted() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let (kept, deleted) = (MessageId::from(Uuid::new_v4()), MessageId::from(Uuid::new_v4()));
+
+    for id in [kept, deleted] {
+        repo.insert(InsertMessageInput {
+            id,
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: "release notes".into(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("insert should succeed");
+    }
+    mark_deleted(&mongo.db, &deleted).await;
+
+    let (matches, total) = repo
+        .search_messages(&channel, &"release".into(), &GetPaginated::default())
+        .await
+        .expect("search should succeed");
+
+    assert_eq!(total, 1);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].id, kept);
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn participants_count_only_remaining_messages() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let (regular, occasional, gone) = (
+        AuthorId::from(Uuid::new_v4()),
+        AuthorId::from(Uuid::new_v4()),
+        AuthorId::from(Uuid::new_v4()),
+    );
+
+    let mut gone_message = None;
+    for author in [regular, regular, occasional, gone] {
+        let id = MessageId::from(Uuid::new_v4());
+        repo.insert(InsertMessageInput {
+            id,
+            channel_id: channel,
+            author_id: author,
+            content: "hello".into(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("insert should succeed");
+        if author == gone {
+            gone_message = Some(id);
+        }
+    }
+    mark_deleted(&mongo.db, &gone_message.unwrap()).await;
+
+    let (participants, total) = repo
+        .list_channel_participants(&channel, &GetPaginated::default())
+        .await
+        .expect("participants should succeed");
+
+    assert_eq!(total, 2);
+    let counts: Vec<(AuthorId, u64)> = participants
+        .iter()
+        .map(|p| (p.author_id, p.message_count))
+        .collect();
+    assert_eq!(counts, vec![(regular, 2), (occasional, 1)]);
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn latest_message_per_channel_skips_soft_deleted() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let (first, second) = (ChannelId::from(Uuid::new_v4()), ChannelId::from(Uuid::new_v4()));
+    let mut ids = Vec::new();
+    for channel in [first, second, first, second, second] {
+        let id = MessageId::from(Uuid::new_v4());
+        repo.insert(InsertMessageInput {
+            id,
+            channel_id: channel,
+            author_id: AuthorId::from(Uuid::new_v4()),
+            content: "sidebar".into(),
+            reply_to_message_id: None,
+            attachments: vec![],
+            ephemeral: false,
+        })
+        .await
+        .expect("insert should succeed");
+        ids.push(id);
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+
+    mark_deleted(&mongo.db, &ids[4]).await;
+
+    let latest = repo
+        .latest_messages_for_channels(&[first, second, ChannelId::from(Uuid::new_v4())])
+        .await
+        .expect("latest messages should succeed");
+
+    assert_eq!(latest.len(), 2);
+    assert_eq!(latest[&first].id, ids[2]);
+    assert_eq!(latest[&second].id, ids[3]);
+
+    mongo.teardown().await;
+}
+
+#[tokio::test]
+async fn deleted_message_is_kept_out_of_list_but_still_fetchable() {
+    let Some(mongo) = TestMongo::start().await else {
+        return;
+    };
+    let repo = MongoMessageRepository::new(&mongo.db);
+
+    let channel = ChannelId::from(Uuid::new_v4());
+    let id = MessageId::from(Uuid::new_v4());
+    repo.insert(InsertMessageInput {
+        id,
+        channel_id: channel,
+        author_id: AuthorId::from(Uuid::new_v4()),
+        content: "kept for audit".into(),
+        reply_to_message_id: None,
+        attachments: vec![],
+        ephemeral: false,
+    })
+    .await
+    .expect("insert should succeed");
+
+    repo.delete(&id).await.expect("delete should succeed");
+
+    let (listed, total) = repo
+        .list(&channel, &GetPaginated::default())
+        .await
+        .expect("list should succeed");
+    assert!(listed.is_empty());
+    assert_eq!(total, 0);
+    assert!(repo.find_by_id(&id).await.expect("find should succeed").is_none());
+
+    let kept = repo
+        .find_including_deleted(&id)
+        .await
+        .expect("find should succeed")
+        .expect("soft-deleted message should still exist");
+    assert_eq!(kept.content, "kept for audit");
+
+    // deleting again finds nothing left to delete, and an edit racing the
+    // delete can't rewrite it
+    assert!(matches!(repo.delete(&id).await, Err(CoreError::MessageNotFound { .. })));
+    let edit = UpdateMessageInput {
+        id,
+        content: Some("edited after delete".into()),
+    };
+    assert!(matches!(repo.update(edit).await, Err(CoreError::MessageNotFound { .. })));
+    let kept = repo.find_including_deleted(&id).await.unwrap().unwrap();
+    assert_eq!(kept.content, "kept for audit");
+
+    repo.purge(&id).await.expect("purge should succeed");
+    assert!(repo.find_including_deleted(&id).await.expect("find should succeed").is_none());
+
+    mongo.teardown().await;
+}
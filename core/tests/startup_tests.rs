@@ -0,0 +1,23 @@
+use messages_core::{create_repositories, domain::common::CoreError};
+
+#[tokio::test]
+async fn bad_mongo_uri_names_mongodb_as_the_failed_dependency() {
+    let result = create_repositories("postgres://127.0.0.1:5432", "message_test_db", &"http://localhost:3004".into()).await;
+
+    match result {
+        Err(CoreError::DependencyUnavailable { dependency, reason }) => {
+            assert_eq!(dependency, "mongodb");
+            assert!(!reason.is_empty());
+        }
+        Err(other) => panic!("expected a dependency error, got {other:?}"),
+        Ok(_) => panic!("a non-mongodb URI should be rejected"),
+    }
+}
+
+#[tokio::test]
+async fn reachable_looking_uri_still_builds_repositories() {
+    // The client connects lazily, so an unreachable host is only reported by health checks
+    let result = create_repositories("mongodb://127.0.0.1:1", "message_test_db", &"http://localhost:3004".into()).await;
+
+    assert!(result.is_ok());
+}
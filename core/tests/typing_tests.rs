@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use messages_core::domain::attachment::port::MockAttachmentRepository;
+use messages_core::domain::authorization::entities::{Actor, Permission, Resource};
+use messages_core::domain::authorization::ports::MockAuthorizer;
+use messages_core::domain::common::CoreError;
+use messages_core::domain::common::services::Service;
+use messages_core::domain::health::port::MockHealthRepository;
+use messages_core::domain::message::entities::ChannelId;
+use messages_core::domain::message::events::ChannelTypingEvent;
+use messages_core::domain::message::ports::{MessageService, MockMessageRepository};
+use messages_core::domain::outbox::ports::{MockEventPublisher, MockOutboxEventRepository};
+use prost::Message as _;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn typing_is_published_without_touching_the_outbox() {
+    let outbox = MockOutboxEventRepository::new();
+    let publisher = MockEventPublisher::new();
+    let authorizer = MockAuthorizer::new();
+    let actor = Actor::from(Uuid::new_v4());
+    let channel = ChannelId::from(Uuid::new_v4());
+    authorizer.grant(actor, Permission::SendMessages, Resource::Channel(channel.0));
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        outbox.clone(),
+    )
+    .with_authorizer(Arc::new(authorizer))
+    .with_event_publisher(Arc::new(publisher.clone()));
+
+    service
+        .publish_typing(&actor, &channel)
+        .await
+        .expect("typing should be published");
+
+    let published = publisher.published();
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0].exchange_name, "notifications");
+    assert_eq!(published[0].routing_key, "channel.typing");
+    let event = ChannelTypingEvent::decode(published[0].payload.as_slice()).unwrap();
+    assert_eq!(event.channel_id, channel.to_string());
+    assert_eq!(event.user_id, actor.author_id().to_string());
+    assert!(chrono::DateTime::parse_from_rfc3339(&event.timestamp).is_ok());
+
+    assert!(outbox.events().is_empty());
+}
+
+#[tokio::test]
+async fn typing_requires_send_messages() {
+    let publisher = MockEventPublisher::new();
+    let service = Service::new(
+        MockMessageRepository::new(),
+        MockHealthRepository::new(),
+        MockAttachmentRepository::new(),
+        MockOutboxEventRepository::new(),
+    )
+    .with_authorizer(Arc::new(MockAuthorizer::new()))
+    .with_event_publisher(Arc::new(publisher.clone()));
+
+    let res = service
+        .publish_typing(&Actor::from(Uuid::new_v4()), &ChannelId::from(Uuid::new_v4()))
+        .await;
+
+    assert!(matches!(res, Err(CoreError::Forbidden)));
+    assert!(publisher.published().is_empty());
+}